@@ -293,6 +293,7 @@ XLang-Rust 提供了多种内建数据类型。所有类型都是对象，变量
   - 算术: `range + int`, `range - int` (移动区间两端), `range + range`, `range - range` (对应端点相加减)。
   - 包含判断: `int in range`, `sub_range in range`。
   - 长度: `len(range)` 返回 `end - start`。
+  - 索引访问: `range[index]` 返回 `start + index` (支持负数下标)；`range[sub_range]` 按相同规则返回子区间，越界时报错。
   - 迭代: 可迭代区间内的所有整数（从 `start` 到 `end - 1`）。
   - 比较: `==` 比较区间的起始和结束点是否都相等。
 
@@ -682,9 +683,9 @@ XLang-Rust 将许多内置功能组织为模块，必须通过模块名称访问
 ==== fs 模块 (文件系统操作)
 - `fs.read(path)`：读取文本文件内容，返回字符串。
 - `fs.read_bytes(path)`：读取文件内容，返回字节序列。
-- `fs.write(path, content)`：将字符串内容写入文件。
+- `fs.write(path, content)`：将字符串内容写入文件，写入失败时报错。
 - `fs.write_bytes(path, content)`：将字节序列写入文件。
-- `fs.append(path, content)`：将字符串内容追加到文件末尾。
+- `fs.append(path, content)`：将字符串或字节序列追加到文件末尾，文件不存在时创建。
 - `fs.append_bytes(path, content)`：将字节序列追加到文件末尾。
 - `fs.exists(path)`：检查文件或目录是否存在，返回布尔值。
 - `fs.is_file(path)`：检查路径是否为文件，返回布尔值。
@@ -696,6 +697,8 @@ XLang-Rust 将许多内置功能组织为模块，必须通过模块名称访问
 ==== io 模块 (输入/输出操作)
 - `io.print(...)`：与全局 `print` 函数相同。
 - `io.input([prompt])`：与全局 `input` 函数相同。
+- `io.log(level, message)`：按级别输出带颜色的日志，`level` 为 `"debug"`、`"info"`、`"warn"`、`"error"` 之一；`warn` 与 `error` 写入 stderr，其余写入 stdout。
+- `io.set_log_level(level)`：设置全局最低日志级别，低于该级别的日志不会输出，默认为 `"info"`。
 
 ==== types 模块 (类型转换)
 - `types.int(value)`：与全局 `int` 函数相同。
@@ -704,6 +707,16 @@ XLang-Rust 将许多内置功能组织为模块，必须通过模块名称访问
 - `types.bool(value)`：与全局 `bool` 函数相同。
 - `types.bytes(value)`：与全局 `bytes` 函数相同。
 - `types.len(value)`：与全局 `len` 函数相同。
+- `types.equals(a, b)`：与 `==` 相同的结构相等比较。
+- `types.is(a, b)`：判断 `a` 与 `b` 是否为同一个对象，两个值相等但独立创建的元组返回 `false`。
+- `types.keys(tuple)`：按顺序返回元组中所有键值对（`key : value` 或 `key => value`）的键，普通元素被跳过。
+- `types.values(tuple)`：按顺序返回元组中所有键值对的值，普通元素被跳过。
+- `types.has_key(tuple, key)`：判断元组中是否存在键等于 `key` 的键值对。
+- `types.merge(base, overrides)`：返回合并后的新元组，`overrides` 中的键值对替换 `base` 中同键的项，新键追加到末尾，`base` 中的普通元素原样保留。
+- `types.reverse(tuple)`：返回元素顺序反转后的新元组。
+- `types.unique(tuple)`：返回去除重复元素后的新元组，保留每个元素第一次出现的位置。可哈希的元素按结构哈希查重；`named`、`lambda` 等不可哈希的元素逐个比较，最坏情况为 O(n^2)。
+- `types.freeze(value)`：递归冻结 `value` 及其引用的对象并返回 `value`，对冻结对象赋值会得到 `AssignError`，重复冻结没有副作用。lambda 只冻结自身，其参数与捕获仍可修改；拷贝得到的新对象不会被冻结。
+- `types.is_frozen(value)`：判断 `value` 是否已被冻结。
 
 ==== os 模块 (操作系统交互)
 - `os.getcwd()`：获取当前工作目录，返回字符串路径。
@@ -714,6 +727,9 @@ XLang-Rust 将许多内置功能组织为模块，必须通过模块名称访问
 - `os.path_separator()`：获取系统路径分隔符（Windows 为 `\`，其他系统为 `/`）。
 - `os.system_name()`：获取当前操作系统名称。
 - `os.system(command)`：执行系统命令，返回命令退出码。
+- `os.run(command, [args])`：不经过 shell 直接启动程序 `command`（`args` 为字符串元组）并等待其结束，返回 `(exit_code => int, stdout => string, stderr => string)`；无法启动时报错。
+- `os.spawn(command, [args])`：与 `os.run` 相同，但启动后立即返回子进程 ID。
+- `os.wait(pid)`：等待 `os.spawn` 启动的子进程结束，返回值与 `os.run` 相同，每个子进程只能等待一次。
 - `os.join_path(...paths)`：将多个路径组合成一个路径。
 - `os.dirname(path)`：获取路径的父目录部分。
 - `os.basename(path)`：获取路径的文件名部分。
@@ -733,10 +749,20 @@ XLang-Rust 将许多内置功能组织为模块，必须通过模块名称访问
 - `string_utils.strip(string, [chars])`：移除字符串两端的空白或指定字符。
 - `string_utils.lower(string)`：将字符串转换为小写。
 - `string_utils.upper(string)`：将字符串转换为大写。
+- `string_utils.char_len(string)`：返回字符串的字符数，与 `len(string)` 相同。
+- `string_utils.byte_len(string)`：返回字符串按 UTF-8 编码后的字节数。
+- `string_utils.url_encode(string)`：对字符串进行百分号编码（空格编码为 `%20`）。
+- `string_utils.url_decode(string)`：百分号解码，`%` 序列不完整或结果不是合法 UTF-8 时报错。
+- `string_utils.build_query(params)`：将 `(k1 : v1, k2 : v2, ...)` 编码为 `k1=v1&k2=v2` 形式的查询字符串。
 
 ==== serialization 模块 (序列化)
 - `serialization.json_encode(value)`：将值编码为 JSON 字符串。
 - `serialization.json_decode(string)`：将 JSON 字符串解码为值。
+- `serialization.csv_parse(string)`：按 RFC 4180 解析 CSV，返回由行元组组成的元组，字段均为字符串；引号不匹配时报错。
+- `serialization.csv_write(rows)`：将行元组组成的元组写为 CSV 字符串，必要时为字段加上引号。
+- `serialization.toml_decode(string)`：解析 TOML，表转换为键值对元组，数组转换为元组，日期时间转换为字符串；解析失败时报错并给出行列位置。
+- `serialization.compress(bytes, algo)`：以 `algo` 指定的格式压缩字节串，支持 `"gzip"`、`"deflate"`（raw deflate）与 `"zlib"`，返回 `bytes`；未知的算法报 `ValueError`。
+- `serialization.decompress(bytes, algo)`：解压字节串并返回 `bytes`，数据损坏或与 `algo` 不匹配时报 `ValueError`。
 
 ==== time 模块 (时间相关)
 - `time.timestamp()`：获取当前 UNIX 时间戳（秒数），返回浮点数。
@@ -744,6 +770,22 @@ XLang-Rust 将许多内置功能组织为模块，必须通过模块名称访问
 
 ==== async_request 模块 (异步网络请求)
 - `async_request.request(url, method, data)`：发送异步网络请求，返回生成器对象。
+  - 可选命名参数 `headers => (key : value, ...)` 与 `timeout => 毫秒数`；超时或网络错误时结果中的 `error_message` 为错误描述（超时为 `"Request timed out"`）。
+
+==== http 模块 (同步网络请求)
+- `http.get(url)`：阻塞地发送 GET 请求，返回 `(status => int, body => string, headers => tuple)`。
+- `http.post(url, body, [headers])`：阻塞地发送 POST 请求，`body` 为字符串或字节序列，`headers` 为 `(key : value, ...)` 元组。网络错误会以 ValueError 的形式抛出。
+
+==== control 模块 (错误处理)
+- `control.try(body, handler)`：无参数调用 `body` 并返回其结果；`body` 出错时以 `(kind => string, message => string, value => object)` 调用 `handler` 并返回 `handler` 的结果。`kind` 为错误类别（如 `"TypeError"`），`value` 为出错时针对的对象（没有时为 `null`）。调用深度超限等执行器错误不会被捕获。
+- `control.throw(message)`：抛出类别为 `"Error"` 的错误，`value` 为字符串 `message`（`raise` 是关键字，因此命名为 `throw`）。
+- `control.raise_with(kind, value)`：抛出类别为字符串 `kind`、携带任意值 `value` 的错误，可在 `control.try` 的 `handler` 中取出。
+
+==== crypto 模块 (哈希与签名)
+- `crypto.sha256(data)`、`crypto.sha1(data)`、`crypto.md5(data)`：计算摘要并以 `bytes` 返回，`data` 可以是 `bytes` 或字符串（按 UTF-8 编码）。SHA-1 与 MD5 不再安全，只适合兼容旧格式或做非安全场景的校验。
+- `crypto.hex_digest(algo, data)`：以小写十六进制字符串返回摘要，`algo` 为 `"sha256"`、`"sha1"` 或 `"md5"`，未知的算法报 `ValueError`。
+- `crypto.hmac_sha256(key, message)`：计算 HMAC-SHA256 并以 32 字节的 `bytes` 返回，`key` 与 `message` 可以是 `bytes` 或字符串。
+- `crypto.secure_compare(a, b)`：以常数时间比较两段 `bytes`（或字符串）是否相等，用于校验签名等秘密值；长度不同时仍按较长一方的长度完成比较并返回 `false`。
 
 ==== asyncio 模块 (异步 IO)
 - `asyncio.pause(lambda)`：暂停指定lambda的执行
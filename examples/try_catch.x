@@ -7,7 +7,7 @@ try_catch := (pair?) -> {
 
 result := #try_catch {
     () -> {
-        "A"[-1]
+        "A"[5]
     }
 } : {
     (f?, err?) -> {
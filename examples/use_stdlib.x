@@ -27,7 +27,7 @@ try_catch.try(
 );
 
 result := #(try_catch.try_catch) {
-    () -> "A"[-1]
+    () -> "A"[5]
 } : {
     (f?, err?) -> {
         builtins.print("Error occurred:", err, "in", f);
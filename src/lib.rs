@@ -0,0 +1,2 @@
+// XLang 的标准库，供命令行程序与集成测试共用
+pub mod stdlib;
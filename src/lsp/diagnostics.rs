@@ -282,6 +282,18 @@ fn create_diagnostic_from_parser_error<'t>(
                 related_information: Some(related),
             }
         },
+        ParserError::UnclosedParenthesis(opening) => {
+            // 括号直到输入末尾都未闭合
+            let range = get_token_range(opening, source_code);
+            Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::Error),
+                code: Some(serde_json::Value::String("AST-E009".to_string())),
+                source: Some("xlang-lsp".to_string()),
+                message: format!("未闭合的括号: '{}'", opening.token),
+                related_information: None,
+            }
+        },
         ParserError::InvalidSyntax(token) => {
             // 无效语法错误
             let range = get_token_range(token, source_code);
@@ -2,8 +2,9 @@ mod lsp;
 use colored::Colorize;
 use rustyline::highlight::CmdKind;
 
-mod stdlib;
-use crate::stdlib::inject_builtin_functions;
+use xlang_rust::stdlib::inject_builtin_functions;
+use xlang_rust::stdlib::{json_to_vmobject, to_json_value, type_name_of};
+use xlang_vm_core::executor::context::{Context as VMContext, ContextFrameType};
 use xlang_vm_core::executor::variable::VMInstructions;
 use xlang_vm_core::executor::variable::VMLambda;
 use xlang_vm_core::executor::variable::VMTuple;
@@ -11,18 +12,25 @@ use xlang_vm_core::executor::variable::*;
 use xlang_vm_core::executor::vm::*;
 use xlang_vm_core::gc::GCRef;
 use xlang_vm_core::gc::GCSystem;
+use xlang_vm_core::instruction_set::VMInstruction;
 use xlang_vm_core::instruction_set::VMInstructionPackage;
 use xlang_vm_core::ir::IRPackage;
 use xlang_vm_core::ir_translator::IRTranslator;
+use xlang_vm_core::opcode::{OpcodeArgument, ProcessedOpcode};
 
 use clap::{Parser, Subcommand};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Write;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 use xlang_frontend::compile::{build_code, compile_to_bytecode};
 use xlang_frontend::dir_stack::DirStack;
-use xlang_frontend::parser::lexer::lexer;
+use xlang_frontend::parser::lexer::{lexer, TokenType};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -53,11 +61,23 @@ enum Commands {
         /// Input file path (source code, .xir file, or .xbc bytecode file)
         #[arg(required = true)]
         input: PathBuf,
+
+        /// Print each executed instruction with its IP and the top of stack to stderr
+        #[arg(long)]
+        trace: bool,
+
+        /// Count calls and executed instructions per function and print a table to stderr on exit
+        #[arg(long, conflicts_with = "trace")]
+        profile: bool,
+
+        /// Arguments passed to the script, available via os.argv()
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
     },
 
-    /// Display IR file content
+    /// Display disassembled IR or bytecode file content
     DisplayIR {
-        /// Input IR file path
+        /// Input file path (.xir file or .xbc bytecode file)
         #[arg(required = true)]
         input: PathBuf,
     },
@@ -84,9 +104,232 @@ enum Commands {
     },
 }
 
+// 崩溃提示，若能解析到源码位置则附带 line:col
+fn crash_header(e: &VMError) -> String {
+    match e.source_position() {
+        Some(position) => format!("VM Crashed at {}:{}!:", position.line, position.column),
+        None => "VM Crashed!:".to_string(),
+    }
+    .bright_red()
+    .underline()
+    .bold()
+    .to_string()
+}
+
+// 回溯中最多展示的帧数，失控的递归可能有上万帧
+const TRACEBACK_SHOWN_FRAMES: usize = 20;
+
+// 调用栈回溯，由外向内排列，最后一帧为出错的函数；帧数过多时只展示最内层的若干帧
+fn format_traceback(frames: &[TraceFrame]) -> String {
+    let mut output = "Traceback (most recent call last):"
+        .bright_yellow()
+        .bold()
+        .to_string();
+    let skipped = frames.len().saturating_sub(TRACEBACK_SHOWN_FRAMES);
+    if skipped > 0 {
+        output.push_str(&format!(
+            "\n  {}",
+            format!("... ({} more frames)", skipped).bright_black()
+        ));
+    }
+    for frame in &frames[skipped..] {
+        match &frame.position {
+            Some(position) => output.push_str(&format!(
+                "\n  at {} ({})\n  {} {}",
+                frame.signature.bright_cyan(),
+                format!("{}:{}", position.line, position.column).bright_white(),
+                format!("{:4} |", position.line).bright_black(),
+                position.source_line.trim()
+            )),
+            None => output.push_str(&format!(
+                "\n  at {} ({})",
+                frame.signature.bright_cyan(),
+                "unknown position".bright_black()
+            )),
+        }
+    }
+    output
+}
+
+// 完整的崩溃信息：崩溃提示加上错误详情，以及调用栈（如果有）
+fn crash_report(e: &mut VMError) -> String {
+    let header = crash_header(e);
+    let traceback = e.traceback().map(|frames| format_traceback(frames));
+    match traceback {
+        Some(traceback) => format!("{} {}\n\n{}", header, e.to_string(), traceback),
+        None => format!("{} {}", header, e.to_string()),
+    }
+}
+
+// --trace 输出中栈顶 repr 的最大字符数
+const TRACE_TOP_MAX_CHARS: usize = 80;
+
+// --trace 使用的钩子：每条指令执行前输出协程 ID、IP、指令及当前栈顶
+struct TraceHook {
+    out: Box<dyn std::io::Write>,
+}
+
+impl TraceHook {
+    fn new(out: Box<dyn std::io::Write>) -> Self {
+        TraceHook { out }
+    }
+}
+
+impl ExecutorHook for TraceHook {
+    fn on_instruction(
+        &mut self,
+        ip: usize,
+        coroutine_id: isize,
+        opcode: &ProcessedOpcode,
+        executor: &VMExecutor,
+    ) -> HookAction {
+        let mut instruction = match VMInstruction::from_opcode(opcode.instruction) {
+            Some(instruction) => format!("{:?}", instruction),
+            None => format!("Unknown(0x{:02x})", opcode.instruction),
+        };
+        for operand in [&opcode.operand1, &opcode.operand2, &opcode.operand3] {
+            if !matches!(operand, OpcodeArgument::None) {
+                instruction.push_str(&format!(" {:?}", operand));
+            }
+        }
+        let top = match executor.stack.last() {
+            Some(VMStackObject::VMObject(value)) => try_repr_vmobject(&mut value.clone(), None)
+                .unwrap_or_else(|mut err| {
+                    err.consume_ref();
+                    "<unrepresentable>".to_string()
+                }),
+            Some(VMStackObject::LastIP(..)) => "<frame>".to_string(),
+            None => "<empty>".to_string(),
+        };
+        // 栈顶可能是很大的对象（如内置模块），只保留开头部分
+        let top = if top.chars().count() > TRACE_TOP_MAX_CHARS {
+            format!(
+                "{}...",
+                top.chars().take(TRACE_TOP_MAX_CHARS).collect::<String>()
+            )
+        } else {
+            top
+        };
+        // 输出失败（如管道被关闭）不影响程序执行
+        let _ = writeln!(
+            self.out,
+            "[{}] {:>6}: {:<40} top: {}",
+            coroutine_id, ip, instruction, top
+        );
+        HookAction::Continue
+    }
+}
+
+// 单个函数的统计数据
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct FunctionProfile {
+    calls: u64,        // 进入该函数的次数
+    instructions: u64, // 在该函数内执行的指令数
+}
+
+// --profile 使用的钩子：按 IP 所在的函数（function_ips 中不超过 IP 的最近入口）统计执行次数
+struct ProfileHook {
+    stats: Rc<RefCell<HashMap<String, FunctionProfile>>>,
+    entries: HashMap<usize, Vec<(usize, String)>>, // 按指令包地址缓存排好序的函数入口
+    depths: HashMap<isize, usize>,                 // 各协程上一条指令时的函数调用深度
+}
+
+impl ProfileHook {
+    fn new(stats: Rc<RefCell<HashMap<String, FunctionProfile>>>) -> Self {
+        ProfileHook {
+            stats,
+            entries: HashMap::new(),
+            depths: HashMap::new(),
+        }
+    }
+}
+
+impl ExecutorHook for ProfileHook {
+    fn on_instruction(
+        &mut self,
+        ip: usize,
+        coroutine_id: isize,
+        _opcode: &ProcessedOpcode,
+        executor: &VMExecutor,
+    ) -> HookAction {
+        let Some(instructions) = executor.lambda_instructions.last() else {
+            return HookAction::Continue;
+        };
+        if !instructions.isinstance::<VMInstructions>() {
+            return HookAction::Continue;
+        }
+        let package = &instructions
+            .as_const_type::<VMInstructions>()
+            .vm_instructions_package;
+        let entries = self
+            .entries
+            .entry(package as *const VMInstructionPackage as usize)
+            .or_insert_with(|| {
+                let mut entries: Vec<(usize, String)> = package
+                    .get_table()
+                    .iter()
+                    .map(|(name, ip)| (*ip, name.clone()))
+                    .collect();
+                entries.sort();
+                entries
+            });
+        let Some((entry_ip, name)) = entries
+            .iter()
+            .take_while(|(entry_ip, _)| *entry_ip <= ip)
+            .last()
+        else {
+            return HookAction::Continue;
+        };
+        // 只有调用深度增加且位于函数入口时才算一次调用，循环跳回入口不计入
+        let depth = executor.context.function_depth();
+        let last_depth = self.depths.insert(coroutine_id, depth).unwrap_or(0);
+        let mut stats = self.stats.borrow_mut();
+        let profile = stats.entry(name.clone()).or_default();
+        if *entry_ip == ip && depth > last_depth {
+            profile.calls += 1;
+        }
+        profile.instructions += 1;
+        HookAction::Continue
+    }
+}
+
+// 将 --profile 的统计结果按执行指令数从多到少排成表格
+fn format_profile(stats: &HashMap<String, FunctionProfile>) -> String {
+    let mut rows: Vec<(&String, &FunctionProfile)> = stats.iter().collect();
+    rows.sort_by(|(a_name, a), (b_name, b)| {
+        b.instructions
+            .cmp(&a.instructions)
+            .then(b.calls.cmp(&a.calls))
+            .then(a_name.cmp(b_name))
+    });
+    let mut output = format!("{:>10} {:>14}  {}", "calls", "instructions", "function")
+        .bold()
+        .to_string();
+    for (name, profile) in rows {
+        output.push_str(&format!(
+            "\n{:>10} {:>14}  {}",
+            profile.calls, profile.instructions, name
+        ));
+    }
+    output
+}
+
 // Execute compiled code
-fn execute_ir(package: VMInstructionPackage, _dir_stack: &mut DirStack) -> Result<(), VMError> {
+fn execute_ir(
+    package: VMInstructionPackage,
+    _dir_stack: &mut DirStack,
+    script_args: &[String],
+    trace: bool,
+    profile: bool,
+) -> Result<(), VMError> {
     let mut coroutine_pool = VMCoroutinePool::new(true);
+    if trace {
+        coroutine_pool.hook = Some(Box::new(TraceHook::new(Box::new(std::io::stderr()))));
+    }
+    let profile_stats = Rc::new(RefCell::new(HashMap::new()));
+    if profile {
+        coroutine_pool.hook = Some(Box::new(ProfileHook::new(profile_stats.clone())));
+    }
     let mut gc_system = GCSystem::new(None);
 
     let mut default_args_tuple = gc_system.new_object(VMTuple::new(&mut vec![]));
@@ -115,6 +358,7 @@ fn execute_ir(package: VMInstructionPackage, _dir_stack: &mut DirStack) -> Resul
             .get_executor_mut(coro_id)
             .unwrap()
             .get_context_mut(),
+        script_args,
         &mut gc_system,
     );
 
@@ -129,12 +373,11 @@ fn execute_ir(package: VMInstructionPackage, _dir_stack: &mut DirStack) -> Resul
     }
 
     let result = coroutine_pool.run_until_finished(&mut gc_system);
+    if profile {
+        eprintln!("{}", format_profile(&profile_stats.borrow()));
+    }
     if let Err(mut e) = result {
-        eprintln!(
-            "{} {}",
-            "VM Crashed!:".bright_red().underline().bold(),
-            e.to_string()
-        );
+        eprintln!("{}", crash_report(&mut e));
         e.consume_ref();
         main_lambda.drop_ref();
         gc_system.collect();
@@ -159,10 +402,48 @@ fn execute_ir(package: VMInstructionPackage, _dir_stack: &mut DirStack) -> Resul
     Ok(())
 }
 
+// REPL 使用的钩子：在主协程的 __main__ 即将返回时记录其中可见的变量，供之后的输入继续使用
+struct BindingCaptureHook {
+    coroutine_id: isize,
+    excluded: HashSet<String>, // 内置模块与 Out 等每次都会重新注入的变量
+    captured: Rc<RefCell<Vec<(String, GCRef)>>>,
+}
+
+impl ExecutorHook for BindingCaptureHook {
+    fn on_instruction(
+        &mut self,
+        _ip: usize,
+        coroutine_id: isize,
+        opcode: &ProcessedOpcode,
+        executor: &VMExecutor,
+    ) -> HookAction {
+        if coroutine_id != self.coroutine_id
+            || executor.context.function_depth() != 1
+            || VMInstruction::from_opcode(opcode.instruction) != Some(VMInstruction::Return)
+        {
+            return HookAction::Continue;
+        }
+        let mut captured = self.captured.borrow_mut();
+        for (_, value) in captured.iter_mut() {
+            value.drop_ref();
+        }
+        captured.clear();
+        for (name, value) in executor.context.iter_vars() {
+            if self.excluded.contains(name) {
+                continue;
+            }
+            let mut value = value.clone();
+            captured.push((name.clone(), value.clone_ref()));
+        }
+        HookAction::Continue
+    }
+}
+
 fn execute_ir_repl(
     package: VMInstructionPackage,
     gc_system: &mut GCSystem,
     input_arguments: &mut GCRef,
+    bindings: &mut VMContext,
 ) -> Result<GCRef, VMError> {
     let mut coroutine_pool = VMCoroutinePool::new(false);
 
@@ -194,13 +475,11 @@ fn execute_ir_repl(
     main_lambda.drop_ref();
     let coro_id = coroutine_pool.new_coroutine(&mut wrapped, &mut default_args_tuple, gc_system)?;
 
-    let result = inject_builtin_functions(
-        coroutine_pool
-            .get_executor_mut(coro_id)
-            .unwrap()
-            .get_context_mut(),
-        gc_system,
-    );
+    let context = coroutine_pool
+        .get_executor_mut(coro_id)
+        .unwrap()
+        .get_context_mut();
+    let result = inject_builtin_functions(context, &[], gc_system);
 
     if let Err(mut e) = result {
         eprintln!(
@@ -213,13 +492,60 @@ fn execute_ir_repl(
         return Err(VMError::AssertFailed);
     }
 
-    coroutine_pool.run_until_finished(gc_system)?;
+    // 恢复之前输入中定义的变量
+    let excluded: HashSet<String> = context.iter_vars().map(|(name, _)| name.clone()).collect();
+    for (name, value) in bindings.iter_vars() {
+        let mut value = value.clone();
+        if let Err(e) = context.let_var(name, &mut value, gc_system) {
+            wrapped.drop_ref();
+            return Err(VMError::ContextError(e));
+        }
+    }
+
+    let captured = Rc::new(RefCell::new(Vec::new()));
+    coroutine_pool.hook = Some(Box::new(BindingCaptureHook {
+        coroutine_id: coro_id,
+        excluded,
+        captured: captured.clone(),
+    }));
+
+    let mut result = coroutine_pool.run_until_finished(gc_system);
+    for (name, mut value) in captured.borrow_mut().drain(..) {
+        if result.is_ok() {
+            if let Err(e) = bindings.let_var(&name, &mut value, gc_system) {
+                result = Err(VMError::ContextError(e));
+            }
+        }
+        value.drop_ref();
+    }
+    result?;
     gc_system.collect();
 
     Ok(wrapped)
 }
 
-fn run_file(path: &PathBuf) -> Result<(), String> {
+// 读取 .xbc 字节码文件，或将 .xir 文件翻译为字节码
+fn load_instruction_package(path: &Path) -> Result<VMInstructionPackage, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if extension == "xbc" {
+        return VMInstructionPackage::read_from_file(path.to_str().unwrap())
+            .map_err(|e| format!("Error reading bytecode file: {}", e));
+    }
+    let package = IRPackage::read_from_file(path.to_str().unwrap())
+        .map_err(|e| format!("Error reading IR file: {}", e))?;
+    let mut translator = IRTranslator::new(&package);
+    translator
+        .translate()
+        .map_err(|e| format!("IR translation failed: {:?}", e))?;
+    Ok(translator.get_result())
+}
+
+fn run_file(
+    path: &PathBuf,
+    script_args: &[String],
+    trace: bool,
+    profile: bool,
+) -> Result<(), String> {
     let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
     match extension {
         "xir" => {
@@ -246,7 +572,7 @@ fn run_file(path: &PathBuf) -> Result<(), String> {
                     ))
                     .unwrap();
 
-                    match execute_ir(result, &mut dir_stack) {
+                    match execute_ir(result, &mut dir_stack, script_args, trace, profile) {
                         Ok(_) => Ok(()),
                         Err(mut e) => {
                             let err = Err(format!("Execution error: {}", e.to_string())
@@ -273,7 +599,7 @@ fn run_file(path: &PathBuf) -> Result<(), String> {
                             .to_path_buf(),
                     ))
                     .unwrap();
-                    match execute_ir(bytecode, &mut dir_stack) {
+                    match execute_ir(bytecode, &mut dir_stack, script_args, trace, profile) {
                         Ok(_) => Ok(()),
                         Err(mut e) => {
                             let err = Err(format!("Execution error: {}", e.to_string())
@@ -322,7 +648,7 @@ fn run_file(path: &PathBuf) -> Result<(), String> {
                                 .bright_red()
                                 .to_string());
                             };
-                            match execute_ir(result, &mut dir_stack) {
+                            match execute_ir(result, &mut dir_stack, script_args, trace, profile) {
                                 Ok(_) => Ok(()),
                                 Err(mut e) => {
                                     let err = Err(format!("Execution error: {}", e.to_string())
@@ -516,6 +842,15 @@ fn run_repl() -> Result<(), String> {
     println!("{}", "Type 'exit' or 'quit' to exit".bright_blue());
     println!("{}", "Use up/down arrows to navigate history".bright_blue());
     println!("{}", "Press Tab for completion suggestions".bright_blue());
+    println!(
+        "{}",
+        "Use :vars to list bound variables, :type <expr> to show the type of an expression"
+            .bright_blue()
+    );
+    println!(
+        "{}",
+        "Use :save <file> / :load <file> to save or restore bound variables".bright_blue()
+    );
 
     // 定义XLang关键字和自动补全器
     #[derive(rustyline_derive::Helper)]
@@ -672,7 +1007,19 @@ fn run_repl() -> Result<(), String> {
 
     impl Highlighter for XLangHelper {
         fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
-            self.highlighter.highlight(line, pos)
+            let spans = highlight_spans(line, &self.keywords);
+            // 匹配括号的高亮只在括号处插入控制序列，第一个不同的字节即为括号的位置
+            let bracket = match self.highlighter.highlight(line, pos) {
+                Cow::Borrowed(_) => None,
+                Owned(highlighted) => line
+                    .bytes()
+                    .zip(highlighted.bytes())
+                    .position(|(a, b)| a != b),
+            };
+            if spans.is_empty() && bracket.is_none() {
+                return Cow::Borrowed(line);
+            }
+            Owned(colorize_line(line, &spans, bracket))
         }
 
         fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
@@ -692,7 +1039,9 @@ fn run_repl() -> Result<(), String> {
         }
 
         fn highlight_char(&self, line: &str, pos: usize, forced: CmdKind) -> bool {
-            self.highlighter.highlight_char(line, pos, forced)
+            // 需要调用以更新匹配括号的位置；关键字等着色依赖整行，每次输入都重新高亮
+            self.highlighter.highlight_char(line, pos, forced);
+            true
         }
     }
 
@@ -741,6 +1090,9 @@ fn run_repl() -> Result<(), String> {
 
     let mut gc_system = GCSystem::new(None);
     let mut input_arguments = gc_system.new_object(VMTuple::new(&mut vec![]));
+    // 之前的输入中定义的顶层变量
+    let mut bindings = VMContext::new();
+    bindings.new_frame(&Vec::new(), ContextFrameType::FunctionFrame, 0, false);
 
     let mut line_count = 0;
     let mut dir_stack = DirStack::new(None).unwrap();
@@ -769,6 +1121,53 @@ fn run_repl() -> Result<(), String> {
                     break;
                 }
 
+                // 处理以 ':' 开头的元命令
+                if let Some(command) = input.strip_prefix(':') {
+                    let _ = rl.add_history_entry(input);
+                    match command.split_whitespace().next().unwrap_or("") {
+                        "vars" => print_repl_vars(&bindings),
+                        "type" => match command["type".len()..].trim() {
+                            "" => println!("{}", "Usage: :type <expr>".red()),
+                            expr => print_repl_type(
+                                expr,
+                                &mut gc_system,
+                                &mut dir_stack,
+                                &mut input_arguments,
+                                &mut bindings,
+                            ),
+                        },
+                        "save" => match command["save".len()..].trim() {
+                            "" => println!("{}", "Usage: :save <file>".red()),
+                            path => {
+                                match save_repl_bindings(Path::new(path), &bindings, &mut gc_system)
+                                {
+                                    Ok(count) => println!(
+                                        "{}",
+                                        format!("Saved {} variables to {}", count, path).green()
+                                    ),
+                                    Err(e) => println!("{}", e.red()),
+                                }
+                            }
+                        },
+                        "load" => match command["load".len()..].trim() {
+                            "" => println!("{}", "Usage: :load <file>".red()),
+                            path => match load_repl_bindings(
+                                Path::new(path),
+                                &mut bindings,
+                                &mut gc_system,
+                            ) {
+                                Ok(count) => println!(
+                                    "{}",
+                                    format!("Loaded {} variables from {}", count, path).green()
+                                ),
+                                Err(e) => println!("{}", e.red()),
+                            },
+                        },
+                        other => println!("{}", format!("Unknown command: :{}", other).red()),
+                    }
+                    continue;
+                }
+
                 // 添加到输入缓冲区
                 input_buffer.push_str(&line);
                 input_buffer.push('\n');
@@ -824,7 +1223,12 @@ fn run_repl() -> Result<(), String> {
                         let mut translator = IRTranslator::new(&package);
                         if translator.translate().is_ok() {
                             let result = translator.get_result();
-                            match execute_ir_repl(result, &mut gc_system, &mut input_arguments) {
+                            match execute_ir_repl(
+                                result,
+                                &mut gc_system,
+                                &mut input_arguments,
+                                &mut bindings,
+                            ) {
                                 Ok(mut lambda_ref) => {
                                     let executed = lambda_ref.as_type::<VMLambda>();
                                     let result_ref = &mut executed.result;
@@ -901,6 +1305,8 @@ fn run_repl() -> Result<(), String> {
         }
     }
 
+    bindings.drop_all_frames();
+
     // 保存历史
     if let Err(e) = rl.save_history(&history_path) {
         eprintln!(
@@ -911,6 +1317,201 @@ fn run_repl() -> Result<(), String> {
 
     Ok(())
 }
+// REPL 语法高亮的着色类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HighlightKind {
+    Keyword,
+    String,
+    Number,
+}
+
+// 使用词法分析器找出一行代码中需要着色的区间（字节范围），按出现顺序排列
+fn highlight_spans(line: &str, keywords: &HashSet<String>) -> Vec<(Range<usize>, HighlightKind)> {
+    lexer::tokenize(line)
+        .iter()
+        .filter_map(|token| {
+            let kind = match token.token_type {
+                TokenType::NUMBER => HighlightKind::Number,
+                TokenType::STRING | TokenType::BASE64 => HighlightKind::String,
+                TokenType::IDENTIFIER if keywords.contains(token.token) => HighlightKind::Keyword,
+                _ => return None,
+            };
+            let end = token.position + token.origin_token.len();
+            if end > line.len() || !line.is_char_boundary(end) {
+                return None;
+            }
+            Some((token.position..end, kind))
+        })
+        .collect()
+}
+
+// 按区间为一行代码着色，bracket 为需要高亮的匹配括号所在的字节位置
+fn colorize_line(
+    line: &str,
+    spans: &[(Range<usize>, HighlightKind)],
+    bracket: Option<usize>,
+) -> String {
+    let mut output = String::new();
+    let mut last = 0;
+    let push_plain = |output: &mut String, text: &str, offset: usize| match bracket {
+        Some(bracket) if bracket >= offset && bracket < offset + text.len() => {
+            let bracket_len = text[bracket - offset..].chars().next().unwrap().len_utf8();
+            output.push_str(&text[..bracket - offset]);
+            output.push_str(
+                &text[bracket - offset..bracket - offset + bracket_len]
+                    .blue()
+                    .bold()
+                    .to_string(),
+            );
+            output.push_str(&text[bracket - offset + bracket_len..]);
+        }
+        _ => output.push_str(text),
+    };
+    for (range, kind) in spans {
+        if range.start < last {
+            continue;
+        }
+        push_plain(&mut output, &line[last..range.start], last);
+        let text = &line[range.clone()];
+        let colored = match kind {
+            HighlightKind::Keyword => text.bright_magenta().bold(),
+            HighlightKind::String => text.green(),
+            HighlightKind::Number => text.yellow(),
+        };
+        output.push_str(&colored.to_string());
+        last = range.end;
+    }
+    push_plain(&mut output, &line[last..], last);
+    output
+}
+
+// REPL 的 :vars 命令，按名字顺序输出已绑定的变量
+fn print_repl_vars(bindings: &VMContext) {
+    let mut vars: Vec<(&String, &GCRef)> = bindings.iter_vars().collect();
+    if vars.is_empty() {
+        println!("{}", "No variables bound".yellow());
+        return;
+    }
+    vars.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, value) in vars {
+        match try_repr_vmobject(&mut value.clone(), None) {
+            Ok(repr) => println!("{} = {}", name.blue().bold(), repr.bright_white()),
+            Err(mut e) => {
+                println!(
+                    "{} = {}",
+                    name.blue().bold(),
+                    format!("<Unable to repr: {}>", e.to_string()).red()
+                );
+                e.consume_ref();
+            }
+        }
+    }
+}
+
+// REPL 的 :type 命令，求值表达式并输出其类型名，结果不会记录到 Out 中
+fn print_repl_type(
+    expr: &str,
+    gc_system: &mut GCSystem,
+    dir_stack: &mut DirStack,
+    input_arguments: &mut GCRef,
+    bindings: &mut VMContext,
+) {
+    let package = match build_code(expr, dir_stack) {
+        Ok(package) => package,
+        Err(e) => {
+            println!("{}", format!("Compilation error: {}", e).red().bold());
+            return;
+        }
+    };
+    let mut translator = IRTranslator::new(&package);
+    if translator.translate().is_err() {
+        println!("{}", "IR translation failed.".red());
+        return;
+    }
+    let package = translator.get_result();
+    match execute_ir_repl(package, gc_system, input_arguments, bindings) {
+        Ok(mut lambda_ref) => {
+            let name = type_name_of(&lambda_ref.as_const_type::<VMLambda>().result);
+            println!("{}", name.bright_white().bold());
+            lambda_ref.drop_ref();
+        }
+        Err(mut e) => {
+            println!(
+                "{}",
+                format!("Execution error: {}", e.to_string())
+                    .red()
+                    .bold()
+                    .underline()
+            );
+            e.consume_ref();
+        }
+    }
+    gc_system.collect();
+}
+
+// REPL 的 :save 命令，将可序列化的变量以 JSON 对象的形式写入文件
+// lambda 等无法序列化的变量会被跳过并给出警告，返回写入的变量个数
+fn save_repl_bindings(
+    path: &Path,
+    bindings: &VMContext,
+    gc_system: &mut GCSystem,
+) -> Result<usize, String> {
+    let mut vars: Vec<(&String, &GCRef)> = bindings.iter_vars().collect();
+    vars.sort_by(|a, b| a.0.cmp(b.0));
+    let mut saved = serde_json::Map::new();
+    for (name, value) in vars {
+        match to_json_value(&mut value.clone(), gc_system) {
+            Ok(json) => {
+                saved.insert(name.clone(), json);
+            }
+            Err(mut e) => {
+                println!(
+                    "{}",
+                    format!("Warning: skipping '{}': {}", name, e.to_string()).yellow()
+                );
+                e.consume_ref();
+            }
+        }
+    }
+    let count = saved.len();
+    let content = serde_json::to_string_pretty(&serde_json::Value::Object(saved))
+        .map_err(|e| format!("Failed to serialize variables: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(count)
+}
+
+// REPL 的 :load 命令，读取 :save 写入的文件并绑定其中的变量，返回绑定的变量个数
+fn load_repl_bindings(
+    path: &Path,
+    bindings: &mut VMContext,
+    gc_system: &mut GCSystem,
+) -> Result<usize, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let saved = match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(serde_json::Value::Object(saved)) => saved,
+        Ok(_) => {
+            return Err(format!(
+                "{} does not contain saved variables",
+                path.display()
+            ))
+        }
+        Err(e) => return Err(format!("Failed to parse {}: {}", path.display(), e)),
+    };
+    let count = saved.len();
+    for (name, json) in saved {
+        let mut value = json_to_vmobject(json, gc_system).map_err(|mut e| {
+            let message = format!("Failed to restore '{}': {}", name, e.to_string());
+            e.consume_ref();
+            message
+        })?;
+        let result = bindings.let_var(&name, &mut value, gc_system);
+        value.drop_ref();
+        result.map_err(|e| e.to_string())?;
+    }
+    Ok(count)
+}
+
 // Function to check if input is complete
 fn is_input_complete(input: &str) -> bool {
     // 1. Check for unbalanced brackets/braces/parens and unterminated strings
@@ -1021,72 +1622,78 @@ fn main() {
                 std::process::exit(1);
             }
         }
-        Commands::Run { input } => {
-            if let Err(e) = run_file(&input) {
+        Commands::Run {
+            input,
+            trace,
+            profile,
+            args,
+        } => {
+            if let Err(e) = run_file(&input, &args, trace, profile) {
                 eprintln!("{}", e);
                 std::process::exit(1);
             }
         }
         Commands::DisplayIR { input } => {
-            match IRPackage::read_from_file(input.to_str().unwrap()) {
-                Ok(package) => {
-                    let ir = package.instructions;
-                    let source = package.source.unwrap_or_else(|| "No source".to_string());
-                    let function_ips = package.function_ips;
-
-                    // 打印 IR 包基本信息
-                    println!("{}", "IR Package Information:".bright_blue().bold());
-                    println!(
-                        "Source:\n{}",
-                        if source == "No source" {
-                            source.yellow()
-                        } else {
-                            source.bright_green()
-                        }
-                    );
-                    println!("\n{}", "Function entry points:".bright_blue());
+            let package = match load_instruction_package(&input) {
+                Ok(package) => package,
+                Err(e) => {
+                    eprintln!("{}", e.bright_red());
+                    std::process::exit(1);
+                }
+            };
+            let source = package
+                .get_source()
+                .clone()
+                .unwrap_or_else(|| "No source".to_string());
+            let mut function_ips: Vec<(&String, &usize)> = package.get_table().iter().collect();
+            function_ips.sort_by_key(|(_, ip)| **ip);
+
+            // 打印指令包基本信息
+            println!("{}", "IR Package Information:".bright_blue().bold());
+            println!(
+                "Source:\n{}",
+                if source == "No source" {
+                    source.yellow()
+                } else {
+                    source.bright_green()
+                }
+            );
+            println!("\n{}", "Function entry points:".bright_blue());
 
-                    // 打印函数入口点信息
-                    for (name, ip) in &function_ips {
-                        println!("  {} => {}", name.bright_yellow(), ip);
-                    }
+            // 打印函数入口点信息
+            for (name, ip) in &function_ips {
+                println!("  {} => {}", name.bright_yellow(), ip);
+            }
 
-                    println!("\n{}", "Instructions:".bright_blue().bold());
-
-                    // 查找每条指令所属的函数
-                    for (i, (debug_info, instruction)) in ir.iter().enumerate() {
-                        // 检查是否是函数入口点
-                        for (name, &ip) in &function_ips {
-                            if ip == i {
-                                println!(
-                                    "\n{} {}:",
-                                    "Function".bright_magenta().bold(),
-                                    name.bright_cyan().bold()
-                                );
-                            }
-                        }
+            println!("\n{}", "Instructions:".bright_blue().bold());
 
-                        // 显示源代码位置（如果有）
-                        let pos_info = if debug_info.code_position != 0 {
-                            format!("[pos: {}]", debug_info.code_position).bright_black()
-                        } else {
-                            "".normal()
-                        };
-
-                        // 格式化输出指令
-                        let instr_str = format!(
-                            "{:06}: {:<30} {}",
-                            i,
-                            format!("{:?}", instruction),
-                            pos_info
+            for instruction in package.disassemble() {
+                // 检查是否是函数入口点
+                for (name, &ip) in &function_ips {
+                    if ip == instruction.index {
+                        println!(
+                            "\n{} {}:",
+                            "Function".bright_magenta().bold(),
+                            name.bright_cyan().bold()
                         );
-                        println!("{}", instr_str.bright_white());
                     }
                 }
-                Err(e) => {
-                    eprintln!("{}", format!("Error reading IR file: {}", e).bright_red());
-                    std::process::exit(1);
-                }
+
+                // 显示源代码位置（如果有）
+                let pos_info = match instruction.debug_position {
+                    Some(pos) if pos != 0 => format!("[pos: {}]", pos).bright_black(),
+                    _ => "".normal(),
+                };
+
+                // 格式化输出指令
+                let instr_str = format!(
+                    "{:06}: {:<30} {}",
+                    instruction.index,
+                    format!("{} {}", instruction.mnemonic, instruction.operands.join(", "))
+                        .trim_end(),
+                    pos_info
+                );
+                println!("{}", instr_str.bright_white());
             }
         }
         Commands::Translate { input, output } => {
@@ -1111,3 +1718,299 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 编译并以注入了内置模块的主协程运行脚本
+    fn run_script(code: &str, gc_system: &mut GCSystem) -> Result<(), VMError> {
+        run_script_with_args(code, &[], gc_system)
+    }
+
+    fn run_script_with_args(
+        code: &str,
+        script_args: &[String],
+        gc_system: &mut GCSystem,
+    ) -> Result<(), VMError> {
+        run_script_with_hook(code, script_args, None, gc_system)
+    }
+
+    fn run_script_with_hook(
+        code: &str,
+        script_args: &[String],
+        hook: Option<Box<dyn ExecutorHook>>,
+        gc_system: &mut GCSystem,
+    ) -> Result<(), VMError> {
+        let mut dir_stack = DirStack::new(None).expect("Failed to create dir stack");
+        let ir_package = build_code(code, &mut dir_stack).expect("Failed to build code");
+        let mut translator = IRTranslator::new(&ir_package);
+        translator.translate().expect("Failed to translate IR");
+        let package = translator.get_result();
+
+        let mut default_args_tuple = gc_system.new_object(VMTuple::new(&mut vec![]));
+        let mut lambda_instructions = gc_system.new_object(VMInstructions::new(&package));
+        let mut lambda_result = gc_system.new_object(VMNull::new());
+        let mut main_lambda = gc_system.new_object(VMLambda::new(
+            0,
+            "__main__".to_string(),
+            &mut default_args_tuple,
+            None,
+            None,
+            &mut VMLambdaBody::VMInstruction(lambda_instructions.clone()),
+            &mut lambda_result,
+            false,
+        ));
+        lambda_instructions.drop_ref();
+        lambda_result.drop_ref();
+
+        let mut coroutine_pool = VMCoroutinePool::new(true);
+        coroutine_pool.hook = hook;
+        main_lambda.clone_ref();
+        let coro_id = coroutine_pool
+            .new_coroutine(&mut main_lambda, &mut default_args_tuple, gc_system)
+            .expect("Failed to create coroutine");
+        inject_builtin_functions(
+            coroutine_pool
+                .get_executor_mut(coro_id)
+                .unwrap()
+                .get_context_mut(),
+            script_args,
+            gc_system,
+        )
+        .expect("Failed to inject builtins");
+
+        let result = coroutine_pool.run_until_finished(gc_system);
+        main_lambda.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_assert_message_in_crash_report() {
+        let code = r#"
+        @required io;
+        io.assert(1 + 1 == 2, "unreachable");
+        io.assert(1 + 1 == 3, "math is broken");
+        "#;
+        let mut gc_system = GCSystem::new(None);
+        let mut err = run_script(code, &mut gc_system).expect_err("io.assert should crash the VM");
+        let report = crash_report(&mut err);
+        err.consume_ref();
+        assert!(report.contains("VM Crashed at 4:"));
+        assert!(report.contains("Assertion failed: math is broken"));
+        assert!(!report.contains("Assertion failed: unreachable"));
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    // 多处共享的输出缓冲区，用于捕获 TraceHook 的输出
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace_hook_prints_instructions() {
+        let cli = Cli::try_parse_from(["xlang", "run", "--trace", "script.x"])
+            .expect("Failed to parse run command");
+        assert!(matches!(cli.command, Commands::Run { trace: true, .. }));
+
+        let buffer = SharedBuffer::default();
+        let hook = TraceHook::new(Box::new(buffer.clone()));
+        let mut gc_system = GCSystem::new(None);
+        if let Err(mut err) =
+            run_script_with_hook("x := 40 + 2;", &[], Some(Box::new(hook)), &mut gc_system)
+        {
+            let report = crash_report(&mut err);
+            err.consume_ref();
+            panic!("{}", report);
+        }
+        let output = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+        assert!(output.lines().count() > 0);
+        assert!(output.contains("LoadInt64"), "{}", output);
+        assert!(output.contains("StoreVar"), "{}", output);
+        assert!(output.contains("top: 42"), "{}", output);
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_profile_hook_counts_calls() {
+        let cli = Cli::try_parse_from(["xlang", "run", "--profile", "script.x"])
+            .expect("Failed to parse run command");
+        assert!(matches!(cli.command, Commands::Run { profile: true, .. }));
+
+        let stats = Rc::new(RefCell::new(HashMap::new()));
+        let hook = ProfileHook::new(stats.clone());
+        let mut gc_system = GCSystem::new(None);
+        let code = r#"
+            inc := (x?) -> { return x + 1 };
+            i := 0;
+            while (i < 7) { i = inc(i) };
+        "#;
+        if let Err(mut err) = run_script_with_hook(code, &[], Some(Box::new(hook)), &mut gc_system)
+        {
+            let report = crash_report(&mut err);
+            err.consume_ref();
+            panic!("{}", report);
+        }
+        let stats = stats.borrow();
+        assert_eq!(stats["__main__"].calls, 1);
+        let (name, inc) = stats
+            .iter()
+            .find(|(name, _)| name.as_str() != "__main__")
+            .expect("the called function should be profiled");
+        assert_eq!(inc.calls, 7, "{}", name);
+        assert!(inc.instructions >= 7 * 3);
+        // 表格按执行指令数降序排列，__main__ 在前
+        let table = format_profile(&stats);
+        let main_line = table.find("__main__").unwrap();
+        assert!(main_line < table.find(name.as_str()).unwrap(), "{}", table);
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_repl_bindings_save_load_round_trip() {
+        let mut gc_system = GCSystem::new(None);
+        let mut bindings = VMContext::new();
+        bindings.new_frame(&Vec::new(), ContextFrameType::FunctionFrame, 0, false);
+        let mut items: Vec<GCRef> = [1, 2, 3]
+            .iter()
+            .map(|v| gc_system.new_object(VMInt::new(*v)))
+            .collect();
+        let mut ints = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+        let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
+        let mut result = gc_system.new_object(VMNull::new());
+        let mut lambda = gc_system.new_object(VMLambda::new(
+            0,
+            "<test>::identity".to_string(),
+            &mut params,
+            None,
+            None,
+            &mut VMLambdaBody::VMNativeFunction(|_, _, args, _| Ok(args.clone_ref())),
+            &mut result,
+            false,
+        ));
+        bindings.let_var("ints", &mut ints, &mut gc_system).unwrap();
+        bindings.let_var("f", &mut lambda, &mut gc_system).unwrap();
+
+        let path = std::env::temp_dir().join(format!(
+            "xlang_repl_bindings_test_{}.json",
+            std::process::id()
+        ));
+        // lambda 无法序列化，只保存了 ints
+        assert_eq!(
+            save_repl_bindings(&path, &bindings, &mut gc_system).unwrap(),
+            1
+        );
+
+        let mut restored = VMContext::new();
+        restored.new_frame(&Vec::new(), ContextFrameType::FunctionFrame, 0, false);
+        assert_eq!(
+            load_repl_bindings(&path, &mut restored, &mut gc_system).unwrap(),
+            1
+        );
+        std::fs::remove_file(&path).unwrap();
+        let mut value = restored.get_var("ints").unwrap();
+        assert_eq!(try_repr_vmobject(&mut value, None).unwrap(), "(1, 2, 3)");
+        assert!(restored.get_var("f").is_err());
+
+        value.drop_ref();
+        bindings.drop_all_frames();
+        restored.drop_all_frames();
+        for obj in items
+            .iter_mut()
+            .chain([&mut ints, &mut params, &mut result, &mut lambda])
+        {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_highlight_spans() {
+        let keywords: HashSet<String> = ["if", "return"].iter().map(|k| k.to_string()).collect();
+        assert_eq!(
+            highlight_spans("if x > 10 { return \"a(b)\"; }", &keywords),
+            vec![
+                (0..2, HighlightKind::Keyword),
+                (7..9, HighlightKind::Number),
+                (12..18, HighlightKind::Keyword),
+                (19..25, HighlightKind::String),
+            ]
+        );
+        // 区间按字节计算，非 ASCII 字符不会导致错位
+        assert_eq!(
+            highlight_spans("\"é\" + 1.5", &keywords),
+            vec![
+                (0..4, HighlightKind::String),
+                (7..10, HighlightKind::Number)
+            ]
+        );
+        assert!(highlight_spans("foo := bar;", &keywords).is_empty());
+        // 输入到一半的行（如未闭合的字符串）也不会出错
+        highlight_spans("x := \"unterminated", &keywords);
+
+        assert_eq!(colorize_line("a + b", &[], None), "a + b");
+    }
+
+    #[test]
+    fn test_run_passes_trailing_args_to_os_argv() {
+        let cli = Cli::try_parse_from(["xlang", "run", "script.x", "input.txt", "--verbose"])
+            .expect("Failed to parse run command");
+        let script_args = match cli.command {
+            Commands::Run { input, args, .. } => {
+                assert_eq!(input, PathBuf::from("script.x"));
+                args
+            }
+            _ => panic!("expected the run command"),
+        };
+        assert_eq!(script_args, ["input.txt", "--verbose"]);
+
+        let code = r#"
+        @required io;
+        @required os;
+        args := os.argv();
+        io.assert(lengthof args == 2, "argv should hold the trailing args");
+        io.assert(args[0] == "input.txt", "first arg");
+        io.assert(args[1] == "--verbose", "second arg");
+        "#;
+        let mut gc_system = GCSystem::new(None);
+        if let Err(mut err) = run_script_with_args(code, &script_args, &mut gc_system) {
+            let report = crash_report(&mut err);
+            err.consume_ref();
+            panic!("{}", report);
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_traceback_shows_only_innermost_frames() {
+        let frames: Vec<TraceFrame> = (0..10000)
+            .map(|depth| TraceFrame {
+                signature: format!("Main::function_{}", depth),
+                code_position: None,
+                position: None,
+            })
+            .collect();
+        let traceback = format_traceback(&frames);
+        assert_eq!(traceback.lines().count(), 2 + TRACEBACK_SHOWN_FRAMES);
+        assert!(traceback.contains("9980 more frames"));
+        assert!(!traceback.contains("Main::function_9979"));
+        assert!(traceback.contains("Main::function_9980"));
+        assert!(traceback.contains("Main::function_9999"));
+    }
+}
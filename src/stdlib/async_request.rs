@@ -20,7 +20,7 @@ use xlang_vm_core::{
     gc::{GCRef, GCSystem},
 }; // Import necessary reqwest types
 
-static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+pub(super) static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
     tokio::runtime::Builder::new_multi_thread()
         .enable_all() // 启用所有 Tokio 功能 (IO, time)
         .build()
@@ -28,7 +28,7 @@ static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
 });
 
 // 重用 Reqwest Client 以利用连接池等优化
-static CLIENT: Lazy<Client> = Lazy::new(|| {
+pub(super) static CLIENT: Lazy<Client> = Lazy::new(|| {
     reqwest::Client::builder()
         .build()
         .expect("Failed to create Reqwest client")
@@ -303,15 +303,71 @@ impl VMNativeGeneratorFunction for RequestGenerator {
     }
 }
 
+// 将 (key : value, key => value, ...) 形式的元组解析为 HeaderMap，key / value 会被转换为字符串
+pub(super) fn parse_headers(header_ref: &mut GCRef) -> Result<HeaderMap, VMVariableError> {
+    let mut headers = HeaderMap::new();
+    let header_tuple = header_ref.as_type::<VMTuple>();
+    for header_item in &mut header_tuple.values {
+        let (h_key_str, h_val_str) = if header_item.isinstance::<VMKeyVal>() {
+            let kv = header_item.as_type::<VMKeyVal>();
+            (
+                try_to_string_vmobject(kv.get_key(), None)?,
+                try_to_string_vmobject(kv.get_value(), None)?,
+            )
+        } else if header_item.isinstance::<VMNamed>() {
+            let named_h = header_item.as_type::<VMNamed>();
+            (
+                try_to_string_vmobject(named_h.get_key(), None)?,
+                try_to_string_vmobject(named_h.get_value(), None)?,
+            )
+        } else {
+            return Err(VMVariableError::TypeError(
+                header_item.clone_ref(),
+                "Header items must be key:value or key=>value pairs".to_string(),
+            ));
+        };
+
+        let header_name = HeaderName::from_bytes(h_key_str.as_bytes()).map_err(|e| {
+            VMVariableError::DetailedError(format!("Invalid header name '{}': {}", h_key_str, e))
+        })?;
+        // Use from_str for HeaderValue as it handles validation better for common cases
+        let header_value = HeaderValue::from_str(&h_val_str).map_err(|e| {
+            VMVariableError::DetailedError(format!(
+                "Invalid header value for '{}': {}",
+                h_key_str, e
+            ))
+        })?;
+        // HeaderMap::append allows multiple values for the same header name
+        headers.append(header_name, header_value);
+    }
+    Ok(headers)
+}
+
+// 按顺序查找第一个存在的命名参数，返回其值与实际使用的参数名
+fn get_named_option(
+    tuple_obj: &mut VMTuple,
+    names: &[&'static str],
+    gc_system: &mut GCSystem,
+) -> Option<(GCRef, &'static str)> {
+    names.iter().find_map(|name| {
+        tuple_obj
+            .get_member_by_string(name, gc_system)
+            .ok()
+            .map(|value| (value, *name))
+    })
+}
+
 // --- VM 可调用函数 ---
 
 /// 创建一个异步 HTTP 请求生成器。
 /// 接受一个元组参数，包含命名参数：
 /// - url: string (必需)
 /// - method: string (可选, 默认 "GET")
-/// - header: tuple (可选, 元素为 key:value 或 key=>value, key/value 需为 string)
+/// - headers / header: tuple (可选, 元素为 key:value 或 key=>value, key/value 需为 string)
 /// - body: bytes | string | null (可选)
-/// - timeout_ms: int (可选, 超时毫秒数)
+/// - timeout / timeout_ms: int (可选, 超时毫秒数)
+///
+/// 超时或网络错误不会中断脚本，生成器的结果中 `error_message` 为错误描述（超时为 "Request timed out"）
 pub fn request(
     _self_object: Option<&mut GCRef>,
     _capture: Option<&mut GCRef>,
@@ -362,50 +418,16 @@ pub fn request(
 
     // --- 解析 Headers (可选) ---
     let mut headers = HeaderMap::new();
-    if let Ok(mut header_ref) = tuple_obj.get_member_by_string("header", gc_system) {
+    if let Some((mut header_ref, name)) =
+        get_named_option(tuple_obj, &["headers", "header"], gc_system)
+    {
         if !header_ref.isinstance::<VMTuple>() {
             return Err(VMVariableError::TypeError(
                 header_ref.clone_ref(),
-                "Named argument 'header' must be a tuple".to_string(),
+                format!("Named argument '{}' must be a tuple", name),
             ));
         }
-        let header_tuple = header_ref.as_type::<VMTuple>();
-        for header_item in &mut header_tuple.values {
-            let (h_key_str, h_val_str) = if header_item.isinstance::<VMKeyVal>() {
-                let kv = header_item.as_type::<VMKeyVal>();
-                (
-                    try_to_string_vmobject(kv.get_key(), None)?,
-                    try_to_string_vmobject(kv.get_value(), None)?,
-                )
-            } else if header_item.isinstance::<VMNamed>() {
-                let named_h = header_item.as_type::<VMNamed>();
-                (
-                    try_to_string_vmobject(named_h.get_key(), None)?,
-                    try_to_string_vmobject(named_h.get_value(), None)?,
-                )
-            } else {
-                return Err(VMVariableError::TypeError(
-                    header_item.clone_ref(),
-                    "Header items must be key:value or key=>value pairs".to_string(),
-                ));
-            };
-
-            let header_name = HeaderName::from_bytes(h_key_str.as_bytes()).map_err(|e| {
-                VMVariableError::DetailedError(format!(
-                    "Invalid header name '{}': {}",
-                    h_key_str, e
-                ))
-            })?;
-            // Use from_str for HeaderValue as it handles validation better for common cases
-            let header_value = HeaderValue::from_str(&h_val_str).map_err(|e| {
-                VMVariableError::DetailedError(format!(
-                    "Invalid header value for '{}': {}",
-                    h_key_str, e
-                ))
-            })?;
-            // HeaderMap::append allows multiple values for the same header name
-            headers.append(header_name, header_value);
-        }
+        headers = parse_headers(&mut header_ref)?;
     }
 
     // --- 解析 Body (可选) ---
@@ -433,7 +455,9 @@ pub fn request(
 
     // --- 解析 Timeout (可选) ---
     let mut timeout_opt: Option<Duration> = None;
-    if let Ok(mut timeout_ref) = tuple_obj.get_member_by_string("timeout_ms", gc_system) {
+    if let Some((mut timeout_ref, name)) =
+        get_named_option(tuple_obj, &["timeout", "timeout_ms"], gc_system)
+    {
         if timeout_ref.isinstance::<VMInt>() {
             let ms = timeout_ref.as_const_type::<VMInt>().value;
             if ms > 0 {
@@ -442,15 +466,16 @@ pub fn request(
                 // Treat 0 as no timeout (or default Reqwest timeout)
                 timeout_opt = None;
             } else {
-                return Err(VMVariableError::DetailedError(
-                    "Named argument 'timeout_ms' must be a non-negative integer".to_string(),
-                ));
+                return Err(VMVariableError::DetailedError(format!(
+                    "Named argument '{}' must be a non-negative integer",
+                    name
+                )));
             }
         } else if !timeout_ref.isinstance::<VMNull>() {
             // Allow null to explicitly mean no timeout
             return Err(VMVariableError::TypeError(
                 timeout_ref.clone_ref(),
-                "Named argument 'timeout_ms' must be an integer or null".to_string(),
+                format!("Named argument '{}' must be an integer or null", name),
             ));
         }
     }
@@ -509,3 +534,65 @@ pub fn get_request_functions() -> Vec<(
 )> {
     vec![("request", request)]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    fn named(key: &str, value: &mut GCRef, gc_system: &mut GCSystem) -> GCRef {
+        let mut key = gc_system.new_object(VMString::new(key));
+        let named = gc_system.new_object(VMNamed::new(&mut key, value));
+        key.drop_ref();
+        value.drop_ref();
+        named
+    }
+
+    #[test]
+    fn test_timeout_resolves_to_error_message() {
+        // 只接受连接、从不响应的服务端
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let _server = std::thread::spawn(move || {
+            let _connections: Vec<_> = listener.incoming().take(1).collect();
+            std::thread::sleep(Duration::from_secs(5));
+        });
+
+        let mut gc_system = GCSystem::new(None);
+        let mut url = gc_system.new_object(VMString::new(&format!("http://127.0.0.1:{}/", port)));
+        let mut url = named("url", &mut url, &mut gc_system);
+        let mut timeout = gc_system.new_object(VMInt::new(1));
+        let mut timeout = named("timeout", &mut timeout, &mut gc_system);
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut url, &mut timeout]));
+        let mut lambda = request(None, None, &mut args, &mut gc_system).unwrap();
+
+        let VMLambdaBody::VMNativeGeneratorFunction(ref mut generator) =
+            lambda.as_type::<VMLambda>().lambda_body
+        else {
+            panic!("request should return a generator lambda");
+        };
+        let generator = Arc::get_mut(generator).unwrap();
+        generator.init(&mut args, &mut gc_system).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !generator.is_done() {
+            assert!(Instant::now() < deadline, "request did not time out");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let mut result = generator.get_result(&mut gc_system).unwrap();
+        let mut error_message = result
+            .as_type::<VMTuple>()
+            .get_member_by_string("error_message", &mut gc_system)
+            .unwrap();
+        assert_eq!(
+            try_to_string_vmobject(&mut error_message, None).unwrap(),
+            "Request timed out"
+        );
+
+        for obj in [&mut url, &mut timeout, &mut args, &mut lambda, &mut result] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
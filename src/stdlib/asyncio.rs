@@ -1,6 +1,12 @@
+use std::{sync::Arc, time::Duration};
+
 use super::check_if_tuple;
 use xlang_vm_core::{
-    executor::variable::{VMBoolean, VMCoroutineStatus, VMLambda, VMTuple, VMVariableError},
+    executor::variable::{
+        VMBoolean, VMChannel, VMCoroutineStatus, VMInt, VMLambda, VMLambdaBody,
+        VMNativeGeneratorFunction, VMNull, VMTuple, VMVariableError,
+    },
+    executor::vm::{call_lambda_with_timeout, VMError},
     gc::{GCRef, GCSystem},
 };
 
@@ -223,6 +229,294 @@ pub fn is_crashed(
     Ok(obj)
 }
 // Helper to provide functions for registration
+// asyncio.recv 返回的生成器，通道为空时每一步都让出协程，直到取到一个值
+// channel 不持有引用计数，由生成器所在 lambda 的 capture 保证其存活
+#[derive(Debug)]
+struct RecvGenerator {
+    channel: GCRef,
+    received: Option<GCRef>,
+    done: bool,
+}
+
+impl VMNativeGeneratorFunction for RecvGenerator {
+    fn init(&mut self, _arg: &mut GCRef, _gc_system: &mut GCSystem) -> Result<(), VMVariableError> {
+        if let Some(mut received) = self.received.take() {
+            received.drop_ref();
+        }
+        self.done = false;
+        Ok(())
+    }
+
+    fn step(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        if !self.done {
+            self.received = self.channel.as_type::<VMChannel>().try_recv();
+            self.done = self.received.is_some();
+        }
+        Ok(gc_system.new_object(VMNull::new()))
+    }
+
+    fn get_result(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        match self.received.take() {
+            Some(received) => Ok(received),
+            None => Ok(gc_system.new_object(VMNull::new())),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn clone_generator(&self) -> Arc<Box<dyn VMNativeGeneratorFunction>> {
+        shared_generator(RecvGenerator {
+            channel: self.channel.clone(),
+            received: None,
+            done: false,
+        })
+    }
+}
+
+// asyncio.send 返回的生成器，通道已满时每一步都让出协程，直到值被放入通道
+// channel 与 value 由生成器所在 lambda 的 capture 保证存活
+#[derive(Debug, Clone)]
+struct SendGenerator {
+    channel: GCRef,
+    value: GCRef,
+    done: bool,
+}
+
+impl VMNativeGeneratorFunction for SendGenerator {
+    fn init(&mut self, _arg: &mut GCRef, _gc_system: &mut GCSystem) -> Result<(), VMVariableError> {
+        self.done = false;
+        Ok(())
+    }
+
+    fn step(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        if !self.done {
+            self.done = self
+                .channel
+                .as_type::<VMChannel>()
+                .try_send(&mut self.value);
+        }
+        Ok(gc_system.new_object(VMNull::new()))
+    }
+
+    fn get_result(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        Ok(gc_system.new_object(VMNull::new()))
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn clone_generator(&self) -> Arc<Box<dyn VMNativeGeneratorFunction>> {
+        shared_generator(SendGenerator {
+            done: false,
+            ..self.clone()
+        })
+    }
+}
+
+// VMLambdaBody 要求以 Arc 保存生成器，而持有 GCRef 的生成器不是 Send / Sync，
+// 这些生成器只会在所属的执行器线程中使用
+#[allow(clippy::arc_with_non_send_sync)]
+fn shared_generator(
+    generator: impl VMNativeGeneratorFunction + 'static,
+) -> Arc<Box<dyn VMNativeGeneratorFunction>> {
+    Arc::new(Box::new(generator))
+}
+
+// 把生成器包装为无参数的 lambda，capture 用于保持生成器引用的对象存活
+fn new_generator_lambda(
+    signature: &str,
+    generator: impl VMNativeGeneratorFunction + 'static,
+    capture: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> GCRef {
+    let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
+    let mut result = gc_system.new_object(VMNull::new());
+    let lambda = gc_system.new_object(VMLambda::new(
+        0,
+        signature.to_string(),
+        &mut params,
+        Some(capture),
+        None,
+        &mut VMLambdaBody::VMNativeGeneratorFunction(shared_generator(generator)),
+        &mut result,
+        false,
+    ));
+    params.drop_ref();
+    result.drop_ref();
+    lambda
+}
+
+fn check_channel_arg(tuple: &mut GCRef, func_name: &str) -> Result<GCRef, VMVariableError> {
+    let channel = &mut tuple.as_type::<VMTuple>().values[0];
+    if !channel.isinstance::<VMChannel>() {
+        return Err(VMVariableError::TypeError(
+            channel.clone_ref(),
+            format!("First argument to {} must be a channel", func_name),
+        ));
+    }
+    Ok(channel.clone())
+}
+
+// asyncio.channel(capacity)，创建容量为 capacity 的有界通道
+pub fn channel(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "channel expected 1 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let arg = &mut tuple_obj.values[0];
+    if !arg.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            arg.clone_ref(),
+            "Argument to channel must be an integer".to_string(),
+        ));
+    }
+    let capacity = arg.as_const_type::<VMInt>().value;
+    if capacity < 1 {
+        return Err(VMVariableError::ValueError(
+            arg.clone_ref(),
+            format!("Channel capacity must be at least 1, got {}", capacity),
+        ));
+    }
+    Ok(gc_system.new_object(VMChannel::new(capacity as usize)))
+}
+
+// asyncio.send(chan, value)，返回一个生成器 lambda，调用它会在通道满时让出协程直到放入成功
+// 用法：asyncio.send(chan, value)();
+pub fn send(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "send expected 2 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let channel = check_channel_arg(tuple, "send")?;
+    let value = tuple.as_const_type::<VMTuple>().values[1].clone();
+    let generator = SendGenerator {
+        channel,
+        value,
+        done: false,
+    };
+    Ok(new_generator_lambda(
+        "<builtins>::send_generator",
+        generator,
+        tuple,
+        gc_system,
+    ))
+}
+
+// asyncio.recv(chan)，返回一个生成器 lambda，调用它会在通道空时让出协程，结果为取到的值
+// 用法：value := asyncio.recv(chan)();
+pub fn recv(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "recv expected 1 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let channel = check_channel_arg(tuple, "recv")?;
+    let generator = RecvGenerator {
+        channel,
+        received: None,
+        done: false,
+    };
+    Ok(new_generator_lambda(
+        "<builtins>::recv_generator",
+        generator,
+        tuple,
+        gc_system,
+    ))
+}
+
+// asyncio.with_timeout(lambda, milliseconds)，在独立的协程池中运行 lambda
+// 超时后取消该协程并返回 null，否则返回 lambda 的结果
+pub fn with_timeout(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "with_timeout expected 2 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    if !tuple_obj.values[0].isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            tuple_obj.values[0].clone_ref(),
+            "First argument to with_timeout must be a VMLambda".to_string(),
+        ));
+    }
+    let timeout_arg = &mut tuple_obj.values[1];
+    if !timeout_arg.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            timeout_arg.clone_ref(),
+            "Timeout for with_timeout must be an integer (milliseconds)".to_string(),
+        ));
+    }
+    let milliseconds = timeout_arg.as_const_type::<VMInt>().value;
+    if milliseconds <= 0 {
+        return Err(VMVariableError::ValueError(
+            timeout_arg.clone_ref(),
+            format!("Timeout must be positive, got {}", milliseconds),
+        ));
+    }
+
+    let mut lambda = tuple_obj.values[0].clone();
+    let mut args = gc_system.new_object(VMTuple::new(&mut vec![]));
+    let result = call_lambda_with_timeout(
+        &mut lambda,
+        &mut args,
+        Duration::from_millis(milliseconds as u64),
+        gc_system,
+    );
+    args.drop_ref();
+    match result.map_err(VMError::into_variable_error)? {
+        Some(result) => Ok(result),
+        None => Ok(gc_system.new_object(VMNull::new())),
+    }
+}
+
 pub fn get_asyncio_functions() -> Vec<(
     &'static str,
     fn(
@@ -239,5 +533,9 @@ pub fn get_asyncio_functions() -> Vec<(
         ("is_running", is_running),
         ("is_pending", is_pending),
         ("is_crashed", is_crashed),
+        ("channel", channel),
+        ("send", send),
+        ("recv", recv),
+        ("with_timeout", with_timeout),
     ]
 }
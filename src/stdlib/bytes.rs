@@ -0,0 +1,207 @@
+use xlang_vm_core::{
+    executor::variable::{VMBytes, VMInt, VMTuple, VMVariableError},
+    gc::{GCRef, GCSystem},
+};
+
+use super::{check_arg_count, check_if_tuple};
+
+fn check_bytes_arg(
+    tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+    arg_name: &str,
+) -> Result<(), VMVariableError> {
+    let arg_obj = &mut tuple.as_type::<VMTuple>().values[index];
+    if !arg_obj.isinstance::<VMBytes>() {
+        return Err(VMVariableError::TypeError(
+            arg_obj.clone_ref(),
+            format!("Argument '{}' for {} must be bytes", arg_name, func_name),
+        ));
+    }
+    Ok(())
+}
+
+fn get_int_arg(
+    tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+    arg_name: &str,
+) -> Result<i64, VMVariableError> {
+    let arg_obj = &mut tuple.as_type::<VMTuple>().values[index];
+    if !arg_obj.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            arg_obj.clone_ref(),
+            format!("Argument '{}' for {} must be an int", arg_name, func_name),
+        ));
+    }
+    Ok(arg_obj.as_const_type::<VMInt>().value)
+}
+
+// bytes.splice(bytes, start, end, replacement)，用 replacement 原地替换 [start, end) 区间
+// 与 `bytes = range : value` 不同，replacement 的长度可以与区间不同，缓冲区会随之变长或变短
+// 返回被修改的 bytes 本身，已冻结的 bytes 返回 AssignError
+pub fn splice(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    _gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 4, "splice")?;
+    check_bytes_arg(tuple, 0, "splice", "bytes")?;
+    let start = get_int_arg(tuple, 1, "splice", "start")?;
+    let end = get_int_arg(tuple, 2, "splice", "end")?;
+    check_bytes_arg(tuple, 3, "splice", "replacement")?;
+
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    let replacement = tuple_obj.values[3].as_const_type::<VMBytes>().value.clone();
+    let target = &mut tuple_obj.values[0];
+    if target.is_frozen() {
+        return Err(VMVariableError::AssignError(
+            target.clone_ref(),
+            "Cannot splice a frozen bytes object".to_string(),
+        ));
+    }
+    let len = target.as_const_type::<VMBytes>().value.len() as i64;
+    if start < 0 || start > end || end > len {
+        return Err(VMVariableError::ValueError(
+            target.clone_ref(),
+            format!(
+                "Invalid range {}..{} for splice on bytes of length {}",
+                start, end, len
+            ),
+        ));
+    }
+    target
+        .as_type::<VMBytes>()
+        .value
+        .splice(start as usize..end as usize, replacement);
+    Ok(target.clone_ref())
+}
+
+pub fn get_bytes_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![("splice", splice)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 对 data 执行 splice，返回修改后的内容
+    fn splice_with(
+        data: &[u8],
+        start: i64,
+        end: i64,
+        replacement: &[u8],
+        gc_system: &mut GCSystem,
+    ) -> Result<Vec<u8>, VMVariableError> {
+        let mut target = gc_system.new_object(VMBytes::new(&data.to_vec()));
+        let mut start = gc_system.new_object(VMInt::new(start));
+        let mut end = gc_system.new_object(VMInt::new(end));
+        let mut replacement = gc_system.new_object(VMBytes::new(&replacement.to_vec()));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![
+            &mut target,
+            &mut start,
+            &mut end,
+            &mut replacement,
+        ]));
+        let result = splice(None, None, &mut args, gc_system).map(|mut result| {
+            result.drop_ref();
+            target.as_const_type::<VMBytes>().value.clone()
+        });
+        for obj in [
+            &mut target,
+            &mut start,
+            &mut end,
+            &mut replacement,
+            &mut args,
+        ] {
+            obj.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_splice_insert_delete_replace() {
+        let mut gc_system = GCSystem::new(None);
+        // 插入：空区间
+        assert_eq!(
+            splice_with(b"abef", 2, 2, b"cd", &mut gc_system).unwrap(),
+            b"abcdef"
+        );
+        // 删除：空替换
+        assert_eq!(
+            splice_with(b"abcdef", 1, 4, b"", &mut gc_system).unwrap(),
+            b"aef"
+        );
+        // 替换为更长 / 更短的内容
+        assert_eq!(
+            splice_with(b"abcdef", 2, 4, b"XYZW", &mut gc_system).unwrap(),
+            b"abXYZWef"
+        );
+        assert_eq!(
+            splice_with(b"abcdef", 0, 6, b"z", &mut gc_system).unwrap(),
+            b"z"
+        );
+        // 在末尾追加
+        assert_eq!(
+            splice_with(b"ab", 2, 2, b"c", &mut gc_system).unwrap(),
+            b"abc"
+        );
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_splice_rejects_invalid_range() {
+        let mut gc_system = GCSystem::new(None);
+        for (start, end) in [(3, 1), (-1, 2), (0, 7)] {
+            match splice_with(b"abcdef", start, end, b"", &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("range {}..{} should be rejected", start, end),
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_splice_rejects_frozen_bytes() {
+        let mut gc_system = GCSystem::new(None);
+        let mut target = gc_system.new_object(VMBytes::new(&b"abc".to_vec()));
+        target.freeze();
+        let mut start = gc_system.new_object(VMInt::new(0));
+        let mut end = gc_system.new_object(VMInt::new(1));
+        let mut replacement = gc_system.new_object(VMBytes::new(&b"z".to_vec()));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![
+            &mut target,
+            &mut start,
+            &mut end,
+            &mut replacement,
+        ]));
+        match splice(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::AssignError(..)) => err.consume_ref(),
+            _ => panic!("splicing frozen bytes should fail"),
+        }
+        assert_eq!(target.as_const_type::<VMBytes>().value, b"abc");
+        for obj in [
+            &mut target,
+            &mut start,
+            &mut end,
+            &mut replacement,
+            &mut args,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
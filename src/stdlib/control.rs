@@ -0,0 +1,142 @@
+use xlang_vm_core::{
+    executor::{
+        variable::{VMLambda, VMNamed, VMNull, VMString, VMTuple, VMVariableError},
+        vm::{call_lambda_sync, VMError},
+    },
+    gc::{GCRef, GCSystem},
+};
+
+use super::{check_arg_count, check_if_tuple};
+
+fn get_lambda_arg(
+    tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+) -> Result<GCRef, VMVariableError> {
+    let arg = &mut tuple.as_type::<VMTuple>().values[index];
+    if !arg.isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            arg.clone_ref(),
+            format!("Argument {} for {} must be a lambda", index + 1, func_name),
+        ));
+    }
+    Ok(arg.clone())
+}
+
+// 将错误转换为 (kind => string, message => string, value => object) 元组并释放错误持有的引用
+// 没有对应对象的错误 value 为 null
+fn build_error_tuple(err: &mut VMVariableError, gc_system: &mut GCSystem) -> GCRef {
+    let mut kind = gc_system.new_object(VMString::new(err.kind()));
+    let mut message = gc_system.new_object(VMString::new(&err.to_string()));
+    let mut value = match err.value() {
+        Some(value) => value.clone_ref(),
+        None => gc_system.new_object(VMNull::new()),
+    };
+    err.consume_ref();
+    let mut items = Vec::new();
+    for (key, value) in [
+        ("kind", &mut kind),
+        ("message", &mut message),
+        ("value", &mut value),
+    ] {
+        let mut key = gc_system.new_object(VMString::new(key));
+        items.push(gc_system.new_object(VMNamed::new(&mut key, value)));
+        key.drop_ref();
+        value.drop_ref();
+    }
+    let result = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+    for item in items.iter_mut() {
+        item.drop_ref();
+    }
+    result
+}
+
+// control.try(body, handler)，无参数调用 body 并返回其结果；
+// body 出错时以错误元组 (kind => ..., message => ..., value => ...) 调用 handler 并返回 handler 的结果
+pub fn try_call(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "try")?;
+    let mut body = get_lambda_arg(tuple, 0, "try")?;
+    let mut handler = get_lambda_arg(tuple, 1, "try")?;
+
+    let mut no_args = gc_system.new_object(VMTuple::new(&mut vec![]));
+    let result = call_lambda_sync(&mut body, &mut no_args, gc_system);
+    no_args.drop_ref();
+    let mut err = match result {
+        Ok(result) => return Ok(result),
+        // StackOverflow 等执行器错误不可被捕获
+        Err(err) => err.into_catchable().map_err(VMError::into_variable_error)?,
+    };
+
+    let mut error_tuple = build_error_tuple(&mut err, gc_system);
+    let mut handler_args = gc_system.new_object(VMTuple::new(&mut vec![&mut error_tuple]));
+    error_tuple.drop_ref();
+    let result = call_lambda_sync(&mut handler, &mut handler_args, gc_system)
+        .map_err(VMError::into_variable_error);
+    handler_args.drop_ref();
+    result
+}
+
+// control.throw(message)，抛出类别为 Error、携带 message 的错误
+// raise 是语言的关键字，control.raise(...) 会被解析为 raise 语句，因此命名为 throw
+pub fn throw(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    _gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 1, "throw")?;
+    let message = &mut tuple.as_type::<VMTuple>().values[0];
+    if !message.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            message.clone_ref(),
+            "Argument for throw must be a string".to_string(),
+        ));
+    }
+    Err(VMVariableError::Raised(
+        "Error".to_string(),
+        message.clone_ref(),
+    ))
+}
+
+// control.raise_with(kind, value)，抛出类别为 kind、携带任意值 value 的错误
+pub fn raise_with(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    _gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "raise_with")?;
+    let values = &mut tuple.as_type::<VMTuple>().values;
+    if !values[0].isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            values[0].clone_ref(),
+            "Error kind for raise_with must be a string".to_string(),
+        ));
+    }
+    let kind = values[0].as_const_type::<VMString>().value.clone();
+    Err(VMVariableError::Raised(kind, values[1].clone_ref()))
+}
+
+pub fn get_control_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![
+        ("try", try_call),
+        ("throw", throw),
+        ("raise_with", raise_with),
+    ]
+}
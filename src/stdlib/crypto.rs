@@ -0,0 +1,314 @@
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use xlang_vm_core::{
+    executor::variable::{VMBoolean, VMBytes, VMString, VMTuple, VMVariableError},
+    gc::{GCRef, GCSystem},
+};
+
+use super::{check_arg_count, check_if_tuple};
+
+// 取出待处理的数据，bytes 原样使用，string 使用其 UTF-8 编码
+fn get_data_arg(
+    tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+) -> Result<Vec<u8>, VMVariableError> {
+    let arg = &mut tuple.as_type::<VMTuple>().values[index];
+    if arg.isinstance::<VMBytes>() {
+        return Ok(arg.as_const_type::<VMBytes>().value.clone());
+    }
+    if arg.isinstance::<VMString>() {
+        return Ok(arg.as_const_type::<VMString>().value.as_bytes().to_vec());
+    }
+    Err(VMVariableError::TypeError(
+        arg.clone_ref(),
+        format!(
+            "Argument {} for {} must be bytes or a string",
+            index + 1,
+            func_name
+        ),
+    ))
+}
+
+// 按算法名计算摘要，未知的算法返回 None
+fn digest(algo: &str, data: &[u8]) -> Option<Vec<u8>> {
+    match algo {
+        "sha256" => Some(Sha256::digest(data).to_vec()),
+        "sha1" => Some(Sha1::digest(data).to_vec()),
+        "md5" => Some(Md5::digest(data).to_vec()),
+        _ => None,
+    }
+}
+
+fn digest_function(
+    tuple: &mut GCRef,
+    algo: &str,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 1, algo)?;
+    let data = get_data_arg(tuple, 0, algo)?;
+    let result = digest(algo, &data).expect("digest_function is only used with known algorithms");
+    Ok(gc_system.new_object(VMBytes::new(&result)))
+}
+
+// crypto.sha256(data)，返回 32 字节的摘要
+pub fn sha256(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    digest_function(tuple, "sha256", gc_system)
+}
+
+// crypto.sha1(data)，返回 20 字节的摘要。SHA-1 已不具备抗碰撞性，仅用于兼容旧格式
+pub fn sha1(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    digest_function(tuple, "sha1", gc_system)
+}
+
+// crypto.md5(data)，返回 16 字节的摘要。MD5 只适合做非安全场景的校验
+pub fn md5(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    digest_function(tuple, "md5", gc_system)
+}
+
+// crypto.hex_digest(algo, data)，algo 为 "sha256"、"sha1" 或 "md5"，返回小写十六进制的摘要字符串
+pub fn hex_digest(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "hex_digest")?;
+    let algo_obj = &mut tuple.as_type::<VMTuple>().values[0];
+    if !algo_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            algo_obj.clone_ref(),
+            "Algorithm for hex_digest must be a string".to_string(),
+        ));
+    }
+    let algo = algo_obj.as_const_type::<VMString>().value.clone();
+    let data = get_data_arg(tuple, 1, "hex_digest")?;
+    let Some(result) = digest(&algo, &data) else {
+        return Err(VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!(
+                "Unknown hash algorithm '{}', expected sha256, sha1 or md5",
+                algo
+            ),
+        ));
+    };
+    let hex = result
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    Ok(gc_system.new_object(VMString::new(&hex)))
+}
+
+// crypto.hmac_sha256(key, message)，返回 32 字节的 HMAC-SHA256，key 与 message 可以是 bytes 或字符串
+pub fn hmac_sha256(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "hmac_sha256")?;
+    let key = get_data_arg(tuple, 0, "hmac_sha256")?;
+    let message = get_data_arg(tuple, 1, "hmac_sha256")?;
+    // HMAC 接受任意长度的 key，new_from_slice 不会失败
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("HMAC accepts keys of any length");
+    mac.update(&message);
+    let result = mac.finalize().into_bytes();
+    Ok(gc_system.new_object(VMBytes::new(&result.to_vec())))
+}
+
+// 比较耗时只取决于较长一方的长度，与内容以及第一个不同字节的位置无关
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let len = a.len().max(b.len());
+    let mut diff = a.len() ^ b.len();
+    for i in 0..len {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= (x ^ y) as usize;
+    }
+    std::hint::black_box(diff) == 0
+}
+
+// crypto.secure_compare(a, b)，以常数时间比较两段 bytes（或字符串），用于校验签名等秘密值
+// 长度不同时仍按较长一方的长度完成比较后返回 false
+pub fn secure_compare(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "secure_compare")?;
+    let a = get_data_arg(tuple, 0, "secure_compare")?;
+    let b = get_data_arg(tuple, 1, "secure_compare")?;
+    Ok(gc_system.new_object(VMBoolean::new(constant_time_eq(&a, &b))))
+}
+
+pub fn get_crypto_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![
+        ("sha256", sha256),
+        ("sha1", sha1),
+        ("md5", md5),
+        ("hex_digest", hex_digest),
+        ("hmac_sha256", hmac_sha256),
+        ("secure_compare", secure_compare),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_digests_match_known_vectors() {
+        let mut gc_system = GCSystem::new(None);
+        let mut empty = gc_system.new_object(VMBytes::new(&vec![]));
+        // 字符串按 UTF-8 编码后计算摘要
+        let mut abc = gc_system.new_object(VMString::new("abc"));
+        for (native_fn, algo, mut input, expected) in [
+            (
+                sha256 as NativeFunction,
+                "sha256",
+                empty.clone(),
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                sha256,
+                "sha256",
+                abc.clone(),
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                sha1,
+                "sha1",
+                empty.clone(),
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            ),
+            (
+                sha1,
+                "sha1",
+                abc.clone(),
+                "a9993e364706816aba3e25717850c26c9cd0d89d",
+            ),
+            (
+                md5,
+                "md5",
+                empty.clone(),
+                "d41d8cd98f00b204e9800998ecf8427e",
+            ),
+            (md5, "md5", abc.clone(), "900150983cd24fb0d6963f7d28e17f72"),
+        ] {
+            let mut result = call_native(native_fn, &mut vec![&mut input], &mut gc_system).unwrap();
+            assert_eq!(to_hex(&result.as_const_type::<VMBytes>().value), expected);
+            result.drop_ref();
+
+            let mut algo = gc_system.new_object(VMString::new(algo));
+            let mut hex =
+                call_native(hex_digest, &mut vec![&mut algo, &mut input], &mut gc_system).unwrap();
+            assert_eq!(hex.as_const_type::<VMString>().value, expected);
+            hex.drop_ref();
+            algo.drop_ref();
+        }
+        empty.drop_ref();
+        abc.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_hex_digest_rejects_unknown_algorithm() {
+        let mut gc_system = GCSystem::new(None);
+        let mut algo = gc_system.new_object(VMString::new("sha512"));
+        let mut data = gc_system.new_object(VMBytes::new(&b"abc".to_vec()));
+        match call_native(hex_digest, &mut vec![&mut algo, &mut data], &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("unknown algorithms should be rejected"),
+        }
+        algo.drop_ref();
+        data.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_hmac_sha256_known_vector() {
+        let mut gc_system = GCSystem::new(None);
+        // RFC 4231 test case 2
+        let mut key = gc_system.new_object(VMString::new("Jefe"));
+        let mut message =
+            gc_system.new_object(VMBytes::new(&b"what do ya want for nothing?".to_vec()));
+        let mut mac = call_native(
+            hmac_sha256,
+            &mut vec![&mut key, &mut message],
+            &mut gc_system,
+        )
+        .unwrap();
+        assert_eq!(
+            to_hex(&mac.as_const_type::<VMBytes>().value),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        for obj in [&mut key, &mut message, &mut mac] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_secure_compare() {
+        let mut gc_system = GCSystem::new(None);
+        for (a, b, expected) in [
+            (b"signature".to_vec(), b"signature".to_vec(), true),
+            (b"signature".to_vec(), b"signaturf".to_vec(), false),
+            (b"sig".to_vec(), b"signature".to_vec(), false),
+            (b"signature".to_vec(), vec![], false),
+            (vec![], vec![], true),
+            // 长度相差 256 时长度的异或在低 8 位为 0
+            (vec![0; 256], vec![], false),
+        ] {
+            let mut a = gc_system.new_object(VMBytes::new(&a));
+            let mut b = gc_system.new_object(VMBytes::new(&b));
+            let mut result =
+                call_native(secure_compare, &mut vec![&mut a, &mut b], &mut gc_system).unwrap();
+            assert_eq!(result.as_const_type::<VMBoolean>().value, expected);
+            for obj in [&mut a, &mut b, &mut result] {
+                obj.drop_ref();
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
@@ -1,7 +1,9 @@
 use std::{
+    collections::HashSet,
+    env,
     fs::{self, OpenOptions},
     io::Write,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use xlang_vm_core::{
@@ -20,6 +22,14 @@ fn io_error_to_vm(err: std::io::Error, path: Option<&str>) -> VMVariableError {
     VMVariableError::DetailedError(msg)
 }
 
+// 写入失败时返回 ValueError，错误对象指向路径参数
+fn write_error_to_vm(err: std::io::Error, args_tuple: &mut GCRef, path: &str) -> VMVariableError {
+    VMVariableError::ValueError(
+        args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+        format!("Failed to write '{}': {}", path, err),
+    )
+}
+
 // Helper to extract a single string argument (path)
 fn get_path_arg(args_tuple: &mut GCRef, func_name: &str) -> Result<String, VMVariableError> {
     let tuple_obj = args_tuple.as_type::<VMTuple>();
@@ -113,6 +123,41 @@ fn get_path_content_bytes_args(
     Ok((path, content))
 }
 
+// Helper to extract path and content (string or bytes) arguments
+fn get_path_content_any_args(
+    args_tuple: &mut GCRef,
+    func_name: &str,
+) -> Result<(String, Vec<u8>), VMVariableError> {
+    let is_string = args_tuple
+        .as_const_type::<VMTuple>()
+        .values
+        .get(1)
+        .is_some_and(|content| content.isinstance::<VMString>());
+    if is_string {
+        let (path, content) = get_path_content_string_args(args_tuple, func_name)?;
+        return Ok((path, content.into_bytes()));
+    }
+    get_path_content_bytes_args(args_tuple, func_name)
+}
+
+// 以追加模式打开文件（不存在时创建）并写入 content
+fn append_to_file(
+    args_tuple: &mut GCRef,
+    path_str: &str,
+    content: &[u8],
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let result = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path_str)
+        .and_then(|mut file| file.write_all(content));
+    match result {
+        Ok(_) => Ok(gc_system.new_object(VMNull::new())),
+        Err(e) => Err(write_error_to_vm(e, args_tuple, path_str)),
+    }
+}
+
 // fs.read(path) -> string
 fn read_file(
     _self_object: Option<&mut GCRef>,
@@ -160,7 +205,7 @@ fn write_file(
 
     match fs::write(path, content) {
         Ok(_) => Ok(gc_system.new_object(VMNull::new())),
-        Err(e) => Err(io_error_to_vm(e, Some(&path_str))),
+        Err(e) => Err(write_error_to_vm(e, args_tuple, &path_str)),
     }
 }
 
@@ -177,11 +222,11 @@ fn write_bytes(
 
     match fs::write(path, content) {
         Ok(_) => Ok(gc_system.new_object(VMNull::new())),
-        Err(e) => Err(io_error_to_vm(e, Some(&path_str))),
+        Err(e) => Err(write_error_to_vm(e, args_tuple, &path_str)),
     }
 }
 
-// fs.append(path, content)
+// fs.append(path, content)，content 可以是字符串或字节序列
 fn append_file(
     _self_object: Option<&mut GCRef>,
     _capture: Option<&mut GCRef>,
@@ -189,16 +234,8 @@ fn append_file(
     gc_system: &mut GCSystem,
 ) -> Result<GCRef, VMVariableError> {
     check_if_tuple(args_tuple)?;
-    let (path_str, content) = get_path_content_string_args(args_tuple, "append")?;
-    let path = Path::new(&path_str);
-
-    match OpenOptions::new().append(true).create(true).open(path) {
-        Ok(mut file) => match file.write_all(content.as_bytes()) {
-            Ok(_) => Ok(gc_system.new_object(VMNull::new())),
-            Err(e) => Err(io_error_to_vm(e, Some(&path_str))),
-        },
-        Err(e) => Err(io_error_to_vm(e, Some(&path_str))),
-    }
+    let (path_str, content) = get_path_content_any_args(args_tuple, "append")?;
+    append_to_file(args_tuple, &path_str, &content, gc_system)
 }
 
 // fs.append_bytes(path, content)
@@ -210,17 +247,60 @@ fn append_bytes(
 ) -> Result<GCRef, VMVariableError> {
     check_if_tuple(args_tuple)?;
     let (path_str, content) = get_path_content_bytes_args(args_tuple, "append_bytes")?;
-    let path = Path::new(&path_str);
+    append_to_file(args_tuple, &path_str, &content, gc_system)
+}
 
-    match OpenOptions::new().append(true).create(true).open(path) {
-        Ok(mut file) => match file.write_all(&content) {
-            Ok(_) => Ok(gc_system.new_object(VMNull::new())),
-            Err(e) => Err(io_error_to_vm(e, Some(&path_str))),
-        },
-        Err(e) => Err(io_error_to_vm(e, Some(&path_str))),
+// fs.cwd() -> string
+// DirStack 通过切换进程工作目录生效，因此这里返回的就是当前脚本所在的目录
+fn cwd(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 0 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("cwd expected 0 arguments, got {}", arg_count),
+        ));
+    }
+    match env::current_dir() {
+        Ok(path) => Ok(gc_system.new_object(VMString::new(&path.to_string_lossy()))),
+        Err(e) => Err(io_error_to_vm(e, None)),
     }
 }
 
+// fs.join(components) -> string
+// components 可以是一个字符串元组，也可以直接以多个字符串参数传入
+fn join(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let tuple_obj = args_tuple.as_type::<VMTuple>();
+    let components = if tuple_obj.values.len() == 1 && tuple_obj.values[0].isinstance::<VMTuple>() {
+        &mut tuple_obj.values[0].as_type::<VMTuple>().values
+    } else {
+        &mut tuple_obj.values
+    };
+
+    let mut path = PathBuf::new();
+    for component in components.iter_mut() {
+        if !component.isinstance::<VMString>() {
+            return Err(VMVariableError::TypeError(
+                component.clone_ref(),
+                "Path components for join must be strings".to_string(),
+            ));
+        }
+        path.push(&component.as_const_type::<VMString>().value);
+    }
+    Ok(gc_system.new_object(VMString::new(&path.to_string_lossy())))
+}
+
 // fs.exists(path) -> bool
 fn exists(
     _self_object: Option<&mut GCRef>,
@@ -359,6 +439,96 @@ fn listdir(
     }
 }
 
+// 确认路径存在且是目录，否则返回 ValueError
+fn ensure_directory(args_tuple: &mut GCRef, path_str: &str) -> Result<(), VMVariableError> {
+    let path = Path::new(path_str);
+    if !path.exists() {
+        return Err(VMVariableError::ValueError(
+            args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!("Path not found: {}", path_str),
+        ));
+    }
+    if !path.is_dir() {
+        return Err(VMVariableError::ValueError(
+            args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!("Path is not a directory: {}", path_str),
+        ));
+    }
+    Ok(())
+}
+
+fn new_string_tuple(values: &[String], gc_system: &mut GCSystem) -> GCRef {
+    let mut elements: Vec<GCRef> = values
+        .iter()
+        .map(|value| gc_system.new_object(VMString::new(value)))
+        .collect();
+    let result = gc_system.new_object(VMTuple::new(&mut elements.iter_mut().collect()));
+    for element in elements.iter_mut() {
+        element.drop_ref();
+    }
+    result
+}
+
+// fs.read_dir(path) -> tuple<string>，按名称排序
+fn read_dir(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let path_str = get_path_arg(args_tuple, "read_dir")?;
+    ensure_directory(args_tuple, &path_str)?;
+
+    let mut names = Vec::new();
+    for entry in fs::read_dir(&path_str).map_err(|e| io_error_to_vm(e, Some(&path_str)))? {
+        let entry = entry.map_err(|e| io_error_to_vm(e, Some(&path_str)))?;
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    names.sort();
+    Ok(new_string_tuple(&names, gc_system))
+}
+
+// 深度优先收集 dir 下的所有文件，visited 记录已进入目录的规范路径以避免符号链接成环
+fn walk_into(
+    dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<String>,
+) -> Result<(), std::io::Error> {
+    if !visited.insert(dir.canonicalize()?) {
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            walk_into(&path, visited, files)?;
+        } else {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+// fs.walk(path) -> tuple<string>，递归列出目录下的所有文件路径
+fn walk(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let path_str = get_path_arg(args_tuple, "walk")?;
+    ensure_directory(args_tuple, &path_str)?;
+
+    let mut files = Vec::new();
+    walk_into(Path::new(&path_str), &mut HashSet::new(), &mut files)
+        .map_err(|e| io_error_to_vm(e, Some(&path_str)))?;
+    Ok(new_string_tuple(&files, gc_system))
+}
+
 // Helper to provide functions for registration
 pub fn get_fs_module() -> Vec<(
     &'static str,
@@ -382,5 +552,200 @@ pub fn get_fs_module() -> Vec<(
         ("remove", remove),
         ("mkdir", mkdir),
         ("listdir", listdir),
+        ("cwd", cwd),
+        ("join", join),
+        ("read_dir", read_dir),
+        ("walk", walk),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+
+    fn call_with_strings(
+        native_fn: NativeFunction,
+        args: &[&str],
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut values: Vec<GCRef> = args
+            .iter()
+            .map(|arg| gc_system.new_object(VMString::new(arg)))
+            .collect();
+        let result = call_native(native_fn, &mut values.iter_mut().collect(), gc_system);
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        result
+    }
+
+    fn string_value(mut value: GCRef) -> String {
+        let result = value.as_const_type::<VMString>().value.clone();
+        value.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_cwd_join_and_exists() {
+        let mut gc_system = GCSystem::new(None);
+
+        let current = string_value(call_with_strings(cwd, &[], &mut gc_system).unwrap());
+        assert_eq!(PathBuf::from(&current), env::current_dir().unwrap());
+
+        let joined = string_value(
+            call_with_strings(join, &[&current, "src", "main.rs"], &mut gc_system).unwrap(),
+        );
+        assert_eq!(
+            PathBuf::from(&joined),
+            PathBuf::from(&current).join("src").join("main.rs")
+        );
+
+        let mut exists_result = call_with_strings(exists, &[&current], &mut gc_system).unwrap();
+        assert!(exists_result.as_const_type::<VMBoolean>().value);
+        exists_result.drop_ref();
+
+        let missing = string_value(
+            call_with_strings(
+                join,
+                &[&current, "no_such_entry_for_fs_test"],
+                &mut gc_system,
+            )
+            .unwrap(),
+        );
+        let mut exists_result = call_with_strings(exists, &[&missing], &mut gc_system).unwrap();
+        assert!(!exists_result.as_const_type::<VMBoolean>().value);
+        exists_result.drop_ref();
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_join_accepts_tuple_and_rejects_non_strings() {
+        let mut gc_system = GCSystem::new(None);
+        let mut a = gc_system.new_object(VMString::new("a"));
+        let mut b = gc_system.new_object(VMString::new("b"));
+        let mut components = gc_system.new_object(VMTuple::new(&mut vec![&mut a, &mut b]));
+        let mut args_tuple = gc_system.new_object(VMTuple::new(&mut vec![&mut components]));
+        let joined = string_value(join(None, None, &mut args_tuple, &mut gc_system).unwrap());
+        assert_eq!(PathBuf::from(joined), Path::new("a").join("b"));
+        args_tuple.drop_ref();
+
+        let mut null = gc_system.new_object(VMNull::new());
+        let mut args_tuple = gc_system.new_object(VMTuple::new(&mut vec![&mut a, &mut null]));
+        match join(None, None, &mut args_tuple, &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("join should reject non-string components"),
+        }
+        for obj in [&mut a, &mut b, &mut components, &mut null, &mut args_tuple] {
+            obj.drop_ref();
+        }
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn tuple_len(mut value: GCRef) -> usize {
+        let len = value.as_const_type::<VMTuple>().values.len();
+        value.drop_ref();
+        len
+    }
+
+    #[test]
+    fn test_read_dir_and_walk() {
+        let mut gc_system = GCSystem::new(None);
+        let root = env::temp_dir().join(format!("xlang_fs_walk_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a").join("b")).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::write(root.join("top.txt"), "top").unwrap();
+        fs::write(root.join("a").join("one.txt"), "1").unwrap();
+        fs::write(root.join("a").join("b").join("two.txt"), "2").unwrap();
+        fs::write(root.join("a").join("b").join("three.txt"), "3").unwrap();
+        // 指回根目录的符号链接，walk 不应陷入死循环
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, root.join("a").join("loop")).unwrap();
+
+        let root_str = root.to_string_lossy().into_owned();
+        let entries = call_with_strings(read_dir, &[&root_str], &mut gc_system).unwrap();
+        assert_eq!(tuple_len(entries), 3);
+
+        let files = call_with_strings(walk, &[&root_str], &mut gc_system).unwrap();
+        assert_eq!(tuple_len(files), 4);
+
+        let file_path = root.join("top.txt").to_string_lossy().into_owned();
+        let missing_path = root.join("missing").to_string_lossy().into_owned();
+        for (native_fn, path) in [
+            (read_dir as NativeFunction, &file_path),
+            (walk, &file_path),
+            (read_dir, &missing_path),
+            (walk, &missing_path),
+        ] {
+            match call_with_strings(native_fn, &[path], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("expected ValueError for {}", path),
+            }
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn call_with_path_and(
+        native_fn: NativeFunction,
+        path: &str,
+        content: &mut GCRef,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut path = gc_system.new_object(VMString::new(path));
+        let result = call_native(native_fn, &mut vec![&mut path, content], gc_system);
+        path.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_bytes_round_trip_and_append() {
+        let mut gc_system = GCSystem::new(None);
+        let root = env::temp_dir().join(format!("xlang_fs_bytes_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let file_path = root.join("data.bin").to_string_lossy().into_owned();
+
+        let data: Vec<u8> = (0..=255).collect();
+        let mut bytes = gc_system.new_object(VMBytes::new(&data));
+        call_with_path_and(write_bytes, &file_path, &mut bytes, &mut gc_system)
+            .unwrap()
+            .drop_ref();
+        let mut read_back = call_with_strings(read_bytes, &[&file_path], &mut gc_system).unwrap();
+        assert_eq!(read_back.as_const_type::<VMBytes>().value, data);
+        read_back.drop_ref();
+
+        let mut text = gc_system.new_object(VMString::new("tail"));
+        call_with_path_and(append_file, &file_path, &mut text, &mut gc_system)
+            .unwrap()
+            .drop_ref();
+        call_with_path_and(append_file, &file_path, &mut bytes, &mut gc_system)
+            .unwrap()
+            .drop_ref();
+        let expected = [data.as_slice(), b"tail", data.as_slice()].concat();
+        assert_eq!(fs::read(&file_path).unwrap(), expected);
+
+        // 目录不能作为文件写入
+        let dir_path = root.to_string_lossy().into_owned();
+        for native_fn in [write_bytes as NativeFunction, append_file] {
+            match call_with_path_and(native_fn, &dir_path, &mut bytes, &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("writing to a directory should be a ValueError"),
+            }
+        }
+
+        bytes.drop_ref();
+        text.drop_ref();
+        fs::remove_dir_all(&root).unwrap();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
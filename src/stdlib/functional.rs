@@ -0,0 +1,828 @@
+use xlang_vm_core::{
+    executor::{
+        native_module::create_native_lambda,
+        variable::{
+            try_assign_as_vmobject, try_copy_as_vmobject, try_deepcopy_as_vmobject,
+            try_eq_as_vmobject, VMBoolean, VMDict, VMInt, VMLambda, VMLambdaBody, VMNamed, VMNull,
+            VMObject, VMString, VMTuple, VMVariableError,
+        },
+        vm::{call_lambda_sync, VMError},
+    },
+    gc::{GCRef, GCSystem},
+};
+
+use super::{call_lambda, check_arg_count, check_if_tuple};
+
+// 校验参数个数，并取出 (tuple, fn) 中的元素列表与 lambda
+fn get_target_and_lambda(
+    tuple: &mut GCRef,
+    func_name: &str,
+    expected_args: usize,
+) -> Result<(Vec<GCRef>, GCRef), VMVariableError> {
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != expected_args {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "{} expected {} arguments, got {}",
+                func_name,
+                expected_args,
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let target_obj = &mut tuple_obj.values[0];
+    if !target_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            target_obj.clone_ref(),
+            format!("First argument for {} must be a tuple", func_name),
+        ));
+    }
+    let values = target_obj.as_const_type::<VMTuple>().values.clone();
+    let lambda_obj = &mut tuple_obj.values[1];
+    if !lambda_obj.isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            lambda_obj.clone_ref(),
+            format!("Second argument for {} must be a lambda", func_name),
+        ));
+    }
+    Ok((values, lambda_obj.clone()))
+}
+
+fn drop_all(values: &mut [GCRef]) {
+    for value in values.iter_mut() {
+        value.drop_ref();
+    }
+}
+
+// functional.map(tuple, fn)，返回由 fn(x) 组成的新元组
+pub fn map(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let (mut values, mut lambda) = get_target_and_lambda(tuple, "map", 2)?;
+    let mut mapped = Vec::with_capacity(values.len());
+    for value in values.iter_mut() {
+        match call_lambda(&mut lambda, &mut vec![value], gc_system) {
+            Ok(result) => mapped.push(result),
+            Err(err) => {
+                drop_all(&mut mapped);
+                return Err(err);
+            }
+        }
+    }
+    let result = gc_system.new_object(VMTuple::new(&mut mapped.iter_mut().collect()));
+    drop_all(&mut mapped);
+    Ok(result)
+}
+
+// functional.filter(tuple, fn)，保留 fn(x) 为 true 的元素
+pub fn filter(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let (mut values, mut lambda) = get_target_and_lambda(tuple, "filter", 2)?;
+    let mut kept = Vec::new();
+    for value in values.iter_mut() {
+        let mut result = call_lambda(&mut lambda, &mut vec![value], gc_system)?;
+        if !result.isinstance::<VMBoolean>() {
+            return Err(VMVariableError::TypeError(
+                result,
+                "Predicate for filter must return a boolean".to_string(),
+            ));
+        }
+        if result.as_const_type::<VMBoolean>().value {
+            kept.push(value.clone());
+        }
+        result.drop_ref();
+    }
+    Ok(gc_system.new_object(VMTuple::new(&mut kept.iter_mut().collect())))
+}
+
+// functional.reduce(tuple, fn, init)，从左到右折叠：acc = fn(acc, x)
+pub fn reduce(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let (mut values, mut lambda) = get_target_and_lambda(tuple, "reduce", 3)?;
+    let mut acc = tuple.as_type::<VMTuple>().values[2].clone_ref();
+    for value in values.iter_mut() {
+        let result = call_lambda(&mut lambda, &mut vec![&mut acc, value], gc_system);
+        acc.drop_ref();
+        acc = result?;
+    }
+    Ok(acc)
+}
+
+// 计算 assign_members 会填充 params 中的哪些位置：先按名字匹配命名参数，再按顺序填充剩余的命名参数
+fn bound_param_indices(params: &VMTuple, args: &VMTuple) -> Vec<bool> {
+    let mut bound = vec![false; params.values.len()];
+    let mut positional_count = 0;
+    for arg in args.values.iter() {
+        if !arg.isinstance::<VMNamed>() {
+            positional_count += 1;
+            continue;
+        }
+        let key = arg.as_const_type::<VMNamed>().get_const_key();
+        if let Some(index) = params.values.iter().position(|param| {
+            param.isinstance::<VMNamed>()
+                && try_eq_as_vmobject(param.as_const_type::<VMNamed>().get_const_key(), key)
+        }) {
+            bound[index] = true;
+        }
+    }
+    for (index, param) in params.values.iter().enumerate() {
+        if positional_count == 0 {
+            break;
+        }
+        if param.isinstance::<VMNamed>() && !bound[index] {
+            bound[index] = true;
+            positional_count -= 1;
+        }
+    }
+    bound
+}
+
+// functional.partial(fn, args)，返回预先绑定了部分参数的新 lambda，之后调用时只需提供其余参数
+// 新 lambda 与 fn 共享函数体、capture 和 self，但拥有独立的默认参数元组
+pub fn partial(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "partial expected 2 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let lambda_obj = &mut tuple_obj.values[0];
+    if !lambda_obj.isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            lambda_obj.clone_ref(),
+            "First argument for partial must be a lambda".to_string(),
+        ));
+    }
+    let mut lambda_ref = lambda_obj.clone();
+    let args_obj = &mut tuple_obj.values[1];
+    if !args_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            args_obj.clone_ref(),
+            "Second argument for partial must be a tuple".to_string(),
+        ));
+    }
+    let mut args = args_obj.clone();
+    bind_args(&mut lambda_ref, &mut args, gc_system)
+}
+
+// 复制 lambda 并把 args 绑定到新的默认参数元组上，已绑定的命名参数被移到末尾
+fn bind_args(
+    lambda_ref: &mut GCRef,
+    args: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let lambda = lambda_ref.as_type::<VMLambda>();
+
+    // 复制命名参数本身，使 assign_members 不会修改原 lambda 的默认参数
+    let mut params = Vec::new();
+    for param in lambda
+        .default_args_tuple
+        .as_type::<VMTuple>()
+        .values
+        .iter_mut()
+    {
+        let copied = if param.isinstance::<VMNamed>() {
+            try_copy_as_vmobject(param, gc_system)
+        } else {
+            Ok(param.clone_ref())
+        };
+        match copied {
+            Ok(copied) => params.push(copied),
+            Err(err) => {
+                drop_all(&mut params);
+                return Err(err);
+            }
+        }
+    }
+    let mut default_args = gc_system.new_object(VMTuple::new(&mut params.iter_mut().collect()));
+    drop_all(&mut params);
+
+    let bound = bound_param_indices(
+        lambda.default_args_tuple.as_const_type::<VMTuple>(),
+        args.as_const_type::<VMTuple>(),
+    );
+    let default_args_obj = default_args.as_type::<VMTuple>();
+    if let Err(err) = default_args_obj.assign_members(args) {
+        default_args.drop_ref();
+        return Err(err);
+    }
+    // 之后的位置参数总是填充最靠前的命名参数，因此把已绑定的参数移到末尾，
+    // 让它们只能通过名字被覆盖（参数按名字绑定，顺序不影响调用）
+    let mut unbound = Vec::new();
+    let mut rest = Vec::new();
+    for (index, value) in default_args_obj.values.drain(..).enumerate() {
+        if bound.get(index).copied().unwrap_or(true) {
+            rest.push(value);
+        } else {
+            unbound.push(value);
+        }
+    }
+    unbound.append(&mut rest);
+    default_args_obj.values = unbound;
+
+    let mut lambda_body = match lambda.lambda_body {
+        VMLambdaBody::VMNativeGeneratorFunction(ref generator) => {
+            VMLambdaBody::VMNativeGeneratorFunction(generator.clone_generator())
+        }
+        ref body => body.clone(),
+    };
+    let mut result = gc_system.new_object(VMNull::new());
+    let alias = lambda.alias_const().clone();
+    let new_lambda = gc_system.new_object(VMLambda::new_with_alias(
+        lambda.code_position,
+        lambda.signature.clone(),
+        &mut default_args,
+        lambda.capture.as_mut(),
+        lambda.self_object.as_mut(),
+        &mut lambda_body,
+        &mut result,
+        &alias,
+        lambda.dynamic_params,
+    ));
+    default_args.drop_ref();
+    result.drop_ref();
+    Ok(new_lambda)
+}
+
+// functional.arity(fn)，返回 fn 声明的参数个数（即默认参数元组中可被位置参数填充的命名参数个数）
+// 原生函数没有参数声明，返回 -1 表示未知/可变
+pub fn arity(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "arity expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let lambda_obj = &mut tuple_obj.values[0];
+    if !lambda_obj.isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            lambda_obj.clone_ref(),
+            "Argument for arity must be a lambda".to_string(),
+        ));
+    }
+    let lambda = lambda_obj.as_const_type::<VMLambda>();
+    let arity = match lambda.lambda_body {
+        VMLambdaBody::VMNativeFunction(_) => -1,
+        _ => lambda
+            .default_args_tuple
+            .as_const_type::<VMTuple>()
+            .values
+            .iter()
+            .filter(|param| param.isinstance::<VMNamed>())
+            .count() as i64,
+    };
+    Ok(gc_system.new_object(VMInt::new(arity)))
+}
+
+// 取出第 index 个参数中的元组元素，非元组时返回 TypeError
+fn get_tuple_arg(
+    tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+) -> Result<Vec<GCRef>, VMVariableError> {
+    let arg = &mut tuple.as_type::<VMTuple>().values[index];
+    if !arg.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            arg.clone_ref(),
+            format!("Argument {} for {} must be a tuple", index + 1, func_name),
+        ));
+    }
+    Ok(arg.as_const_type::<VMTuple>().values.clone())
+}
+
+// 由若干 (a, b) 二元组组成结果元组，并释放中间对象
+fn build_pairs(pairs: Vec<(GCRef, GCRef)>, gc_system: &mut GCSystem) -> GCRef {
+    let mut tuples: Vec<GCRef> = pairs
+        .into_iter()
+        .map(|(mut first, mut second)| {
+            gc_system.new_object(VMTuple::new(&mut vec![&mut first, &mut second]))
+        })
+        .collect();
+    let result = gc_system.new_object(VMTuple::new(&mut tuples.iter_mut().collect()));
+    drop_all(&mut tuples);
+    result
+}
+
+// functional.zip(a, b)，返回 (a[i], b[i]) 组成的元组，长度取两者中较短的一个
+pub fn zip(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "zip")?;
+    let first = get_tuple_arg(tuple, 0, "zip")?;
+    let second = get_tuple_arg(tuple, 1, "zip")?;
+    Ok(build_pairs(
+        first.into_iter().zip(second).collect(),
+        gc_system,
+    ))
+}
+
+// functional.enumerate(tuple)，返回 (index, value) 组成的元组，index 从 0 开始
+pub fn enumerate(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 1, "enumerate")?;
+    let values = get_tuple_arg(tuple, 0, "enumerate")?;
+    let mut indices: Vec<GCRef> = (0..values.len())
+        .map(|index| gc_system.new_object(VMInt::new(index as i64)))
+        .collect();
+    let result = build_pairs(indices.iter().cloned().zip(values).collect(), gc_system);
+    drop_all(&mut indices);
+    Ok(result)
+}
+
+// functional.rename(fn, name)，返回 fn 的副本，其 signature 被替换为 name
+// 便于动态生成的函数在调试输出和错误回溯中显示可读的名字
+pub fn rename(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "rename")?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    let lambda_obj = &mut tuple_obj.values[0];
+    if !lambda_obj.isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            lambda_obj.clone_ref(),
+            "First argument for rename must be a lambda".to_string(),
+        ));
+    }
+    let mut lambda_ref = lambda_obj.clone();
+    let name_obj = &mut tuple_obj.values[1];
+    if !name_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            name_obj.clone_ref(),
+            "Second argument for rename must be a string".to_string(),
+        ));
+    }
+    let name = name_obj.as_const_type::<VMString>().value.clone();
+    let mut renamed = try_copy_as_vmobject(&mut lambda_ref, gc_system)?;
+    renamed.as_type::<VMLambda>().signature = name;
+    Ok(renamed)
+}
+
+// functional.fix(fn)，返回 fn 的副本，其第一个参数被绑定为这个副本本身，
+// 因此 fn 可以通过第一个参数递归调用自己而无需具名，例如 fix((self_fn?, n?) -> ...)(5)
+// 递归调用仍在同一个执行器中进行，无限递归会触发 StackOverflow 而不是耗尽原生栈
+pub fn fix(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 1, "fix")?;
+    let lambda_obj = &mut tuple.as_type::<VMTuple>().values[0];
+    if !lambda_obj.isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            lambda_obj.clone_ref(),
+            "Argument for fix must be a lambda".to_string(),
+        ));
+    }
+    let has_param = matches!(
+        lambda_obj.as_const_type::<VMLambda>().lambda_body,
+        VMLambdaBody::VMInstruction(_)
+    ) && lambda_obj
+        .as_const_type::<VMLambda>()
+        .default_args_tuple
+        .as_const_type::<VMTuple>()
+        .values
+        .iter()
+        .any(|param| param.isinstance::<VMNamed>());
+    if !has_param {
+        return Err(VMVariableError::TypeError(
+            lambda_obj.clone_ref(),
+            "Argument for fix must be a lambda with at least one parameter".to_string(),
+        ));
+    }
+    let mut lambda_ref = lambda_obj.clone();
+    let mut no_args = gc_system.new_object(VMTuple::new(&mut vec![]));
+    let fixed = bind_args(&mut lambda_ref, &mut no_args, gc_system);
+    no_args.drop_ref();
+    let mut fixed = fixed?;
+
+    // 新 lambda 的默认参数元组引用它自身，形成的环由 GC 的可达性分析回收
+    let mut self_ref = fixed.clone();
+    let params = &mut fixed.as_type::<VMLambda>().default_args_tuple;
+    let params_obj = params.as_type::<VMTuple>();
+    let index = params_obj
+        .values
+        .iter()
+        .position(|param| param.isinstance::<VMNamed>())
+        .unwrap();
+    if let Err(err) = try_assign_as_vmobject(&mut params_obj.values[index], &mut self_ref) {
+        fixed.drop_ref();
+        return Err(err);
+    }
+    let bound = params_obj.values.remove(index);
+    params_obj.values.push(bound);
+    Ok(fixed)
+}
+
+// functional.memoize(fn)，返回包装了 fn 的新 lambda，相同的参数元组（按结构化哈希与相等判断）只调用 fn 一次
+// 缓存不设上限，随包装 lambda 一同被回收；参数必须可哈希，缓存命中时返回的是同一个结果对象
+pub fn memoize(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 1, "memoize")?;
+    let lambda_obj = &mut tuple.as_type::<VMTuple>().values[0];
+    if !lambda_obj.isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            lambda_obj.clone_ref(),
+            "Argument for memoize must be a lambda".to_string(),
+        ));
+    }
+    let signature = format!(
+        "<builtins>::functional.memoize({})",
+        lambda_obj.as_const_type::<VMLambda>().signature
+    );
+    let mut cache = gc_system.new_object(VMDict::new());
+    let mut capture = gc_system.new_object(VMTuple::new(&mut vec![lambda_obj, &mut cache]));
+    cache.drop_ref();
+    let memoized = create_native_lambda(signature, memoized_call, Some(&mut capture), gc_system);
+    capture.drop_ref();
+    Ok(memoized)
+}
+
+// memoize 返回的 lambda 的函数体，capture 为 (fn, cache)
+fn memoized_call(
+    _self_object: Option<&mut GCRef>,
+    capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let Some(capture) = capture else {
+        return Err(VMVariableError::DetailedError(
+            "memoized lambda is missing its cache".to_string(),
+        ));
+    };
+    let capture_obj = capture.as_type::<VMTuple>();
+    let mut lambda = capture_obj.values[0].clone();
+    let mut cache = capture_obj.values[1].clone();
+    if let Some(cached) = cache.as_type::<VMDict>().get(tuple)? {
+        return Ok(cached.clone_ref());
+    }
+
+    let mut result =
+        call_lambda_sync(&mut lambda, tuple, gc_system).map_err(VMError::into_variable_error)?;
+    // 参数之后可能被原地修改，缓存中保存它的深拷贝
+    let mut key = match try_deepcopy_as_vmobject(tuple, gc_system) {
+        Ok(key) => key,
+        Err(err) => {
+            result.drop_ref();
+            return Err(err);
+        }
+    };
+    let inserted = cache.as_type::<VMDict>().insert(&mut key, &mut result);
+    key.drop_ref();
+    if let Err(err) = inserted {
+        result.drop_ref();
+        return Err(err);
+    }
+    Ok(result)
+}
+
+pub fn get_functional_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![
+        ("map", map),
+        ("filter", filter),
+        ("reduce", reduce),
+        ("partial", partial),
+        ("arity", arity),
+        ("zip", zip),
+        ("enumerate", enumerate),
+        ("rename", rename),
+        ("fix", fix),
+        ("memoize", memoize),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+    use xlang_vm_core::executor::variable::try_repr_vmobject;
+
+    fn native_lambda(name: &str, body: NativeFunction, gc_system: &mut GCSystem) -> GCRef {
+        let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
+        let mut result = gc_system.new_object(VMNull::new());
+        let lambda = gc_system.new_object(VMLambda::new(
+            0,
+            format!("<test>::{}", name),
+            &mut params,
+            None,
+            None,
+            &mut VMLambdaBody::VMNativeFunction(body),
+            &mut result,
+            false,
+        ));
+        params.drop_ref();
+        result.drop_ref();
+        lambda
+    }
+
+    fn int_tuple(values: &[i64], gc_system: &mut GCSystem) -> GCRef {
+        let mut items: Vec<GCRef> = values
+            .iter()
+            .map(|v| gc_system.new_object(VMInt::new(*v)))
+            .collect();
+        let tuple = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+        drop_all(&mut items);
+        tuple
+    }
+
+    fn double(
+        _: Option<&mut GCRef>,
+        _: Option<&mut GCRef>,
+        args: &mut GCRef,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let value = args.as_const_type::<VMTuple>().values[0]
+            .as_const_type::<VMInt>()
+            .value;
+        Ok(gc_system.new_object(VMInt::new(value * 2)))
+    }
+
+    fn is_even(
+        _: Option<&mut GCRef>,
+        _: Option<&mut GCRef>,
+        args: &mut GCRef,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let value = args.as_const_type::<VMTuple>().values[0]
+            .as_const_type::<VMInt>()
+            .value;
+        Ok(gc_system.new_object(VMBoolean::new(value % 2 == 0)))
+    }
+
+    fn add(
+        _: Option<&mut GCRef>,
+        _: Option<&mut GCRef>,
+        args: &mut GCRef,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let values = &args.as_const_type::<VMTuple>().values;
+        let a = values[0].as_const_type::<VMInt>().value;
+        let b = values[1].as_const_type::<VMInt>().value;
+        Ok(gc_system.new_object(VMInt::new(a + b)))
+    }
+
+    #[test]
+    fn test_map_filter_reduce() {
+        let mut gc_system = GCSystem::new(None);
+        let mut target = int_tuple(&[1, 2, 3, 4], &mut gc_system);
+        let mut init = gc_system.new_object(VMInt::new(0));
+        let mut double_fn = native_lambda("double", double, &mut gc_system);
+        let mut even_fn = native_lambda("is_even", is_even, &mut gc_system);
+        let mut add_fn = native_lambda("add", add, &mut gc_system);
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target, &mut double_fn]));
+        let mut mapped = map(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(
+            try_repr_vmobject(&mut mapped, None).unwrap(),
+            "(2, 4, 6, 8)"
+        );
+        args.drop_ref();
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target, &mut even_fn]));
+        let mut filtered = filter(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(try_repr_vmobject(&mut filtered, None).unwrap(), "(2, 4)");
+        args.drop_ref();
+
+        let mut args =
+            gc_system.new_object(VMTuple::new(&mut vec![&mut target, &mut add_fn, &mut init]));
+        let mut sum = reduce(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(try_repr_vmobject(&mut sum, None).unwrap(), "10");
+        args.drop_ref();
+
+        for obj in [
+            &mut target,
+            &mut init,
+            &mut double_fn,
+            &mut even_fn,
+            &mut add_fn,
+            &mut mapped,
+            &mut filtered,
+            &mut sum,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_empty_tuple() {
+        let mut gc_system = GCSystem::new(None);
+        let mut target = int_tuple(&[], &mut gc_system);
+        let mut init = gc_system.new_object(VMInt::new(42));
+        let mut double_fn = native_lambda("double", double, &mut gc_system);
+        let mut even_fn = native_lambda("is_even", is_even, &mut gc_system);
+        let mut add_fn = native_lambda("add", add, &mut gc_system);
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target, &mut double_fn]));
+        let mut mapped = map(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(try_repr_vmobject(&mut mapped, None).unwrap(), "()");
+        args.drop_ref();
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target, &mut even_fn]));
+        let mut filtered = filter(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(try_repr_vmobject(&mut filtered, None).unwrap(), "()");
+        args.drop_ref();
+
+        let mut args =
+            gc_system.new_object(VMTuple::new(&mut vec![&mut target, &mut add_fn, &mut init]));
+        let mut reduced = reduce(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(try_repr_vmobject(&mut reduced, None).unwrap(), "42");
+        args.drop_ref();
+
+        for obj in [
+            &mut target,
+            &mut init,
+            &mut double_fn,
+            &mut even_fn,
+            &mut add_fn,
+            &mut mapped,
+            &mut filtered,
+            &mut reduced,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_zip_truncates_to_shorter() {
+        let mut gc_system = GCSystem::new(None);
+        let mut first = int_tuple(&[1, 2, 3], &mut gc_system);
+        let mut second = int_tuple(&[10, 20], &mut gc_system);
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut first, &mut second]));
+        let mut zipped = zip(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(
+            try_repr_vmobject(&mut zipped, None).unwrap(),
+            "((1, 10), (2, 20))"
+        );
+        args.drop_ref();
+
+        let mut not_a_tuple = gc_system.new_object(VMInt::new(1));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut first, &mut not_a_tuple]));
+        match zip(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("zip should reject a non-tuple"),
+        }
+        args.drop_ref();
+
+        for obj in [&mut first, &mut second, &mut zipped, &mut not_a_tuple] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_enumerate() {
+        let mut gc_system = GCSystem::new(None);
+        let mut target = int_tuple(&[7, 8, 9], &mut gc_system);
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target]));
+        let mut enumerated = enumerate(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(
+            try_repr_vmobject(&mut enumerated, None).unwrap(),
+            "((0, 7), (1, 8), (2, 9))"
+        );
+        for obj in [&mut target, &mut args, &mut enumerated] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut gc_system = GCSystem::new(None);
+        let mut lambda = native_lambda("double", double, &mut gc_system);
+        let mut name = gc_system.new_object(VMString::new("make_adder::<lambda>"));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut lambda, &mut name]));
+        let mut renamed = rename(None, None, &mut args, &mut gc_system).unwrap();
+        assert!(try_repr_vmobject(&mut renamed, None)
+            .unwrap()
+            .starts_with("make_adder::<lambda>::"));
+        // 原 lambda 不受影响
+        assert_eq!(
+            lambda.as_const_type::<VMLambda>().signature,
+            "<test>::double"
+        );
+        args.drop_ref();
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut renamed, &mut lambda]));
+        match rename(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("rename should reject a non-string name"),
+        }
+        args.drop_ref();
+
+        for obj in [&mut lambda, &mut name, &mut renamed] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_partial() {
+        let mut gc_system = GCSystem::new(None);
+        let mut add_fn = native_lambda("add", add, &mut gc_system);
+        let mut bound = int_tuple(&[3], &mut gc_system);
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut add_fn, &mut bound]));
+        let mut add3 = partial(None, None, &mut args, &mut gc_system).unwrap();
+        args.drop_ref();
+
+        let mut four = gc_system.new_object(VMInt::new(4));
+        let mut result = call_lambda(&mut add3, &mut vec![&mut four], &mut gc_system).unwrap();
+        assert_eq!(try_repr_vmobject(&mut result, None).unwrap(), "7");
+        // 原 lambda 的默认参数不受影响
+        let original_defaults = &add_fn.as_const_type::<VMLambda>().default_args_tuple;
+        assert!(original_defaults
+            .as_const_type::<VMTuple>()
+            .values
+            .is_empty());
+
+        let mut not_a_lambda = gc_system.new_object(VMInt::new(1));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut not_a_lambda, &mut bound]));
+        match partial(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("partial should reject a non-lambda"),
+        }
+        args.drop_ref();
+
+        for obj in [
+            &mut add_fn,
+            &mut bound,
+            &mut add3,
+            &mut four,
+            &mut result,
+            &mut not_a_lambda,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
@@ -0,0 +1,124 @@
+use xlang_vm_core::{
+    executor::variable::{VMInt, VMKeyVal, VMNull, VMString, VMTuple, VMVariableError},
+    gc::{GCRef, GCSystem},
+};
+
+use super::check_if_tuple;
+
+fn check_no_args(tuple: &mut GCRef, func_name: &str) -> Result<(), VMVariableError> {
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 0 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("{} expected 0 arguments, got {}", func_name, arg_count),
+        ));
+    }
+    Ok(())
+}
+
+// gc.stats()，返回 (objects : int, native_refs : int, collections : int)
+// 计数在构建返回值之前读取，不包含返回值本身
+pub fn stats(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_no_args(tuple, "stats")?;
+    let entries = [
+        ("objects", gc_system._count()),
+        ("native_refs", gc_system.native_ref_count()),
+        ("collections", gc_system.collection_count()),
+    ];
+    let mut pairs = Vec::with_capacity(entries.len());
+    for (key, value) in entries {
+        let mut key_obj = gc_system.new_object(VMString::new(key));
+        let mut val_obj = gc_system.new_object(VMInt::new(value as i64));
+        pairs.push(gc_system.new_object(VMKeyVal::new(&mut key_obj, &mut val_obj)));
+        key_obj.drop_ref();
+        val_obj.drop_ref();
+    }
+    let result = gc_system.new_object(VMTuple::new(&mut pairs.iter_mut().collect()));
+    for pair in pairs.iter_mut() {
+        pair.drop_ref();
+    }
+    Ok(result)
+}
+
+// gc.collect()，立即执行一次完整回收
+pub fn collect(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_no_args(tuple, "collect")?;
+    gc_system.collect();
+    Ok(gc_system.new_object(VMNull::new()))
+}
+
+pub fn get_gc_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![("stats", stats), ("collect", collect)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(stats: &mut GCRef, key: &str, gc_system: &mut GCSystem) -> i64 {
+        let value = stats
+            .as_type::<VMTuple>()
+            .get_member_by_string(key, gc_system)
+            .unwrap();
+        value.as_const_type::<VMInt>().value
+    }
+
+    #[test]
+    fn test_collect_reduces_live_objects() {
+        let mut gc_system = GCSystem::new(None);
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![]));
+
+        // 200 个对象：每个元组引用一个整数
+        let mut garbage = Vec::new();
+        for i in 0..100 {
+            let mut value = gc_system.new_object(VMInt::new(i));
+            garbage.push(gc_system.new_object(VMTuple::new(&mut vec![&mut value])));
+            value.drop_ref();
+        }
+        let mut before = stats(None, None, &mut args, &mut gc_system).unwrap();
+        let objects_before = stat(&mut before, "objects", &mut gc_system);
+        assert_eq!(objects_before, 201);
+        assert!(stat(&mut before, "native_refs", &mut gc_system) > 100);
+        let collections_before = stat(&mut before, "collections", &mut gc_system);
+        before.drop_ref();
+
+        for obj in garbage.iter_mut() {
+            obj.drop_ref();
+        }
+        let mut result = collect(None, None, &mut args, &mut gc_system).unwrap();
+        result.drop_ref();
+
+        let mut after = stats(None, None, &mut args, &mut gc_system).unwrap();
+        // 剩下 args 以及 collect 返回后才释放的 null
+        assert_eq!(stat(&mut after, "objects", &mut gc_system), 2);
+        assert_eq!(
+            stat(&mut after, "collections", &mut gc_system),
+            collections_before + 1
+        );
+        after.drop_ref();
+
+        args.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
@@ -0,0 +1,304 @@
+use reqwest::{header::HeaderMap, Method};
+use rustc_hash::FxHashMap;
+use xlang_vm_core::{
+    executor::variable::{VMBytes, VMInt, VMNamed, VMString, VMTuple, VMVariableError},
+    gc::{GCRef, GCSystem},
+};
+
+use super::async_request::{parse_headers, CLIENT, RUNTIME};
+use super::{build_dict, check_if_tuple};
+
+fn get_url_arg(tuple: &mut GCRef, func_name: &str) -> Result<String, VMVariableError> {
+    let url_obj = &mut tuple.as_type::<VMTuple>().values[0];
+    if !url_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            url_obj.clone_ref(),
+            format!("Argument 'url' for {} must be a string", func_name),
+        ));
+    }
+    Ok(url_obj.as_const_type::<VMString>().value.clone())
+}
+
+struct Response {
+    status: i64,
+    headers: HeaderMap,
+    body: String,
+}
+
+// 在共享的 tokio runtime 上同步地完成一次请求，网络错误以字符串形式返回
+fn send_blocking(
+    method: Method,
+    url: &str,
+    headers: HeaderMap,
+    body: Option<Vec<u8>>,
+) -> Result<Response, String> {
+    RUNTIME.block_on(async {
+        let mut request = CLIENT.request(method, url).headers(headers);
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        let status = response.status().as_u16() as i64;
+        let headers = response.headers().clone();
+        let body = response.bytes().await.map_err(|e| e.to_string())?;
+        Ok(Response {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    })
+}
+
+// 构造 (status => int, body => string, headers => (name : value, ...))
+fn build_response(response: Response, gc_system: &mut GCSystem) -> GCRef {
+    let mut header_values: Vec<(String, GCRef)> = response
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (
+                name.as_str().to_string(),
+                gc_system.new_object(VMString::new(&value)),
+            )
+        })
+        .collect();
+    let mut headers_map: FxHashMap<&str, GCRef> = header_values
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.clone()))
+        .collect();
+    let mut headers = build_dict(&mut headers_map, gc_system);
+    for (_, value) in header_values.iter_mut() {
+        value.drop_ref();
+    }
+
+    let mut status = gc_system.new_object(VMInt::new(response.status));
+    let mut body = gc_system.new_object(VMString::new(&response.body));
+    let mut items = Vec::new();
+    for (key, value) in [
+        ("status", &mut status),
+        ("body", &mut body),
+        ("headers", &mut headers),
+    ] {
+        let mut key = gc_system.new_object(VMString::new(key));
+        items.push(gc_system.new_object(VMNamed::new(&mut key, value)));
+        key.drop_ref();
+        value.drop_ref();
+    }
+    let result = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+    for item in items.iter_mut() {
+        item.drop_ref();
+    }
+    result
+}
+
+fn request_blocking(
+    tuple: &mut GCRef,
+    method: Method,
+    url: &str,
+    headers: HeaderMap,
+    body: Option<Vec<u8>>,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    match send_blocking(method, url, headers, body) {
+        Ok(response) => Ok(build_response(response, gc_system)),
+        Err(err) => Err(VMVariableError::ValueError(
+            tuple.clone_ref(),
+            format!("HTTP request to '{}' failed: {}", url, err),
+        )),
+    }
+}
+
+// http.get(url)，阻塞地发送 GET 请求
+pub fn get(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("get expected 1 argument, got {}", arg_count),
+        ));
+    }
+    let url = get_url_arg(tuple, "get")?;
+    request_blocking(tuple, Method::GET, &url, HeaderMap::new(), None, gc_system)
+}
+
+// http.post(url, body, [headers])，body 为 string 或 bytes，headers 为 (key : value, ...) 元组
+pub fn post(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 2 && arg_count != 3 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("post expected 2 or 3 arguments, got {}", arg_count),
+        ));
+    }
+    let url = get_url_arg(tuple, "post")?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    let body_obj = &mut tuple_obj.values[1];
+    let body = if body_obj.isinstance::<VMString>() {
+        body_obj
+            .as_const_type::<VMString>()
+            .value
+            .as_bytes()
+            .to_vec()
+    } else if body_obj.isinstance::<VMBytes>() {
+        body_obj.as_const_type::<VMBytes>().value.clone()
+    } else {
+        return Err(VMVariableError::TypeError(
+            body_obj.clone_ref(),
+            "Argument 'body' for post must be a string or bytes".to_string(),
+        ));
+    };
+    let headers = if arg_count == 3 {
+        let headers_obj = &mut tuple_obj.values[2];
+        if !headers_obj.isinstance::<VMTuple>() {
+            return Err(VMVariableError::TypeError(
+                headers_obj.clone_ref(),
+                "Argument 'headers' for post must be a tuple".to_string(),
+            ));
+        }
+        parse_headers(headers_obj)?
+    } else {
+        HeaderMap::new()
+    };
+    request_blocking(tuple, Method::POST, &url, headers, Some(body), gc_system)
+}
+
+pub fn get_http_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![("get", get), ("post", post)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unreachable_host_is_value_error() {
+        let mut gc_system = GCSystem::new(None);
+        let mut url = gc_system.new_object(VMString::new("http://127.0.0.1:9/"));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut url]));
+        match get(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("connection failure should be reported as a ValueError"),
+        }
+        url.drop_ref();
+        args.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    // 需要本地监听端口，默认不运行：cargo test --features http-tests
+    #[cfg(feature = "http-tests")]
+    #[test]
+    fn test_get_and_post_against_mock_server() {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+        use xlang_vm_core::executor::variable::try_repr_vmobject;
+
+        // 接受两个连接，把请求方法与 body 写回响应体
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                let mut x_test = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let (name, value) = line.split_once(": ").unwrap();
+                    match name.to_ascii_lowercase().as_str() {
+                        "content-length" => content_length = value.parse().unwrap(),
+                        "x-test" => x_test = value.to_string(),
+                        _ => {}
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let method = request_line.split(' ').next().unwrap();
+                let reply = format!("{} {}{}", method, String::from_utf8(body).unwrap(), x_test);
+                write!(
+                    stream,
+                    "HTTP/1.1 201 Created\r\nContent-Length: {}\r\nX-Mock: yes\r\nConnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                )
+                .unwrap();
+            }
+        });
+
+        let mut gc_system = GCSystem::new(None);
+        let base = format!("http://127.0.0.1:{}/", port);
+
+        let mut url = gc_system.new_object(VMString::new(&base));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut url]));
+        let mut response = get(None, None, &mut args, &mut gc_system).unwrap();
+        let repr = try_repr_vmobject(&mut response, None).unwrap();
+        assert!(
+            repr.starts_with("(\"status\" => 201, \"body\" => \"GET \""),
+            "{}",
+            repr
+        );
+        assert!(repr.contains("\"x-mock\": \"yes\""), "{}", repr);
+        response.drop_ref();
+        args.drop_ref();
+
+        let mut body = gc_system.new_object(VMString::new("payload"));
+        let mut key = gc_system.new_object(VMString::new("X-Test"));
+        let mut value = gc_system.new_object(VMString::new("!"));
+        let mut header = gc_system.new_object(xlang_vm_core::executor::variable::VMKeyVal::new(
+            &mut key, &mut value,
+        ));
+        let mut headers = gc_system.new_object(VMTuple::new(&mut vec![&mut header]));
+        let mut args =
+            gc_system.new_object(VMTuple::new(&mut vec![&mut url, &mut body, &mut headers]));
+        let mut response = post(None, None, &mut args, &mut gc_system).unwrap();
+        let repr = try_repr_vmobject(&mut response, None).unwrap();
+        assert!(
+            repr.starts_with("(\"status\" => 201, \"body\" => \"POST payload!\""),
+            "{}",
+            repr
+        );
+
+        for obj in [
+            &mut response,
+            &mut args,
+            &mut url,
+            &mut body,
+            &mut key,
+            &mut value,
+            &mut header,
+            &mut headers,
+        ] {
+            obj.drop_ref();
+        }
+        server.join().unwrap();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
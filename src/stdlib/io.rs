@@ -1,7 +1,11 @@
 use super::check_if_tuple;
-use std::io::Write;
+use colored::{ColoredString, Colorize};
+use std::io::{BufRead, Read, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
 use xlang_vm_core::{
-    executor::variable::{try_to_string_vmobject, VMNull, VMString, VMTuple, VMVariableError},
+    executor::variable::{
+        try_to_bool_as_vmobject, try_to_string_vmobject, VMNull, VMString, VMTuple, VMVariableError,
+    },
     gc::{GCRef, GCSystem},
 };
 
@@ -68,6 +72,235 @@ pub fn input(
     return Ok(gc_system.new_object(VMString::new(&data)));
 }
 
+// io.assert(condition, message)，condition 按 bool 规则转换，为 false 时报错并附带 message
+pub fn assert(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "assert expected 2 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let message_obj = &mut tuple_obj.values[1];
+    if !message_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            message_obj.clone_ref(),
+            "Argument 'message' for assert must be a string".to_string(),
+        ));
+    }
+    let message = message_obj.as_const_type::<VMString>().value.clone();
+
+    let condition_obj = &mut tuple_obj.values[0];
+    // 与 if / while 使用同样的真值规则
+    let condition = try_to_bool_as_vmobject(condition_obj)?;
+    if !condition {
+        return Err(VMVariableError::ValueError(
+            condition_obj.clone_ref(),
+            format!("Assertion failed: {}", message),
+        ));
+    }
+    Ok(gc_system.new_object(VMNull::new()))
+}
+
+fn check_no_args(tuple: &mut GCRef, func_name: &str) -> Result<(), VMVariableError> {
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 0 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("{} expected 0 arguments, got {}", func_name, arg_count),
+        ));
+    }
+    Ok(())
+}
+
+// 从 reader 读取一行并去掉结尾的换行符，EOF 时返回 null
+fn read_line_from(
+    reader: &mut impl BufRead,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) => Ok(gc_system.new_object(VMNull::new())),
+        Ok(_) => {
+            let line = line.trim_end_matches(['\r', '\n']);
+            Ok(gc_system.new_object(VMString::new(line)))
+        }
+        Err(err) => Err(VMVariableError::ValueError(
+            tuple.clone_ref(),
+            format!("Failed to read line from stdin: {}", err),
+        )),
+    }
+}
+
+// 读取 reader 中剩余的全部内容
+fn read_all_from(
+    reader: &mut impl Read,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let mut content = String::new();
+    match reader.read_to_string(&mut content) {
+        Ok(_) => Ok(gc_system.new_object(VMString::new(&content))),
+        Err(err) => Err(VMVariableError::ValueError(
+            tuple.clone_ref(),
+            format!("Failed to read stdin: {}", err),
+        )),
+    }
+}
+
+// io.read_line()，返回不含换行符的一行，EOF 时返回 null
+pub fn read_line(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_no_args(tuple, "read_line")?;
+    read_line_from(&mut std::io::stdin().lock(), tuple, gc_system)
+}
+
+// io.read_all()，读取 stdin 直到 EOF
+pub fn read_all(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_no_args(tuple, "read_all")?;
+    read_all_from(&mut std::io::stdin().lock(), tuple, gc_system)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LogLevel {
+    Debug = 0,
+    Info = 1,
+    Warn = 2,
+    Error = 3,
+}
+
+impl LogLevel {
+    fn parse(name: &str) -> Option<LogLevel> {
+        match name {
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" => Some(LogLevel::Warn),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> LogLevel {
+        match value {
+            0 => LogLevel::Debug,
+            1 => LogLevel::Info,
+            2 => LogLevel::Warn,
+            _ => LogLevel::Error,
+        }
+    }
+
+    fn label(self) -> ColoredString {
+        match self {
+            LogLevel::Debug => "[DEBUG]".bright_black(),
+            LogLevel::Info => "[INFO]".green(),
+            LogLevel::Warn => "[WARN]".yellow(),
+            LogLevel::Error => "[ERROR]".red().bold(),
+        }
+    }
+}
+
+// 全局最低日志级别，低于该级别的 io.log 输出会被丢弃，默认为 info
+static MIN_LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+fn get_level_arg(
+    tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+) -> Result<LogLevel, VMVariableError> {
+    let level_obj = &mut tuple.as_type::<VMTuple>().values[index];
+    if !level_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            level_obj.clone_ref(),
+            format!("Argument 'level' for {} must be a string", func_name),
+        ));
+    }
+    let name = level_obj.as_const_type::<VMString>().value.clone();
+    LogLevel::parse(&name).ok_or_else(|| {
+        VMVariableError::ValueError(
+            level_obj.clone_ref(),
+            format!(
+                "Invalid log level {:?}, expected one of \"debug\", \"info\", \"warn\", \"error\"",
+                name
+            ),
+        )
+    })
+}
+
+// 级别不低于全局最低级别时写入一行日志，返回是否实际输出
+fn log_to(out: &mut impl Write, level: LogLevel, message: &str) -> bool {
+    if level < LogLevel::from_u8(MIN_LOG_LEVEL.load(Ordering::Relaxed)) {
+        return false;
+    }
+    writeln!(out, "{} {}", level.label(), message).unwrap_or(());
+    true
+}
+
+// io.log(level, message)，warn / error 写入 stderr，其余写入 stdout
+pub fn log(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("log expected 2 arguments, got {}", arg_count),
+        ));
+    }
+    let level = get_level_arg(tuple, 0, "log")?;
+    let message = try_to_string_vmobject(&mut tuple.as_type::<VMTuple>().values[1], None)?;
+    if level >= LogLevel::Warn {
+        log_to(&mut std::io::stderr().lock(), level, &message);
+    } else {
+        log_to(&mut std::io::stdout().lock(), level, &message);
+    }
+    Ok(gc_system.new_object(VMNull::new()))
+}
+
+// io.set_log_level(level)，设置 io.log 的全局最低输出级别
+pub fn set_log_level(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("set_log_level expected 1 argument, got {}", arg_count),
+        ));
+    }
+    let level = get_level_arg(tuple, 0, "set_log_level")?;
+    MIN_LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+    Ok(gc_system.new_object(VMNull::new()))
+}
+
 // Helper to provide functions for registration
 pub fn get_io_functions() -> Vec<(
     &'static str,
@@ -78,5 +311,111 @@ pub fn get_io_functions() -> Vec<(
         &mut GCSystem,
     ) -> Result<GCRef, VMVariableError>,
 )> {
-    vec![("print", print), ("input", input)]
+    vec![
+        ("print", print),
+        ("input", input),
+        ("assert", assert),
+        ("read_line", read_line),
+        ("read_all", read_all),
+        ("log", log),
+        ("set_log_level", set_log_level),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use xlang_vm_core::executor::variable::try_repr_vmobject;
+
+    fn read_repr(result: Result<GCRef, VMVariableError>) -> String {
+        let mut value = result.unwrap();
+        let repr = try_repr_vmobject(&mut value, None).unwrap();
+        value.drop_ref();
+        repr
+    }
+
+    #[test]
+    fn test_read_line_and_read_all() {
+        let mut gc_system = GCSystem::new(None);
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![]));
+
+        let mut input = Cursor::new("first\r\nsecond\nthird");
+        assert_eq!(
+            read_repr(read_line_from(&mut input, &mut args, &mut gc_system)),
+            "\"first\""
+        );
+        assert_eq!(
+            read_repr(read_line_from(&mut input, &mut args, &mut gc_system)),
+            "\"second\""
+        );
+        assert_eq!(
+            read_repr(read_line_from(&mut input, &mut args, &mut gc_system)),
+            "\"third\""
+        );
+        assert_eq!(
+            read_repr(read_line_from(&mut input, &mut args, &mut gc_system)),
+            "null"
+        );
+
+        let mut input = Cursor::new("line 1\nline 2\n");
+        assert_eq!(
+            read_repr(read_line_from(&mut input, &mut args, &mut gc_system)),
+            "\"line 1\""
+        );
+        assert_eq!(
+            read_repr(read_all_from(&mut input, &mut args, &mut gc_system)),
+            "\"line 2\\n\""
+        );
+        assert_eq!(
+            read_repr(read_all_from(&mut input, &mut args, &mut gc_system)),
+            "\"\""
+        );
+
+        // 非 UTF-8 输入视为读取错误
+        let mut input = Cursor::new(vec![0xff, 0xfe, b'\n']);
+        match read_line_from(&mut input, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("invalid UTF-8 should be reported as a ValueError"),
+        }
+
+        args.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_log_level_filtering() {
+        let mut gc_system = GCSystem::new(None);
+        let set_level = |name: &str, gc_system: &mut GCSystem| {
+            let mut level = gc_system.new_object(VMString::new(name));
+            let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut level]));
+            let result = set_log_level(None, None, &mut args, gc_system).map(|mut result| {
+                result.drop_ref();
+            });
+            level.drop_ref();
+            args.drop_ref();
+            result
+        };
+
+        set_level("warn", &mut gc_system).unwrap();
+        let mut out = Vec::new();
+        assert!(!log_to(&mut out, LogLevel::Debug, "hidden"));
+        assert!(!log_to(&mut out, LogLevel::Info, "hidden"));
+        assert!(log_to(&mut out, LogLevel::Warn, "careful"));
+        assert!(log_to(&mut out, LogLevel::Error, "broken"));
+        let output = String::from_utf8(out).unwrap();
+        assert!(!output.contains("hidden"));
+        assert!(output.contains("careful") && output.contains("broken"));
+        assert_eq!(output.lines().count(), 2);
+
+        match set_level("verbose", &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("unknown log level should be reported as a ValueError"),
+        }
+        set_level("info", &mut gc_system).unwrap();
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
 }
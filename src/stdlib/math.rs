@@ -0,0 +1,943 @@
+use xlang_vm_core::{
+    executor::variable::{
+        try_add_as_vmobject, try_greater_than_as_vmobject, try_less_than_as_vmobject,
+        try_mul_as_vmobject, VMBoolean, VMFloat, VMInt, VMTuple, VMVariableError,
+    },
+    gc::{GCRef, GCSystem},
+};
+
+use super::{check_arg_count, check_if_tuple};
+
+// Helper to extract a numeric argument as f64, accepting both int and float
+fn get_number_arg(
+    args_tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+) -> Result<f64, VMVariableError> {
+    let tuple_obj = args_tuple.as_type::<VMTuple>();
+    if index >= tuple_obj.values.len() {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("{} missing argument at position {}", func_name, index),
+        ));
+    }
+    let arg_obj = &mut tuple_obj.values[index];
+    if arg_obj.isinstance::<VMFloat>() {
+        return Ok(arg_obj.as_const_type::<VMFloat>().value);
+    }
+    if arg_obj.isinstance::<VMInt>() {
+        return Ok(arg_obj.as_const_type::<VMInt>().value as f64);
+    }
+    Err(VMVariableError::TypeError(
+        arg_obj.clone_ref(),
+        format!("Argument for {} must be an integer or float", func_name),
+    ))
+}
+
+// math.is_nan(number)
+pub fn is_nan(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("is_nan expected 1 argument, got {}", arg_count),
+        ));
+    }
+    let value = get_number_arg(tuple, 0, "is_nan")?;
+    Ok(gc_system.new_object(VMBoolean::new(value.is_nan())))
+}
+
+// math.is_infinite(number)
+pub fn is_infinite(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("is_infinite expected 1 argument, got {}", arg_count),
+        ));
+    }
+    let value = get_number_arg(tuple, 0, "is_infinite")?;
+    Ok(gc_system.new_object(VMBoolean::new(value.is_infinite())))
+}
+
+// Helper to extract an integer argument
+fn get_int_arg(
+    args_tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+) -> Result<i64, VMVariableError> {
+    let tuple_obj = args_tuple.as_type::<VMTuple>();
+    if index >= tuple_obj.values.len() {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("{} missing argument at position {}", func_name, index),
+        ));
+    }
+    let arg_obj = &mut tuple_obj.values[index];
+    if !arg_obj.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            arg_obj.clone_ref(),
+            format!("Argument for {} must be an integer", func_name),
+        ));
+    }
+    Ok(arg_obj.as_const_type::<VMInt>().value)
+}
+
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// 结果超出 i64 时（如 gcd(i64::MIN, 0)）返回 OverflowError
+fn overflow_error(tuple: &mut GCRef, func_name: &str) -> VMVariableError {
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    VMVariableError::OverflowError(
+        tuple_obj.values[0].clone_ref(),
+        tuple_obj.values[1].clone_ref(),
+        format!("{} result does not fit in an integer", func_name),
+    )
+}
+
+// math.gcd(a, b)，结果非负，gcd(0, 0) = 0
+pub fn gcd(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "gcd")?;
+    let a = get_int_arg(tuple, 0, "gcd")?;
+    let b = get_int_arg(tuple, 1, "gcd")?;
+    let result = gcd_u64(a.unsigned_abs(), b.unsigned_abs());
+    match i64::try_from(result) {
+        Ok(result) => Ok(gc_system.new_object(VMInt::new(result))),
+        Err(_) => Err(overflow_error(tuple, "gcd")),
+    }
+}
+
+// math.lcm(a, b)，结果非负，任一参数为 0 时返回 0
+pub fn lcm(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "lcm")?;
+    let a = get_int_arg(tuple, 0, "lcm")?.unsigned_abs();
+    let b = get_int_arg(tuple, 1, "lcm")?.unsigned_abs();
+    if a == 0 || b == 0 {
+        return Ok(gc_system.new_object(VMInt::new(0)));
+    }
+    let result = (a / gcd_u64(a, b))
+        .checked_mul(b)
+        .and_then(|result| i64::try_from(result).ok());
+    match result {
+        Some(result) => Ok(gc_system.new_object(VMInt::new(result))),
+        None => Err(overflow_error(tuple, "lcm")),
+    }
+}
+
+// math.floordiv(a, b)，向下取整的整数除法，b 为 0 时返回 ValueError
+pub fn floordiv(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "floordiv")?;
+    get_int_arg(tuple, 0, "floordiv")?;
+    get_int_arg(tuple, 1, "floordiv")?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    let (dividend, divisor) = tuple_obj.values.split_at_mut(1);
+    dividend[0]
+        .as_type::<VMInt>()
+        .floor_div(&mut divisor[0], gc_system)
+}
+
+// math.isqrt(n)，返回不大于 sqrt(n) 的最大整数
+pub fn isqrt(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 1, "isqrt")?;
+    let n = get_int_arg(tuple, 0, "isqrt")?;
+    if n < 0 {
+        return Err(VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            "isqrt is not defined for negative numbers".to_string(),
+        ));
+    }
+    Ok(gc_system.new_object(VMInt::new(n.isqrt())))
+}
+
+// 把取整后的浮点数转换为整数，NaN、无穷或超出 i64 范围时返回 OverflowError
+fn float_to_int(value: f64, tuple: &mut GCRef, func_name: &str) -> Result<i64, VMVariableError> {
+    // i64::MAX 无法用 f64 精确表示，2^63 本身已经越界
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if value.is_finite() && (-LIMIT..LIMIT).contains(&value) {
+        return Ok(value as i64);
+    }
+    Err(VMVariableError::OverflowError(
+        tuple.as_type::<VMTuple>().values[0].clone_ref(),
+        tuple.clone_ref(),
+        format!("{} result does not fit in an integer", func_name),
+    ))
+}
+
+// 整数参数原样返回，浮点数参数用 rounding 取整后转换为整数
+fn round_with(
+    tuple: &mut GCRef,
+    func_name: &str,
+    rounding: fn(f64) -> f64,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 1, func_name)?;
+    let arg_obj = &tuple.as_const_type::<VMTuple>().values[0];
+    if arg_obj.isinstance::<VMInt>() {
+        let value = arg_obj.as_const_type::<VMInt>().value;
+        return Ok(gc_system.new_object(VMInt::new(value)));
+    }
+    let value = get_number_arg(tuple, 0, func_name)?;
+    let result = float_to_int(rounding(value), tuple, func_name)?;
+    Ok(gc_system.new_object(VMInt::new(result)))
+}
+
+// math.round(x)，四舍五入，.5 时远离 0 取整
+pub fn round(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    round_with(tuple, "round", f64::round, gc_system)
+}
+
+// math.round_half_even(x)，银行家舍入，.5 时取最近的偶数
+pub fn round_half_even(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    round_with(tuple, "round_half_even", f64::round_ties_even, gc_system)
+}
+
+// math.trunc(x)，向 0 取整
+pub fn trunc(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    round_with(tuple, "trunc", f64::trunc, gc_system)
+}
+
+// math.round_to(x, decimals)，保留 decimals 位小数（可为负数，表示十位、百位等），返回浮点数
+pub fn round_to(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "round_to")?;
+    let value = get_number_arg(tuple, 0, "round_to")?;
+    let decimals = get_int_arg(tuple, 1, "round_to")?.clamp(-308, 308) as i32;
+    let factor = 10f64.powi(decimals);
+    let scaled = value * factor;
+    // 放大后溢出时原值已经没有更多小数位可以舍入
+    let result = if scaled.is_finite() {
+        scaled.round() / factor
+    } else {
+        value
+    };
+    Ok(gc_system.new_object(VMFloat::new(result)))
+}
+
+// 以下位运算均按 64 位补码处理整数，例如 popcount(-1) = 64
+fn bit_count_with(
+    tuple: &mut GCRef,
+    func_name: &str,
+    count: fn(i64) -> u32,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 1, func_name)?;
+    let n = get_int_arg(tuple, 0, func_name)?;
+    Ok(gc_system.new_object(VMInt::new(count(n) as i64)))
+}
+
+// math.popcount(n)，二进制表示中 1 的个数
+pub fn popcount(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    bit_count_with(tuple, "popcount", i64::count_ones, gc_system)
+}
+
+// math.leading_zeros(n)，leading_zeros(0) = 64
+pub fn leading_zeros(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    bit_count_with(tuple, "leading_zeros", i64::leading_zeros, gc_system)
+}
+
+// math.trailing_zeros(n)，trailing_zeros(0) = 64
+pub fn trailing_zeros(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    bit_count_with(tuple, "trailing_zeros", i64::trailing_zeros, gc_system)
+}
+
+// math.bit(n, i)，返回第 i 位（0 为最低位）的值 0 或 1，i 必须在 [0, 64) 内
+pub fn bit(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "bit")?;
+    let n = get_int_arg(tuple, 0, "bit")?;
+    let index = get_int_arg(tuple, 1, "bit")?;
+    if !(0..64).contains(&index) {
+        return Err(VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[1].clone_ref(),
+            format!("bit index must be in [0, 64), got {}", index),
+        ));
+    }
+    Ok(gc_system.new_object(VMInt::new((n >> index) & 1)))
+}
+
+// 取出唯一的元组参数中的所有元素（不增加引用计数）
+fn get_items_arg(tuple: &mut GCRef, func_name: &str) -> Result<Vec<GCRef>, VMVariableError> {
+    check_arg_count(tuple, 1, func_name)?;
+    let arg_obj = &mut tuple.as_type::<VMTuple>().values[0];
+    if !arg_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            arg_obj.clone_ref(),
+            format!("Argument for {} must be a tuple", func_name),
+        ));
+    }
+    Ok(arg_obj.as_const_type::<VMTuple>().values.clone())
+}
+
+// 从 initial 开始依次用 op 累积 items，int 与 float 混合时按 op 的规则提升为 float
+fn fold_items(
+    items: &mut [GCRef],
+    initial: i64,
+    op: fn(&mut GCRef, &mut GCRef, &mut GCSystem) -> Result<GCRef, VMVariableError>,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let mut acc = gc_system.new_object(VMInt::new(initial));
+    for item in items.iter_mut() {
+        let next = op(&mut acc, item, gc_system);
+        acc.drop_ref();
+        acc = next?;
+    }
+    Ok(acc)
+}
+
+// 返回 items 中使 better(candidate, best) 成立的最靠前的元素
+fn select_item(
+    tuple: &mut GCRef,
+    items: &mut [GCRef],
+    func_name: &str,
+    better: fn(&mut GCRef, &mut GCRef) -> Result<bool, VMVariableError>,
+) -> Result<GCRef, VMVariableError> {
+    let Some((first, rest)) = items.split_first_mut() else {
+        return Err(VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!("{} of an empty tuple", func_name),
+        ));
+    };
+    let mut best = first;
+    for item in rest.iter_mut() {
+        if better(item, best)? {
+            best = item;
+        }
+    }
+    Ok(best.clone_ref())
+}
+
+// math.sum(tuple)，空元组返回 0
+pub fn sum(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let mut items = get_items_arg(tuple, "sum")?;
+    fold_items(&mut items, 0, try_add_as_vmobject, gc_system)
+}
+
+// math.product(tuple)，空元组返回 1
+pub fn product(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let mut items = get_items_arg(tuple, "product")?;
+    fold_items(&mut items, 1, try_mul_as_vmobject, gc_system)
+}
+
+// math.min(tuple)，返回最小的元素本身，空元组为 ValueError
+pub fn min(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    _gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let mut items = get_items_arg(tuple, "min")?;
+    select_item(tuple, &mut items, "min", try_less_than_as_vmobject)
+}
+
+// math.max(tuple)，返回最大的元素本身，空元组为 ValueError
+pub fn max(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    _gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let mut items = get_items_arg(tuple, "max")?;
+    select_item(tuple, &mut items, "max", try_greater_than_as_vmobject)
+}
+
+// math.clamp(x, lo, hi)，返回限制在 [lo, hi] 内的 x（x、lo、hi 之一本身），lo > hi 时为 ValueError
+pub fn clamp(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    _gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 3, "clamp")?;
+    let values = &mut tuple.as_type::<VMTuple>().values;
+    let mut x = values[0].clone();
+    let mut lo = values[1].clone();
+    let mut hi = values[2].clone();
+    if try_greater_than_as_vmobject(&mut lo, &mut hi)? {
+        return Err(VMVariableError::ValueError(
+            tuple.clone_ref(),
+            "clamp lower bound must not be greater than upper bound".to_string(),
+        ));
+    }
+    if try_less_than_as_vmobject(&mut x, &mut lo)? {
+        return Ok(lo.clone_ref());
+    }
+    if try_greater_than_as_vmobject(&mut x, &mut hi)? {
+        return Ok(hi.clone_ref());
+    }
+    Ok(x.clone_ref())
+}
+
+// math.lerp(a, b, t)，返回 a + (b - a) * t，结果总是 float
+pub fn lerp(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 3, "lerp")?;
+    let a = get_number_arg(tuple, 0, "lerp")?;
+    let b = get_number_arg(tuple, 1, "lerp")?;
+    let t = get_number_arg(tuple, 2, "lerp")?;
+    Ok(gc_system.new_object(VMFloat::new(a + (b - a) * t)))
+}
+
+// 零参数的常量函数，返回对应的 VMFloat
+fn float_constant(
+    tuple: &mut GCRef,
+    value: f64,
+    func_name: &str,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 0, func_name)?;
+    Ok(gc_system.new_object(VMFloat::new(value)))
+}
+
+// math.pi()
+pub fn pi(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    float_constant(tuple, std::f64::consts::PI, "pi", gc_system)
+}
+
+// math.e()
+pub fn e(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    float_constant(tuple, std::f64::consts::E, "e", gc_system)
+}
+
+// math.inf()，正无穷
+pub fn inf(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    float_constant(tuple, f64::INFINITY, "inf", gc_system)
+}
+
+// math.nan()
+pub fn nan(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    float_constant(tuple, f64::NAN, "nan", gc_system)
+}
+
+pub fn get_math_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![
+        ("is_nan", is_nan),
+        ("is_infinite", is_infinite),
+        ("gcd", gcd),
+        ("lcm", lcm),
+        ("floordiv", floordiv),
+        ("isqrt", isqrt),
+        ("round", round),
+        ("round_half_even", round_half_even),
+        ("trunc", trunc),
+        ("round_to", round_to),
+        ("popcount", popcount),
+        ("leading_zeros", leading_zeros),
+        ("trailing_zeros", trailing_zeros),
+        ("bit", bit),
+        ("sum", sum),
+        ("product", product),
+        ("min", min),
+        ("max", max),
+        ("clamp", clamp),
+        ("lerp", lerp),
+        ("pi", pi),
+        ("e", e),
+        ("inf", inf),
+        ("nan", nan),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+    use xlang_vm_core::executor::variable::{try_eq_as_vmobject, try_repr_vmobject, VMString};
+
+    fn call_with_ints(
+        native_fn: NativeFunction,
+        args: &[i64],
+        gc_system: &mut GCSystem,
+    ) -> Result<i64, VMVariableError> {
+        let mut values: Vec<GCRef> = args
+            .iter()
+            .map(|arg| gc_system.new_object(VMInt::new(*arg)))
+            .collect();
+        let result = call_native(native_fn, &mut values.iter_mut().collect(), gc_system).map(
+            |mut result| {
+                let value = result.as_const_type::<VMInt>().value;
+                result.drop_ref();
+                value
+            },
+        );
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_gcd_lcm_isqrt() {
+        let mut gc_system = GCSystem::new(None);
+        for (a, b, expected_gcd, expected_lcm) in [
+            (48, 36, 12, 144),
+            (0, 0, 0, 0),
+            (0, 5, 5, 0),
+            (-4, 6, 2, 12),
+            (17, 5, 1, 85),
+        ] {
+            assert_eq!(
+                call_with_ints(gcd, &[a, b], &mut gc_system).unwrap(),
+                expected_gcd
+            );
+            assert_eq!(
+                call_with_ints(lcm, &[a, b], &mut gc_system).unwrap(),
+                expected_lcm
+            );
+        }
+        for (n, expected) in [(0, 0), (1, 1), (15, 3), (16, 4), (i64::MAX, 3037000499)] {
+            assert_eq!(
+                call_with_ints(isqrt, &[n], &mut gc_system).unwrap(),
+                expected
+            );
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_math_int_errors() {
+        let mut gc_system = GCSystem::new(None);
+        match call_with_ints(lcm, &[i64::MAX, i64::MAX - 1], &mut gc_system) {
+            Err(mut err @ VMVariableError::OverflowError(..)) => err.consume_ref(),
+            _ => panic!("lcm should report overflow"),
+        }
+        match call_with_ints(gcd, &[i64::MIN, 0], &mut gc_system) {
+            Err(mut err @ VMVariableError::OverflowError(..)) => err.consume_ref(),
+            _ => panic!("gcd(i64::MIN, 0) should report overflow"),
+        }
+        match call_with_ints(isqrt, &[-1], &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("isqrt should reject negative numbers"),
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_floordiv_rounds_toward_negative_infinity() {
+        let mut gc_system = GCSystem::new(None);
+        for (a, b, expected) in [
+            (7, 2, 3),
+            (-7, 2, -4),
+            (7, -2, -4),
+            (-7, -2, 3),
+            (-6, 2, -3),
+            (0, -3, 0),
+            (i64::MIN, 1, i64::MIN),
+        ] {
+            assert_eq!(
+                call_with_ints(floordiv, &[a, b], &mut gc_system).unwrap(),
+                expected,
+                "{} floordiv {}",
+                a,
+                b
+            );
+        }
+        match call_with_ints(floordiv, &[5, 0], &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("floordiv should reject a zero divisor"),
+        }
+        match call_with_ints(floordiv, &[i64::MIN, -1], &mut gc_system) {
+            Err(mut err @ VMVariableError::OverflowError(..)) => err.consume_ref(),
+            _ => panic!("i64::MIN floordiv -1 should report overflow"),
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn call_with_float(
+        native_fn: NativeFunction,
+        args: &[f64],
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut values: Vec<GCRef> = args
+            .iter()
+            .map(|arg| gc_system.new_object(VMFloat::new(*arg)))
+            .collect();
+        let result = call_native(native_fn, &mut values.iter_mut().collect(), gc_system);
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        result
+    }
+
+    fn rounded(native_fn: NativeFunction, value: f64, gc_system: &mut GCSystem) -> i64 {
+        let mut result = call_with_float(native_fn, &[value], gc_system).unwrap();
+        let value = result.as_const_type::<VMInt>().value;
+        result.drop_ref();
+        value
+    }
+
+    #[test]
+    fn test_round_half_boundaries() {
+        let mut gc_system = GCSystem::new(None);
+        for (value, expected_round, expected_half_even, expected_trunc) in [
+            (0.5, 1, 0, 0),
+            (1.5, 2, 2, 1),
+            (2.5, 3, 2, 2),
+            (-0.5, -1, 0, 0),
+            (-2.5, -3, -2, -2),
+            (2.4999, 2, 2, 2),
+            (-2.7, -3, -3, -2),
+        ] {
+            assert_eq!(rounded(round, value, &mut gc_system), expected_round);
+            assert_eq!(
+                rounded(round_half_even, value, &mut gc_system),
+                expected_half_even
+            );
+            assert_eq!(rounded(trunc, value, &mut gc_system), expected_trunc);
+        }
+        assert_eq!(call_with_ints(round, &[7], &mut gc_system).unwrap(), 7);
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_round_to_and_overflow() {
+        let mut gc_system = GCSystem::new(None);
+        for (value, decimals, expected) in [
+            (1.23456, 2, 1.23),
+            (1.235, 0, 1.0),
+            (1250.0, -2, 1300.0),
+            (1.5, 400, 1.5),
+        ] {
+            let mut value_obj = gc_system.new_object(VMFloat::new(value));
+            let mut decimals_obj = gc_system.new_object(VMInt::new(decimals));
+            let mut tuple =
+                gc_system.new_object(VMTuple::new(&mut vec![&mut value_obj, &mut decimals_obj]));
+            let mut result = round_to(None, None, &mut tuple, &mut gc_system).unwrap();
+            assert_eq!(result.as_const_type::<VMFloat>().value, expected);
+            for obj in [&mut value_obj, &mut decimals_obj, &mut tuple, &mut result] {
+                obj.drop_ref();
+            }
+        }
+        for value in [1e20, -1e20, f64::NAN, f64::INFINITY] {
+            for native_fn in [round, round_half_even, trunc] {
+                match call_with_float(native_fn, &[value], &mut gc_system) {
+                    Err(mut err @ VMVariableError::OverflowError(..)) => err.consume_ref(),
+                    _ => panic!("{} should not fit in an integer", value),
+                }
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_bit_operations() {
+        let mut gc_system = GCSystem::new(None);
+        for (native_fn, n, expected) in [
+            (popcount as NativeFunction, 0b1011, 3),
+            (popcount, 0, 0),
+            (popcount, -1, 64),
+            (leading_zeros, 0b1011, 60),
+            (leading_zeros, 0, 64),
+            (leading_zeros, -1, 0),
+            (trailing_zeros, 0b1000, 3),
+            (trailing_zeros, 0, 64),
+            (trailing_zeros, i64::MIN, 63),
+        ] {
+            assert_eq!(
+                call_with_ints(native_fn, &[n], &mut gc_system).unwrap(),
+                expected
+            );
+        }
+        let bits: Vec<i64> = (0..4)
+            .map(|i| call_with_ints(bit, &[0b1011, i], &mut gc_system).unwrap())
+            .collect();
+        assert_eq!(bits, [1, 1, 0, 1]);
+        assert_eq!(call_with_ints(bit, &[-1, 63], &mut gc_system).unwrap(), 1);
+        for index in [-1, 64] {
+            match call_with_ints(bit, &[0b1011, index], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("bit index {} should be rejected", index),
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    // 以 (items,) 调用 native_fn，返回结果的 repr
+    fn call_with_items(
+        native_fn: NativeFunction,
+        items: &mut [GCRef],
+        gc_system: &mut GCSystem,
+    ) -> Result<String, VMVariableError> {
+        let mut inner = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+        let result = call_native(native_fn, &mut vec![&mut inner], gc_system).map(|mut result| {
+            let repr = try_repr_vmobject(&mut result, None).unwrap();
+            result.drop_ref();
+            repr
+        });
+        inner.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_reductions_over_mixed_numbers() {
+        let mut gc_system = GCSystem::new(None);
+        let mut ints = vec![
+            gc_system.new_object(VMInt::new(3)),
+            gc_system.new_object(VMInt::new(-2)),
+            gc_system.new_object(VMInt::new(4)),
+        ];
+        let mut mixed = vec![
+            gc_system.new_object(VMInt::new(3)),
+            gc_system.new_object(VMFloat::new(1.5)),
+            gc_system.new_object(VMInt::new(5)),
+        ];
+        for (items, expected) in [
+            (&mut ints, ["5", "-24", "-2", "4"]),
+            (&mut mixed, ["9.5", "22.5", "1.5", "5"]),
+        ] {
+            for (native_fn, expected) in [sum as NativeFunction, product, min, max]
+                .into_iter()
+                .zip(expected)
+            {
+                assert_eq!(
+                    call_with_items(native_fn, items, &mut gc_system).unwrap(),
+                    expected
+                );
+            }
+        }
+        for value in ints.iter_mut().chain(mixed.iter_mut()) {
+            value.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_reductions_over_empty_tuple() {
+        let mut gc_system = GCSystem::new(None);
+        assert_eq!(call_with_items(sum, &mut [], &mut gc_system).unwrap(), "0");
+        assert_eq!(
+            call_with_items(product, &mut [], &mut gc_system).unwrap(),
+            "1"
+        );
+        for native_fn in [min as NativeFunction, max] {
+            match call_with_items(native_fn, &mut [], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("min/max of an empty tuple should be a ValueError"),
+            }
+        }
+        let mut text = vec![gc_system.new_object(VMString::new("a"))];
+        match call_with_items(sum, &mut text, &mut gc_system) {
+            Err(mut err) => err.consume_ref(),
+            Ok(_) => panic!("sum of strings should fail"),
+        }
+        text[0].drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_float_constants() {
+        let mut gc_system = GCSystem::new(None);
+        let mut pi_obj = call_with_float(pi, &[], &mut gc_system).unwrap();
+        assert!(
+            (pi_obj.as_const_type::<VMFloat>().value - std::f64::consts::PI).abs() < f64::EPSILON
+        );
+        let mut e_obj = call_with_float(e, &[], &mut gc_system).unwrap();
+        assert!(
+            (e_obj.as_const_type::<VMFloat>().value - std::f64::consts::E).abs() < f64::EPSILON
+        );
+        let mut inf_obj = call_with_float(inf, &[], &mut gc_system).unwrap();
+        assert_eq!(inf_obj.as_const_type::<VMFloat>().value, f64::INFINITY);
+        let mut nan_obj = call_with_float(nan, &[], &mut gc_system).unwrap();
+        assert!(!try_eq_as_vmobject(&nan_obj, &nan_obj));
+        match call_with_float(pi, &[1.0], &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("pi should not accept arguments"),
+        }
+        for obj in [&mut pi_obj, &mut e_obj, &mut inf_obj, &mut nan_obj] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_clamp_boundaries() {
+        let mut gc_system = GCSystem::new(None);
+        for (x, expected) in [(-5, 0), (0, 0), (5, 5), (10, 10), (15, 10)] {
+            assert_eq!(
+                call_with_ints(clamp, &[x, 0, 10], &mut gc_system).unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            call_with_ints(clamp, &[3, 7, 7], &mut gc_system).unwrap(),
+            7
+        );
+        let mut clamped = call_with_float(clamp, &[1.5, 0.0, 1.0], &mut gc_system).unwrap();
+        assert_eq!(clamped.as_const_type::<VMFloat>().value, 1.0);
+        clamped.drop_ref();
+        match call_with_ints(clamp, &[5, 10, 0], &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("clamp should reject lo > hi"),
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_lerp() {
+        let mut gc_system = GCSystem::new(None);
+        for (a, b, t, expected) in [
+            (0.0, 10.0, 0.0, 0.0),
+            (0.0, 10.0, 0.25, 2.5),
+            (0.0, 10.0, 1.0, 10.0),
+            (10.0, 20.0, 1.5, 25.0),
+            (4.0, -4.0, 0.5, 0.0),
+        ] {
+            let mut result = call_with_float(lerp, &[a, b, t], &mut gc_system).unwrap();
+            assert_eq!(result.as_const_type::<VMFloat>().value, expected);
+            result.drop_ref();
+        }
+        let mut a = gc_system.new_object(VMInt::new(1));
+        let mut b = gc_system.new_object(VMInt::new(3));
+        let mut t = gc_system.new_object(VMFloat::new(0.5));
+        let mut tuple = gc_system.new_object(VMTuple::new(&mut vec![&mut a, &mut b, &mut t]));
+        let mut result = lerp(None, None, &mut tuple, &mut gc_system).unwrap();
+        assert_eq!(result.as_const_type::<VMFloat>().value, 2.0);
+        for obj in [&mut a, &mut b, &mut t, &mut tuple, &mut result] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
@@ -1,21 +1,33 @@
 mod async_request;
 mod asyncio;
+mod bytes;
+mod control;
+mod crypto;
 mod fs;
+mod functional;
+mod gc;
+mod http;
 mod io;
+mod math;
 mod os;
+mod random;
+mod regex;
 mod serialization;
 mod string_utils;
 mod time;
 mod types;
 
+pub use serialization::{json_to_vmobject, to_json_value};
+pub use types::type_name_of;
+
 use rustc_hash::FxHashMap;
 use xlang_vm_core::executor::context::Context;
 use xlang_vm_core::executor::ffi::vm_clambda_loading;
+use xlang_vm_core::executor::native_module::{self, NativeFunction};
 use xlang_vm_core::executor::variable::{
-    VMCLambdaInstruction, VMKeyVal, VMLambda, VMLambdaBody, VMNull, VMString, VMTuple,
-    VMVariableError,
+    VMCLambdaInstruction, VMKeyVal, VMString, VMTuple, VMVariableError,
 };
-use xlang_vm_core::executor::vm::VMError;
+use xlang_vm_core::executor::vm::{call_lambda_sync, VMError};
 use xlang_vm_core::gc::{GCRef, GCSystem};
 pub(crate) fn check_if_tuple(tuple: &mut GCRef) -> Result<(), VMVariableError> {
     if !tuple.isinstance::<VMTuple>() {
@@ -26,43 +38,73 @@ pub(crate) fn check_if_tuple(tuple: &mut GCRef) -> Result<(), VMVariableError> {
     }
     Ok(())
 }
+
+// 检查参数元组中的参数个数
+pub(crate) fn check_arg_count(
+    tuple: &mut GCRef,
+    expected: usize,
+    func_name: &str,
+) -> Result<(), VMVariableError> {
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != expected {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "{} expected {} argument{}, got {}",
+                func_name,
+                expected,
+                if expected == 1 { "" } else { "s" },
+                arg_count
+            ),
+        ));
+    }
+    Ok(())
+}
+// 在 native 函数中同步调用一个 lambda，参数按位置组装为元组
+// 返回值持有一个新的引用，调用者负责 drop_ref
+pub(crate) fn call_lambda(
+    lambda: &mut GCRef,
+    args: &mut Vec<&mut GCRef>,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let mut args_tuple = gc_system.new_object(VMTuple::new(args));
+    let result =
+        call_lambda_sync(lambda, &mut args_tuple, gc_system).map_err(VMError::into_variable_error);
+    args_tuple.drop_ref();
+    result
+}
+
+// 测试中以位置参数直接调用 native 函数，不会消耗参数的引用
+#[cfg(test)]
+pub(crate) fn call_native(
+    native_fn: NativeFunction,
+    args: &mut Vec<&mut GCRef>,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let mut args_tuple = gc_system.new_object(VMTuple::new(args));
+    let result = native_fn(None, None, &mut args_tuple, gc_system);
+    args_tuple.drop_ref();
+    result
+}
+
 // Helper function to create a native VMLambda
 pub(crate) fn create_native_lambda(
     name: &str,
-    native_fn: fn(
-        Option<&mut GCRef>,
-        Option<&mut GCRef>,
-        &mut GCRef,
-        &mut GCSystem,
-    ) -> Result<GCRef, VMVariableError>,
+    native_fn: NativeFunction,
     gc_system: &mut GCSystem,
 ) -> Result<GCRef, VMVariableError> {
-    // Create empty tuple for default args (can be shared or created anew)
-    // Using a shared empty tuple might be slightly more efficient if possible,
-    // but creating anew is safer and simpler here.
-    let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
-    let mut result = gc_system.new_object(VMNull::new()); // Default result placeholder
-
-    let lambda = gc_system.new_object(VMLambda::new(
-        0,                               // code_position, 0 for native
-        format!("<builtins>::{}", name), // signature
-        &mut params,
-        None, // capture
-        None, // self_object
-        &mut VMLambdaBody::VMNativeFunction(native_fn),
-        &mut result,
-        false,
-    ));
-
-    // Drop refs owned by the lambda now
-    params.drop_ref();
-    result.drop_ref();
-
-    Ok(lambda)
+    Ok(native_module::create_native_lambda(
+        format!("<builtins>::{}", name),
+        native_fn,
+        None,
+        gc_system,
+    ))
 }
 
 // Helper function to build a module tuple from a map of functions
+// 函数签名为 <builtins>::模块名.函数名，native 函数出错时会以此标注错误来源
 pub(crate) fn build_module(
+    module_name: &str,
     functions: &FxHashMap<
         &str,
         for<'a> fn(
@@ -76,7 +118,8 @@ pub(crate) fn build_module(
 ) -> GCRef {
     let mut module = gc_system.new_object(VMTuple::new(&mut vec![]));
     for (name, func) in functions {
-        let mut func_ref = create_native_lambda(name, *func, gc_system).unwrap();
+        let mut func_ref =
+            create_native_lambda(&format!("{}.{}", module_name, name), *func, gc_system).unwrap();
         let mut key = gc_system.new_object(VMString::new(name));
         let mut kv_pair = gc_system.new_object(VMKeyVal::new(&mut key, &mut func_ref));
         let _ = module.as_type::<VMTuple>().append(&mut kv_pair);
@@ -99,21 +142,6 @@ pub(crate) fn build_dict(keyvals: &mut FxHashMap<&str, GCRef>, gc_system: &mut G
     dict
 }
 
-pub(crate) fn build_dict_using_string(
-    keyvals: &mut FxHashMap<String, GCRef>,
-    gc_system: &mut GCSystem,
-) -> GCRef {
-    let mut dict = gc_system.new_object(VMTuple::new(&mut vec![]));
-    for (key, value) in keyvals {
-        let mut key_ref = gc_system.new_object(VMString::new(key));
-        let mut kv_pair = gc_system.new_object(VMKeyVal::new(&mut key_ref, value));
-        let _ = dict.as_type::<VMTuple>().append(&mut kv_pair);
-        key_ref.drop_ref(); // Drop the ref created by VMString::new
-        kv_pair.drop_ref(); // Drop the ref created by VMKeyVal::new
-    }
-    dict
-}
-
 pub fn load_clambda(
     _self_object: Option<&mut GCRef>,
     _capture: Option<&mut GCRef>,
@@ -154,45 +182,93 @@ pub fn load_clambda(
     ))
 }
 
+// script_args 为传给脚本的命令行参数，通过 os.argv() 读取
 pub fn inject_builtin_functions(
     context: &mut Context,
+    script_args: &[String],
     gc_system: &mut GCSystem,
 ) -> Result<(), VMError> {
     let fs = fs::get_fs_module();
     let fs_map = fs.into_iter().collect::<FxHashMap<_, _>>();
-    let fs_module = build_module(&fs_map, gc_system);
+    let fs_module = build_module("fs", &fs_map, gc_system);
 
     let io = io::get_io_functions();
     let io_map = io.into_iter().collect::<FxHashMap<_, _>>();
-    let io_module = build_module(&io_map, gc_system);
+    let io_module = build_module("io", &io_map, gc_system);
 
     let types = types::get_type_conversion_functions();
     let types_map = types.into_iter().collect::<FxHashMap<_, _>>();
-    let types_module = build_module(&types_map, gc_system);
+    let types_module = build_module("types", &types_map, gc_system);
 
     let serialization = serialization::get_serialization_functions();
     let serialization_map = serialization.into_iter().collect::<FxHashMap<_, _>>();
-    let serialization_module = build_module(&serialization_map, gc_system);
+    let serialization_module = build_module("serialization", &serialization_map, gc_system);
 
     let string_utils = string_utils::get_string_utils_module();
     let string_utils_map = string_utils.into_iter().collect::<FxHashMap<_, _>>();
-    let string_utils_module = build_module(&string_utils_map, gc_system);
+    let string_utils_module = build_module("string_utils", &string_utils_map, gc_system);
 
     let async_request = async_request::get_request_functions();
     let async_request_map = async_request.into_iter().collect::<FxHashMap<_, _>>();
-    let async_request_module = build_module(&async_request_map, gc_system);
+    let async_request_module = build_module("async_request", &async_request_map, gc_system);
 
     let time = time::get_time_function();
     let time_map = time.into_iter().collect::<FxHashMap<_, _>>();
-    let time_module = build_module(&time_map, gc_system);
+    let time_module = build_module("time", &time_map, gc_system);
 
     let asyncio = asyncio::get_asyncio_functions();
     let asyncio_map = asyncio.into_iter().collect::<FxHashMap<_, _>>();
-    let asyncio_module = build_module(&asyncio_map, gc_system);
+    let asyncio_module = build_module("asyncio", &asyncio_map, gc_system);
 
     let os = os::get_os_functions();
     let os_map = os.into_iter().collect::<FxHashMap<_, _>>();
-    let os_module = build_module(&os_map, gc_system);
+    let mut os_module = build_module("os", &os_map, gc_system);
+    let mut argv_ref = os::create_argv_lambda(script_args, gc_system);
+    let mut argv_key = gc_system.new_object(VMString::new("argv"));
+    let mut argv_pair = gc_system.new_object(VMKeyVal::new(&mut argv_key, &mut argv_ref));
+    os_module
+        .as_type::<VMTuple>()
+        .append(&mut argv_pair)
+        .expect("os module should accept the argv entry");
+    argv_ref.drop_ref();
+    argv_key.drop_ref();
+    argv_pair.drop_ref();
+
+    let math = math::get_math_functions();
+    let math_map = math.into_iter().collect::<FxHashMap<_, _>>();
+    let math_module = build_module("math", &math_map, gc_system);
+
+    let random = random::get_random_functions();
+    let random_map = random.into_iter().collect::<FxHashMap<_, _>>();
+    let random_module = build_module("random", &random_map, gc_system);
+
+    let functional = functional::get_functional_functions();
+    let functional_map = functional.into_iter().collect::<FxHashMap<_, _>>();
+    let functional_module = build_module("functional", &functional_map, gc_system);
+
+    let gc = gc::get_gc_functions();
+    let gc_map = gc.into_iter().collect::<FxHashMap<_, _>>();
+    let gc_module = build_module("gc", &gc_map, gc_system);
+
+    let regex = regex::get_regex_functions();
+    let regex_map = regex.into_iter().collect::<FxHashMap<_, _>>();
+    let regex_module = build_module("regex", &regex_map, gc_system);
+
+    let bytes = bytes::get_bytes_functions();
+    let bytes_map = bytes.into_iter().collect::<FxHashMap<_, _>>();
+    let bytes_module = build_module("bytes", &bytes_map, gc_system);
+
+    let http = http::get_http_functions();
+    let http_map = http.into_iter().collect::<FxHashMap<_, _>>();
+    let http_module = build_module("http", &http_map, gc_system);
+
+    let control = control::get_control_functions();
+    let control_map = control.into_iter().collect::<FxHashMap<_, _>>();
+    let control_module = build_module("control", &control_map, gc_system);
+
+    let crypto = crypto::get_crypto_functions();
+    let crypto_map = crypto.into_iter().collect::<FxHashMap<_, _>>();
+    let crypto_module = build_module("crypto", &crypto_map, gc_system);
 
     let mut builtins_map = FxHashMap::default();
     builtins_map.insert("fs", fs_module);
@@ -204,6 +280,15 @@ pub fn inject_builtin_functions(
     builtins_map.insert("time", time_module);
     builtins_map.insert("asyncio", asyncio_module);
     builtins_map.insert("os", os_module);
+    builtins_map.insert("math", math_module);
+    builtins_map.insert("random", random_module);
+    builtins_map.insert("functional", functional_module);
+    builtins_map.insert("gc", gc_module);
+    builtins_map.insert("regex", regex_module);
+    builtins_map.insert("bytes", bytes_module);
+    builtins_map.insert("http", http_module);
+    builtins_map.insert("control", control_module);
+    builtins_map.insert("crypto", crypto_module);
 
     for (name, module) in &mut builtins_map {
         context
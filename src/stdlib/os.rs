@@ -1,19 +1,28 @@
 use std::{
+    cell::RefCell,
     env,
     path::{Path, PathBuf},
-    process::Command,
+    process::{Child, Command, Output, Stdio},
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use rustc_hash::FxHashMap;
 
 use xlang_vm_core::{
-    executor::variable::{VMBoolean, VMInt, VMNull, VMString, VMTuple, VMVariableError},
+    executor::variable::{
+        VMBoolean, VMInt, VMKeyVal, VMLambda, VMLambdaBody, VMNamed, VMNull, VMString, VMTuple,
+        VMVariableError,
+    },
     gc::{GCRef, GCSystem},
 };
 
 use super::{build_dict, check_if_tuple};
 
+thread_local! {
+    // os.spawn 启动且尚未被 os.wait 回收的子进程，按进程 ID 索引
+    static CHILDREN: RefCell<FxHashMap<i64, Child>> = RefCell::new(FxHashMap::default());
+}
+
 // 获取当前工作目录
 fn getcwd(
     _self_object: Option<&mut GCRef>,
@@ -74,7 +83,7 @@ fn chdir(
     }
 }
 
-// 获取环境变量
+// 获取环境变量，未设置时返回 null
 fn getenv(
     _self_object: Option<&mut GCRef>,
     _capture: Option<&mut GCRef>,
@@ -98,16 +107,21 @@ fn getenv(
         ));
     }
 
-    let var_name = tuple_obj.values[0]
-        .as_const_type::<VMString>()
-        .value
-        .clone();
-    match env::var(var_name.clone()) {
-        Ok(value) => Ok(gc_system.new_object(VMString::new(&value))),
-        Err(_) => Ok(gc_system.new_object(VMString::new(""))), // 返回空字符串而非错误
+    let var_name = &tuple_obj.values[0].as_const_type::<VMString>().value;
+    if !is_valid_env_name(var_name) {
+        return Ok(gc_system.new_object(VMNull::new()));
+    }
+    match env::var_os(var_name) {
+        Some(value) => Ok(gc_system.new_object(VMString::new(&value.to_string_lossy()))),
+        None => Ok(gc_system.new_object(VMNull::new())),
     }
 }
 
+// std::env::set_var 在名称为空或包含 '=' / '\0' 时会 panic，需要提前校验
+fn is_valid_env_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['=', '\0'])
+}
+
 // 设置环境变量
 fn setenv(
     _self_object: Option<&mut GCRef>,
@@ -125,13 +139,13 @@ fn setenv(
         ));
     }
 
-    if !tuple_obj.values[0].isinstance::<VMString>()
-        || !tuple_obj.values[1].isinstance::<VMString>()
-    {
-        return Err(VMVariableError::TypeError(
-            args_tuple.clone_ref(),
-            "setenv() arguments must be strings".to_string(),
-        ));
+    for value in &mut tuple_obj.values {
+        if !value.isinstance::<VMString>() {
+            return Err(VMVariableError::TypeError(
+                value.clone_ref(),
+                "setenv() arguments must be strings".to_string(),
+            ));
+        }
     }
 
     let var_name = tuple_obj.values[0]
@@ -143,11 +157,24 @@ fn setenv(
         .value
         .clone();
 
+    if !is_valid_env_name(&var_name) {
+        return Err(VMVariableError::ValueError(
+            tuple_obj.values[0].clone_ref(),
+            "setenv() name must be non-empty and must not contain '=' or NUL".to_string(),
+        ));
+    }
+    if var_value.contains('\0') {
+        return Err(VMVariableError::ValueError(
+            tuple_obj.values[1].clone_ref(),
+            "setenv() value must not contain NUL".to_string(),
+        ));
+    }
+
     env::set_var(var_name, var_value);
     Ok(gc_system.new_object(VMNull::new()))
 }
 
-// 获取所有环境变量
+// 获取所有环境变量，返回由 name: value 键值对组成的元组
 fn environ(
     _self_object: Option<&mut GCRef>,
     _capture: Option<&mut GCRef>,
@@ -156,16 +183,20 @@ fn environ(
 ) -> Result<GCRef, VMVariableError> {
     check_if_tuple(args_tuple)?;
 
-    let env_vars: Vec<(String, String)> = env::vars().collect();
-    let mut env_dict = FxHashMap::default();
-
-    for (key, val) in env_vars {
-        let mut val_obj = gc_system.new_object(VMString::new(&val));
-        env_dict.insert(key.clone(), val_obj.clone_ref());
+    let mut pairs = Vec::new();
+    for (key, val) in env::vars_os() {
+        let mut key_obj = gc_system.new_object(VMString::new(&key.to_string_lossy()));
+        let mut val_obj = gc_system.new_object(VMString::new(&val.to_string_lossy()));
+        pairs.push(gc_system.new_object(VMKeyVal::new(&mut key_obj, &mut val_obj)));
+        key_obj.drop_ref();
         val_obj.drop_ref();
     }
 
-    Ok(super::build_dict_using_string(&mut env_dict, gc_system))
+    let result = gc_system.new_object(VMTuple::new(&mut pairs.iter_mut().collect()));
+    for pair in pairs.iter_mut() {
+        pair.drop_ref();
+    }
+    Ok(result)
 }
 
 // 获取路径分隔符
@@ -244,6 +275,148 @@ fn system(
     }
 }
 
+// 解析 (command, args?) 参数并构建 Command，stdout 与 stderr 重定向到管道
+fn build_command(args_tuple: &mut GCRef, func_name: &str) -> Result<Command, VMVariableError> {
+    let tuple_obj = args_tuple.as_type::<VMTuple>();
+    if tuple_obj.values.is_empty() || tuple_obj.values.len() > 2 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("{}() takes 1 or 2 arguments", func_name),
+        ));
+    }
+    if !tuple_obj.values[0].isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            tuple_obj.values[0].clone_ref(),
+            format!("{}() command must be a string", func_name),
+        ));
+    }
+    let mut command = Command::new(&tuple_obj.values[0].as_const_type::<VMString>().value);
+    if let Some(command_args) = tuple_obj.values.get_mut(1) {
+        if !command_args.isinstance::<VMTuple>() {
+            return Err(VMVariableError::TypeError(
+                command_args.clone_ref(),
+                format!("{}() arguments must be a tuple of strings", func_name),
+            ));
+        }
+        for arg in command_args.as_type::<VMTuple>().values.iter_mut() {
+            if !arg.isinstance::<VMString>() {
+                return Err(VMVariableError::TypeError(
+                    arg.clone_ref(),
+                    format!("{}() arguments must be a tuple of strings", func_name),
+                ));
+            }
+            command.arg(&arg.as_const_type::<VMString>().value);
+        }
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    Ok(command)
+}
+
+// 将进程输出转换为 (exit_code => int, stdout => string, stderr => string)
+// 被信号终止等没有退出码的情况下 exit_code 为 -1
+fn build_output(output: &Output, gc_system: &mut GCSystem) -> GCRef {
+    let mut exit_code = gc_system.new_object(VMInt::new(output.status.code().unwrap_or(-1) as i64));
+    let mut stdout = gc_system.new_object(VMString::new(&String::from_utf8_lossy(&output.stdout)));
+    let mut stderr = gc_system.new_object(VMString::new(&String::from_utf8_lossy(&output.stderr)));
+    let mut pairs = Vec::with_capacity(3);
+    for (name, value) in [
+        ("exit_code", &mut exit_code),
+        ("stdout", &mut stdout),
+        ("stderr", &mut stderr),
+    ] {
+        let mut key = gc_system.new_object(VMString::new(name));
+        pairs.push(gc_system.new_object(VMNamed::new(&mut key, value)));
+        key.drop_ref();
+        value.drop_ref();
+    }
+    let result = gc_system.new_object(VMTuple::new(&mut pairs.iter_mut().collect()));
+    for pair in pairs.iter_mut() {
+        pair.drop_ref();
+    }
+    result
+}
+
+// os.run(command, args?)，不经过 shell 直接启动进程并等待其结束
+fn run(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let mut command = build_command(args_tuple, "run")?;
+    match command.output() {
+        Ok(output) => Ok(build_output(&output, gc_system)),
+        Err(e) => Err(VMVariableError::ValueError(
+            args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!("Failed to launch process: {}", e),
+        )),
+    }
+}
+
+// os.spawn(command, args?)，启动进程后立即返回其进程 ID，之后用 os.wait(pid) 获取结果
+fn spawn(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let mut command = build_command(args_tuple, "spawn")?;
+    match command.spawn() {
+        Ok(child) => {
+            let pid = child.id() as i64;
+            CHILDREN.with(|children| children.borrow_mut().insert(pid, child));
+            Ok(gc_system.new_object(VMInt::new(pid)))
+        }
+        Err(e) => Err(VMVariableError::ValueError(
+            args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!("Failed to launch process: {}", e),
+        )),
+    }
+}
+
+// os.wait(pid)，等待 os.spawn 启动的进程结束，返回值与 os.run 相同；每个进程只能等待一次
+fn wait(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let tuple_obj = args_tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            "wait() takes exactly 1 argument".to_string(),
+        ));
+    }
+    let handle = &mut tuple_obj.values[0];
+    if !handle.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            handle.clone_ref(),
+            "wait() argument must be a process ID returned by spawn()".to_string(),
+        ));
+    }
+    let pid = handle.as_const_type::<VMInt>().value;
+    let Some(child) = CHILDREN.with(|children| children.borrow_mut().remove(&pid)) else {
+        return Err(VMVariableError::ValueError(
+            handle.clone_ref(),
+            format!("No spawned process with ID {}", pid),
+        ));
+    };
+    match child.wait_with_output() {
+        Ok(output) => Ok(build_output(&output, gc_system)),
+        Err(e) => Err(VMVariableError::DetailedError(format!(
+            "Failed to wait for process {}: {}",
+            pid, e
+        ))),
+    }
+}
+
 // 连接路径
 fn join_path(
     _self_object: Option<&mut GCRef>,
@@ -525,12 +698,67 @@ fn args(
     let mut args_tuple = Vec::with_capacity(args.len());
 
     for arg in args {
-        let mut arg_obj = gc_system.new_object(VMString::new(&arg));
-        args_tuple.push(arg_obj.clone_ref());
-        arg_obj.drop_ref();
+        args_tuple.push(gc_system.new_object(VMString::new(&arg)));
+    }
+
+    let result = gc_system.new_object(VMTuple::new(&mut args_tuple.iter_mut().collect()));
+    for arg in args_tuple.iter_mut() {
+        arg.drop_ref();
+    }
+    Ok(result)
+}
+
+// 获取脚本参数（Run 命令中脚本路径之后的参数），参数保存在 lambda 的 capture 中
+fn argv(
+    _self_object: Option<&mut GCRef>,
+    capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 0 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("argv expected 0 arguments, got {}", arg_count),
+        ));
     }
+    // 每次返回新的元组，避免脚本修改共享的参数列表
+    match capture {
+        Some(capture) if capture.isinstance::<VMTuple>() => {
+            let values = &mut capture.as_type::<VMTuple>().values;
+            Ok(gc_system.new_object(VMTuple::new(&mut values.iter_mut().collect())))
+        }
+        _ => Ok(gc_system.new_object(VMTuple::new(&mut vec![]))),
+    }
+}
 
-    Ok(gc_system.new_object(VMTuple::new(&mut args_tuple.iter_mut().collect())))
+// 构建 os.argv，script_args 在注入内置模块时由调用方提供
+pub(crate) fn create_argv_lambda(script_args: &[String], gc_system: &mut GCSystem) -> GCRef {
+    let mut values: Vec<GCRef> = script_args
+        .iter()
+        .map(|arg| gc_system.new_object(VMString::new(arg)))
+        .collect();
+    let mut capture = gc_system.new_object(VMTuple::new(&mut values.iter_mut().collect()));
+    for value in values.iter_mut() {
+        value.drop_ref();
+    }
+    let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
+    let mut result = gc_system.new_object(VMNull::new());
+    let lambda = gc_system.new_object(VMLambda::new(
+        0,
+        "<builtins>::os.argv".to_string(),
+        &mut params,
+        Some(&mut capture),
+        None,
+        &mut VMLambdaBody::VMNativeFunction(argv),
+        &mut result,
+        false,
+    ));
+    capture.drop_ref();
+    params.drop_ref();
+    result.drop_ref();
+    lambda
 }
 
 // 导出函数列表
@@ -552,6 +780,9 @@ pub fn get_os_functions() -> Vec<(
         ("path_separator", path_separator),
         ("system_name", system_name),
         ("system", system),
+        ("run", run),
+        ("spawn", spawn),
+        ("wait", wait),
         ("join_path", join_path),
         ("dirname", dirname),
         ("basename", basename),
@@ -563,3 +794,148 @@ pub fn get_os_functions() -> Vec<(
         ("args", args),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+    use xlang_vm_core::executor::variable::try_repr_vmobject;
+
+    fn call_with_strings(
+        native_fn: NativeFunction,
+        args: &[&str],
+        gc_system: &mut GCSystem,
+    ) -> GCRef {
+        let mut values: Vec<GCRef> = args
+            .iter()
+            .map(|arg| gc_system.new_object(VMString::new(arg)))
+            .collect();
+        let result = call_native(native_fn, &mut values.iter_mut().collect(), gc_system).unwrap();
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_setenv_then_getenv() {
+        let mut gc_system = GCSystem::new(None);
+        let name = "XLANG_OS_TEST_SETENV_THEN_GETENV";
+
+        let mut unset = call_with_strings(getenv, &[name], &mut gc_system);
+        assert!(unset.isinstance::<VMNull>());
+        unset.drop_ref();
+
+        let mut result = call_with_strings(setenv, &[name, "hello"], &mut gc_system);
+        result.drop_ref();
+
+        let mut value = call_with_strings(getenv, &[name], &mut gc_system);
+        assert_eq!(value.as_const_type::<VMString>().value, "hello");
+        value.drop_ref();
+
+        let mut vars = call_with_strings(environ, &[], &mut gc_system);
+        let repr = try_repr_vmobject(&mut vars, None).unwrap();
+        assert!(repr.contains(&format!("\"{}\": \"hello\"", name)));
+        vars.drop_ref();
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_env_rejects_invalid_arguments() {
+        let mut gc_system = GCSystem::new(None);
+        let mut number = gc_system.new_object(VMInt::new(1));
+        let mut args_tuple = gc_system.new_object(VMTuple::new(&mut vec![&mut number]));
+        match getenv(None, None, &mut args_tuple, &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("getenv should reject non-string names"),
+        }
+        number.drop_ref();
+        args_tuple.drop_ref();
+
+        let mut name = gc_system.new_object(VMString::new("A=B"));
+        let mut value = gc_system.new_object(VMString::new("x"));
+        let mut args_tuple = gc_system.new_object(VMTuple::new(&mut vec![&mut name, &mut value]));
+        match setenv(None, None, &mut args_tuple, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("setenv should reject names containing '='"),
+        }
+        for obj in [&mut name, &mut value, &mut args_tuple] {
+            obj.drop_ref();
+        }
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    // 用系统自带的 echo 输出 hello，Windows 上 echo 是 cmd 的内置命令
+    fn echo_args(gc_system: &mut GCSystem) -> GCRef {
+        #[cfg(windows)]
+        let args = ["cmd", "/C", "echo", "hello"];
+        #[cfg(not(windows))]
+        let args = ["echo", "hello"];
+        let mut command = gc_system.new_object(VMString::new(args[0]));
+        let mut values: Vec<GCRef> = args[1..]
+            .iter()
+            .map(|arg| gc_system.new_object(VMString::new(arg)))
+            .collect();
+        let mut command_args = gc_system.new_object(VMTuple::new(&mut values.iter_mut().collect()));
+        let args_tuple =
+            gc_system.new_object(VMTuple::new(&mut vec![&mut command, &mut command_args]));
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        command.drop_ref();
+        command_args.drop_ref();
+        args_tuple
+    }
+
+    #[test]
+    fn test_run_and_spawn_echo() {
+        let mut gc_system = GCSystem::new(None);
+        let mut args_tuple = echo_args(&mut gc_system);
+
+        let mut output = run(None, None, &mut args_tuple, &mut gc_system).unwrap();
+        let repr = try_repr_vmobject(&mut output, None).unwrap();
+        assert!(
+            repr.starts_with("(\"exit_code\" => 0, \"stdout\" => \"hello"),
+            "{}",
+            repr
+        );
+        output.drop_ref();
+
+        let mut pid = spawn(None, None, &mut args_tuple, &mut gc_system).unwrap();
+        let mut wait_args = gc_system.new_object(VMTuple::new(&mut vec![&mut pid]));
+        let mut output = wait(None, None, &mut wait_args, &mut gc_system).unwrap();
+        assert_eq!(try_repr_vmobject(&mut output, None).unwrap(), repr);
+        output.drop_ref();
+        match wait(None, None, &mut wait_args, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("a process can only be waited for once"),
+        }
+        for obj in [&mut args_tuple, &mut pid, &mut wait_args] {
+            obj.drop_ref();
+        }
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_run_reports_launch_failure() {
+        let mut gc_system = GCSystem::new(None);
+        let mut command = gc_system.new_object(VMString::new("__xlang_missing_command__"));
+        let mut args_tuple = gc_system.new_object(VMTuple::new(&mut vec![&mut command]));
+        match run(None, None, &mut args_tuple, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("run should report commands that cannot be launched"),
+        }
+        command.drop_ref();
+        args_tuple.drop_ref();
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
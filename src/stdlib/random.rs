@@ -0,0 +1,197 @@
+use std::cell::Cell;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use xlang_vm_core::{
+    executor::variable::{VMFloat, VMInt, VMNull, VMTuple, VMVariableError},
+    gc::{GCRef, GCSystem},
+};
+
+use super::check_if_tuple;
+
+thread_local! {
+    // 线程局部的 RNG 状态，random.seed 会重置它以获得可复现的序列
+    static RNG_STATE: Cell<u64> = Cell::new(initial_seed());
+}
+
+fn initial_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0x2545_F491_4F6C_DD1D)
+}
+
+// SplitMix64: 足够简单，且对任意种子（包括 0）都能产生良好分布
+fn next_u64() -> u64 {
+    RNG_STATE.with(|state| {
+        let mut z = state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state.set(z);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    })
+}
+
+// [0, 1) 区间内的浮点数，取高 53 位保证精度
+fn next_f64() -> f64 {
+    (next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+// [0, bound) 区间内的无偏整数
+fn next_below(bound: u64) -> u64 {
+    if bound == 0 {
+        return next_u64();
+    }
+    let zone = u64::MAX - (u64::MAX % bound);
+    loop {
+        let value = next_u64();
+        if value < zone {
+            return value % bound;
+        }
+    }
+}
+
+pub fn seed(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "seed expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let seed_obj = &mut tuple_obj.values[0];
+    if !seed_obj.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            seed_obj.clone_ref(),
+            "Argument for seed must be an integer".to_string(),
+        ));
+    }
+    let value = seed_obj.as_const_type::<VMInt>().value;
+    RNG_STATE.with(|state| state.set(value as u64));
+    Ok(gc_system.new_object(VMNull::new()))
+}
+
+pub fn int(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "int expected 2 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    for value in &mut tuple_obj.values {
+        if !value.isinstance::<VMInt>() {
+            return Err(VMVariableError::TypeError(
+                value.clone_ref(),
+                "Arguments for int must be integers".to_string(),
+            ));
+        }
+    }
+    let min = tuple_obj.values[0].as_const_type::<VMInt>().value;
+    let max = tuple_obj.values[1].as_const_type::<VMInt>().value;
+    if min > max {
+        return Err(VMVariableError::ValueError2Param(
+            tuple_obj.values[0].clone_ref(),
+            tuple_obj.values[1].clone_ref(),
+            "int requires min <= max".to_string(),
+        ));
+    }
+    // 闭区间 [min, max]，跨度为 u64::MAX + 1 时 next_below(0) 返回完整的 u64
+    let span = (max as i128 - min as i128 + 1) as u128;
+    let offset = if span > u64::MAX as u128 {
+        next_u64()
+    } else {
+        next_below(span as u64)
+    };
+    let result = (min as i128 + offset as i128) as i64;
+    Ok(gc_system.new_object(VMInt::new(result)))
+}
+
+pub fn float(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if !tuple_obj.values.is_empty() {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "float expected 0 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    Ok(gc_system.new_object(VMFloat::new(next_f64())))
+}
+
+pub fn choice(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    _gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "choice expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let target_obj = &mut tuple_obj.values[0];
+    if !target_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            target_obj.clone_ref(),
+            "Argument for choice must be a tuple".to_string(),
+        ));
+    }
+    let items = target_obj.as_type::<VMTuple>();
+    if items.values.is_empty() {
+        return Err(VMVariableError::ValueError(
+            target_obj.clone_ref(),
+            "Cannot choose from an empty tuple".to_string(),
+        ));
+    }
+    let index = next_below(items.values.len() as u64) as usize;
+    Ok(items.values[index].clone_ref())
+}
+
+pub fn get_random_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![
+        ("seed", seed),
+        ("int", int),
+        ("float", float),
+        ("choice", choice),
+    ]
+}
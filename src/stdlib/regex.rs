@@ -0,0 +1,215 @@
+use std::cell::RefCell;
+
+use ::regex::Regex;
+use rustc_hash::FxHashMap;
+use xlang_vm_core::{
+    executor::variable::{VMNull, VMString, VMTuple, VMVariableError},
+    gc::{GCRef, GCSystem},
+};
+
+use super::{check_arg_count, check_if_tuple};
+
+// 缓存的正则数量上限，超出后整体清空，避免动态生成的模式无限增长
+const MAX_CACHED_PATTERNS: usize = 256;
+
+thread_local! {
+    // 按模式字符串缓存编译后的正则
+    static PATTERN_CACHE: RefCell<FxHashMap<String, Regex>> = RefCell::new(FxHashMap::default());
+}
+
+fn get_string_arg(
+    tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+    arg_name: &str,
+) -> Result<String, VMVariableError> {
+    let arg_obj = &mut tuple.as_type::<VMTuple>().values[index];
+    if !arg_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            arg_obj.clone_ref(),
+            format!("Argument '{}' for {} must be a string", arg_name, func_name),
+        ));
+    }
+    Ok(arg_obj.as_const_type::<VMString>().value.clone())
+}
+
+// 取出第 0 个参数作为模式并编译（优先使用缓存），非法模式返回 ValueError
+fn get_regex_arg(tuple: &mut GCRef, func_name: &str) -> Result<Regex, VMVariableError> {
+    let pattern = get_string_arg(tuple, 0, func_name, "pattern")?;
+    if let Some(regex) = PATTERN_CACHE.with(|cache| cache.borrow().get(&pattern).cloned()) {
+        return Ok(regex);
+    }
+    let regex = Regex::new(&pattern).map_err(|err| {
+        VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!("Invalid pattern for {}: {}", func_name, err),
+        )
+    })?;
+    PATTERN_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= MAX_CACHED_PATTERNS {
+            cache.clear();
+        }
+        cache.insert(pattern, regex.clone());
+    });
+    Ok(regex)
+}
+
+// regex.find(pattern, subject)，返回第一个匹配的字符串，无匹配时返回 null
+pub fn find(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "find")?;
+    let regex = get_regex_arg(tuple, "find")?;
+    let subject = get_string_arg(tuple, 1, "find", "subject")?;
+    match regex.find(&subject) {
+        Some(matched) => Ok(gc_system.new_object(VMString::new(matched.as_str()))),
+        None => Ok(gc_system.new_object(VMNull::new())),
+    }
+}
+
+// regex.find_all(pattern, subject)，返回所有不重叠匹配组成的元组
+pub fn find_all(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "find_all")?;
+    let regex = get_regex_arg(tuple, "find_all")?;
+    let subject = get_string_arg(tuple, 1, "find_all", "subject")?;
+    let mut matches: Vec<GCRef> = regex
+        .find_iter(&subject)
+        .map(|matched| gc_system.new_object(VMString::new(matched.as_str())))
+        .collect();
+    let result = gc_system.new_object(VMTuple::new(&mut matches.iter_mut().collect()));
+    for matched in matches.iter_mut() {
+        matched.drop_ref();
+    }
+    Ok(result)
+}
+
+// regex.replace(pattern, subject, replacement)，替换所有匹配，replacement 中可用 $1 / ${name} 引用分组
+pub fn replace(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 3, "replace")?;
+    let regex = get_regex_arg(tuple, "replace")?;
+    let subject = get_string_arg(tuple, 1, "replace", "subject")?;
+    let replacement = get_string_arg(tuple, 2, "replace", "replacement")?;
+    let result = regex.replace_all(&subject, replacement.as_str());
+    Ok(gc_system.new_object(VMString::new(&result)))
+}
+
+pub fn get_regex_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![("find", find), ("find_all", find_all), ("replace", replace)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+    use xlang_vm_core::executor::variable::try_repr_vmobject;
+
+    // 以字符串参数调用，返回结果的 repr
+    fn call_with_strings(
+        native_fn: NativeFunction,
+        args: &[&str],
+        gc_system: &mut GCSystem,
+    ) -> Result<String, VMVariableError> {
+        let mut values: Vec<GCRef> = args
+            .iter()
+            .map(|arg| gc_system.new_object(VMString::new(arg)))
+            .collect();
+        let result = call_native(native_fn, &mut values.iter_mut().collect(), gc_system).map(
+            |mut result| {
+                let repr = try_repr_vmobject(&mut result, None).unwrap();
+                result.drop_ref();
+                repr
+            },
+        );
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_find_and_find_all() {
+        let mut gc_system = GCSystem::new(None);
+        let subject = "a1 b22 c333";
+        assert_eq!(
+            call_with_strings(find, &[r"\d+", subject], &mut gc_system).unwrap(),
+            "\"1\""
+        );
+        assert_eq!(
+            call_with_strings(find, &[r"x+", subject], &mut gc_system).unwrap(),
+            "null"
+        );
+        assert_eq!(
+            call_with_strings(find_all, &[r"\d+", subject], &mut gc_system).unwrap(),
+            "(\"1\", \"22\", \"333\")"
+        );
+        assert_eq!(
+            call_with_strings(find_all, &[r"x+", subject], &mut gc_system).unwrap(),
+            "()"
+        );
+        // 第二次使用同一模式时命中缓存
+        assert_eq!(
+            call_with_strings(find, &[r"\d+", "no digits 7"], &mut gc_system).unwrap(),
+            "\"7\""
+        );
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut gc_system = GCSystem::new(None);
+        assert_eq!(
+            call_with_strings(replace, &[r"\s+", "a  b\t\tc", " "], &mut gc_system).unwrap(),
+            "\"a b c\""
+        );
+        assert_eq!(
+            call_with_strings(replace, &[r"\d", "abc", "#"], &mut gc_system).unwrap(),
+            "\"abc\""
+        );
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let mut gc_system = GCSystem::new(None);
+        for native_fn in [find, find_all] {
+            match call_with_strings(native_fn, &["(unclosed", "subject"], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("invalid pattern should be rejected"),
+            }
+        }
+        match call_with_strings(replace, &["[", "subject", ""], &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("invalid pattern should be rejected"),
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
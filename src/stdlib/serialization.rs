@@ -1,10 +1,17 @@
+use std::io::{Read, Write};
+
 use base64::Engine;
+use flate2::{
+    read::{DeflateDecoder, GzDecoder, ZlibDecoder},
+    write::{DeflateEncoder, GzEncoder, ZlibEncoder},
+    Compression,
+};
 use rustc_hash::FxHashSet as HashSet;
 use serde_json::Value as JsonValue;
 use xlang_vm_core::{
     executor::variable::{
-        try_repr_vmobject, VMBoolean, VMBytes, VMFloat, VMInt, VMKeyVal, VMNull, VMString, VMTuple,
-        VMVariableError,
+        try_repr_vmobject, try_to_string_vmobject, VMBoolean, VMBytes, VMFloat, VMInt, VMKeyVal,
+        VMNull, VMString, VMTuple, VMVariableError,
     },
     gc::{GCRef, GCSystem},
 };
@@ -36,7 +43,7 @@ fn vmobject_to_json(
             .map(JsonValue::Number)
             .ok_or_else(|| {
                 VMVariableError::ValueError(
-                    value.clone(),
+                    value.clone_ref(),
                     "Cannot encode NaN or Infinity in JSON".to_string(),
                 )
             })
@@ -58,7 +65,7 @@ fn vmobject_to_json(
                     _ => {
                         visited.remove(&ptr);
                         return Err(VMVariableError::TypeError(
-                            kv.get_key().clone(),
+                            kv.get_key().clone_ref(),
                             "JSON object keys must be strings".to_string(),
                         ));
                     }
@@ -86,7 +93,7 @@ fn vmobject_to_json(
         ))
     } else {
         Err(VMVariableError::TypeError(
-            value.clone(),
+            value.clone_ref(),
             format!(
                 "Type '{}' cannot be directly encoded to JSON",
                 // Use the imported try_repr_vmobject
@@ -99,8 +106,20 @@ fn vmobject_to_json(
     result
 }
 
-// Helper function (remains private to this module)
-fn json_to_vmobject(value: JsonValue, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+// 将值转换为 JSON，REPL 的 :save 也使用这一逻辑
+pub fn to_json_value(
+    value: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<JsonValue, VMVariableError> {
+    let mut visited = HashSet::default();
+    vmobject_to_json(value, gc_system, &mut visited)
+}
+
+// 将 JSON 转换回值，REPL 的 :load 也使用这一逻辑
+pub fn json_to_vmobject(
+    value: JsonValue,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
     match value {
         JsonValue::Null => Ok(gc_system.new_object(VMNull::new())),
         JsonValue::Bool(b) => Ok(gc_system.new_object(VMBoolean::new(b))),
@@ -175,8 +194,7 @@ pub fn json_encode(
     }
     let object_to_encode = &mut tuple_obj.values[0];
 
-    let mut visited = HashSet::default();
-    let json_value = vmobject_to_json(object_to_encode, gc_system, &mut visited)?;
+    let json_value = to_json_value(object_to_encode, gc_system)?;
 
     match serde_json::to_string(&json_value) {
         Ok(json_string) => Ok(gc_system.new_object(VMString::new(&json_string))),
@@ -224,6 +242,446 @@ pub fn json_decode(
     }
 }
 
+// serialization.base64_encode(bytes)，使用与 bytes 的 repr 相同的标准 base64 编码
+pub fn base64_encode(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "base64_encode expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+
+    let bytes_obj = &mut tuple_obj.values[0];
+    if !bytes_obj.isinstance::<VMBytes>() {
+        return Err(VMVariableError::TypeError(
+            bytes_obj.clone_ref(),
+            "Argument to base64_encode must be bytes".to_string(),
+        ));
+    }
+
+    let encoded = base64::engine::general_purpose::STANDARD
+        .encode(&bytes_obj.as_const_type::<VMBytes>().value);
+    Ok(gc_system.new_object(VMString::new(&encoded)))
+}
+
+// serialization.base64_decode(string)，非法输入返回 ValueError
+pub fn base64_decode(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "base64_decode expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+
+    let string_obj = &mut tuple_obj.values[0];
+    if !string_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            string_obj.clone_ref(),
+            "Argument to base64_decode must be a string".to_string(),
+        ));
+    }
+
+    match base64::engine::general_purpose::STANDARD
+        .decode(&string_obj.as_const_type::<VMString>().value)
+    {
+        Ok(decoded) => Ok(gc_system.new_object(VMBytes::new(&decoded))),
+        Err(e) => Err(VMVariableError::ValueError(
+            string_obj.clone_ref(),
+            format!("Failed to decode base64 string: {}", e),
+        )),
+    }
+}
+
+// 按 RFC 4180 解析 CSV：字段以 ',' 分隔，记录以 "\n" 或 "\r\n" 分隔，
+// 引号内可以包含逗号和换行，"" 表示一个引号。引号不匹配时返回带行号的错误描述
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    // 当前字段是否以引号开头，以及引号是否已经闭合
+    let mut quoted = false;
+    let mut in_quotes = false;
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            ',' => {
+                row.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+                quoted = false;
+                line += 1;
+            }
+            '"' if field.is_empty() && !quoted => {
+                quoted = true;
+                in_quotes = true;
+            }
+            '"' => {
+                return Err(format!(
+                    "unexpected quote in unquoted field on line {}",
+                    line
+                ))
+            }
+            _ if quoted => {
+                return Err(format!(
+                    "unexpected character {:?} after closing quote on line {}",
+                    c, line
+                ))
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!(
+            "unterminated quoted field starting before line {}",
+            line
+        ));
+    }
+    // 末尾的换行不会产生额外的空行
+    if quoted || !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn write_csv_field(field: &str, out: &mut String) {
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
+}
+
+// serialization.csv_parse(string)，返回由行元组组成的元组，每个字段都是字符串
+pub fn csv_parse(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "csv_parse expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+
+    let string_obj = &mut tuple_obj.values[0];
+    if !string_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            string_obj.clone_ref(),
+            "Argument to csv_parse must be a string".to_string(),
+        ));
+    }
+
+    let rows = parse_csv(&string_obj.as_const_type::<VMString>().value).map_err(|e| {
+        VMVariableError::ValueError(
+            string_obj.clone_ref(),
+            format!("Failed to parse CSV: {}", e),
+        )
+    })?;
+    let mut row_refs = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut fields: Vec<GCRef> = row
+            .iter()
+            .map(|field| gc_system.new_object(VMString::new(field)))
+            .collect();
+        row_refs.push(gc_system.new_object(VMTuple::new(&mut fields.iter_mut().collect())));
+        for mut field in fields {
+            field.drop_ref();
+        }
+    }
+    let result = gc_system.new_object(VMTuple::new(&mut row_refs.iter_mut().collect()));
+    for mut row in row_refs {
+        row.drop_ref();
+    }
+    Ok(result)
+}
+
+// serialization.csv_write(rows)，rows 为行元组组成的元组，非字符串字段按 string() 的规则转换
+// 含有逗号、引号或换行的字段会被加上引号，行之间以 "\n" 分隔
+pub fn csv_write(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "csv_write expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+
+    let rows_obj = &mut tuple_obj.values[0];
+    if !rows_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            rows_obj.clone_ref(),
+            "Argument to csv_write must be a tuple of rows".to_string(),
+        ));
+    }
+
+    let mut output = String::new();
+    for row in rows_obj.as_type::<VMTuple>().values.iter_mut() {
+        if !row.isinstance::<VMTuple>() {
+            return Err(VMVariableError::TypeError(
+                row.clone_ref(),
+                "Each row for csv_write must be a tuple".to_string(),
+            ));
+        }
+        for (index, field) in row.as_type::<VMTuple>().values.iter_mut().enumerate() {
+            if index > 0 {
+                output.push(',');
+            }
+            write_csv_field(&try_to_string_vmobject(field, None)?, &mut output);
+        }
+        output.push('\n');
+    }
+    Ok(gc_system.new_object(VMString::new(&output)))
+}
+
+// 将 TOML 值转换为 VM 对象：表转换为键值对元组，数组转换为元组，日期时间转换为字符串
+fn toml_to_vmobject(value: toml::Value, gc_system: &mut GCSystem) -> GCRef {
+    match value {
+        toml::Value::String(s) => gc_system.new_object(VMString::new(&s)),
+        toml::Value::Integer(i) => gc_system.new_object(VMInt::new(i)),
+        toml::Value::Float(f) => gc_system.new_object(VMFloat::new(f)),
+        toml::Value::Boolean(b) => gc_system.new_object(VMBoolean::new(b)),
+        toml::Value::Datetime(datetime) => {
+            gc_system.new_object(VMString::new(&datetime.to_string()))
+        }
+        toml::Value::Array(array) => {
+            let mut items: Vec<GCRef> = array
+                .into_iter()
+                .map(|item| toml_to_vmobject(item, gc_system))
+                .collect();
+            let tuple = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+            for mut item in items {
+                item.drop_ref();
+            }
+            tuple
+        }
+        toml::Value::Table(table) => {
+            let mut pairs = Vec::with_capacity(table.len());
+            for (key, value) in table {
+                let mut key = gc_system.new_object(VMString::new(&key));
+                let mut value = toml_to_vmobject(value, gc_system);
+                pairs.push(gc_system.new_object(VMKeyVal::new(&mut key, &mut value)));
+                key.drop_ref();
+                value.drop_ref();
+            }
+            let tuple = gc_system.new_object(VMTuple::new(&mut pairs.iter_mut().collect()));
+            for mut pair in pairs {
+                pair.drop_ref();
+            }
+            tuple
+        }
+    }
+}
+
+// serialization.toml_decode(string)，解析失败时返回带行列位置的 ValueError
+pub fn toml_decode(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "toml_decode expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+
+    let string_obj = &mut tuple_obj.values[0];
+    if !string_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            string_obj.clone_ref(),
+            "Argument to toml_decode must be a string".to_string(),
+        ));
+    }
+
+    match string_obj
+        .as_const_type::<VMString>()
+        .value
+        .parse::<toml::Table>()
+    {
+        Ok(table) => Ok(toml_to_vmobject(toml::Value::Table(table), gc_system)),
+        // toml 的错误信息中已包含行列位置与出错的源码片段
+        Err(e) => Err(VMVariableError::ValueError(
+            string_obj.clone_ref(),
+            format!("Failed to parse TOML: {}", e.to_string().trim_end()),
+        )),
+    }
+}
+
+// compress / decompress 支持的压缩格式：gzip、raw deflate (RFC 1951) 与 zlib (RFC 1950)
+enum CompressionFormat {
+    Gzip,
+    Deflate,
+    Zlib,
+}
+
+// 检查 (bytes, algo) 参数，未知的算法名返回 ValueError
+fn get_compression_args<'a>(
+    tuple: &'a mut GCRef,
+    func_name: &str,
+) -> Result<(&'a mut GCRef, CompressionFormat), VMVariableError> {
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("{} expected 2 arguments, got {}", func_name, arg_count),
+        ));
+    }
+    let values = &mut tuple.as_type::<VMTuple>().values;
+    if !values[1].isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            values[1].clone_ref(),
+            format!("Algorithm for {} must be a string", func_name),
+        ));
+    }
+    let algo = values[1].as_const_type::<VMString>().value.clone();
+    let format = match algo.as_str() {
+        "gzip" => CompressionFormat::Gzip,
+        "deflate" => CompressionFormat::Deflate,
+        "zlib" => CompressionFormat::Zlib,
+        _ => {
+            return Err(VMVariableError::ValueError(
+                values[1].clone_ref(),
+                format!(
+                    "Unknown compression algorithm '{}', expected gzip, deflate or zlib",
+                    algo
+                ),
+            ))
+        }
+    };
+    let bytes_obj = &mut values[0];
+    if !bytes_obj.isinstance::<VMBytes>() {
+        return Err(VMVariableError::TypeError(
+            bytes_obj.clone_ref(),
+            format!("Data for {} must be bytes", func_name),
+        ));
+    }
+    Ok((bytes_obj, format))
+}
+
+// serialization.compress(bytes, algo)，algo 为 "gzip"、"deflate" 或 "zlib"，使用默认压缩等级
+pub fn compress(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let (bytes_obj, format) = get_compression_args(tuple, "compress")?;
+    let data = &bytes_obj.as_const_type::<VMBytes>().value;
+    let level = Compression::default();
+    let result = match format {
+        CompressionFormat::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), level);
+            encoder.write_all(data).and_then(|_| encoder.finish())
+        }
+        CompressionFormat::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), level);
+            encoder.write_all(data).and_then(|_| encoder.finish())
+        }
+        CompressionFormat::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), level);
+            encoder.write_all(data).and_then(|_| encoder.finish())
+        }
+    };
+    match result {
+        Ok(compressed) => Ok(gc_system.new_object(VMBytes::new(&compressed))),
+        Err(e) => Err(VMVariableError::ValueError(
+            bytes_obj.clone_ref(),
+            format!("Failed to compress bytes: {}", e),
+        )),
+    }
+}
+
+// serialization.decompress(bytes, algo)，数据损坏或与 algo 不匹配时返回 ValueError
+pub fn decompress(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let (bytes_obj, format) = get_compression_args(tuple, "decompress")?;
+    let data = bytes_obj.as_const_type::<VMBytes>().value.as_slice();
+    let mut decompressed = Vec::new();
+    let result = match format {
+        CompressionFormat::Gzip => GzDecoder::new(data).read_to_end(&mut decompressed),
+        CompressionFormat::Deflate => DeflateDecoder::new(data).read_to_end(&mut decompressed),
+        CompressionFormat::Zlib => ZlibDecoder::new(data).read_to_end(&mut decompressed),
+    };
+    match result {
+        Ok(_) => Ok(gc_system.new_object(VMBytes::new(&decompressed))),
+        Err(e) => Err(VMVariableError::ValueError(
+            bytes_obj.clone_ref(),
+            format!("Failed to decompress bytes: {}", e),
+        )),
+    }
+}
+
 // Helper to provide functions for registration
 pub fn get_serialization_functions() -> Vec<(
     &'static str,
@@ -234,5 +692,243 @@ pub fn get_serialization_functions() -> Vec<(
         &mut GCSystem,
     ) -> Result<GCRef, VMVariableError>,
 )> {
-    vec![("json_encode", json_encode), ("json_decode", json_decode)]
+    vec![
+        ("json_encode", json_encode),
+        ("json_decode", json_decode),
+        ("base64_encode", base64_encode),
+        ("base64_decode", base64_decode),
+        ("csv_parse", csv_parse),
+        ("csv_write", csv_write),
+        ("toml_decode", toml_decode),
+        ("compress", compress),
+        ("decompress", decompress),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+    use xlang_vm_core::executor::variable::try_eq_as_vmobject;
+
+    #[test]
+    fn test_base64_round_trip() {
+        let mut gc_system = GCSystem::new(None);
+        for (data, expected) in [
+            (vec![], ""),
+            (b"f".to_vec(), "Zg=="),
+            (b"XLang".to_vec(), "WExhbmc="),
+            (vec![0x00, 0xff, 0xfe, 0x80], "AP/+gA=="),
+        ] {
+            let mut bytes = gc_system.new_object(VMBytes::new(&data));
+            let mut encoded =
+                call_native(base64_encode, &mut vec![&mut bytes], &mut gc_system).unwrap();
+            assert_eq!(encoded.as_const_type::<VMString>().value, expected);
+            let mut decoded =
+                call_native(base64_decode, &mut vec![&mut encoded], &mut gc_system).unwrap();
+            assert_eq!(decoded.as_const_type::<VMBytes>().value, data);
+            for obj in [&mut bytes, &mut encoded, &mut decoded] {
+                obj.drop_ref();
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_base64_decode_rejects_garbage() {
+        let mut gc_system = GCSystem::new(None);
+        for input in ["not base64!", "Zg=", "Z"] {
+            let mut string = gc_system.new_object(VMString::new(input));
+            match call_native(base64_decode, &mut vec![&mut string], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("base64_decode should reject {:?}", input),
+            }
+            string.drop_ref();
+        }
+        let mut number = gc_system.new_object(VMInt::new(1));
+        match call_native(base64_encode, &mut vec![&mut number], &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("base64_encode should reject non-bytes input"),
+        }
+        number.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let mut gc_system = GCSystem::new(None);
+        let text = "name,quote\r\nalice,\"hello, world\"\nbob,\"she said \"\"hi\"\"\nbye\"\n";
+        let mut string = gc_system.new_object(VMString::new(text));
+        let mut rows = call_native(csv_parse, &mut vec![&mut string], &mut gc_system).unwrap();
+        assert_eq!(
+            try_repr_vmobject(&mut rows, None).unwrap(),
+            "((\"name\", \"quote\"), (\"alice\", \"hello, world\"), (\"bob\", \"she said \\\"hi\\\"\\nbye\"))"
+        );
+
+        let mut written = call_native(csv_write, &mut vec![&mut rows], &mut gc_system).unwrap();
+        assert_eq!(
+            written.as_const_type::<VMString>().value,
+            "name,quote\nalice,\"hello, world\"\nbob,\"she said \"\"hi\"\"\nbye\"\n"
+        );
+        let mut reparsed = call_native(csv_parse, &mut vec![&mut written], &mut gc_system).unwrap();
+        assert!(try_eq_as_vmobject(&rows, &reparsed));
+
+        for obj in [&mut string, &mut rows, &mut written, &mut reparsed] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_csv_parse_rejects_malformed_quoting() {
+        let mut gc_system = GCSystem::new(None);
+        for input in ["a,\"unterminated\n", "a,b\"c\n", "\"closed\"x,y\n"] {
+            let mut string = gc_system.new_object(VMString::new(input));
+            match call_native(csv_parse, &mut vec![&mut string], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("csv_parse should reject {:?}", input),
+            }
+            string.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_toml_decode_nested_tables() {
+        let mut gc_system = GCSystem::new(None);
+        let config = r#"
+title = "demo"
+ports = [8000, 8001]
+
+[server]
+host = "localhost"
+ratio = 0.5
+
+[server.tls]
+enabled = true
+"#;
+        let mut string = gc_system.new_object(VMString::new(config));
+        let mut decoded = call_native(toml_decode, &mut vec![&mut string], &mut gc_system).unwrap();
+
+        let mut server_key = gc_system.new_object(VMString::new("server"));
+        let mut tls_key = gc_system.new_object(VMString::new("tls"));
+        let mut enabled_key = gc_system.new_object(VMString::new("enabled"));
+        let server = decoded
+            .as_type::<VMTuple>()
+            .get_member(&mut server_key)
+            .unwrap();
+        let tls = server
+            .as_type::<VMTuple>()
+            .get_member(&mut tls_key)
+            .unwrap();
+        let enabled = tls
+            .as_type::<VMTuple>()
+            .get_member(&mut enabled_key)
+            .unwrap();
+        assert!(enabled.as_const_type::<VMBoolean>().value);
+        assert_eq!(
+            try_repr_vmobject(&mut decoded, None).unwrap(),
+            "(\"ports\": (8000, 8001), \"server\": (\"host\": \"localhost\", \"ratio\": 0.5, \"tls\": (\"enabled\": true,)), \"title\": \"demo\")"
+        );
+
+        for obj in [
+            &mut string,
+            &mut decoded,
+            &mut server_key,
+            &mut tls_key,
+            &mut enabled_key,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_toml_decode_reports_location() {
+        let mut gc_system = GCSystem::new(None);
+        let mut string = gc_system.new_object(VMString::new("a = 1\nb = \n"));
+        match call_native(toml_decode, &mut vec![&mut string], &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => {
+                assert!(err.to_string().contains("line 2"), "{}", err.to_string());
+                err.consume_ref();
+            }
+            _ => panic!("toml_decode should reject a missing value"),
+        }
+        string.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn call_with_algo(
+        native_fn: NativeFunction,
+        data: &mut GCRef,
+        algo: &str,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut algo = gc_system.new_object(VMString::new(algo));
+        let result = call_native(native_fn, &mut vec![data, &mut algo], gc_system);
+        algo.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let mut gc_system = GCSystem::new(None);
+        let payload = b"XLang compresses repeated text, repeated text, repeated text".repeat(16);
+        for data in [vec![], payload.clone()] {
+            let mut bytes = gc_system.new_object(VMBytes::new(&data));
+            for algo in ["gzip", "deflate", "zlib"] {
+                let mut compressed =
+                    call_with_algo(compress, &mut bytes, algo, &mut gc_system).unwrap();
+                if data == payload {
+                    assert!(compressed.as_const_type::<VMBytes>().value.len() < data.len());
+                }
+                let mut decompressed =
+                    call_with_algo(decompress, &mut compressed, algo, &mut gc_system).unwrap();
+                assert_eq!(decompressed.as_const_type::<VMBytes>().value, data);
+                compressed.drop_ref();
+                decompressed.drop_ref();
+            }
+            bytes.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_decompress_rejects_random_bytes() {
+        let mut gc_system = GCSystem::new(None);
+        // 固定种子的 xorshift，保证测试数据可复现
+        let mut state: u32 = 0x2545_f491;
+        let noise: Vec<u8> = (0..64)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        let mut bytes = gc_system.new_object(VMBytes::new(&noise));
+        for algo in ["gzip", "deflate", "zlib"] {
+            match call_with_algo(decompress, &mut bytes, algo, &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("decompress should reject random bytes as {}", algo),
+            }
+        }
+        for native_fn in [compress as NativeFunction, decompress] {
+            match call_with_algo(native_fn, &mut bytes, "lzma", &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("unknown algorithms should be rejected"),
+            }
+        }
+        bytes.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
 }
@@ -1,5 +1,8 @@
 use xlang_vm_core::{
-    executor::variable::{VMBoolean, VMInt, VMNull, VMString, VMTuple, VMVariableError},
+    executor::variable::{
+        try_to_string_vmobject, VMBoolean, VMBytes, VMFloat, VMInt, VMKeyVal, VMNamed, VMNull,
+        VMString, VMTuple, VMVariableError,
+    },
     gc::{GCRef, GCSystem},
 };
 
@@ -136,7 +139,7 @@ fn split(
     Ok(result_tuple)
 }
 
-// string_utils.join(separator, iterable)
+// string_utils.join(separator, iterable)，非字符串元素通过 to_string 转换后拼接
 fn join(
     _self_object: Option<&mut GCRef>,
     _capture: Option<&mut GCRef>,
@@ -160,15 +163,12 @@ fn join(
 
     let mut string_parts = Vec::with_capacity(iterable_tuple.values.len());
     for item in &mut iterable_tuple.values {
-        if !item.isinstance::<VMString>() {
-            return Err(VMVariableError::TypeError(
-                item.clone_ref(),
-                "All elements in the iterable for join must be strings".to_string(),
-            ));
+        if item.isinstance::<VMString>() {
+            string_parts.push(item.as_const_type::<VMString>().value.clone());
+        } else {
+            string_parts.push(try_to_string_vmobject(item, None)?);
         }
-        string_parts.push(item.as_const_type::<VMString>().value.as_str());
     }
-    // ... rest of join implementation remains the same ...
     let joined_string = string_parts.join(&separator);
     Ok(gc_system.new_object(VMString::new(&joined_string)))
 }
@@ -192,7 +192,7 @@ fn replace(
     }
 
     let target_str = get_string_arg(None, None, args_tuple, 0, "replace", "string")?;
-    let old_str = get_string_arg(None, None, args_tuple, 1, "replace", "old")?;
+    let old_str = get_non_empty_pattern_arg(args_tuple, 1, "replace")?;
     let new_str = get_string_arg(None, None, args_tuple, 2, "replace", "new")?;
 
     // Optional count argument
@@ -211,6 +211,47 @@ fn replace(
     Ok(gc_system.new_object(VMString::new(&result_string)))
 }
 
+// string_utils.replace_first(string, old, new)
+fn replace_first(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+
+    if arg_count != 3 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("replace_first expected 3 arguments, got {}", arg_count),
+        ));
+    }
+
+    let target_str = get_string_arg(None, None, args_tuple, 0, "replace_first", "string")?;
+    let old_str = get_non_empty_pattern_arg(args_tuple, 1, "replace_first")?;
+    let new_str = get_string_arg(None, None, args_tuple, 2, "replace_first", "new")?;
+
+    let result_string = target_str.replacen(&old_str, &new_str, 1);
+    Ok(gc_system.new_object(VMString::new(&result_string)))
+}
+
+// 替换的目标串不能为空，否则会在每个字符之间插入，语义含糊
+fn get_non_empty_pattern_arg(
+    args_tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+) -> Result<String, VMVariableError> {
+    let old_str = get_string_arg(None, None, args_tuple, index, func_name, "old")?;
+    if old_str.is_empty() {
+        return Err(VMVariableError::ValueError(
+            args_tuple.as_type::<VMTuple>().values[index].clone_ref(),
+            format!("Argument 'old' for {} must not be empty", func_name),
+        ));
+    }
+    Ok(old_str)
+}
+
 // string_utils.startswith(string, prefix)
 fn startswith(
     _self_object: Option<&mut GCRef>,
@@ -261,6 +302,35 @@ fn endswith(
     Ok(gc_system.new_object(VMBoolean::new(result)))
 }
 
+// string_utils.find(string, needle)
+// 返回第一次出现的字符下标（与字符串索引一致，而非字节偏移），不存在时返回 -1
+fn find(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let tuple_obj = args_tuple.as_const_type::<VMTuple>();
+    let arg_count = tuple_obj.values.len();
+
+    if arg_count != 2 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("find expected 2 arguments, got {}", arg_count),
+        ));
+    }
+
+    let target_str = get_string_arg(None, None, args_tuple, 0, "find", "string")?;
+    let needle_str = get_string_arg(None, None, args_tuple, 1, "find", "needle")?;
+
+    let result = match target_str.find(&needle_str) {
+        Some(byte_offset) => target_str[..byte_offset].chars().count() as i64,
+        None => -1,
+    };
+    Ok(gc_system.new_object(VMInt::new(result)))
+}
+
 // string_utils.strip(string, [chars])
 fn strip(
     _self_object: Option<&mut GCRef>,
@@ -295,6 +365,79 @@ fn strip(
     Ok(gc_system.new_object(VMString::new(&result_string)))
 }
 
+#[derive(Clone, Copy)]
+enum TrimSide {
+    Both,
+    Start,
+    End,
+}
+
+// trim/trim_start/trim_end 的公共实现，未给出 chars 时去除 Unicode 空白
+fn trim_with_side(
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+    func_name: &str,
+    side: TrimSide,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+    if !(1..=2).contains(&arg_count) {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("{} expected 1 or 2 arguments, got {}", func_name, arg_count),
+        ));
+    }
+
+    let target_str = get_string_arg(None, None, args_tuple, 0, func_name, "string")?;
+    let chars_to_strip: Option<Vec<char>> = if arg_count > 1 {
+        let chars_str = get_string_arg(None, None, args_tuple, 1, func_name, "chars")?;
+        Some(chars_str.chars().collect())
+    } else {
+        None
+    };
+    let should_strip = |c: char| match &chars_to_strip {
+        Some(chars) => chars.contains(&c),
+        None => c.is_whitespace(),
+    };
+
+    let result_string = match side {
+        TrimSide::Both => target_str.trim_matches(should_strip),
+        TrimSide::Start => target_str.trim_start_matches(should_strip),
+        TrimSide::End => target_str.trim_end_matches(should_strip),
+    };
+    Ok(gc_system.new_object(VMString::new(result_string)))
+}
+
+// string_utils.trim(string, [chars])
+fn trim(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    trim_with_side(args_tuple, gc_system, "trim", TrimSide::Both)
+}
+
+// string_utils.trim_start(string, [chars])
+fn trim_start(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    trim_with_side(args_tuple, gc_system, "trim_start", TrimSide::Start)
+}
+
+// string_utils.trim_end(string, [chars])
+fn trim_end(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    trim_with_side(args_tuple, gc_system, "trim_end", TrimSide::End)
+}
+
 // string_utils.lower(string)
 fn lower(
     _self_object: Option<&mut GCRef>,
@@ -341,6 +484,613 @@ fn upper(
     Ok(gc_system.new_object(VMString::new(&result_string)))
 }
 
+// string_utils.char_len(string)，返回字符数，与 len(string) 相同
+fn char_len(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("char_len expected 1 argument, got {}", arg_count),
+        ));
+    }
+
+    let target_str = get_string_arg(None, None, args_tuple, 0, "char_len", "string")?;
+    Ok(gc_system.new_object(VMInt::new(target_str.chars().count() as i64)))
+}
+
+// string_utils.byte_len(string)，返回 UTF-8 编码后的字节数
+fn byte_len(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("byte_len expected 1 argument, got {}", arg_count),
+        ));
+    }
+
+    let target_str = get_string_arg(None, None, args_tuple, 0, "byte_len", "string")?;
+    Ok(gc_system.new_object(VMInt::new(target_str.len() as i64)))
+}
+
+// 百分号编码，只保留 RFC 3986 中的非保留字符 A-Z a-z 0-9 - _ . ~
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+// 百分号解码，'+' 不会被视为空格；不完整的 %XX 或解码结果不是合法 UTF-8 时返回错误描述
+fn percent_decode(text: &str) -> Result<String, String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] != b'%' {
+            decoded.push(bytes[index]);
+            index += 1;
+            continue;
+        }
+        let digits = bytes.get(index + 1..index + 3);
+        match digits {
+            Some(digits) if digits.iter().all(u8::is_ascii_hexdigit) => {
+                let digits = std::str::from_utf8(digits).unwrap();
+                decoded.push(u8::from_str_radix(digits, 16).unwrap());
+                index += 3;
+            }
+            _ => {
+                return Err(format!(
+                    "malformed percent sequence at byte {}: {}",
+                    index,
+                    String::from_utf8_lossy(&bytes[index..bytes.len().min(index + 3)])
+                ))
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|e| format!("decoded bytes are not valid utf-8: {}", e))
+}
+
+// string_utils.url_encode(string)
+fn url_encode(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("url_encode expected 1 argument, got {}", arg_count),
+        ));
+    }
+
+    let target_str = get_string_arg(None, None, args_tuple, 0, "url_encode", "string")?;
+    Ok(gc_system.new_object(VMString::new(&percent_encode(&target_str))))
+}
+
+// string_utils.url_decode(string)
+fn url_decode(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("url_decode expected 1 argument, got {}", arg_count),
+        ));
+    }
+
+    let target_str = get_string_arg(None, None, args_tuple, 0, "url_decode", "string")?;
+    match percent_decode(&target_str) {
+        Ok(decoded) => Ok(gc_system.new_object(VMString::new(&decoded))),
+        Err(message) => Err(VMVariableError::ValueError(
+            args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!("url_decode {}", message),
+        )),
+    }
+}
+
+// string_utils.build_query((k1 : v1, k2 => v2, ...))，键和值转换为字符串后分别编码，按原顺序以 & 连接
+fn build_query(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("build_query expected 1 argument, got {}", arg_count),
+        ));
+    }
+
+    let params = get_tuple_arg(None, None, args_tuple, 0, "build_query", "params")?;
+    let mut pairs = Vec::new();
+    for item in params.as_type::<VMTuple>().values.iter_mut() {
+        let (key, value) = if item.isinstance::<VMKeyVal>() {
+            let keyval = item.as_type::<VMKeyVal>();
+            (
+                try_to_string_vmobject(keyval.get_key(), None)?,
+                try_to_string_vmobject(keyval.get_value(), None)?,
+            )
+        } else if item.isinstance::<VMNamed>() {
+            let named = item.as_type::<VMNamed>();
+            (
+                try_to_string_vmobject(named.get_key(), None)?,
+                try_to_string_vmobject(named.get_value(), None)?,
+            )
+        } else {
+            return Err(VMVariableError::TypeError(
+                item.clone_ref(),
+                "Items for build_query must be key:value or key=>value pairs".to_string(),
+            ));
+        };
+        pairs.push(format!(
+            "{}={}",
+            percent_encode(&key),
+            percent_encode(&value)
+        ));
+    }
+    Ok(gc_system.new_object(VMString::new(&pairs.join("&"))))
+}
+
+// string_utils.to_hex(bytes)
+fn to_hex(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let tuple_obj = args_tuple.as_type::<VMTuple>();
+    let arg_count = tuple_obj.values.len();
+
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("to_hex expected 1 argument, got {}", arg_count),
+        ));
+    }
+
+    let bytes_obj = &mut tuple_obj.values[0];
+    if !bytes_obj.isinstance::<VMBytes>() {
+        return Err(VMVariableError::TypeError(
+            bytes_obj.clone_ref(),
+            "Argument 'bytes' for to_hex must be bytes".to_string(),
+        ));
+    }
+    let result_string = bytes_obj
+        .as_const_type::<VMBytes>()
+        .value
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    Ok(gc_system.new_object(VMString::new(&result_string)))
+}
+
+// string_utils.from_hex(string)
+fn from_hex(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let tuple_obj = args_tuple.as_const_type::<VMTuple>();
+    let arg_count = tuple_obj.values.len();
+
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("from_hex expected 1 argument, got {}", arg_count),
+        ));
+    }
+
+    let hex_str = get_string_arg(None, None, args_tuple, 0, "from_hex", "string")?;
+    if hex_str.len() % 2 != 0 {
+        return Err(VMVariableError::ValueError(
+            args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            "from_hex requires an even number of hex digits".to_string(),
+        ));
+    }
+    let mut byte_vec = Vec::with_capacity(hex_str.len() / 2);
+    for pair in hex_str.as_bytes().chunks(2) {
+        let byte = std::str::from_utf8(pair)
+            .ok()
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+        match byte {
+            // from_str_radix 允许前导 '+'，这里只接受十六进制数字
+            Some(byte) if pair.iter().all(u8::is_ascii_hexdigit) => byte_vec.push(byte),
+            _ => {
+                return Err(VMVariableError::ValueError(
+                    args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+                    format!(
+                        "from_hex found invalid hex digits: {}",
+                        String::from_utf8_lossy(pair)
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(gc_system.new_object(VMBytes::new(&byte_vec)))
+}
+
+// string_utils.decode_bytes(bytes, encoding)
+// encoding: "utf-8" | "latin-1" | "utf-8-lossy"
+fn decode_bytes(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let tuple_obj = args_tuple.as_type::<VMTuple>();
+    let arg_count = tuple_obj.values.len();
+
+    if arg_count != 2 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("decode_bytes expected 2 arguments, got {}", arg_count),
+        ));
+    }
+
+    let encoding = get_string_arg(None, None, args_tuple, 1, "decode_bytes", "encoding")?;
+    let tuple_obj = args_tuple.as_type::<VMTuple>();
+    let bytes_obj = &mut tuple_obj.values[0];
+    if !bytes_obj.isinstance::<VMBytes>() {
+        return Err(VMVariableError::TypeError(
+            bytes_obj.clone_ref(),
+            "Argument 'bytes' for decode_bytes must be bytes".to_string(),
+        ));
+    }
+    let data = &bytes_obj.as_const_type::<VMBytes>().value;
+    let decoded = match encoding.to_lowercase().as_str() {
+        "utf-8" => match String::from_utf8(data.clone()) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                return Err(VMVariableError::ValueError(
+                    bytes_obj.clone_ref(),
+                    format!("decode_bytes failed to decode utf-8: {}", e),
+                ));
+            }
+        },
+        // latin-1 的每个字节直接对应 U+0000..=U+00FF
+        "latin-1" => data.iter().map(|&byte| byte as char).collect(),
+        "utf-8-lossy" => String::from_utf8_lossy(data).into_owned(),
+        _ => {
+            return Err(VMVariableError::ValueError(
+                tuple_obj.values[1].clone_ref(),
+                format!(
+                    "Unknown encoding for decode_bytes: {} (expected utf-8, latin-1 or utf-8-lossy)",
+                    encoding
+                ),
+            ));
+        }
+    };
+    Ok(gc_system.new_object(VMString::new(&decoded)))
+}
+
+// 取出第 index 个参数的字节内容
+fn get_bytes_arg(
+    args_tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+    arg_name: &str,
+) -> Result<Vec<u8>, VMVariableError> {
+    let bytes_obj = &mut args_tuple.as_type::<VMTuple>().values[index];
+    if !bytes_obj.isinstance::<VMBytes>() {
+        return Err(VMVariableError::TypeError(
+            bytes_obj.clone_ref(),
+            format!("Argument '{}' for {} must be bytes", arg_name, func_name),
+        ));
+    }
+    Ok(bytes_obj.as_const_type::<VMBytes>().value.clone())
+}
+
+// 对两个等长的 bytes 逐字节应用 op，长度不一致时返回 ValueError
+fn bytes_binary_op(
+    args_tuple: &mut GCRef,
+    func_name: &str,
+    op: fn(u8, u8) -> u8,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 2 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("{} expected 2 arguments, got {}", func_name, arg_count),
+        ));
+    }
+    let left = get_bytes_arg(args_tuple, 0, func_name, "left")?;
+    let right = get_bytes_arg(args_tuple, 1, func_name, "right")?;
+    if left.len() != right.len() {
+        return Err(VMVariableError::ValueError(
+            args_tuple.clone_ref(),
+            format!(
+                "{} requires bytes of equal length, got {} and {}",
+                func_name,
+                left.len(),
+                right.len()
+            ),
+        ));
+    }
+    let result: Vec<u8> = left.iter().zip(&right).map(|(&a, &b)| op(a, b)).collect();
+    Ok(gc_system.new_object(VMBytes::new(&result)))
+}
+
+// string_utils.bytes_and(left, right)
+fn bytes_and(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    bytes_binary_op(args_tuple, "bytes_and", |a, b| a & b, gc_system)
+}
+
+// string_utils.bytes_or(left, right)
+fn bytes_or(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    bytes_binary_op(args_tuple, "bytes_or", |a, b| a | b, gc_system)
+}
+
+// string_utils.bytes_xor(left, right)
+fn bytes_xor(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    bytes_binary_op(args_tuple, "bytes_xor", |a, b| a ^ b, gc_system)
+}
+
+// string_utils.bytes_not(bytes)，逐字节取反
+fn bytes_not(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 1 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("bytes_not expected 1 argument, got {}", arg_count),
+        ));
+    }
+    let data = get_bytes_arg(args_tuple, 0, "bytes_not", "bytes")?;
+    let result: Vec<u8> = data.iter().map(|byte| !byte).collect();
+    Ok(gc_system.new_object(VMBytes::new(&result)))
+}
+
+// 模板片段：原样输出的文本或按位置填充的占位符
+enum FormatPiece {
+    Literal(String),
+    Placeholder,
+}
+
+// 解析 {} 占位符，{{ 与 }} 转义为字面量花括号，其余单独出现的花括号视为错误
+fn parse_format_template(template: &str) -> Result<Vec<FormatPiece>, String> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                literal.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                if !literal.is_empty() {
+                    pieces.push(FormatPiece::Literal(std::mem::take(&mut literal)));
+                }
+                pieces.push(FormatPiece::Placeholder);
+            }
+            ('{', _) => return Err("unmatched '{' in format template".to_string()),
+            ('}', _) => return Err("unmatched '}' in format template".to_string()),
+            _ => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        pieces.push(FormatPiece::Literal(literal));
+    }
+    Ok(pieces)
+}
+
+// string_utils.format(template, args)
+fn format(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+
+    if arg_count != 2 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("format expected 2 arguments, got {}", arg_count),
+        ));
+    }
+
+    let template = get_string_arg(None, None, args_tuple, 0, "format", "template")?;
+    let pieces = match parse_format_template(&template) {
+        Ok(pieces) => pieces,
+        Err(msg) => {
+            return Err(VMVariableError::ValueError(
+                args_tuple.as_type::<VMTuple>().values[0].clone_ref(),
+                msg,
+            ));
+        }
+    };
+    let values_obj = get_tuple_arg(None, None, args_tuple, 1, "format", "args")?;
+    let placeholder_count = pieces
+        .iter()
+        .filter(|piece| matches!(piece, FormatPiece::Placeholder))
+        .count();
+    let value_count = values_obj.as_const_type::<VMTuple>().values.len();
+    if placeholder_count != value_count {
+        return Err(VMVariableError::ValueError(
+            values_obj.clone_ref(),
+            format!(
+                "format template has {} placeholder(s) but {} argument(s) were given",
+                placeholder_count, value_count
+            ),
+        ));
+    }
+
+    let mut values = values_obj.as_type::<VMTuple>().values.iter_mut();
+    let mut result_string = String::new();
+    for piece in pieces {
+        match piece {
+            FormatPiece::Literal(text) => result_string.push_str(&text),
+            FormatPiece::Placeholder => {
+                let value = values.next().unwrap();
+                result_string.push_str(&try_to_string_vmobject(value, None)?);
+            }
+        }
+    }
+    Ok(gc_system.new_object(VMString::new(&result_string)))
+}
+
+// 读取非负整数参数（精度、宽度等），负数返回 ValueError
+fn get_non_negative_int_arg(
+    args_tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+    arg_name: &str,
+) -> Result<usize, VMVariableError> {
+    let arg_obj = &mut args_tuple.as_type::<VMTuple>().values[index];
+    if !arg_obj.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            arg_obj.clone_ref(),
+            format!(
+                "Argument '{}' for {} must be an integer",
+                arg_name, func_name
+            ),
+        ));
+    }
+    let value = arg_obj.as_const_type::<VMInt>().value;
+    usize::try_from(value).map_err(|_| {
+        VMVariableError::ValueError(
+            arg_obj.clone_ref(),
+            format!(
+                "Argument '{}' for {} must not be negative, got {}",
+                arg_name, func_name, value
+            ),
+        )
+    })
+}
+
+// string_utils.format_float(value, precision)，保留 precision 位小数
+fn format_float(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 2 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("format_float expected 2 arguments, got {}", arg_count),
+        ));
+    }
+
+    let value_obj = &mut args_tuple.as_type::<VMTuple>().values[0];
+    let value = if value_obj.isinstance::<VMFloat>() {
+        value_obj.as_const_type::<VMFloat>().value
+    } else if value_obj.isinstance::<VMInt>() {
+        value_obj.as_const_type::<VMInt>().value as f64
+    } else {
+        return Err(VMVariableError::TypeError(
+            value_obj.clone_ref(),
+            "Argument 'value' for format_float must be an integer or float".to_string(),
+        ));
+    };
+    let precision = get_non_negative_int_arg(args_tuple, 1, "format_float", "precision")?;
+    Ok(gc_system.new_object(VMString::new(&format!("{:.*}", precision, value))))
+}
+
+// string_utils.format_int(value, width, pad)，pad 为 "0" 或 " "，宽度不足时左侧填充
+fn format_int(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    args_tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(args_tuple)?;
+    let arg_count = args_tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 3 {
+        return Err(VMVariableError::TypeError(
+            args_tuple.clone_ref(),
+            format!("format_int expected 3 arguments, got {}", arg_count),
+        ));
+    }
+
+    let value_obj = &mut args_tuple.as_type::<VMTuple>().values[0];
+    if !value_obj.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            value_obj.clone_ref(),
+            "Argument 'value' for format_int must be an integer".to_string(),
+        ));
+    }
+    let value = value_obj.as_const_type::<VMInt>().value;
+    let width = get_non_negative_int_arg(args_tuple, 1, "format_int", "width")?;
+    let pad = get_string_arg(None, None, args_tuple, 2, "format_int", "pad")?;
+    // 零填充放在符号之后，如 -0042
+    let formatted = match pad.as_str() {
+        "0" => format!("{:0width$}", value, width = width),
+        " " => format!("{:>width$}", value, width = width),
+        _ => {
+            return Err(VMVariableError::ValueError(
+                args_tuple.as_type::<VMTuple>().values[2].clone_ref(),
+                format!("format_int pad must be \"0\" or \" \", got {:?}", pad),
+            ));
+        }
+    };
+    Ok(gc_system.new_object(VMString::new(&formatted)))
+}
+
 // Helper to provide functions for registration
 pub fn get_string_utils_module() -> Vec<(
     &'static str,
@@ -355,10 +1105,667 @@ pub fn get_string_utils_module() -> Vec<(
         ("split", split),
         ("join", join),
         ("replace", replace),
+        ("replace_first", replace_first),
         ("startswith", startswith),
         ("endswith", endswith),
+        ("starts_with", startswith),
+        ("ends_with", endswith),
+        ("find", find),
         ("strip", strip),
+        ("trim", trim),
+        ("trim_start", trim_start),
+        ("trim_end", trim_end),
         ("lower", lower),
         ("upper", upper),
+        ("char_len", char_len),
+        ("byte_len", byte_len),
+        ("url_encode", url_encode),
+        ("url_decode", url_decode),
+        ("build_query", build_query),
+        ("to_hex", to_hex),
+        ("from_hex", from_hex),
+        ("decode_bytes", decode_bytes),
+        ("bytes_and", bytes_and),
+        ("bytes_or", bytes_or),
+        ("bytes_xor", bytes_xor),
+        ("bytes_not", bytes_not),
+        ("format", format),
+        ("format_float", format_float),
+        ("format_int", format_int),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+    use xlang_vm_core::executor::variable::{try_length_of_as_vmobject, try_repr_vmobject};
+
+    fn trim_string(
+        native_fn: NativeFunction,
+        target: &str,
+        chars: Option<&str>,
+        gc_system: &mut GCSystem,
+    ) -> String {
+        let mut target = gc_system.new_object(VMString::new(target));
+        let mut chars = chars.map(|chars| gc_system.new_object(VMString::new(chars)));
+        let mut values = vec![&mut target];
+        if let Some(chars) = chars.as_mut() {
+            values.push(chars);
+        }
+        let mut trimmed = call_native(native_fn, &mut values, gc_system).unwrap();
+        let value = trimmed.as_const_type::<VMString>().value.clone();
+        trimmed.drop_ref();
+        target.drop_ref();
+        if let Some(chars) = chars.as_mut() {
+            chars.drop_ref();
+        }
+        value
+    }
+
+    #[test]
+    fn test_trim() {
+        let mut gc_system = GCSystem::new(None);
+        let padded = "\u{3000} \thi there\r\n";
+        assert_eq!(trim_string(trim, padded, None, &mut gc_system), "hi there");
+        assert_eq!(
+            trim_string(trim_start, padded, None, &mut gc_system),
+            "hi there\r\n"
+        );
+        assert_eq!(
+            trim_string(trim_end, padded, None, &mut gc_system),
+            "\u{3000} \thi there"
+        );
+
+        assert_eq!(trim_string(trim, "xxhix", Some("x"), &mut gc_system), "hi");
+        assert_eq!(
+            trim_string(trim_start, "xxhix", Some("x"), &mut gc_system),
+            "hix"
+        );
+        assert_eq!(
+            trim_string(trim_end, "xxhix", Some("x"), &mut gc_system),
+            "xxhi"
+        );
+        assert_eq!(
+            trim_string(trim, "-=hi=-", Some("=-"), &mut gc_system),
+            "hi"
+        );
+        // 自定义字符集时不再去除空白
+        assert_eq!(
+            trim_string(trim, " xhix ", Some("x"), &mut gc_system),
+            " xhix "
+        );
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_trim_rejects_non_string() {
+        let mut gc_system = GCSystem::new(None);
+        let mut number = gc_system.new_object(VMInt::new(42));
+        for native_fn in [trim, trim_start, trim_end] {
+            match call_native(native_fn, &mut vec![&mut number], &mut gc_system) {
+                Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+                _ => panic!("trim should reject non-string input"),
+            }
+        }
+        number.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn replace_with(
+        native_fn: NativeFunction,
+        subject: &str,
+        from: &str,
+        to: &str,
+        gc_system: &mut GCSystem,
+    ) -> Result<String, VMVariableError> {
+        let mut subject = gc_system.new_object(VMString::new(subject));
+        let mut from = gc_system.new_object(VMString::new(from));
+        let mut to = gc_system.new_object(VMString::new(to));
+        let args = &mut vec![&mut subject, &mut from, &mut to];
+        let result = call_native(native_fn, args, gc_system).map(|mut replaced| {
+            let value = replaced.as_const_type::<VMString>().value.clone();
+            replaced.drop_ref();
+            value
+        });
+        for obj in [&mut subject, &mut from, &mut to] {
+            obj.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_replace_and_replace_first() {
+        let mut gc_system = GCSystem::new(None);
+        assert_eq!(
+            replace_with(replace, "a-b-c-d", "-", "+", &mut gc_system).unwrap(),
+            "a+b+c+d"
+        );
+        assert_eq!(
+            replace_with(replace_first, "a-b-c-d", "-", "+", &mut gc_system).unwrap(),
+            "a+b-c-d"
+        );
+        // 不重叠匹配："aaa" 中只有一处 "aa"
+        assert_eq!(
+            replace_with(replace, "aaa", "aa", "b", &mut gc_system).unwrap(),
+            "ba"
+        );
+        for native_fn in [replace, replace_first] {
+            assert_eq!(
+                replace_with(native_fn, "hello", "xyz", "!", &mut gc_system).unwrap(),
+                "hello"
+            );
+            match replace_with(native_fn, "hello", "", "!", &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("empty pattern should be rejected"),
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let mut gc_system = GCSystem::new(None);
+        for data in [
+            vec![],
+            vec![0u8],
+            vec![0x00, 0x7f, 0x80, 0xff],
+            b"XLang".to_vec(),
+        ] {
+            let mut bytes = gc_system.new_object(VMBytes::new(&data));
+            let mut hex = call_native(to_hex, &mut vec![&mut bytes], &mut gc_system).unwrap();
+            assert_eq!(
+                hex.as_const_type::<VMString>().value,
+                data.iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>()
+            );
+            let mut decoded = call_native(from_hex, &mut vec![&mut hex], &mut gc_system).unwrap();
+            assert_eq!(decoded.as_const_type::<VMBytes>().value, data);
+            bytes.drop_ref();
+            hex.drop_ref();
+            decoded.drop_ref();
+        }
+        gc_system.collect();
+    }
+
+    #[test]
+    fn test_from_hex_rejects_invalid_input() {
+        let mut gc_system = GCSystem::new(None);
+        for input in ["abc", "zz", "+f"] {
+            let mut hex = gc_system.new_object(VMString::new(input));
+            match call_native(from_hex, &mut vec![&mut hex], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("from_hex should reject {:?}", input),
+            }
+            hex.drop_ref();
+        }
+        gc_system.collect();
+    }
+
+    fn decode(data: &[u8], encoding: &str, gc_system: &mut GCSystem) -> Result<String, ()> {
+        let mut bytes = gc_system.new_object(VMBytes::new(&data.to_vec()));
+        let mut encoding = gc_system.new_object(VMString::new(encoding));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut bytes, &mut encoding]));
+        let result = match decode_bytes(None, None, &mut args, gc_system) {
+            Ok(mut decoded) => {
+                let value = decoded.as_const_type::<VMString>().value.clone();
+                decoded.drop_ref();
+                Ok(value)
+            }
+            Err(mut err @ VMVariableError::ValueError(..)) => {
+                err.consume_ref();
+                Err(())
+            }
+            Err(mut err) => panic!("unexpected error: {}", err.to_string()),
+        };
+        bytes.drop_ref();
+        encoding.drop_ref();
+        args.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_decode_bytes() {
+        let mut gc_system = GCSystem::new(None);
+        let data = [0x63, 0x61, 0x66, 0xe9];
+        assert_eq!(
+            decode(&data, "latin-1", &mut gc_system),
+            Ok("café".to_string())
+        );
+        assert_eq!(decode(&data, "utf-8", &mut gc_system), Err(()));
+        assert_eq!(
+            decode(&data, "utf-8-lossy", &mut gc_system),
+            Ok("caf\u{fffd}".to_string())
+        );
+        assert_eq!(
+            decode("café".as_bytes(), "utf-8", &mut gc_system),
+            Ok("café".to_string())
+        );
+        assert_eq!(decode(&data, "ebcdic", &mut gc_system), Err(()));
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn bytes_op(
+        native_fn: NativeFunction,
+        operands: &[&[u8]],
+        gc_system: &mut GCSystem,
+    ) -> Result<Vec<u8>, VMVariableError> {
+        let mut values: Vec<GCRef> = operands
+            .iter()
+            .map(|data| gc_system.new_object(VMBytes::new(&data.to_vec())))
+            .collect();
+        let result = call_native(native_fn, &mut values.iter_mut().collect(), gc_system).map(
+            |mut result| {
+                let value = result.as_const_type::<VMBytes>().value.clone();
+                result.drop_ref();
+                value
+            },
+        );
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_bytes_xor_with_key() {
+        let mut gc_system = GCSystem::new(None);
+        let plain = b"XLang!";
+        let key = [0x13, 0x37, 0xff, 0x00, 0xa5, 0x5a];
+        let cipher = bytes_op(bytes_xor, &[plain, &key], &mut gc_system).unwrap();
+        assert_eq!(cipher, vec![0x4b, 0x7b, 0x9e, 0x6e, 0xc2, 0x7b]);
+        // 再次异或同一密钥还原明文
+        let decoded = bytes_op(bytes_xor, &[&cipher, &key], &mut gc_system).unwrap();
+        assert_eq!(decoded, plain.to_vec());
+        assert_eq!(
+            bytes_op(
+                bytes_and,
+                &[&[0b1100, 0xff], &[0b1010, 0x0f]],
+                &mut gc_system
+            )
+            .unwrap(),
+            vec![0b1000, 0x0f]
+        );
+        assert_eq!(
+            bytes_op(
+                bytes_or,
+                &[&[0b1100, 0x00], &[0b1010, 0xf0]],
+                &mut gc_system
+            )
+            .unwrap(),
+            vec![0b1110, 0xf0]
+        );
+        assert_eq!(
+            bytes_op(bytes_not, &[&[0x00, 0x0f, 0xff]], &mut gc_system).unwrap(),
+            vec![0xff, 0xf0, 0x00]
+        );
+        assert!(bytes_op(bytes_xor, &[&[], &[]], &mut gc_system)
+            .unwrap()
+            .is_empty());
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_bytes_ops_reject_mismatched_lengths() {
+        let mut gc_system = GCSystem::new(None);
+        for native_fn in [bytes_and, bytes_or, bytes_xor] {
+            match bytes_op(native_fn, &[b"abc", b"ab"], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("mismatched lengths should be rejected"),
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn call_with_strings(
+        native_fn: NativeFunction,
+        args: &[&str],
+        gc_system: &mut GCSystem,
+    ) -> Result<String, VMVariableError> {
+        let mut values: Vec<GCRef> = args
+            .iter()
+            .map(|arg| gc_system.new_object(VMString::new(arg)))
+            .collect();
+        let result = call_native(native_fn, &mut values.iter_mut().collect(), gc_system).map(
+            |mut result| {
+                let repr = try_repr_vmobject(&mut result, None).unwrap();
+                result.drop_ref();
+                repr
+            },
+        );
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_char_len_and_byte_len() {
+        let mut gc_system = GCSystem::new(None);
+        let mut target = gc_system.new_object(VMString::new("héllo, 世界"));
+        let mut chars = call_native(char_len, &mut vec![&mut target], &mut gc_system).unwrap();
+        let mut bytes = call_native(byte_len, &mut vec![&mut target], &mut gc_system).unwrap();
+        assert_eq!(chars.as_const_type::<VMInt>().value, 9);
+        assert_eq!(bytes.as_const_type::<VMInt>().value, 14);
+        // len(string) 与 types.len(string) 都与 char_len 一致
+        assert_eq!(try_length_of_as_vmobject(&mut target).unwrap(), 9);
+        let mut types_len = call_native(
+            super::super::types::len,
+            &mut vec![&mut target],
+            &mut gc_system,
+        )
+        .unwrap();
+        assert_eq!(types_len.as_const_type::<VMInt>().value, 9);
+        for obj in [&mut target, &mut chars, &mut bytes, &mut types_len] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_url_encode_decode_round_trip() {
+        let mut gc_system = GCSystem::new(None);
+        let cases = [
+            ("hello world", "hello%20world"),
+            ("a+b=c&d", "a%2Bb%3Dc%26d"),
+            ("你好", "%E4%BD%A0%E5%A5%BD"),
+            ("safe-_.~", "safe-_.~"),
+        ];
+        for (plain, encoded) in cases {
+            let quoted = format!("{:?}", encoded);
+            assert_eq!(
+                call_with_strings(url_encode, &[plain], &mut gc_system).unwrap(),
+                quoted
+            );
+            assert_eq!(
+                call_with_strings(url_decode, &[encoded], &mut gc_system).unwrap(),
+                format!("{:?}", plain)
+            );
+        }
+        // 小写十六进制同样可以解码
+        assert_eq!(
+            call_with_strings(url_decode, &["%e4%bd%a0"], &mut gc_system).unwrap(),
+            "\"你\""
+        );
+        for malformed in ["%", "abc%2", "%zz", "%ff"] {
+            match call_with_strings(url_decode, &[malformed], &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("{:?} should be rejected", malformed),
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_build_query() {
+        let mut gc_system = GCSystem::new(None);
+        let mut key = gc_system.new_object(VMString::new("q"));
+        let mut value = gc_system.new_object(VMString::new("x lang"));
+        let mut first = gc_system.new_object(VMKeyVal::new(&mut key, &mut value));
+        let mut page_key = gc_system.new_object(VMString::new("page"));
+        let mut page = gc_system.new_object(VMInt::new(2));
+        let mut second = gc_system.new_object(VMNamed::new(&mut page_key, &mut page));
+        let mut params = gc_system.new_object(VMTuple::new(&mut vec![&mut first, &mut second]));
+        let mut query = call_native(build_query, &mut vec![&mut params], &mut gc_system).unwrap();
+        assert_eq!(query.as_const_type::<VMString>().value, "q=x%20lang&page=2");
+        for obj in [
+            &mut key,
+            &mut value,
+            &mut first,
+            &mut page_key,
+            &mut page,
+            &mut second,
+            &mut params,
+            &mut query,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_starts_with_ends_with_and_find() {
+        let mut gc_system = GCSystem::new(None);
+        let cases: [(NativeFunction, &[&str], &str); 8] = [
+            (startswith, &["XLang", "XL"], "true"),
+            (startswith, &["XLang", "ng"], "false"),
+            (endswith, &["XLang", "ng"], "true"),
+            (endswith, &["XLang", "XL"], "false"),
+            (find, &["hello world", "o"], "4"),
+            (find, &["hello world", "xyz"], "-1"),
+            (find, &["hello", ""], "0"),
+            // 返回字符下标而非字节偏移
+            (find, &["你好, world", "world"], "4"),
+        ];
+        for (native_fn, args, expected) in cases {
+            assert_eq!(
+                call_with_strings(native_fn, args, &mut gc_system).unwrap(),
+                expected
+            );
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_string_predicates_reject_non_strings() {
+        let mut gc_system = GCSystem::new(None);
+        for native_fn in [startswith, endswith, find] {
+            let mut subject = gc_system.new_object(VMString::new("abc"));
+            let mut needle = gc_system.new_object(VMInt::new(1));
+            match call_native(
+                native_fn,
+                &mut vec![&mut subject, &mut needle],
+                &mut gc_system,
+            ) {
+                Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+                _ => panic!("non-string arguments should be rejected"),
+            }
+            subject.drop_ref();
+            needle.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn format_with(
+        template: &str,
+        values: &[i64],
+        gc_system: &mut GCSystem,
+    ) -> Result<String, VMVariableError> {
+        let mut template = gc_system.new_object(VMString::new(template));
+        let mut items: Vec<GCRef> = values
+            .iter()
+            .map(|v| gc_system.new_object(VMInt::new(*v)))
+            .collect();
+        let mut values = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut template, &mut values]));
+        let result = format(None, None, &mut args, gc_system).map(|mut formatted| {
+            let value = formatted.as_const_type::<VMString>().value.clone();
+            formatted.drop_ref();
+            value
+        });
+        for item in items.iter_mut() {
+            item.drop_ref();
+        }
+        for obj in [&mut template, &mut values, &mut args] {
+            obj.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_format_placeholders_and_escapes() {
+        let mut gc_system = GCSystem::new(None);
+        assert_eq!(
+            format_with("{} + {} = {}", &[1, 2, 3], &mut gc_system).unwrap(),
+            "1 + 2 = 3"
+        );
+        assert_eq!(
+            format_with("{{{}}} {{}}", &[42], &mut gc_system).unwrap(),
+            "{42} {}"
+        );
+        assert_eq!(format_with("plain", &[], &mut gc_system).unwrap(), "plain");
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_format_rejects_mismatched_arity() {
+        let mut gc_system = GCSystem::new(None);
+        for (template, values) in [("{} {}", &[1][..]), ("{}", &[1, 2][..]), ("{ }", &[1][..])] {
+            match format_with(template, values, &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("format should reject {:?} with {:?}", template, values),
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn join_with(separator: &str, mut items: Vec<GCRef>, gc_system: &mut GCSystem) -> String {
+        let mut separator = gc_system.new_object(VMString::new(separator));
+        let mut values = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut separator, &mut values]));
+        let mut joined = join(None, None, &mut args, gc_system).unwrap();
+        let value = joined.as_const_type::<VMString>().value.clone();
+        joined.drop_ref();
+        for item in items.iter_mut() {
+            item.drop_ref();
+        }
+        for obj in [&mut separator, &mut values, &mut args] {
+            obj.drop_ref();
+        }
+        value
+    }
+
+    #[test]
+    fn test_join_numbers_and_strings() {
+        let mut gc_system = GCSystem::new(None);
+        let numbers = vec![
+            gc_system.new_object(VMInt::new(1)),
+            gc_system.new_object(VMInt::new(2)),
+            gc_system.new_object(VMInt::new(3)),
+        ];
+        assert_eq!(join_with(", ", numbers, &mut gc_system), "1, 2, 3");
+        let mixed = vec![
+            gc_system.new_object(VMString::new("a")),
+            gc_system.new_object(VMInt::new(42)),
+            gc_system.new_object(VMString::new("b")),
+        ];
+        assert_eq!(join_with(", ", mixed, &mut gc_system), "a, 42, b");
+        assert_eq!(join_with(", ", vec![], &mut gc_system), "");
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn format_number(
+        native_fn: NativeFunction,
+        mut values: Vec<GCRef>,
+        gc_system: &mut GCSystem,
+    ) -> Result<String, VMVariableError> {
+        let result = call_native(native_fn, &mut values.iter_mut().collect(), gc_system).map(
+            |mut formatted| {
+                let value = formatted.as_const_type::<VMString>().value.clone();
+                formatted.drop_ref();
+                value
+            },
+        );
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_format_float_rounding() {
+        let mut gc_system = GCSystem::new(None);
+        for (value, expected) in [
+            (12.3456, "12.35"),
+            (2.675, "2.67"), // 2.675 在二进制中略小于 2.675
+            (0.125, "0.12"), // 恰好位于中点时舍入到偶数
+            (0.375, "0.38"),
+            (-1.005, "-1.00"),
+            (1.999, "2.00"),
+        ] {
+            let values = vec![
+                gc_system.new_object(VMFloat::new(value)),
+                gc_system.new_object(VMInt::new(2)),
+            ];
+            assert_eq!(
+                format_number(format_float, values, &mut gc_system).unwrap(),
+                expected
+            );
+        }
+        let values = vec![
+            gc_system.new_object(VMInt::new(7)),
+            gc_system.new_object(VMInt::new(0)),
+        ];
+        assert_eq!(
+            format_number(format_float, values, &mut gc_system).unwrap(),
+            "7"
+        );
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_format_int_padding() {
+        let mut gc_system = GCSystem::new(None);
+        for (value, width, pad, expected) in [
+            (42, 5, "0", "00042"),
+            (-42, 5, "0", "-0042"),
+            (42, 5, " ", "   42"),
+            (123456, 3, "0", "123456"),
+        ] {
+            let values = vec![
+                gc_system.new_object(VMInt::new(value)),
+                gc_system.new_object(VMInt::new(width)),
+                gc_system.new_object(VMString::new(pad)),
+            ];
+            assert_eq!(
+                format_number(format_int, values, &mut gc_system).unwrap(),
+                expected
+            );
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_format_number_rejects_invalid_arguments() {
+        let mut gc_system = GCSystem::new(None);
+        let values = vec![
+            gc_system.new_object(VMFloat::new(1.5)),
+            gc_system.new_object(VMInt::new(-1)),
+        ];
+        match format_number(format_float, values, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("negative precision should be rejected"),
+        }
+        for (width, pad) in [(-3, "0"), (3, "x")] {
+            let values = vec![
+                gc_system.new_object(VMInt::new(1)),
+                gc_system.new_object(VMInt::new(width)),
+                gc_system.new_object(VMString::new(pad)),
+            ];
+            match format_number(format_int, values, &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("format_int should reject width {} pad {:?}", width, pad),
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+}
@@ -1,16 +1,21 @@
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, NaiveDate, NaiveDateTime,
+};
 use std::{
+    fmt::Write,
     sync::Arc,
     time::{Duration, Instant, SystemTime},
 };
 use xlang_vm_core::{
     executor::variable::{
-        VMFloat, VMInt, VMLambda, VMLambdaBody, VMNativeGeneratorFunction, VMNull, VMTuple,
-        VMVariableError,
+        VMFloat, VMInt, VMLambda, VMLambdaBody, VMNativeGeneratorFunction, VMNull, VMString,
+        VMTuple, VMVariableError,
     },
     gc::{GCRef, GCSystem},
 };
 
-use super::check_if_tuple;
+use super::{check_arg_count, check_if_tuple};
 
 #[derive(Debug, Clone)]
 struct SleepGenerator {
@@ -71,6 +76,11 @@ impl VMNativeGeneratorFunction for SleepGenerator {
     fn clone_generator(&self) -> Arc<Box<dyn VMNativeGeneratorFunction>> {
         Arc::new(Box::new(self.clone()))
     }
+
+    // 声明唤醒时间，协程池在此之前会调度其他协程而不是反复轮询
+    fn deadline(&self) -> Option<Instant> {
+        self.start_time.map(|start| start + self.duration)
+    }
 }
 
 // Entry function exposed to the VM
@@ -91,22 +101,32 @@ pub fn sleep_entry(
             ),
         ));
     }
-    let duration_arg = tuple_obj.values[0].clone();
+    let mut duration_arg = tuple_obj.values[0].clone();
 
     // Create the generator state
     let mut generator = SleepGenerator::new();
     // Initialize the generator with the duration argument
-    if duration_arg.isinstance::<VMInt>() {
-        let duration = duration_arg.as_const_type::<VMInt>().to_int()?;
-        generator.duration = Duration::from_secs(duration as u64);
+    let seconds = if duration_arg.isinstance::<VMInt>() {
+        duration_arg.as_const_type::<VMInt>().to_int()? as f64
     } else if duration_arg.isinstance::<VMFloat>() {
-        let duration = duration_arg.as_const_type::<VMFloat>().to_float()?;
-        generator.duration = Duration::from_secs_f64(duration);
+        duration_arg.as_const_type::<VMFloat>().to_float()?
     } else {
         return Err(VMVariableError::TypeError(
-            duration_arg.clone(),
+            duration_arg.clone_ref(),
             "sleep argument must be an int or float".to_string(),
         ));
+    };
+    match Duration::try_from_secs_f64(seconds) {
+        Ok(duration) => generator.duration = duration,
+        Err(_) => {
+            return Err(VMVariableError::ValueError(
+                duration_arg.clone_ref(),
+                format!(
+                    "sleep duration must be a non-negative finite number, got {}",
+                    seconds
+                ),
+            ))
+        }
     }
 
     // Create an empty tuple for default args
@@ -148,6 +168,113 @@ pub fn timestamp(
     return Ok(result);
 }
 
+fn get_string_arg(
+    tuple: &mut GCRef,
+    index: usize,
+    func_name: &str,
+    arg_name: &str,
+) -> Result<String, VMVariableError> {
+    let arg_obj = &mut tuple.as_type::<VMTuple>().values[index];
+    if !arg_obj.isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            arg_obj.clone_ref(),
+            format!("Argument '{}' for {} must be a string", arg_name, func_name),
+        ));
+    }
+    Ok(arg_obj.as_const_type::<VMString>().value.clone())
+}
+
+// 解析 chrono 风格的格式串，非法格式返回 ValueError
+fn parse_format_arg<'a>(
+    tuple: &mut GCRef,
+    index: usize,
+    fmt: &'a str,
+    func_name: &str,
+) -> Result<Vec<Item<'a>>, VMVariableError> {
+    StrftimeItems::new(fmt).parse().map_err(|_| {
+        VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[index].clone_ref(),
+            format!("Invalid format string for {}: {:?}", func_name, fmt),
+        )
+    })
+}
+
+// time.format(epoch_seconds, fmt)，按 UTC 时间格式化，epoch_seconds 可以是整数或浮点数
+pub fn format(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "format")?;
+    let epoch_arg = &mut tuple.as_type::<VMTuple>().values[0];
+    let datetime = if epoch_arg.isinstance::<VMInt>() {
+        DateTime::from_timestamp(epoch_arg.as_const_type::<VMInt>().value, 0)
+    } else if epoch_arg.isinstance::<VMFloat>() {
+        let seconds = epoch_arg.as_const_type::<VMFloat>().value;
+        let whole = seconds.floor();
+        let nanos = ((seconds - whole) * 1_000_000_000.0) as u32;
+        if whole.is_finite() && whole >= i64::MIN as f64 && whole <= i64::MAX as f64 {
+            DateTime::from_timestamp(whole as i64, nanos.min(999_999_999))
+        } else {
+            None
+        }
+    } else {
+        return Err(VMVariableError::TypeError(
+            epoch_arg.clone_ref(),
+            "Argument 'epoch_seconds' for format must be an integer or float".to_string(),
+        ));
+    };
+    let Some(datetime) = datetime else {
+        return Err(VMVariableError::ValueError(
+            epoch_arg.clone_ref(),
+            "Timestamp for format is out of range".to_string(),
+        ));
+    };
+    let fmt = get_string_arg(tuple, 1, "format", "fmt")?;
+    let items = parse_format_arg(tuple, 1, &fmt, "format")?;
+    let mut formatted = String::new();
+    if write!(formatted, "{}", datetime.format_with_items(items.iter())).is_err() {
+        return Err(VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[1].clone_ref(),
+            format!("Format string {:?} cannot be applied to a timestamp", fmt),
+        ));
+    }
+    Ok(gc_system.new_object(VMString::new(&formatted)))
+}
+
+// time.parse(str, fmt)，返回 epoch 秒数；格式中没有时区时按 UTC 处理，只有日期时取当天零点
+pub fn parse(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    check_arg_count(tuple, 2, "parse")?;
+    let input = get_string_arg(tuple, 0, "parse", "str")?;
+    let fmt = get_string_arg(tuple, 1, "parse", "fmt")?;
+    parse_format_arg(tuple, 1, &fmt, "parse")?;
+    let epoch = DateTime::parse_from_str(&input, &fmt)
+        .map(|datetime| datetime.timestamp())
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(&input, &fmt)
+                .map(|datetime| datetime.and_utc().timestamp())
+        })
+        .or_else(|_| {
+            NaiveDate::parse_from_str(&input, &fmt)
+                .map(|date| date.and_time(Default::default()).and_utc().timestamp())
+        });
+    match epoch {
+        Ok(epoch) => Ok(gc_system.new_object(VMInt::new(epoch))),
+        Err(err) => Err(VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[0].clone_ref(),
+            format!("Cannot parse {:?} with format {:?}: {}", input, fmt, err),
+        )),
+    }
+}
+
 // Helper to provide the entry function for registration
 pub fn get_time_function() -> Vec<(
     &'static str,
@@ -158,5 +285,136 @@ pub fn get_time_function() -> Vec<(
         &mut GCSystem,
     ) -> Result<GCRef, VMVariableError>,
 )> {
-    vec![("sleep", sleep_entry), ("timestamp", timestamp)]
+    vec![
+        ("sleep", sleep_entry),
+        ("timestamp", timestamp),
+        ("format", format),
+        ("parse", parse),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+
+    fn call_sleep(duration: &mut GCRef, gc_system: &mut GCSystem) -> Result<(), VMVariableError> {
+        let mut tuple = gc_system.new_object(VMTuple::new(&mut vec![duration]));
+        let result = sleep_entry(None, None, &mut tuple, gc_system).map(|mut lambda| {
+            lambda.drop_ref();
+        });
+        tuple.drop_ref();
+        duration.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_sleep_validates_duration() {
+        let mut gc_system = GCSystem::new(None);
+        let mut zero = gc_system.new_object(VMInt::new(0));
+        call_sleep(&mut zero, &mut gc_system).unwrap();
+        let mut half = gc_system.new_object(VMFloat::new(0.5));
+        call_sleep(&mut half, &mut gc_system).unwrap();
+        for value in [-1.0, -0.5, f64::NAN, f64::INFINITY] {
+            let mut duration = gc_system.new_object(VMFloat::new(value));
+            match call_sleep(&mut duration, &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("sleep({}) should be rejected", value),
+            }
+        }
+        let mut negative = gc_system.new_object(VMInt::new(-1));
+        match call_sleep(&mut negative, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("sleep(-1) should be rejected"),
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    // 以 (value, fmt) 调用 format / parse，消耗 value 的引用
+    fn call_with_format(
+        native_fn: NativeFunction,
+        value: &mut GCRef,
+        fmt: &str,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut fmt = gc_system.new_object(VMString::new(fmt));
+        let result = call_native(native_fn, &mut vec![value, &mut fmt], gc_system);
+        fmt.drop_ref();
+        value.drop_ref();
+        result
+    }
+
+    fn format_epoch(epoch: &mut GCRef, fmt: &str, gc_system: &mut GCSystem) -> String {
+        let mut result = call_with_format(format, epoch, fmt, gc_system).unwrap();
+        let formatted = result.as_const_type::<VMString>().value.clone();
+        result.drop_ref();
+        formatted
+    }
+
+    fn parse_epoch(input: &str, fmt: &str, gc_system: &mut GCSystem) -> i64 {
+        let mut input = gc_system.new_object(VMString::new(input));
+        let mut result = call_with_format(parse, &mut input, fmt, gc_system).unwrap();
+        let epoch = result.as_const_type::<VMInt>().value;
+        result.drop_ref();
+        epoch
+    }
+
+    #[test]
+    fn test_format_and_parse_iso8601() {
+        let mut gc_system = GCSystem::new(None);
+        let epoch = 1_700_000_000;
+        let mut epoch_obj = gc_system.new_object(VMInt::new(epoch));
+        let iso = format_epoch(&mut epoch_obj, "%+", &mut gc_system);
+        assert_eq!(iso, "2023-11-14T22:13:20+00:00");
+        assert_eq!(parse_epoch(&iso, "%+", &mut gc_system), epoch);
+
+        let mut epoch_obj = gc_system.new_object(VMFloat::new(epoch as f64 + 0.5));
+        let formatted = format_epoch(&mut epoch_obj, "%Y-%m-%d %H:%M:%S%.3f", &mut gc_system);
+        assert_eq!(formatted, "2023-11-14 22:13:20.500");
+        // 没有时区信息时按 UTC 解析，只有日期时取零点
+        assert_eq!(
+            parse_epoch("2023-11-14 22:13:20", "%Y-%m-%d %H:%M:%S", &mut gc_system),
+            epoch
+        );
+        assert_eq!(
+            parse_epoch("2023-11-14", "%Y-%m-%d", &mut gc_system),
+            epoch - (22 * 3600 + 13 * 60 + 20)
+        );
+        assert_eq!(
+            parse_epoch(
+                "2023-11-15T06:13:20+08:00",
+                "%Y-%m-%dT%H:%M:%S%:z",
+                &mut gc_system
+            ),
+            epoch
+        );
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_format_and_parse_errors() {
+        let mut gc_system = GCSystem::new(None);
+        let mut epoch = gc_system.new_object(VMInt::new(0));
+        match call_with_format(format, &mut epoch, "%Y-%Q", &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("invalid format string should be rejected"),
+        }
+        let mut epoch = gc_system.new_object(VMFloat::new(f64::NAN));
+        match call_with_format(format, &mut epoch, "%Y", &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("NaN timestamp should be rejected"),
+        }
+        for (input, fmt) in [("not a date", "%Y-%m-%d"), ("2023-11-14", "%Y-%m-%d %Q")] {
+            let mut input = gc_system.new_object(VMString::new(input));
+            match call_with_format(parse, &mut input, fmt, &mut gc_system) {
+                Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+                _ => panic!("parse({:?}) should be rejected", fmt),
+            }
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
 }
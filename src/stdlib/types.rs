@@ -1,12 +1,29 @@
+use std::cmp::Ordering;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
 use xlang_vm_core::{
     executor::variable::{
+        try_eq_as_vmobject,
+        try_greater_than_as_vmobject,
+        try_hash_as_vmobject,
+        try_length_of_as_vmobject,
+        try_less_than_as_vmobject,
+        try_to_bool_as_vmobject,
         try_to_string_vmobject,
+        type_name_of_vmobject,
         // Import necessary VM types
         VMBoolean,
         VMBytes,
+        VMDict,
         VMFloat,
         VMInt,
+        VMKeyVal,
+        VMLambda,
+        VMNamed,
         VMNull,
+        VMRange,
+        VMSet,
         VMString,
         VMTuple,
         VMVariableError,
@@ -14,7 +31,7 @@ use xlang_vm_core::{
     gc::{GCRef, GCSystem},
 };
 // Assuming check_if_tuple will be available via super
-use super::check_if_tuple;
+use super::{call_lambda, check_if_tuple};
 
 pub fn len(
     _self_object: Option<&mut GCRef>,
@@ -33,25 +50,9 @@ pub fn len(
             ),
         ));
     }
-    let target_obj = &mut tuple_obj.values[0];
-    if target_obj.isinstance::<VMTuple>() {
-        let inner_tuple = target_obj.as_const_type::<VMTuple>();
-        let obj = gc_system.new_object(VMInt::new(inner_tuple.values.len() as i64));
-        Ok(obj)
-    } else if target_obj.isinstance::<VMString>() {
-        let inner_string = target_obj.as_const_type::<VMString>();
-        let obj = gc_system.new_object(VMInt::new(inner_string.value.len() as i64));
-        return Ok(obj);
-    } else if target_obj.isinstance::<VMBytes>() {
-        let inner_bytes = target_obj.as_const_type::<VMBytes>();
-        let obj = gc_system.new_object(VMInt::new(inner_bytes.value.len() as i64));
-        return Ok(obj);
-    } else {
-        return Err(VMVariableError::TypeError(
-            target_obj.clone_ref(), // Error points to the specific object
-            "Argument for len must be a string, bytes, or tuple".to_string(),
-        ));
-    }
+    // 与 lengthof 一致：字符串按字符计数
+    let length = try_length_of_as_vmobject(&mut tuple_obj.values[0])?;
+    Ok(gc_system.new_object(VMInt::new(length as i64)))
 }
 
 pub fn to_int(
@@ -97,6 +98,77 @@ pub fn to_int(
     ))
 }
 
+// 按进制解析整数，radix 为 0 时根据 0x/0b/0o 前缀自动判断（无前缀视为十进制）
+fn parse_int_with_radix(text: &str, radix: i64) -> Result<i64, String> {
+    if radix != 0 && !(2..=36).contains(&radix) {
+        return Err(format!(
+            "radix must be 0 or between 2 and 36, got {}",
+            radix
+        ));
+    }
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (radix, digits) = if radix == 0 {
+        let lower = unsigned.get(..2).map(|prefix| prefix.to_ascii_lowercase());
+        match lower.as_deref() {
+            Some("0x") => (16, &unsigned[2..]),
+            Some("0b") => (2, &unsigned[2..]),
+            Some("0o") => (8, &unsigned[2..]),
+            _ => (10, unsigned),
+        }
+    } else {
+        (radix as u32, unsigned)
+    };
+    if digits.starts_with(['+', '-']) {
+        return Err(format!("invalid digit in {:?} for radix {}", text, radix));
+    }
+    i64::from_str_radix(&format!("{}{}", sign, digits), radix)
+        .map_err(|err| format!("cannot parse {:?} with radix {}: {}", text, radix, err))
+}
+
+// types.parse_int(string, radix)
+pub fn parse_int(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "parse_int expected 2 arguments, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    if !tuple_obj.values[0].isinstance::<VMString>() {
+        return Err(VMVariableError::TypeError(
+            tuple_obj.values[0].clone_ref(),
+            "First argument for parse_int must be a string".to_string(),
+        ));
+    }
+    if !tuple_obj.values[1].isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            tuple_obj.values[1].clone_ref(),
+            "Radix for parse_int must be an integer".to_string(),
+        ));
+    }
+    let text = &tuple_obj.values[0].as_const_type::<VMString>().value;
+    let radix = tuple_obj.values[1].as_const_type::<VMInt>().value;
+    match parse_int_with_radix(text, radix) {
+        Ok(value) => Ok(gc_system.new_object(VMInt::new(value))),
+        Err(message) => Err(VMVariableError::ValueError(
+            tuple_obj.values[0].clone_ref(),
+            message,
+        )),
+    }
+}
+
 pub fn to_float(
     _self_object: Option<&mut GCRef>,
     _capture: Option<&mut GCRef>,
@@ -184,30 +256,8 @@ pub fn to_bool(
             ),
         ));
     }
-    let target_obj = &mut tuple_obj.values[0];
-    if target_obj.isinstance::<VMInt>() {
-        let data = target_obj.as_const_type::<VMInt>().to_bool()?;
-        return Ok(gc_system.new_object(VMBoolean::new(data)));
-    }
-    if target_obj.isinstance::<VMFloat>() {
-        let data = target_obj.as_const_type::<VMFloat>().to_bool()?;
-        return Ok(gc_system.new_object(VMBoolean::new(data)));
-    }
-    if target_obj.isinstance::<VMString>() {
-        let data = target_obj.as_const_type::<VMString>().to_bool()?;
-        return Ok(gc_system.new_object(VMBoolean::new(data)));
-    }
-    if target_obj.isinstance::<VMNull>() {
-        return Ok(gc_system.new_object(VMBoolean::new(false)));
-    }
-    if target_obj.isinstance::<VMBoolean>() {
-        let data = target_obj.as_const_type::<VMBoolean>().to_bool()?;
-        return Ok(gc_system.new_object(VMBoolean::new(data)));
-    }
-    Err(VMVariableError::TypeError(
-        target_obj.clone_ref(), // Error points to the specific object
-        "Argument for bool must be convertible to a boolean".to_string(),
-    ))
+    let data = try_to_bool_as_vmobject(&mut tuple_obj.values[0])?;
+    Ok(gc_system.new_object(VMBoolean::new(data)))
 }
 pub fn to_bytes(
     _self_object: Option<&mut GCRef>,
@@ -234,25 +284,40 @@ pub fn to_bytes(
         let string_value = target_obj.as_const_type::<VMString>().value.clone();
         return Ok(gc_system.new_object(VMBytes::new(&string_value.as_bytes().to_vec())));
     } else if target_obj.isinstance::<VMInt>() {
-        // 支持单字节的整数转字节
-        let int_value = target_obj.as_const_type::<VMInt>().value;
-        if !(0..=255).contains(&int_value) {
+        // 整数表示长度，生成全零的字节序列
+        let count = target_obj.as_const_type::<VMInt>().value;
+        if count < 0 {
+            return Err(VMVariableError::ValueError(
+                target_obj.clone_ref(),
+                "Byte count for bytes must not be negative".to_string(),
+            ));
+        }
+        // 长度来自脚本，分配失败时报告错误而不是终止进程
+        let mut zeros = Vec::new();
+        if zeros.try_reserve_exact(count as usize).is_err() {
             return Err(VMVariableError::ValueError(
                 target_obj.clone_ref(),
-                "Integer values for bytes conversion must be between 0 and 255".to_string(),
+                format!("Cannot allocate {} bytes", count),
             ));
         }
-        return Ok(gc_system.new_object(VMBytes::new(&vec![int_value as u8])));
+        zeros.resize(count as usize, 0u8);
+        // 直接移入缓冲区，避免 VMBytes::new 再复制一份
+        let mut bytes = gc_system.new_object(VMBytes::new(&Vec::new()));
+        bytes.as_type::<VMBytes>().value = zeros;
+        return Ok(bytes);
     } else if target_obj.isinstance::<VMTuple>() {
         // 支持整数元组转字节序列
         let inner_tuple = target_obj.as_type::<VMTuple>();
         let mut byte_vec = Vec::with_capacity(inner_tuple.values.len());
 
-        for value in &mut inner_tuple.values {
+        for (index, value) in inner_tuple.values.iter_mut().enumerate() {
             if !value.isinstance::<VMInt>() {
                 return Err(VMVariableError::ValueError(
                     value.clone_ref(),
-                    "All elements in tuple must be integers for bytes conversion".to_string(),
+                    format!(
+                        "Element {} of tuple must be an integer for bytes conversion",
+                        index
+                    ),
                 ));
             }
 
@@ -260,7 +325,10 @@ pub fn to_bytes(
             if !(0..=255).contains(&int_value) {
                 return Err(VMVariableError::ValueError(
                     value.clone_ref(),
-                    "Integer values for bytes conversion must be between 0 and 255".to_string(),
+                    format!(
+                        "Element {} of tuple is {}, but bytes values must be between 0 and 255",
+                        index, int_value
+                    ),
                 ));
             }
 
@@ -272,27 +340,1767 @@ pub fn to_bytes(
 
     Err(VMVariableError::TypeError(
         target_obj.clone_ref(), // Error points to the specific object
-        "Argument for bytes must be bytes, string, integer (0-255), or tuple of integers (0-255)"
+        "Argument for bytes must be bytes, string, integer count, or tuple of integers (0-255)"
             .to_string(),
     ))
 }
 
-// Helper to provide functions for registration
-pub fn get_type_conversion_functions() -> Vec<(
-    &'static str,
-    fn(
-        Option<&mut GCRef>,
-        Option<&mut GCRef>,
-        &mut GCRef,
-        &mut GCSystem,
-    ) -> Result<GCRef, VMVariableError>,
-)> {
-    vec![
-        ("len", len),
-        ("int", to_int),
-        ("float", to_float),
-        ("string", to_string),
-        ("bool", to_bool),
-        ("bytes", to_bytes),
-    ]
+pub fn to_tuple(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "to_tuple expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let target_obj = &mut tuple_obj.values[0];
+    if target_obj.isinstance::<VMSet>() {
+        return target_obj.as_type::<VMSet>().materialize(gc_system);
+    }
+    if target_obj.isinstance::<VMTuple>()
+        || target_obj.isinstance::<VMString>()
+        || target_obj.isinstance::<VMBytes>()
+        || target_obj.isinstance::<VMRange>()
+    {
+        // 其余可迭代对象视为带有恒等过滤器的集合
+        let mut filter = gc_system.new_object(VMNull::new());
+        let mut set = gc_system.new_object(VMSet::new(target_obj, &mut filter));
+        let result = set.as_type::<VMSet>().materialize(gc_system);
+        set.drop_ref();
+        filter.drop_ref();
+        return result;
+    }
+    Err(VMVariableError::TypeError(
+        target_obj.clone_ref(), // Error points to the specific object
+        "Argument for to_tuple must be a set, tuple, string, bytes, or range".to_string(),
+    ))
+}
+
+// slice 的可选整数参数，null 表示使用默认值
+fn get_slice_bound(
+    tuple: &mut GCRef,
+    index: usize,
+    arg_name: &str,
+) -> Result<Option<i64>, VMVariableError> {
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if index >= tuple_obj.values.len() {
+        return Ok(None);
+    }
+    let arg_obj = &mut tuple_obj.values[index];
+    if arg_obj.isinstance::<VMNull>() {
+        return Ok(None);
+    }
+    if !arg_obj.isinstance::<VMInt>() {
+        return Err(VMVariableError::TypeError(
+            arg_obj.clone_ref(),
+            format!(
+                "Argument '{}' for slice must be an integer or null",
+                arg_name
+            ),
+        ));
+    }
+    Ok(Some(arg_obj.as_const_type::<VMInt>().value))
+}
+
+// 将负数下标换算为从末尾计数，并将越界的下标截断到合法范围（与 Python 一致）
+fn adjust_slice_bound(bound: i64, len: i64, step: i64) -> i64 {
+    if bound < 0 {
+        let bound = bound + len;
+        if bound < 0 {
+            if step < 0 {
+                -1
+            } else {
+                0
+            }
+        } else {
+            bound
+        }
+    } else if bound >= len {
+        if step < 0 {
+            len - 1
+        } else {
+            len
+        }
+    } else {
+        bound
+    }
+}
+
+// types.slice(tuple, start, end, [step])，start/end 可为 null，step 为负数时反向选取
+pub fn slice(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if !(3..=4).contains(&arg_count) {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("slice expected 3 or 4 arguments, got {}", arg_count),
+        ));
+    }
+    let target_obj = &mut tuple.as_type::<VMTuple>().values[0];
+    if !target_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            target_obj.clone_ref(),
+            "First argument for slice must be a tuple".to_string(),
+        ));
+    }
+    let start = get_slice_bound(tuple, 1, "start")?;
+    let end = get_slice_bound(tuple, 2, "end")?;
+    let step = get_slice_bound(tuple, 3, "step")?.unwrap_or(1);
+    if step == 0 {
+        return Err(VMVariableError::ValueError(
+            tuple.as_type::<VMTuple>().values[3].clone_ref(),
+            "slice step cannot be zero".to_string(),
+        ));
+    }
+
+    let values = &tuple.as_const_type::<VMTuple>().values[0]
+        .as_const_type::<VMTuple>()
+        .values;
+    let len = values.len() as i64;
+    let (default_start, default_end) = if step > 0 { (0, len) } else { (len - 1, -1) };
+    let start = start.map_or(default_start, |start| adjust_slice_bound(start, len, step));
+    let end = end.map_or(default_end, |end| adjust_slice_bound(end, len, step));
+
+    let mut selected = Vec::new();
+    let mut index = start;
+    while (step > 0 && index < end) || (step < 0 && index > end) {
+        selected.push(values[index as usize].clone());
+        index += step;
+    }
+    Ok(gc_system.new_object(VMTuple::new(&mut selected.iter_mut().collect())))
+}
+
+// type_name 使用的类型名，REPL 的 :type 命令也复用这一逻辑
+// 与 typeof 共用同一映射，typeof 得到空字符串的类型在这里记为 unknown
+pub fn type_name_of(value: &GCRef) -> &'static str {
+    type_name_of_vmobject(value).unwrap_or("unknown")
+}
+
+// types.type_name(value)，返回便于日志输出的类型名，与 typeof 的结果一致（无法识别的类型记为 unknown）
+pub fn type_name(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "type_name expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let name = type_name_of(&tuple_obj.values[0]);
+    Ok(gc_system.new_object(VMString::new(name)))
+}
+
+// 稳定的归并排序，比较函数可能失败（类型不可比较或比较器出错）
+fn merge_sort<F>(values: Vec<GCRef>, compare: &mut F) -> Result<Vec<GCRef>, VMVariableError>
+where
+    F: FnMut(&mut GCRef, &mut GCRef) -> Result<Ordering, VMVariableError>,
+{
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+    let mut left = values;
+    let right = left.split_off(left.len() / 2);
+    let left = merge_sort(left, compare)?;
+    let right = merge_sort(right, compare)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(l), Some(r)) = (left.peek_mut(), right.peek_mut()) {
+        if compare(r, l)? == Ordering::Less {
+            merged.push(right.next().unwrap());
+        } else {
+            merged.push(left.next().unwrap());
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+fn default_compare(a: &mut GCRef, b: &mut GCRef) -> Result<Ordering, VMVariableError> {
+    // 字符串按字典序排序，其余交给数值比较
+    if a.isinstance::<VMString>() && b.isinstance::<VMString>() {
+        return Ok(a
+            .as_const_type::<VMString>()
+            .value
+            .cmp(&b.as_const_type::<VMString>().value));
+    }
+    let compared = try_less_than_as_vmobject(a, b).and_then(|less| {
+        if less {
+            Ok(Ordering::Less)
+        } else if try_greater_than_as_vmobject(a, b)? {
+            Ok(Ordering::Greater)
+        } else {
+            Ok(Ordering::Equal)
+        }
+    });
+    compared.map_err(|mut err| {
+        err.consume_ref();
+        VMVariableError::TypeError(
+            b.clone_ref(),
+            "sort requires mutually comparable elements (numbers or strings)".to_string(),
+        )
+    })
+}
+
+fn get_tuple_target(
+    tuple: &mut GCRef,
+    func_name: &str,
+    expected_args: usize,
+) -> Result<Vec<GCRef>, VMVariableError> {
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != expected_args {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "{} expected {} argument(s), got {}",
+                func_name,
+                expected_args,
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let target_obj = &mut tuple_obj.values[0];
+    if !target_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            target_obj.clone_ref(),
+            format!("Argument for {} must be a tuple", func_name),
+        ));
+    }
+    Ok(target_obj.as_const_type::<VMTuple>().values.clone())
+}
+
+// types.flatten(tuple)，展开一层嵌套的元组
+pub fn flatten(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let values = get_tuple_target(tuple, "flatten", 1)?;
+    let mut flattened = Vec::with_capacity(values.len());
+    for value in values {
+        if value.isinstance::<VMTuple>() {
+            flattened.extend(value.as_const_type::<VMTuple>().values.iter().cloned());
+        } else {
+            flattened.push(value);
+        }
+    }
+    Ok(gc_system.new_object(VMTuple::new(&mut flattened.iter_mut().collect())))
+}
+
+// 递归展开嵌套元组，path 记录当前递归路径上的元组，用于发现循环引用
+fn flatten_deep_into(
+    tuple: &mut GCRef,
+    path: &mut Vec<GCRef>,
+    flattened: &mut Vec<GCRef>,
+) -> Result<(), VMVariableError> {
+    if path
+        .iter()
+        .any(|prev| std::ptr::eq(prev.get_const_reference(), tuple.get_const_reference()))
+    {
+        return Err(VMVariableError::ValueError(
+            tuple.clone_ref(),
+            "Cannot flatten a self-referential tuple".to_string(),
+        ));
+    }
+    path.push(tuple.clone());
+    for value in tuple.as_type::<VMTuple>().values.iter_mut() {
+        if value.isinstance::<VMTuple>() {
+            flatten_deep_into(value, path, flattened)?;
+        } else {
+            flattened.push(value.clone());
+        }
+    }
+    path.pop();
+    Ok(())
+}
+
+// types.flatten_deep(tuple)，递归展开所有嵌套的元组，VMKeyVal / VMNamed 保持原样
+pub fn flatten_deep(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    get_tuple_target(tuple, "flatten_deep", 1)?;
+    let mut target = tuple.as_const_type::<VMTuple>().values[0].clone();
+    let mut flattened = Vec::new();
+    flatten_deep_into(&mut target, &mut Vec::new(), &mut flattened)?;
+    Ok(gc_system.new_object(VMTuple::new(&mut flattened.iter_mut().collect())))
+}
+
+// types.sort(tuple)，返回新的元组，不修改原元组
+pub fn sort(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let values = get_tuple_target(tuple, "sort", 1)?;
+    let mut sorted = merge_sort(values, &mut default_compare)?;
+    Ok(gc_system.new_object(VMTuple::new(&mut sorted.iter_mut().collect())))
+}
+
+// types.sort_by(tuple, comparator)，comparator(a, b) 返回负数、零或正数
+pub fn sort_by(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let values = get_tuple_target(tuple, "sort_by", 2)?;
+    let mut comparator = tuple.as_const_type::<VMTuple>().values[1].clone();
+    if !comparator.isinstance::<VMLambda>() {
+        return Err(VMVariableError::TypeError(
+            comparator.clone_ref(),
+            "Comparator for sort_by must be a lambda".to_string(),
+        ));
+    }
+    let mut compare = |a: &mut GCRef, b: &mut GCRef| -> Result<Ordering, VMVariableError> {
+        let mut result = call_lambda(&mut comparator, &mut vec![a, b], gc_system)?;
+        if !result.isinstance::<VMInt>() {
+            return Err(VMVariableError::TypeError(
+                result,
+                "Comparator for sort_by must return an integer".to_string(),
+            ));
+        }
+        let ordering = result.as_const_type::<VMInt>().value.cmp(&0);
+        result.drop_ref();
+        Ok(ordering)
+    };
+    let mut sorted = merge_sort(values, &mut compare)?;
+    Ok(gc_system.new_object(VMTuple::new(&mut sorted.iter_mut().collect())))
+}
+
+// types.hash(value)，相等的值得到相同的哈希值，不可哈希的类型返回 TypeError
+pub fn hash(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "hash expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let value = try_hash_as_vmobject(&mut tuple_obj.values[0])?;
+    Ok(gc_system.new_object(VMInt::new(value as i64)))
+}
+
+fn get_two_args<'a>(
+    tuple: &'a mut GCRef,
+    func_name: &str,
+) -> Result<(&'a GCRef, &'a GCRef), VMVariableError> {
+    let arg_count = tuple.as_const_type::<VMTuple>().values.len();
+    if arg_count != 2 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!("{} expected 2 arguments, got {}", func_name, arg_count),
+        ));
+    }
+    let values = &tuple.as_const_type::<VMTuple>().values;
+    Ok((&values[0], &values[1]))
+}
+
+// types.equals(a, b)，与 == 相同的结构相等比较（元组、字典等逐项比较）
+pub fn equals(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let (a, b) = get_two_args(tuple, "equals")?;
+    let result = try_eq_as_vmobject(a, b);
+    Ok(gc_system.new_object(VMBoolean::new(result)))
+}
+
+// types.is(a, b)，判断 a 与 b 是否为同一个 GC 对象，而非仅仅值相等
+pub fn is(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let (a, b) = get_two_args(tuple, "is")?;
+    let result = std::ptr::eq(a.get_const_reference(), b.get_const_reference());
+    Ok(gc_system.new_object(VMBoolean::new(result)))
+}
+
+// types.dict((k1 : v1, k2 : v2, ...))，重复的键以最后一次出现的值为准
+pub fn dict(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "dict expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let target_obj = &mut tuple_obj.values[0];
+    if !target_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            target_obj.clone_ref(),
+            "Argument for dict must be a tuple of key-value pairs".to_string(),
+        ));
+    }
+    let mut result = gc_system.new_object(VMDict::new());
+    for item in target_obj.as_type::<VMTuple>().values.iter_mut() {
+        if !item.isinstance::<VMKeyVal>() {
+            result.drop_ref();
+            return Err(VMVariableError::TypeError(
+                item.clone_ref(),
+                "Elements for dict must be key-value pairs".to_string(),
+            ));
+        }
+        let kv = item.as_type::<VMKeyVal>();
+        let mut key = kv.get_key().clone();
+        if let Err(err) = result.as_type::<VMDict>().insert(&mut key, kv.get_value()) {
+            result.drop_ref();
+            return Err(err);
+        }
+    }
+    Ok(result)
+}
+
+// 若 item 为 VMKeyVal 或 VMNamed，返回其键与值（不增加引用计数）
+fn get_pair_parts(item: &GCRef) -> Option<(GCRef, GCRef)> {
+    if item.isinstance::<VMKeyVal>() {
+        let kv = item.as_const_type::<VMKeyVal>();
+        Some((kv.get_const_key().clone(), kv.get_const_value().clone()))
+    } else if item.isinstance::<VMNamed>() {
+        let named = item.as_const_type::<VMNamed>();
+        Some((
+            named.get_const_key().clone(),
+            named.get_const_value().clone(),
+        ))
+    } else {
+        None
+    }
+}
+
+// types.keys(tuple)，按顺序返回所有键值对的键，普通元素被跳过
+pub fn keys(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let values = get_tuple_target(tuple, "keys", 1)?;
+    let mut keys: Vec<GCRef> = values
+        .iter()
+        .filter_map(get_pair_parts)
+        .map(|(key, _)| key)
+        .collect();
+    Ok(gc_system.new_object(VMTuple::new(&mut keys.iter_mut().collect())))
+}
+
+// types.values(tuple)，按顺序返回所有键值对的值，普通元素被跳过
+pub fn values(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let values = get_tuple_target(tuple, "values", 1)?;
+    let mut pair_values: Vec<GCRef> = values
+        .iter()
+        .filter_map(get_pair_parts)
+        .map(|(_, value)| value)
+        .collect();
+    Ok(gc_system.new_object(VMTuple::new(&mut pair_values.iter_mut().collect())))
+}
+
+// types.has_key(tuple, key)，判断是否存在键与 key 相等的键值对
+pub fn has_key(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let values = get_tuple_target(tuple, "has_key", 2)?;
+    let key = &tuple.as_const_type::<VMTuple>().values[1];
+    let found = values
+        .iter()
+        .filter_map(get_pair_parts)
+        .any(|(pair_key, _)| try_eq_as_vmobject(&pair_key, key));
+    Ok(gc_system.new_object(VMBoolean::new(found)))
+}
+
+// types.merge(base, overrides)，返回合并后的新元组，base 与 overrides 本身不会被修改
+// 与 VMTuple::assign_members 相同，按键匹配：overrides 中的键值对替换 base 中键相等的项，
+// 不存在的键追加到末尾；base 中的普通元素原样保留
+pub fn merge(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let mut merged = get_tuple_target(tuple, "merge", 2)?;
+    let overrides_obj = &mut tuple.as_type::<VMTuple>().values[1];
+    if !overrides_obj.isinstance::<VMTuple>() {
+        return Err(VMVariableError::TypeError(
+            overrides_obj.clone_ref(),
+            "Argument 'overrides' for merge must be a tuple".to_string(),
+        ));
+    }
+    for item in overrides_obj.as_type::<VMTuple>().values.iter_mut() {
+        let Some((key, _)) = get_pair_parts(item) else {
+            return Err(VMVariableError::TypeError(
+                item.clone_ref(),
+                "Elements of 'overrides' for merge must be key-value pairs".to_string(),
+            ));
+        };
+        let existing = merged.iter().position(|value| {
+            get_pair_parts(value).is_some_and(|(base_key, _)| try_eq_as_vmobject(&base_key, &key))
+        });
+        match existing {
+            Some(index) => merged[index] = item.clone(),
+            None => merged.push(item.clone()),
+        }
+    }
+    Ok(gc_system.new_object(VMTuple::new(&mut merged.iter_mut().collect())))
+}
+
+// types.reverse(tuple)，返回元素顺序反转后的新元组
+pub fn reverse(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let mut values = get_tuple_target(tuple, "reverse", 1)?;
+    values.reverse();
+    Ok(gc_system.new_object(VMTuple::new(&mut values.iter_mut().collect())))
+}
+
+// types.unique(tuple)，返回去重后的新元组，重复元素只保留第一次出现的位置
+// 可哈希的元素先按结构哈希分桶，只与同桶元素比较；不可哈希的元素（如 named、lambda）
+// 逐个与之前保留的不可哈希元素比较，最坏情况为 O(n^2)
+pub fn unique(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let values = get_tuple_target(tuple, "unique", 1)?;
+    let mut kept: Vec<GCRef> = Vec::new();
+    let mut buckets: FxHashMap<u64, Vec<usize>> = FxHashMap::default();
+    let mut unhashable: Vec<usize> = Vec::new();
+    for mut value in values {
+        let candidates = match try_hash_as_vmobject(&mut value) {
+            Ok(hash) => buckets.entry(hash).or_default(),
+            Err(mut err) => {
+                err.consume_ref();
+                &mut unhashable
+            }
+        };
+        if candidates
+            .iter()
+            .any(|&index| try_eq_as_vmobject(&kept[index], &value))
+        {
+            continue;
+        }
+        candidates.push(kept.len());
+        kept.push(value);
+    }
+    Ok(gc_system.new_object(VMTuple::new(&mut kept.iter_mut().collect())))
+}
+
+// types.freeze(value)，递归冻结 value 及其引用的对象并返回 value，冻结后的对象赋值会返回 AssignError
+// lambda 的捕获属于外部状态，调用时也可能向默认参数写入实参，因此只冻结 lambda 本身而不冻结其参数、捕获与 self
+// 冻结作用于对象本身而不是副本：被其他元组或变量共享的成员会一并冻结，例如
+// `shared := (1, 2); a := (shared,); b := (shared, 3); types.freeze(a);` 之后 b[0] 也无法再赋值，需要隔离时先 deepcopy
+// 赋值、bytes.splice 与 bind 重新绑定 self 都会检查冻结标记；通道的收发不受冻结影响
+// 已冻结的对象可以重复冻结
+pub fn freeze(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    _gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "freeze expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let mut visited: FxHashSet<GCRef> = FxHashSet::default();
+    let mut pending = vec![tuple_obj.values[0].clone()];
+    while let Some(mut obj) = pending.pop() {
+        if !visited.insert(obj.clone()) {
+            continue;
+        }
+        obj.freeze();
+        if obj.isinstance::<VMLambda>() {
+            continue;
+        }
+        pending.extend(obj.get_traceable().references.keys().cloned());
+    }
+    Ok(tuple_obj.values[0].clone_ref())
+}
+
+// types.is_frozen(value)，判断 value 是否已被冻结
+pub fn is_frozen(
+    _self_object: Option<&mut GCRef>,
+    _capture: Option<&mut GCRef>,
+    tuple: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    check_if_tuple(tuple)?;
+    let tuple_obj = tuple.as_type::<VMTuple>();
+    if tuple_obj.values.len() != 1 {
+        return Err(VMVariableError::TypeError(
+            tuple.clone_ref(),
+            format!(
+                "is_frozen expected 1 argument, got {}",
+                tuple.as_const_type::<VMTuple>().values.len()
+            ),
+        ));
+    }
+    let frozen = tuple_obj.values[0].is_frozen();
+    Ok(gc_system.new_object(VMBoolean::new(frozen)))
+}
+
+// Helper to provide functions for registration
+pub fn get_type_conversion_functions() -> Vec<(
+    &'static str,
+    fn(
+        Option<&mut GCRef>,
+        Option<&mut GCRef>,
+        &mut GCRef,
+        &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError>,
+)> {
+    vec![
+        ("len", len),
+        ("int", to_int),
+        ("float", to_float),
+        ("string", to_string),
+        ("bool", to_bool),
+        ("bytes", to_bytes),
+        ("to_tuple", to_tuple),
+        ("slice", slice),
+        ("flatten", flatten),
+        ("flatten_deep", flatten_deep),
+        ("type_name", type_name),
+        ("parse_int", parse_int),
+        ("sort", sort),
+        ("sort_by", sort_by),
+        ("hash", hash),
+        ("dict", dict),
+        ("equals", equals),
+        ("is", is),
+        ("keys", keys),
+        ("values", values),
+        ("has_key", has_key),
+        ("merge", merge),
+        ("reverse", reverse),
+        ("unique", unique),
+        ("freeze", freeze),
+        ("is_frozen", is_frozen),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call_native;
+    use xlang_vm_core::executor::native_module::NativeFunction;
+    use xlang_vm_core::executor::variable::{
+        try_deepcopy_as_vmobject, try_repr_vmobject, VMIterable, VMLambdaBody,
+    };
+
+    fn sort_repr(mut values: Vec<GCRef>, gc_system: &mut GCSystem) -> String {
+        let mut target = gc_system.new_object(VMTuple::new(&mut values.iter_mut().collect()));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target]));
+        let mut sorted = sort(None, None, &mut args, gc_system).unwrap();
+        let repr = try_repr_vmobject(&mut sorted, None).unwrap();
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        target.drop_ref();
+        args.drop_ref();
+        sorted.drop_ref();
+        repr
+    }
+
+    #[test]
+    fn test_sort_ints_floats_and_strings() {
+        let mut gc_system = GCSystem::new(None);
+
+        let ints = [3, -1, 2, 0]
+            .iter()
+            .map(|v| gc_system.new_object(VMInt::new(*v)))
+            .collect();
+        assert_eq!(sort_repr(ints, &mut gc_system), "(-1, 0, 2, 3)");
+
+        let floats = [2.5, -0.5, 1.0]
+            .iter()
+            .map(|v| gc_system.new_object(VMFloat::new(*v)))
+            .collect();
+        assert_eq!(sort_repr(floats, &mut gc_system), "(-0.5, 1, 2.5)");
+
+        let strings = ["pear", "apple", "fig"]
+            .iter()
+            .map(|v| gc_system.new_object(VMString::new(v)))
+            .collect();
+        assert_eq!(
+            sort_repr(strings, &mut gc_system),
+            "(\"apple\", \"fig\", \"pear\")"
+        );
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_sort_rejects_mixed_types() {
+        let mut gc_system = GCSystem::new(None);
+        let mut one = gc_system.new_object(VMInt::new(1));
+        let mut text = gc_system.new_object(VMString::new("a"));
+        let mut target = gc_system.new_object(VMTuple::new(&mut vec![&mut one, &mut text]));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target]));
+        match sort(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("sort should reject mixed element types"),
+        }
+        for obj in [&mut one, &mut text, &mut target, &mut args] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_sort_by_native_comparator() {
+        let mut gc_system = GCSystem::new(None);
+        let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
+        let mut result = gc_system.new_object(VMNull::new());
+        // 降序比较器：b - a
+        let mut comparator = gc_system.new_object(VMLambda::new(
+            0,
+            "<test>::descending".to_string(),
+            &mut params,
+            None,
+            None,
+            &mut VMLambdaBody::VMNativeFunction(|_, _, args, gc_system| {
+                let values = &args.as_const_type::<VMTuple>().values;
+                let a = values[0].as_const_type::<VMInt>().value;
+                let b = values[1].as_const_type::<VMInt>().value;
+                Ok(gc_system.new_object(VMInt::new(b - a)))
+            }),
+            &mut result,
+            false,
+        ));
+        params.drop_ref();
+        result.drop_ref();
+
+        let mut values: Vec<GCRef> = [1, 3, 2]
+            .iter()
+            .map(|v| gc_system.new_object(VMInt::new(*v)))
+            .collect();
+        let mut target = gc_system.new_object(VMTuple::new(&mut values.iter_mut().collect()));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target, &mut comparator]));
+        let mut sorted = sort_by(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(try_repr_vmobject(&mut sorted, None).unwrap(), "(3, 2, 1)");
+
+        for value in values.iter_mut() {
+            value.drop_ref();
+        }
+        for obj in [&mut target, &mut args, &mut sorted, &mut comparator] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn hash_of(value: &mut GCRef, gc_system: &mut GCSystem) -> Result<i64, VMVariableError> {
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![value]));
+        let result = hash(None, None, &mut args, gc_system).map(|mut result| {
+            let value = result.as_const_type::<VMInt>().value;
+            result.drop_ref();
+            value
+        });
+        args.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_equal_values_hash_equally() {
+        let mut gc_system = GCSystem::new(None);
+        let mut one = gc_system.new_object(VMInt::new(1));
+        let mut one_float = gc_system.new_object(VMFloat::new(1.0));
+        let mut two = gc_system.new_object(VMInt::new(2));
+        let mut text = gc_system.new_object(VMString::new("a"));
+        let mut text_copy = gc_system.new_object(VMString::new("a"));
+        let mut zero = gc_system.new_object(VMFloat::new(0.0));
+        let mut negative_zero = gc_system.new_object(VMFloat::new(-0.0));
+        let mut kv = gc_system.new_object(VMKeyVal::new(&mut text, &mut one));
+        let mut kv_copy = gc_system.new_object(VMKeyVal::new(&mut text_copy, &mut one_float));
+        let mut tuple = gc_system.new_object(VMTuple::new(&mut vec![&mut one, &mut kv]));
+        let mut tuple_copy =
+            gc_system.new_object(VMTuple::new(&mut vec![&mut one_float, &mut kv_copy]));
+        let mut swapped = gc_system.new_object(VMTuple::new(&mut vec![&mut kv, &mut one]));
+
+        for (a, b) in [
+            (&mut one, &mut one_float),
+            (&mut text, &mut text_copy),
+            (&mut zero, &mut negative_zero),
+            (&mut kv, &mut kv_copy),
+            (&mut tuple, &mut tuple_copy),
+        ] {
+            assert!(try_eq_as_vmobject(a, b));
+            assert_eq!(
+                hash_of(a, &mut gc_system).unwrap(),
+                hash_of(b, &mut gc_system).unwrap()
+            );
+        }
+        assert_ne!(
+            hash_of(&mut one, &mut gc_system).unwrap(),
+            hash_of(&mut two, &mut gc_system).unwrap()
+        );
+        assert_ne!(
+            hash_of(&mut tuple, &mut gc_system).unwrap(),
+            hash_of(&mut swapped, &mut gc_system).unwrap()
+        );
+
+        for obj in [
+            &mut one,
+            &mut one_float,
+            &mut two,
+            &mut text,
+            &mut text_copy,
+            &mut zero,
+            &mut negative_zero,
+            &mut kv,
+            &mut kv_copy,
+            &mut tuple,
+            &mut tuple_copy,
+            &mut swapped,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_hash_rejects_lambda() {
+        let mut gc_system = GCSystem::new(None);
+        let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
+        let mut result = gc_system.new_object(VMNull::new());
+        let mut lambda = gc_system.new_object(VMLambda::new(
+            0,
+            "<test>::identity".to_string(),
+            &mut params,
+            None,
+            None,
+            &mut VMLambdaBody::VMNativeFunction(|_, _, args, _| Ok(args.clone_ref())),
+            &mut result,
+            false,
+        ));
+        let mut wrapped = gc_system.new_object(VMTuple::new(&mut vec![&mut lambda]));
+        for value in [&mut lambda, &mut wrapped] {
+            match hash_of(value, &mut gc_system) {
+                Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+                _ => panic!("hash should reject lambdas"),
+            }
+        }
+        for obj in [&mut params, &mut result, &mut lambda, &mut wrapped] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn constant_hash(_: &mut GCRef) -> Result<u64, VMVariableError> {
+        Ok(0)
+    }
+
+    fn dict_lookup(dict: &mut GCRef, key: &mut GCRef) -> Option<String> {
+        dict.as_type::<VMDict>()
+            .get(key)
+            .unwrap()
+            .map(|value| try_repr_vmobject(value, None).unwrap())
+    }
+
+    #[test]
+    fn test_dict_hash_collisions() {
+        let mut gc_system = GCSystem::new(None);
+        // 所有键都落在同一个桶中，只能依靠相等比较区分
+        let mut dict = gc_system.new_object(VMDict::with_hasher(constant_hash));
+        let mut a = gc_system.new_object(VMString::new("a"));
+        let mut b = gc_system.new_object(VMString::new("b"));
+        let mut one = gc_system.new_object(VMInt::new(1));
+        let mut one_float = gc_system.new_object(VMFloat::new(1.0));
+        let mut two = gc_system.new_object(VMInt::new(2));
+        let mut missing = gc_system.new_object(VMString::new("missing"));
+
+        dict.as_type::<VMDict>().insert(&mut a, &mut one).unwrap();
+        dict.as_type::<VMDict>().insert(&mut b, &mut two).unwrap();
+        dict.as_type::<VMDict>().insert(&mut one, &mut a).unwrap();
+        assert_eq!(dict.as_const_type::<VMDict>().len(), 3);
+        assert_eq!(dict_lookup(&mut dict, &mut a).as_deref(), Some("1"));
+        assert_eq!(dict_lookup(&mut dict, &mut b).as_deref(), Some("2"));
+        assert_eq!(
+            dict_lookup(&mut dict, &mut one_float).as_deref(),
+            Some("\"a\"")
+        );
+        assert_eq!(dict_lookup(&mut dict, &mut missing), None);
+
+        // 相等的键替换原有的值，不新增条目
+        dict.as_type::<VMDict>()
+            .insert(&mut one_float, &mut b)
+            .unwrap();
+        assert_eq!(dict.as_const_type::<VMDict>().len(), 3);
+        assert_eq!(dict_lookup(&mut dict, &mut one).as_deref(), Some("\"b\""));
+
+        assert!(dict.as_type::<VMDict>().remove(&mut a).unwrap());
+        assert!(!dict.as_type::<VMDict>().remove(&mut a).unwrap());
+        assert!(!dict.as_type::<VMDict>().contains(&mut a).unwrap());
+        assert!(dict.as_type::<VMDict>().contains(&mut b).unwrap());
+        assert_eq!(dict_lookup(&mut dict, &mut one).as_deref(), Some("\"b\""));
+        assert_eq!(
+            try_repr_vmobject(&mut dict, None).unwrap(),
+            "{\"b\": 2, 1: \"b\"}"
+        );
+
+        for obj in [
+            &mut dict,
+            &mut a,
+            &mut b,
+            &mut one,
+            &mut one_float,
+            &mut two,
+            &mut missing,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn dict_from_pairs(pairs: &[(&str, i64)], gc_system: &mut GCSystem) -> GCRef {
+        let mut items: Vec<GCRef> = pairs
+            .iter()
+            .map(|(key, value)| {
+                let mut key = gc_system.new_object(VMString::new(key));
+                let mut value = gc_system.new_object(VMInt::new(*value));
+                let kv = gc_system.new_object(VMKeyVal::new(&mut key, &mut value));
+                key.drop_ref();
+                value.drop_ref();
+                kv
+            })
+            .collect();
+        let mut pairs = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut pairs]));
+        let result = dict(None, None, &mut args, gc_system).unwrap();
+        for item in items.iter_mut() {
+            item.drop_ref();
+        }
+        pairs.drop_ref();
+        args.drop_ref();
+        result
+    }
+
+    #[test]
+    fn test_dict_repr_is_deterministic() {
+        let mut gc_system = GCSystem::new(None);
+        let mut first = dict_from_pairs(&[("b", 2), ("c", 3), ("a", 1)], &mut gc_system);
+        let mut second = dict_from_pairs(&[("a", 1), ("b", 2), ("c", 3)], &mut gc_system);
+        let expected = "{\"a\": 1, \"b\": 2, \"c\": 3}";
+        assert_eq!(try_repr_vmobject(&mut first, None).unwrap(), expected);
+        assert_eq!(try_repr_vmobject(&mut second, None).unwrap(), expected);
+        assert_eq!(try_repr_vmobject(&mut first, None).unwrap(), expected);
+        first.drop_ref();
+        second.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    // 按迭代顺序返回每个键值对的 repr
+    fn dict_items(dict: &mut GCRef, gc_system: &mut GCSystem) -> Vec<String> {
+        let mut seen = Vec::new();
+        dict.as_type::<VMDict>().reset();
+        while let Some(mut kv) = dict.as_type::<VMDict>().next(gc_system) {
+            seen.push(try_repr_vmobject(&mut kv, None).unwrap());
+            kv.drop_ref();
+        }
+        seen
+    }
+
+    #[test]
+    fn test_dict_iteration_order() {
+        let mut gc_system = GCSystem::new(None);
+        let mut items: Vec<GCRef> = [("z", 1), ("a", 2), ("m", 3), ("a", 4)]
+            .iter()
+            .map(|(key, value)| {
+                let mut key = gc_system.new_object(VMString::new(key));
+                let mut value = gc_system.new_object(VMInt::new(*value));
+                let kv = gc_system.new_object(VMKeyVal::new(&mut key, &mut value));
+                key.drop_ref();
+                value.drop_ref();
+                kv
+            })
+            .collect();
+        let mut pairs = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut pairs]));
+        let mut dict_obj = dict(None, None, &mut args, &mut gc_system).unwrap();
+        // 重复的键保留第一次出现的位置，值以最后一次为准
+        assert_eq!(
+            dict_items(&mut dict_obj, &mut gc_system),
+            vec!["\"z\": 1", "\"a\": 4", "\"m\": 3"]
+        );
+        // repr 按键排序，与插入顺序无关
+        assert_eq!(
+            try_repr_vmobject(&mut dict_obj, None).unwrap(),
+            "{\"a\": 4, \"m\": 3, \"z\": 1}"
+        );
+
+        // 删除后重新插入的键排在末尾
+        let mut z = gc_system.new_object(VMString::new("z"));
+        let mut five = gc_system.new_object(VMInt::new(5));
+        dict_obj.as_type::<VMDict>().remove(&mut z).unwrap();
+        dict_obj
+            .as_type::<VMDict>()
+            .insert(&mut z, &mut five)
+            .unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                dict_items(&mut dict_obj, &mut gc_system),
+                vec!["\"a\": 4", "\"m\": 3", "\"z\": 5"]
+            );
+        }
+
+        let mut copied = try_deepcopy_as_vmobject(&mut dict_obj, &mut gc_system).unwrap();
+        assert!(try_eq_as_vmobject(&copied, &dict_obj));
+        assert_eq!(
+            try_repr_vmobject(&mut copied, None).unwrap(),
+            try_repr_vmobject(&mut dict_obj, None).unwrap()
+        );
+
+        for item in items.iter_mut() {
+            item.drop_ref();
+        }
+        for obj in [
+            &mut pairs,
+            &mut args,
+            &mut dict_obj,
+            &mut z,
+            &mut five,
+            &mut copied,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn bytes_of(mut value: GCRef, gc_system: &mut GCSystem) -> Result<Vec<u8>, VMVariableError> {
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut value]));
+        let result = to_bytes(None, None, &mut args, gc_system).map(|mut bytes| {
+            let data = bytes.as_const_type::<VMBytes>().value.clone();
+            bytes.drop_ref();
+            data
+        });
+        value.drop_ref();
+        args.drop_ref();
+        result
+    }
+
+    fn int_tuple(values: &[i64], gc_system: &mut GCSystem) -> GCRef {
+        let mut items: Vec<GCRef> = values
+            .iter()
+            .map(|v| gc_system.new_object(VMInt::new(*v)))
+            .collect();
+        let tuple = gc_system.new_object(VMTuple::new(&mut items.iter_mut().collect()));
+        for item in items.iter_mut() {
+            item.drop_ref();
+        }
+        tuple
+    }
+
+    #[test]
+    fn test_bytes_input_forms() {
+        let mut gc_system = GCSystem::new(None);
+        let tuple = int_tuple(&[0, 1, 127, 255], &mut gc_system);
+        assert_eq!(
+            bytes_of(tuple, &mut gc_system).unwrap(),
+            vec![0, 1, 127, 255]
+        );
+        let empty = int_tuple(&[], &mut gc_system);
+        assert_eq!(bytes_of(empty, &mut gc_system).unwrap(), Vec::<u8>::new());
+
+        let string = gc_system.new_object(VMString::new("é!"));
+        assert_eq!(
+            bytes_of(string, &mut gc_system).unwrap(),
+            "é!".as_bytes().to_vec()
+        );
+
+        let count = gc_system.new_object(VMInt::new(4));
+        assert_eq!(bytes_of(count, &mut gc_system).unwrap(), vec![0; 4]);
+        let zero = gc_system.new_object(VMInt::new(0));
+        assert_eq!(bytes_of(zero, &mut gc_system).unwrap(), Vec::<u8>::new());
+
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_bytes_rejects_invalid_elements() {
+        let mut gc_system = GCSystem::new(None);
+        let out_of_range = int_tuple(&[1, 256, 2], &mut gc_system);
+        match bytes_of(out_of_range, &mut gc_system) {
+            Err(VMVariableError::ValueError(mut value, message)) => {
+                assert_eq!(value.as_const_type::<VMInt>().value, 256);
+                assert!(message.contains("Element 1"), "{}", message);
+                value.drop_ref();
+            }
+            _ => panic!("bytes should reject out-of-range elements"),
+        }
+
+        let mut one = gc_system.new_object(VMInt::new(1));
+        let mut text = gc_system.new_object(VMString::new("x"));
+        let mixed = gc_system.new_object(VMTuple::new(&mut vec![&mut one, &mut text]));
+        match bytes_of(mixed, &mut gc_system) {
+            Err(VMVariableError::ValueError(mut value, message)) => {
+                assert!(value.isinstance::<VMString>());
+                assert!(message.contains("Element 1"), "{}", message);
+                value.drop_ref();
+            }
+            _ => panic!("bytes should reject non-int elements"),
+        }
+        one.drop_ref();
+        text.drop_ref();
+
+        let negative = gc_system.new_object(VMInt::new(-1));
+        match bytes_of(negative, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("bytes should reject a negative count"),
+        }
+
+        let huge = gc_system.new_object(VMInt::new(1 << 62));
+        match bytes_of(huge, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("bytes should report an unsatisfiable count"),
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn slice_repr(values: &[i64], bounds: &[Option<i64>], gc_system: &mut GCSystem) -> String {
+        let mut target = int_tuple(values, gc_system);
+        let mut bounds: Vec<GCRef> = bounds
+            .iter()
+            .map(|bound| match bound {
+                Some(value) => gc_system.new_object(VMInt::new(*value)),
+                None => gc_system.new_object(VMNull::new()),
+            })
+            .collect();
+        let mut args_values = vec![&mut target];
+        args_values.extend(bounds.iter_mut());
+        let mut args = gc_system.new_object(VMTuple::new(&mut args_values));
+        let mut sliced = slice(None, None, &mut args, gc_system).unwrap();
+        let repr = try_repr_vmobject(&mut sliced, None).unwrap();
+        for obj in bounds.iter_mut() {
+            obj.drop_ref();
+        }
+        for obj in [&mut target, &mut args, &mut sliced] {
+            obj.drop_ref();
+        }
+        repr
+    }
+
+    #[test]
+    fn test_slice() {
+        let mut gc_system = GCSystem::new(None);
+        let values = [0, 1, 2, 3, 4, 5];
+        let cases: [(&[Option<i64>], &str); 10] = [
+            (&[Some(1), Some(4)], "(1, 2, 3)"),
+            (&[Some(0), Some(6), Some(2)], "(0, 2, 4)"),
+            (&[None, None, Some(-1)], "(5, 4, 3, 2, 1, 0)"),
+            (&[Some(-1), Some(-100), Some(-1)], "(5, 4, 3, 2, 1, 0)"),
+            (&[Some(4), Some(1), Some(-2)], "(4, 2)"),
+            (&[Some(-2), None], "(4, 5)"),
+            (&[Some(-100), Some(100)], "(0, 1, 2, 3, 4, 5)"),
+            (&[Some(100), Some(-100), Some(-1)], "(5, 4, 3, 2, 1, 0)"),
+            (&[Some(4), Some(1)], "()"),
+            (&[Some(1), Some(4), Some(-1)], "()"),
+        ];
+        for (bounds, expected) in cases {
+            assert_eq!(
+                slice_repr(&values, bounds, &mut gc_system),
+                expected,
+                "bounds {:?}",
+                bounds
+            );
+        }
+        assert_eq!(
+            slice_repr(&[], &[None, None, Some(-1)], &mut gc_system),
+            "()"
+        );
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_slice_rejects_zero_step() {
+        let mut gc_system = GCSystem::new(None);
+        let mut target = int_tuple(&[1, 2, 3], &mut gc_system);
+        let mut start = gc_system.new_object(VMNull::new());
+        let mut end = gc_system.new_object(VMNull::new());
+        let mut step = gc_system.new_object(VMInt::new(0));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![
+            &mut target,
+            &mut start,
+            &mut end,
+            &mut step,
+        ]));
+        match slice(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("slice should reject a zero step"),
+        }
+        for obj in [&mut target, &mut start, &mut end, &mut step, &mut args] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_type_name() {
+        let mut gc_system = GCSystem::new(None);
+        let mut key = gc_system.new_object(VMString::new("k"));
+        let mut value = gc_system.new_object(VMInt::new(1));
+        let values = vec![
+            (gc_system.new_object(VMInt::new(1)), "int"),
+            (gc_system.new_object(VMFloat::new(1.5)), "float"),
+            (gc_system.new_object(VMString::new("s")), "string"),
+            (gc_system.new_object(VMBoolean::new(true)), "bool"),
+            (gc_system.new_object(VMTuple::new(&mut vec![])), "tuple"),
+            (gc_system.new_object(VMNull::new()), "null"),
+            (
+                gc_system.new_object(VMKeyVal::new(&mut key, &mut value)),
+                "keyval",
+            ),
+            (
+                gc_system.new_object(VMNamed::new(&mut key, &mut value)),
+                "named",
+            ),
+            (gc_system.new_object(VMRange::new(0, 3)), "range"),
+            (gc_system.new_object(VMBytes::new(&vec![1, 2])), "bytes"),
+            (gc_system.new_object(VMDict::new()), "dict"),
+        ];
+        for (mut value, expected) in values {
+            let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut value]));
+            let mut name = type_name(None, None, &mut args, &mut gc_system).unwrap();
+            assert_eq!(name.as_const_type::<VMString>().value, expected);
+            for obj in [&mut value, &mut args, &mut name] {
+                obj.drop_ref();
+            }
+        }
+        key.drop_ref();
+        value.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_type_name_of_lambda() {
+        let mut gc_system = GCSystem::new(None);
+        let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
+        let mut result = gc_system.new_object(VMNull::new());
+        let mut lambda = gc_system.new_object(VMLambda::new(
+            0,
+            "<test>::identity".to_string(),
+            &mut params,
+            None,
+            None,
+            &mut VMLambdaBody::VMNativeFunction(|_, _, args, _| Ok(args.clone_ref())),
+            &mut result,
+            false,
+        ));
+        assert_eq!(type_name_of(&lambda), "lambda");
+        assert_eq!(type_name_of(&params), "tuple");
+        for obj in [&mut params, &mut result, &mut lambda] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_flatten_nested_tuples() {
+        let mut gc_system = GCSystem::new(None);
+        let mut key = gc_system.new_object(VMString::new("k"));
+        let mut value = gc_system.new_object(VMInt::new(5));
+        let mut keyval = gc_system.new_object(VMKeyVal::new(&mut key, &mut value));
+        let mut four = gc_system.new_object(VMInt::new(4));
+        let mut level3 = gc_system.new_object(VMTuple::new(&mut vec![&mut four, &mut keyval]));
+        let mut three = gc_system.new_object(VMInt::new(3));
+        let mut level2 = gc_system.new_object(VMTuple::new(&mut vec![&mut three, &mut level3]));
+        let mut two = gc_system.new_object(VMInt::new(2));
+        let mut level1 = gc_system.new_object(VMTuple::new(&mut vec![&mut two, &mut level2]));
+        let mut one = gc_system.new_object(VMInt::new(1));
+        let mut empty = int_tuple(&[], &mut gc_system);
+        let mut nested =
+            gc_system.new_object(VMTuple::new(&mut vec![&mut one, &mut level1, &mut empty]));
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut nested]));
+        let mut shallow = flatten(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(
+            try_repr_vmobject(&mut shallow, None).unwrap(),
+            "(1, 2, (3, (4, \"k\": 5)))"
+        );
+        let mut deep = flatten_deep(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(
+            try_repr_vmobject(&mut deep, None).unwrap(),
+            "(1, 2, 3, 4, \"k\": 5)"
+        );
+
+        for obj in [
+            &mut key,
+            &mut value,
+            &mut keyval,
+            &mut four,
+            &mut level3,
+            &mut three,
+            &mut level2,
+            &mut two,
+            &mut level1,
+            &mut one,
+            &mut empty,
+            &mut nested,
+            &mut args,
+            &mut shallow,
+            &mut deep,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_flatten_deep_rejects_cycles() {
+        let mut gc_system = GCSystem::new(None);
+        let mut cyclic = int_tuple(&[1], &mut gc_system);
+        let mut inner = gc_system.new_object(VMTuple::new(&mut vec![&mut cyclic]));
+        // 位置参数超出命名参数时会被追加到末尾，得到 cyclic = (1, (cyclic,))
+        let mut extra = gc_system.new_object(VMTuple::new(&mut vec![&mut inner]));
+        cyclic
+            .as_type::<VMTuple>()
+            .assign_members(&mut extra)
+            .unwrap();
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut cyclic]));
+        match flatten_deep(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::ValueError(..)) => err.consume_ref(),
+            _ => panic!("flatten_deep should reject self-referential tuples"),
+        }
+        // 只展开一层时不会遇到循环
+        let mut shallow = flatten(None, None, &mut args, &mut gc_system).unwrap();
+        assert_eq!(shallow.as_const_type::<VMTuple>().values.len(), 2);
+
+        for obj in [&mut cyclic, &mut inner, &mut extra, &mut args, &mut shallow] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_bool_truthiness() {
+        let mut gc_system = GCSystem::new(None);
+        let values = vec![
+            (gc_system.new_object(VMBoolean::new(true)), true),
+            (gc_system.new_object(VMBoolean::new(false)), false),
+            (gc_system.new_object(VMInt::new(-3)), true),
+            (gc_system.new_object(VMInt::new(0)), false),
+            (gc_system.new_object(VMFloat::new(0.5)), true),
+            (gc_system.new_object(VMFloat::new(0.0)), false),
+            (gc_system.new_object(VMFloat::new(f64::NAN)), false),
+            (gc_system.new_object(VMString::new("x")), true),
+            (gc_system.new_object(VMString::new("")), false),
+            (gc_system.new_object(VMBytes::new(&vec![0])), true),
+            (gc_system.new_object(VMBytes::new(&vec![])), false),
+            (int_tuple(&[0], &mut gc_system), true),
+            (int_tuple(&[], &mut gc_system), false),
+            (gc_system.new_object(VMRange::new(2, 3)), true),
+            (gc_system.new_object(VMRange::new(3, 3)), false),
+            (gc_system.new_object(VMNull::new()), false),
+        ];
+        for (mut value, expected) in values {
+            let repr = try_to_string_vmobject(&mut value, None).unwrap();
+            let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut value]));
+            let mut result = to_bool(None, None, &mut args, &mut gc_system).unwrap();
+            assert_eq!(
+                result.as_const_type::<VMBoolean>().value,
+                expected,
+                "bool({})",
+                repr
+            );
+            for obj in [&mut value, &mut args, &mut result] {
+                obj.drop_ref();
+            }
+        }
+
+        let mut dict = gc_system.new_object(VMDict::new());
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut dict]));
+        match to_bool(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("bool should reject values without a truth value"),
+        }
+        dict.drop_ref();
+        args.drop_ref();
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn parse_int_with(text: &str, radix: i64, gc_system: &mut GCSystem) -> Result<i64, ()> {
+        let mut text = gc_system.new_object(VMString::new(text));
+        let mut radix = gc_system.new_object(VMInt::new(radix));
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut text, &mut radix]));
+        let result = match parse_int(None, None, &mut args, gc_system) {
+            Ok(mut value) => {
+                let parsed = value.as_const_type::<VMInt>().value;
+                value.drop_ref();
+                Ok(parsed)
+            }
+            Err(mut err @ VMVariableError::ValueError(..)) => {
+                err.consume_ref();
+                Err(())
+            }
+            Err(mut err) => panic!("unexpected error: {}", err.to_string()),
+        };
+        for obj in [&mut text, &mut radix, &mut args] {
+            obj.drop_ref();
+        }
+        result
+    }
+
+    #[test]
+    fn test_parse_int_radix() {
+        let mut gc_system = GCSystem::new(None);
+        assert_eq!(parse_int_with("ff", 16, &mut gc_system), Ok(255));
+        assert_eq!(parse_int_with("-7FFF", 16, &mut gc_system), Ok(-0x7fff));
+        assert_eq!(parse_int_with("1011", 2, &mut gc_system), Ok(11));
+        assert_eq!(parse_int_with("zz", 36, &mut gc_system), Ok(35 * 36 + 35));
+        assert_eq!(parse_int_with("0xff", 0, &mut gc_system), Ok(255));
+        assert_eq!(parse_int_with("-0b101", 0, &mut gc_system), Ok(-5));
+        assert_eq!(parse_int_with("0o17", 0, &mut gc_system), Ok(15));
+        assert_eq!(parse_int_with("42", 0, &mut gc_system), Ok(42));
+        assert_eq!(
+            parse_int_with("-8000000000000000", 16, &mut gc_system),
+            Ok(i64::MIN)
+        );
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_parse_int_rejects_invalid_input() {
+        let mut gc_system = GCSystem::new(None);
+        for (text, radix) in [
+            ("12", 2),
+            ("fg", 16),
+            ("", 10),
+            ("0x", 0),
+            ("0x-1", 0),
+            ("10", 1),
+            ("10", 37),
+            ("8000000000000000", 16),
+        ] {
+            assert_eq!(
+                parse_int_with(text, radix, &mut gc_system),
+                Err(()),
+                "{:?} radix {}",
+                text,
+                radix
+            );
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    fn compare_with(
+        native_fn: NativeFunction,
+        a: &mut GCRef,
+        b: &mut GCRef,
+        gc_system: &mut GCSystem,
+    ) -> bool {
+        let mut result = call_native(native_fn, &mut vec![a, b], gc_system).unwrap();
+        let value = result.as_const_type::<VMBoolean>().value;
+        result.drop_ref();
+        value
+    }
+
+    #[test]
+    fn test_equals_and_is() {
+        let mut gc_system = GCSystem::new(None);
+        let mut one = gc_system.new_object(VMInt::new(1));
+        let mut two = gc_system.new_object(VMInt::new(2));
+        let mut first = gc_system.new_object(VMTuple::new(&mut vec![&mut one, &mut two]));
+        let mut second = gc_system.new_object(VMTuple::new(&mut vec![&mut one, &mut two]));
+        let mut same = first.clone_ref();
+
+        // 两个值相等但独立的元组
+        assert!(compare_with(
+            equals,
+            &mut first,
+            &mut second,
+            &mut gc_system
+        ));
+        assert!(!compare_with(is, &mut first, &mut second, &mut gc_system));
+        // 同一个元组被引用两次
+        assert!(compare_with(equals, &mut first, &mut same, &mut gc_system));
+        assert!(compare_with(is, &mut first, &mut same, &mut gc_system));
+        assert!(!compare_with(equals, &mut one, &mut two, &mut gc_system));
+
+        for obj in [&mut one, &mut two, &mut first, &mut second, &mut same] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_keys_values_and_has_key() {
+        let mut gc_system = GCSystem::new(None);
+        let mut a = gc_system.new_object(VMString::new("a"));
+        let mut b = gc_system.new_object(VMString::new("b"));
+        let mut c = gc_system.new_object(VMString::new("c"));
+        let mut one = gc_system.new_object(VMInt::new(1));
+        let mut two = gc_system.new_object(VMInt::new(2));
+        let mut bare = gc_system.new_object(VMInt::new(3));
+        let mut kv = gc_system.new_object(VMKeyVal::new(&mut a, &mut one));
+        let mut named = gc_system.new_object(VMNamed::new(&mut b, &mut two));
+        // ("a" : 1, 3, b => 2)
+        let mut target =
+            gc_system.new_object(VMTuple::new(&mut vec![&mut kv, &mut bare, &mut named]));
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target]));
+        for (native_fn, expected) in [
+            (keys as NativeFunction, "(\"a\", \"b\")"),
+            (values, "(1, 2)"),
+        ] {
+            let mut result = native_fn(None, None, &mut args, &mut gc_system).unwrap();
+            assert_eq!(try_repr_vmobject(&mut result, None).unwrap(), expected);
+            result.drop_ref();
+        }
+        args.drop_ref();
+
+        for (key, expected) in [
+            (&mut a, true),
+            (&mut b, true),
+            (&mut c, false),
+            (&mut bare, false),
+        ] {
+            let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target, key]));
+            let mut result = has_key(None, None, &mut args, &mut gc_system).unwrap();
+            assert_eq!(result.as_const_type::<VMBoolean>().value, expected);
+            result.drop_ref();
+            args.drop_ref();
+        }
+
+        for obj in [
+            &mut a,
+            &mut b,
+            &mut c,
+            &mut one,
+            &mut two,
+            &mut bare,
+            &mut kv,
+            &mut named,
+            &mut target,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_merge_overrides_and_appends() {
+        let mut gc_system = GCSystem::new(None);
+        let mut host = gc_system.new_object(VMString::new("host"));
+        let mut port = gc_system.new_object(VMString::new("port"));
+        let mut debug = gc_system.new_object(VMString::new("debug"));
+        let mut localhost = gc_system.new_object(VMString::new("localhost"));
+        let mut port_80 = gc_system.new_object(VMInt::new(80));
+        let mut port_8080 = gc_system.new_object(VMInt::new(8080));
+        let mut enabled = gc_system.new_object(VMBoolean::new(true));
+        let mut bare = gc_system.new_object(VMString::new("extra"));
+        let mut base_host = gc_system.new_object(VMKeyVal::new(&mut host, &mut localhost));
+        let mut base_port = gc_system.new_object(VMNamed::new(&mut port, &mut port_80));
+        let mut new_port = gc_system.new_object(VMKeyVal::new(&mut port, &mut port_8080));
+        let mut new_debug = gc_system.new_object(VMNamed::new(&mut debug, &mut enabled));
+        let mut base = gc_system.new_object(VMTuple::new(&mut vec![
+            &mut base_host,
+            &mut bare,
+            &mut base_port,
+        ]));
+        let mut overrides =
+            gc_system.new_object(VMTuple::new(&mut vec![&mut new_debug, &mut new_port]));
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut base, &mut overrides]));
+        let mut merged = merge(None, None, &mut args, &mut gc_system).unwrap();
+        // 同名键保持原位置并取 overrides 的值，新键追加在末尾
+        assert_eq!(
+            try_repr_vmobject(&mut merged, None).unwrap(),
+            "(\"host\": \"localhost\", \"extra\", \"port\": 8080, \"debug\" => true)"
+        );
+        // base 未被修改
+        assert_eq!(
+            try_repr_vmobject(&mut base, None).unwrap(),
+            "(\"host\": \"localhost\", \"extra\", \"port\" => 80)"
+        );
+        merged.drop_ref();
+        args.drop_ref();
+
+        // overrides 中的普通元素无法按键合并
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut overrides, &mut base]));
+        match merge(None, None, &mut args, &mut gc_system) {
+            Err(mut err @ VMVariableError::TypeError(..)) => err.consume_ref(),
+            _ => panic!("bare elements in overrides should be rejected"),
+        }
+        args.drop_ref();
+
+        for obj in [
+            &mut host,
+            &mut port,
+            &mut debug,
+            &mut localhost,
+            &mut port_80,
+            &mut port_8080,
+            &mut enabled,
+            &mut bare,
+            &mut base_host,
+            &mut base_port,
+            &mut new_port,
+            &mut new_debug,
+            &mut base,
+            &mut overrides,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
+
+    #[test]
+    fn test_reverse_and_unique() {
+        let mut gc_system = GCSystem::new(None);
+        let mut a = gc_system.new_object(VMString::new("a"));
+        let mut b = gc_system.new_object(VMString::new("b"));
+        let mut one = gc_system.new_object(VMInt::new(1));
+        let mut two = gc_system.new_object(VMInt::new(2));
+        let mut other_one = gc_system.new_object(VMFloat::new(1.0));
+        let mut kv = gc_system.new_object(VMKeyVal::new(&mut a, &mut one));
+        let mut same_kv = gc_system.new_object(VMKeyVal::new(&mut a, &mut one));
+        let mut other_kv = gc_system.new_object(VMKeyVal::new(&mut a, &mut two));
+        let mut named = gc_system.new_object(VMNamed::new(&mut b, &mut two));
+        let mut same_named = gc_system.new_object(VMNamed::new(&mut b, &mut two));
+        // ("a" : 1, 1, "a" : 1, b => 2, "a" : 2, 1.0, b => 2)
+        let mut target = gc_system.new_object(VMTuple::new(&mut vec![
+            &mut kv,
+            &mut one,
+            &mut same_kv,
+            &mut named,
+            &mut other_kv,
+            &mut other_one,
+            &mut same_named,
+        ]));
+
+        let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut target]));
+        for (native_fn, expected) in [
+            (
+                reverse as NativeFunction,
+                "(\"b\" => 2, 1, \"a\": 2, \"b\" => 2, \"a\": 1, 1, \"a\": 1)",
+            ),
+            (unique, "(\"a\": 1, 1, \"b\" => 2, \"a\": 2)"),
+        ] {
+            let mut result = native_fn(None, None, &mut args, &mut gc_system).unwrap();
+            assert_eq!(try_repr_vmobject(&mut result, None).unwrap(), expected);
+            result.drop_ref();
+        }
+        args.drop_ref();
+
+        // 原元组未被修改
+        assert_eq!(target.as_const_type::<VMTuple>().values.len(), 7);
+
+        for obj in [
+            &mut a,
+            &mut b,
+            &mut one,
+            &mut two,
+            &mut other_one,
+            &mut kv,
+            &mut same_kv,
+            &mut other_kv,
+            &mut named,
+            &mut same_named,
+            &mut target,
+        ] {
+            obj.drop_ref();
+        }
+        gc_system.collect();
+        assert_eq!(gc_system._count(), 0);
+    }
 }
@@ -6,6 +6,7 @@ use crate::parser::lexer::{Token, TokenType};
 pub enum ParserError<'t> {
     UnexpectedToken(&'t Token<'t>),                     // Token
     UnmatchedParenthesis(&'t Token<'t>, &'t Token<'t>), // (opening, closing)
+    UnclosedParenthesis(&'t Token<'t>),                 // opening bracket never closed before EOF
     InvalidSyntax(&'t Token<'t>),
     NotFullyMatched(&'t Token<'t>, &'t Token<'t>),
     InvalidVariableName(&'t Token<'t>),
@@ -138,6 +139,44 @@ impl ParserError<'_> {
                 error_msg
             }
 
+            ParserError::UnclosedParenthesis(opening) => {
+                let (line_num, col) = find_position(opening.position);
+                let line = if line_num < lines.len() {
+                    lines[line_num]
+                } else {
+                    ""
+                };
+                let expected = match opening.token {
+                    "(" => ")",
+                    "[" => "]",
+                    _ => "}",
+                };
+
+                let mut error_msg = format!(
+                    "{}: {}\n\n",
+                    "Parse Error".bright_red().bold(),
+                    format!("Unclosed '{}'", opening.token).yellow()
+                );
+                error_msg.push_str(&format!(
+                    "{} '{}' at {}:{}\n",
+                    "Opening".bright_green(),
+                    opening.token.bright_white(),
+                    (line_num + 1).to_string().bright_cyan(),
+                    (col + 1).to_string().bright_cyan()
+                ));
+                error_msg.push_str(&format!("{}\n", line.white()));
+                error_msg.push_str(&format!("{}{}\n", " ".repeat(col), "^".bright_red().bold()));
+                error_msg.push_str(&format!(
+                    "\n{} {}\n",
+                    "Hint:".bright_green().bold(),
+                    format!("Expected '{}' before end of input", expected)
+                        .bright_white()
+                        .italic()
+                ));
+
+                error_msg
+            }
+
             ParserError::InvalidSyntax(token) => {
                 let (line_num, col) = find_position(token.position);
                 let line = if line_num < lines.len() {
@@ -422,12 +461,9 @@ fn get_next_tokens(
             break;
         }
     }
-    if !stack.is_empty() {
-        let (_, last_position) = stack.pop().unwrap();
-        return Err(ParserError::UnmatchedParenthesis(
-            &tokens[last_position],
-            &tokens[index - 1],
-        ));
+    if let Some((_, last_position)) = stack.pop() {
+        // 到达输入末尾仍有未闭合的括号，报告最内层未闭合括号的位置
+        return Err(ParserError::UnclosedParenthesis(&tokens[last_position]));
     }
     Ok(&tokens[current..current + next_tokens_end])
 }
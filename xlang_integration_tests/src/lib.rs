@@ -3,12 +3,20 @@ mod tests {
     use std::sync::Arc;
 
     use xlang_frontend::compile::build_code;
+    use xlang_rust::stdlib::inject_builtin_functions;
     use xlang_vm_core::{
+        executor::native_module::NativeModuleBuilder,
         executor::variable::{
-            try_repr_vmobject, VMInstructions, VMInt, VMLambda, VMLambdaBody,
-            VMNativeGeneratorFunction, VMNull, VMTuple, VMVariableError,
+            try_deepcopy_as_vmobject, try_index_of_as_vmobject, try_repr_vmobject,
+            try_repr_vmobject_with_depth, try_to_string_vmobject, VMChannel, VMCoroutineStatus,
+            VMInstructions, VMInt, VMLambda, VMLambdaBody, VMNamed, VMNativeGeneratorFunction,
+            VMNull, VMRange, VMSet, VMString, VMTuple, VMVariableError,
         },
+        executor::vm::{ExecutorHook, HookAction, VMCoroutinePool, VMError, VMExecutor},
         gc::GCRef,
+        instruction_set::VMInstructionPackage,
+        ir::{DebugInfo, IROperation, IRPackage, IR},
+        opcode::ProcessedOpcode,
     };
     #[test]
     fn test_xlang_compile_to_ir() {
@@ -36,6 +44,37 @@ mod tests {
         }
     }
 
+    // 去掉终端颜色控制序列，便于断言错误信息
+    fn strip_ansi(text: &str) -> String {
+        let mut result = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_unclosed_parenthesis_reports_opening_position() {
+        let mut dir_stack = xlang_frontend::dir_stack::DirStack::new(None).unwrap();
+        let err = match build_code("foo := (1, 2;", &mut dir_stack) {
+            Err(err) => strip_ansi(&err),
+            Ok(_) => panic!("unclosed parenthesis should not compile"),
+        };
+        assert!(err.contains("Unclosed '('"), "{}", err);
+        assert!(err.contains("'(' at 1:8"), "{}", err);
+        assert!(err.contains("foo := (1, 2;\n       ^\n"), "{}", err);
+        assert!(err.contains("Expected ')' before end of input"), "{}", err);
+    }
+
     #[test]
     fn test_xlang_execute() {
         let code = r#"
@@ -487,4 +526,1695 @@ mod tests {
 
         gc._print_reference_graph();
     }
+
+    // 编译并运行一段代码，返回入口 lambda，调用者负责 drop_ref
+    fn compile_package(code: &str) -> VMInstructionPackage {
+        let mut dir_stack =
+            xlang_frontend::dir_stack::DirStack::new(None).expect("Failed to push directory");
+        let ir_package = build_code(code, &mut dir_stack).expect("Failed to build code");
+        let mut vm_instructions_package =
+            xlang_vm_core::ir_translator::IRTranslator::new(&ir_package);
+        vm_instructions_package
+            .translate()
+            .expect("Failed to translate IR package");
+        vm_instructions_package.get_result()
+    }
+
+    fn build_main_lambda(code: &str, gc: &mut xlang_vm_core::gc::GCSystem) -> (GCRef, GCRef) {
+        build_package_lambda(&compile_package(code), gc)
+    }
+
+    // 编译脚本并在注入了内置模块的主协程中运行到结束
+    fn run_script(code: &str, gc: &mut xlang_vm_core::gc::GCSystem) -> Result<(), VMError> {
+        let (mut lambda, mut default_args_tuple) = build_main_lambda(code, gc);
+        let mut coroutine_pool = VMCoroutinePool::new(true);
+        lambda.clone_ref();
+        let coro_id = coroutine_pool
+            .new_coroutine(&mut lambda, &mut default_args_tuple, gc)
+            .expect("Failed to create coroutine");
+        inject_builtin_functions(
+            coroutine_pool
+                .get_executor_mut(coro_id)
+                .unwrap()
+                .get_context_mut(),
+            &[],
+            gc,
+        )
+        .expect("Failed to inject builtins");
+        let result = coroutine_pool.run_until_finished(gc);
+        lambda.drop_ref();
+        result
+    }
+
+    // 脚本应正常结束，且结束后不残留任何对象
+    fn assert_script_ok(code: &str) {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        if let Err(mut e) = run_script(code, &mut gc) {
+            let message = e.to_string();
+            e.consume_ref();
+            panic!("Failed to execute script: {}", message);
+        }
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    // 脚本应以错误结束，返回错误信息
+    fn script_error(code: &str) -> String {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut e = run_script(code, &mut gc).expect_err("Script should fail");
+        let message = e.to_string();
+        e.consume_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+        message
+    }
+
+    fn build_package_lambda(
+        vm_instructions_package: &VMInstructionPackage,
+        gc: &mut xlang_vm_core::gc::GCSystem,
+    ) -> (GCRef, GCRef) {
+        let mut default_args_tuple = gc.new_object(VMTuple::new(&mut vec![]));
+        let mut default_result = gc.new_object(VMNull::new());
+        let mut lambda_body = gc.new_object(VMInstructions::new(vm_instructions_package));
+        let lambda = gc.new_object(VMLambda::new(
+            0,
+            "__main__".to_string(),
+            &mut default_args_tuple,
+            None,
+            None,
+            &mut VMLambdaBody::VMInstruction(lambda_body.clone()),
+            &mut default_result,
+            false,
+        ));
+        default_result.drop_ref();
+        lambda_body.drop_ref();
+        (lambda, default_args_tuple)
+    }
+
+    fn run_code(code: &str, gc: &mut xlang_vm_core::gc::GCSystem) -> GCRef {
+        let (mut lambda, mut default_args_tuple) = build_main_lambda(code, gc);
+        let mut coroutine_pool = xlang_vm_core::executor::vm::VMCoroutinePool::new(true);
+        lambda.clone_ref();
+        coroutine_pool
+            .new_coroutine(&mut lambda, &mut default_args_tuple, gc)
+            .expect("Failed to create coroutine");
+        if let Err(mut e) = coroutine_pool.run_until_finished(gc) {
+            panic!("Failed to execute code: {}", e.to_string());
+        }
+        lambda
+    }
+
+    #[test]
+    fn test_vmset_materialize() {
+        let code = r#"
+        return 0..10 | (x?) -> x >= 7;
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+
+        let set = &mut lambda.as_type::<VMLambda>().result;
+        assert!(set.isinstance::<VMSet>());
+        let mut collected = set
+            .as_type::<VMSet>()
+            .materialize(&mut gc)
+            .expect("Failed to materialize set");
+        let values = collected
+            .as_const_type::<VMTuple>()
+            .values
+            .iter()
+            .map(|v| v.as_const_type::<VMInt>().value)
+            .collect::<Vec<i64>>();
+        assert_eq!(values, vec![7, 8, 9]);
+
+        collected.drop_ref();
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_script_defined_iterator() {
+        let code = r#"
+        counter := bind (
+            i => 0,
+            next => () -> {
+                if (self.i >= 3) { return null };
+                value := copy self.i;
+                self.i = self.i + 1;
+                return value * 10;
+            },
+            reset => () -> { self.i = 0 },
+        );
+        first := counter |> (x?) -> x + 1;
+        second := counter |> (x?) -> x;
+        return (first, second);
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+        // 每次迭代前都会调用 reset，两次遍历得到相同的序列
+        assert_eq!(
+            try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None).unwrap(),
+            "((1, 11, 21), (0, 10, 20))"
+        );
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_deepcopy_cyclic_tuple() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut one = gc.new_object(VMInt::new(1));
+        let mut cyclic = gc.new_object(VMTuple::new(&mut vec![&mut one]));
+        let mut self_ref = cyclic.clone();
+        cyclic.as_type::<VMTuple>().append(&mut self_ref).unwrap();
+
+        let mut copied = try_deepcopy_as_vmobject(&mut cyclic, &mut gc).unwrap();
+        assert_ne!(copied, cyclic);
+        // 副本中的自引用指向副本本身，而不是原元组
+        assert_eq!(copied.as_const_type::<VMTuple>().values[1], copied);
+        assert_eq!(
+            try_repr_vmobject(&mut copied, None).unwrap(),
+            try_repr_vmobject(&mut cyclic, None).unwrap()
+        );
+
+        // 共享的子对象在副本中仍然共享
+        let mut shared = cyclic.clone();
+        let mut pair = gc.new_object(VMTuple::new(&mut vec![&mut cyclic, &mut shared]));
+        let mut copied_pair = try_deepcopy_as_vmobject(&mut pair, &mut gc).unwrap();
+        let values = &copied_pair.as_const_type::<VMTuple>().values;
+        assert_eq!(values[0], values[1]);
+        assert_ne!(values[0], cyclic);
+
+        for obj in [
+            &mut one,
+            &mut cyclic,
+            &mut copied,
+            &mut pair,
+            &mut copied_pair,
+        ] {
+            obj.drop_ref();
+        }
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_repr_of_deeply_nested_tuple_is_truncated() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut nested = gc.new_object(VMInt::new(0));
+        for _ in 0..100_000 {
+            let outer = gc.new_object(VMTuple::new(&mut vec![&mut nested]));
+            nested.drop_ref();
+            nested = outer;
+        }
+
+        let repr = try_repr_vmobject(&mut nested, None).unwrap();
+        assert!(repr.contains("<...>"));
+        let string = try_to_string_vmobject(&mut nested, None).unwrap();
+        assert!(string.contains("<...>"));
+        assert_eq!(
+            try_repr_vmobject_with_depth(&mut nested, None, 2).unwrap(),
+            "((<...>,),)"
+        );
+
+        nested.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_context_iter_vars() {
+        use xlang_vm_core::executor::context::{Context, ContextFrameType};
+
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut context = Context::new();
+        context.new_frame(&Vec::new(), ContextFrameType::FunctionFrame, 0, false);
+        assert_eq!(context.iter_vars().count(), 0);
+
+        let mut one = gc.new_object(VMInt::new(1));
+        let mut two = gc.new_object(VMInt::new(2));
+        context.let_var("a", &mut one, &mut gc).unwrap();
+        context.let_var("b", &mut two, &mut gc).unwrap();
+        let mut names: Vec<&String> = context.iter_vars().map(|(name, _)| name).collect();
+        names.sort();
+        assert_eq!(names, ["a", "b"]);
+
+        // 内层帧的同名变量遮蔽外层变量
+        context.new_frame(&Vec::new(), ContextFrameType::NormalFrame, 0, false);
+        context.let_var("a", &mut two, &mut gc).unwrap();
+        let vars: Vec<(&String, &GCRef)> = context.iter_vars().collect();
+        assert_eq!(vars.len(), 2);
+        assert!(vars
+            .iter()
+            .any(|(name, value)| *name == "a" && **value == two));
+
+        context.drop_all_frames();
+        one.drop_ref();
+        two.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_vmset_repr_is_stable() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code("return 0..10 | (x?) -> x >= 7;", &mut gc);
+        let set = &mut lambda.as_type::<VMLambda>().result;
+        let before = try_repr_vmobject(set, None).unwrap();
+        // 过滤会调用 filter 并改变其记录的返回值，但不应影响集合的 repr
+        let mut collected = set
+            .as_type::<VMSet>()
+            .materialize(&mut gc)
+            .expect("Failed to materialize set");
+        let after = try_repr_vmobject(set, None).unwrap();
+        assert_eq!(before, after);
+        assert!(before.starts_with("{0..10 | "));
+
+        collected.drop_ref();
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_channel_keeps_buffered_values_alive() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut channel = gc.new_object(VMChannel::new(2));
+        for value in 0..3 {
+            let mut item = gc.new_object(VMInt::new(value));
+            let sent = channel.as_type::<VMChannel>().try_send(&mut item);
+            assert_eq!(sent, value < 2, "only two values fit into the channel");
+            item.drop_ref();
+        }
+        assert!(channel.as_const_type::<VMChannel>().is_full());
+        assert_eq!(
+            try_repr_vmobject(&mut channel, None).unwrap(),
+            "channel(2/2)"
+        );
+
+        // 缓冲区中的值只被通道引用，回收后仍然存活
+        gc.collect();
+        let mut received = Vec::new();
+        while let Some(mut item) = channel.as_type::<VMChannel>().try_recv() {
+            received.push(item.as_const_type::<VMInt>().value);
+            item.drop_ref();
+        }
+        assert_eq!(received, vec![0, 1]);
+        assert!(channel.as_const_type::<VMChannel>().is_empty());
+
+        channel.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_float_special_values_repr() {
+        let code = r#"
+        return (1.0 / 0.0, -1.0 / 0.0, 0.0 / 0.0, 1.5);
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+
+        let repr = try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None)
+            .expect("Failed to repr result");
+        assert_eq!(repr, "(inf, -inf, NaN, 1.5)");
+
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_lambda_equality() {
+        let code = r#"
+        f := (a?, b => 1) -> a + b;
+        g := (a?, b => 1) -> a + b;
+        return (f == copy f, f == deepcopy f, f == g, f == f);
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+
+        let repr = try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None)
+            .expect("Failed to repr result");
+        assert_eq!(repr, "(true, true, false, true)");
+
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_negative_indices() {
+        let code = r#"
+        s := "hello";
+        t := (1, 2, 3);
+        return (s[-1], t[-2], s[(-3)..(-1)], t[(-2)..3], s[0..(-1)]);
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+
+        let repr = try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None)
+            .expect("Failed to repr result");
+        assert_eq!(repr, "(\"o\", 2, \"ll\", (2, 3), \"hell\")");
+
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_range_indexing() {
+        let code = r#"
+        r := 10..20;
+        return (r[3], r[-1], r[2..5], r[2..5][1], r[0..0], (0..10)[3]);
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+
+        let repr = try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None)
+            .expect("Failed to repr result");
+        assert_eq!(repr, "(13, 19, 12..15, 13, 10..10, 3)");
+
+        let mut range = gc.new_object(VMRange::new(10, 20));
+        let mut out_of_range = gc.new_object(VMInt::new(10));
+        let mut too_wide = gc.new_object(VMRange::new(5, 11));
+        for index in [&mut out_of_range, &mut too_wide] {
+            match try_index_of_as_vmobject(&mut range, index, &mut gc) {
+                Err(mut err @ VMVariableError::IndexNotFound(..)) => err.consume_ref(),
+                _ => panic!("indexing past the end of a range should fail"),
+            }
+        }
+
+        for obj in [&mut range, &mut out_of_range, &mut too_wide, &mut lambda] {
+            obj.drop_ref();
+        }
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_conditions_use_truthiness() {
+        let code = r#"
+        empty_string := 0;
+        if ("") { empty_string = 1 };
+        tuple := 0;
+        if ((0,)) { tuple = 1 };
+        n := 3;
+        count := 0;
+        while (n) { n = n - 1; count = count + 1 };
+        null_taken := 0;
+        if (null) { null_taken = 1 };
+        return (empty_string, tuple, count, null_taken);
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+
+        let repr = try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None)
+            .expect("Failed to repr result");
+        assert_eq!(repr, "(0, 1, 3, 0)");
+
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_cancel_coroutine() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+
+        let mut params = gc.new_object(VMTuple::new(&mut vec![]));
+        let mut result = gc.new_object(VMNull::new());
+        let mut generator = gc.new_object(VMLambda::new(
+            0,
+            "<builtins>::custom_generator".to_string(),
+            &mut params,
+            None,
+            None,
+            &mut VMLambdaBody::VMNativeGeneratorFunction(Arc::new(Box::new(TestGenerator::new()))),
+            &mut result,
+            false,
+        ));
+        params.drop_ref();
+        result.drop_ref();
+
+        let mut steps = gc.new_object(VMInt::new(10));
+        let mut args = gc.new_object(VMTuple::new(&mut vec![&mut steps]));
+        steps.drop_ref();
+
+        // 与 async 调用一致，生成器在进入协程池之前完成初始化
+        if let VMLambdaBody::VMNativeGeneratorFunction(ref mut body) =
+            generator.as_type::<VMLambda>().lambda_body
+        {
+            Arc::get_mut(body)
+                .expect("Generator should not be shared")
+                .init(&mut args, &mut gc)
+                .expect("Failed to init generator");
+        }
+
+        let mut coroutine_pool = xlang_vm_core::executor::vm::VMCoroutinePool::new(true);
+        generator.clone_ref();
+        let id = coroutine_pool
+            .new_coroutine(&mut generator, &mut args, &mut gc)
+            .expect("Failed to create coroutine");
+
+        for _ in 0..2 {
+            coroutine_pool
+                .step_all(&mut gc)
+                .unwrap_or_else(|(_, e)| panic!("Failed to step coroutine: {:?}", e));
+        }
+        coroutine_pool
+            .cancel_coroutine(id)
+            .expect("Failed to cancel coroutine");
+        assert!(coroutine_pool.get_executor(id).is_none());
+        assert!(coroutine_pool.cancel_coroutine(id).is_err());
+
+        coroutine_pool
+            .run_until_finished(&mut gc)
+            .expect("Cancelled coroutine should not be scheduled");
+
+        let lambda = generator.as_const_type::<VMLambda>();
+        assert!(lambda.coroutine_status == VMCoroutineStatus::Cancelled);
+        match &lambda.lambda_body {
+            VMLambdaBody::VMNativeGeneratorFunction(generator) => assert!(!generator.is_done()),
+            _ => unreachable!(),
+        }
+
+        generator.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_unbounded_recursion_reports_stack_overflow() {
+        let code = r#"
+        f := (n => 0) -> {
+            return (@dynamic f)(n + 1);
+        };
+        return f(0);
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let (mut lambda, mut default_args_tuple) = build_main_lambda(code, &mut gc);
+        let mut coroutine_pool = VMCoroutinePool::with_max_call_depth(false, 64);
+        coroutine_pool
+            .new_coroutine(&mut lambda.clone_ref(), &mut default_args_tuple, &mut gc)
+            .expect("Failed to create coroutine");
+        let mut err = coroutine_pool
+            .run_until_finished(&mut gc)
+            .expect_err("unbounded recursion should fail");
+        let mut inner = &err;
+        while let VMError::AtSourcePosition(source, _) | VMError::WithTraceback(source, _) = inner {
+            inner = source;
+        }
+        match inner {
+            VMError::StackOverflow(signatures) => {
+                assert_eq!(signatures.len(), 65);
+                assert_eq!(signatures[0], "__main__");
+                assert!(signatures[1..]
+                    .iter()
+                    .all(|signature| signature == &signatures[1]));
+            }
+            _ => panic!("expected StackOverflow, got {}", err.to_string()),
+        }
+        err.consume_ref();
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_call_lambda_with_timeout() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+
+        let mut params = gc.new_object(VMTuple::new(&mut vec![]));
+        let mut result = gc.new_object(VMNull::new());
+        let mut generator = gc.new_object(VMLambda::new(
+            0,
+            "<builtins>::custom_generator".to_string(),
+            &mut params,
+            None,
+            None,
+            &mut VMLambdaBody::VMNativeGeneratorFunction(Arc::new(Box::new(TestGenerator::new()))),
+            &mut result,
+            false,
+        ));
+        params.drop_ref();
+        result.drop_ref();
+
+        let mut steps = gc.new_object(VMInt::new(i64::MAX));
+        let mut args = gc.new_object(VMTuple::new(&mut vec![&mut steps]));
+        steps.drop_ref();
+
+        let start = std::time::Instant::now();
+        let outcome = xlang_vm_core::executor::vm::call_lambda_with_timeout(
+            &mut generator,
+            &mut args,
+            std::time::Duration::from_millis(50),
+            &mut gc,
+        )
+        .expect("Failed to run generator");
+        assert!(outcome.is_none());
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        match &generator.as_const_type::<VMLambda>().lambda_body {
+            VMLambdaBody::VMNativeGeneratorFunction(generator) => assert!(!generator.is_done()),
+            _ => unreachable!(),
+        }
+        generator.drop_ref();
+        args.drop_ref();
+
+        // 按时结束的 lambda 返回其结果
+        let (mut lambda, mut default_args_tuple) = build_main_lambda("return 40 + 2;", &mut gc);
+        let mut value = xlang_vm_core::executor::vm::call_lambda_with_timeout(
+            &mut lambda,
+            &mut default_args_tuple,
+            std::time::Duration::from_secs(5),
+            &mut gc,
+        )
+        .expect("Failed to run lambda")
+        .expect("Lambda should finish before the timeout");
+        assert_eq!(value.as_const_type::<VMInt>().value, 42);
+        value.drop_ref();
+        lambda.drop_ref();
+        default_args_tuple.drop_ref();
+
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_pool_call_lambda() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut main = run_code("return (a?, b?) -> a + b;", &mut gc);
+        let mut add = main.as_type::<VMLambda>().result.clone_ref();
+        main.drop_ref();
+
+        let mut coroutine_pool = VMCoroutinePool::new(false);
+        let mut three = gc.new_object(VMInt::new(3));
+        let mut four = gc.new_object(VMInt::new(4));
+        let mut args = gc.new_object(VMTuple::new(&mut vec![&mut three, &mut four]));
+        let mut result = coroutine_pool
+            .call_lambda(&mut add, &mut args, &mut gc)
+            .expect("Failed to call lambda")
+            .expect("Lambda should finish without a hook");
+        assert_eq!(result.as_const_type::<VMInt>().value, 7);
+        result.drop_ref();
+
+        // 同一个 lambda 可以被再次调用
+        let mut result = coroutine_pool
+            .call_lambda(&mut add, &mut args, &mut gc)
+            .expect("Failed to call lambda again")
+            .expect("Lambda should finish without a hook");
+        assert_eq!(result.as_const_type::<VMInt>().value, 7);
+        result.drop_ref();
+
+        for obj in [&mut add, &mut three, &mut four, &mut args] {
+            obj.drop_ref();
+        }
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    fn greet(
+        _self_object: Option<&mut GCRef>,
+        capture: Option<&mut GCRef>,
+        args: &mut GCRef,
+        gc: &mut xlang_vm_core::gc::GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let greeting = try_to_string_vmobject(capture.unwrap(), None)?;
+        let name = try_to_string_vmobject(&mut args.as_type::<VMTuple>().values[0], None)?;
+        Ok(gc.new_object(VMString::new(&format!("{}, {}!", greeting, name))))
+    }
+
+    #[test]
+    fn test_native_module_builder() {
+        let code = r#"
+        @required greeter;
+        return greeter.greet("XLang");
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut greeting = gc.new_object(VMString::new("Hello"));
+        let mut builder = NativeModuleBuilder::new("greeter");
+        builder.function_with_capture("greet", greet, &greeting);
+
+        let (mut lambda, mut default_args_tuple) = build_main_lambda(code, &mut gc);
+        let mut coroutine_pool = VMCoroutinePool::new(false);
+        let id = coroutine_pool
+            .new_coroutine(&mut lambda.clone_ref(), &mut default_args_tuple, &mut gc)
+            .expect("Failed to create coroutine");
+        builder
+            .inject(
+                coroutine_pool
+                    .get_executor_mut(id)
+                    .unwrap()
+                    .get_context_mut(),
+                &mut gc,
+            )
+            .expect("Failed to inject module");
+        if let Err(mut e) = coroutine_pool.run_until_finished(&mut gc) {
+            panic!("Failed to execute code: {}", e.to_string());
+        }
+        let result = &mut lambda.as_type::<VMLambda>().result;
+        assert_eq!(
+            try_to_string_vmobject(result, None).unwrap(),
+            "Hello, XLang!"
+        );
+
+        let mut module = builder.build(&mut gc);
+        let repr = try_repr_vmobject(&mut module, None).unwrap();
+        assert!(repr.contains("<builtins>::greeter.greet"), "{}", repr);
+        module.drop_ref();
+        greeting.drop_ref();
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_run_one_step() {
+        let code = r#"
+        n := 0;
+        while (n < 100) {
+            n = n + 1;
+        };
+        return n;
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let (mut lambda, mut default_args_tuple) = build_main_lambda(code, &mut gc);
+
+        let mut coroutine_pool = xlang_vm_core::executor::vm::VMCoroutinePool::new(true);
+        coroutine_pool.steps_per_tick = 16;
+        lambda.clone_ref();
+        coroutine_pool
+            .new_coroutine(&mut lambda, &mut default_args_tuple, &mut gc)
+            .expect("Failed to create coroutine");
+
+        let mut calls = 0;
+        while coroutine_pool
+            .run_one_step(&mut gc)
+            .unwrap_or_else(|mut e| panic!("Failed to step code: {}", e.to_string()))
+        {
+            calls += 1;
+            // 两次调用之间协程尚未结束，结果仍为默认值
+            assert_eq!(
+                try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None).unwrap(),
+                "null"
+            );
+        }
+        assert!(calls > 1);
+
+        let repr = try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None)
+            .expect("Failed to repr result");
+        assert_eq!(repr, "100");
+        assert!(!coroutine_pool.run_one_step(&mut gc).unwrap());
+
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    // 统计已执行的指令数，每执行 pause_every 条指令暂停一次
+    struct CountingHook {
+        count: std::rc::Rc<std::cell::Cell<usize>>,
+        pause_every: Option<usize>,
+    }
+
+    impl ExecutorHook for CountingHook {
+        fn on_instruction(
+            &mut self,
+            _ip: usize,
+            _coroutine_id: isize,
+            _opcode: &ProcessedOpcode,
+            _executor: &VMExecutor,
+        ) -> HookAction {
+            self.count.set(self.count.get() + 1);
+            match self.pause_every {
+                Some(n) if self.count.get().is_multiple_of(n) => HookAction::Pause,
+                _ => HookAction::Continue,
+            }
+        }
+    }
+
+    #[test]
+    fn test_pool_call_lambda_paused_by_hook() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut main = run_code(
+            "return (n?) -> { i := 0; while (i < n) { i = i + 1; }; return i; };",
+            &mut gc,
+        );
+        let mut count_to = main.as_type::<VMLambda>().result.clone_ref();
+        main.drop_ref();
+
+        let mut coroutine_pool = VMCoroutinePool::new(false);
+        coroutine_pool.hook = Some(Box::new(CountingHook {
+            count: std::rc::Rc::new(std::cell::Cell::new(0)),
+            pause_every: Some(5),
+        }));
+        let mut ten = gc.new_object(VMInt::new(10));
+        let mut args = gc.new_object(VMTuple::new(&mut vec![&mut ten]));
+        let paused = coroutine_pool
+            .call_lambda(&mut count_to, &mut args, &mut gc)
+            .expect("Failed to call lambda");
+        assert!(paused.is_none());
+        // 暂停时协程仍在池中，状态不会被恢复
+        assert!(coroutine_pool.is_paused());
+        assert_eq!(coroutine_pool.executors.len(), 1);
+        assert!(matches!(
+            count_to.as_const_type::<VMLambda>().coroutine_status,
+            VMCoroutineStatus::Running
+        ));
+
+        while coroutine_pool.is_paused() {
+            coroutine_pool
+                .run_until_finished(&mut gc)
+                .expect("Failed to resume lambda");
+        }
+        assert!(coroutine_pool.executors.is_empty());
+        assert_eq!(
+            count_to
+                .as_const_type::<VMLambda>()
+                .result
+                .as_const_type::<VMInt>()
+                .value,
+            10
+        );
+
+        for obj in [&mut count_to, &mut ten, &mut args] {
+            obj.drop_ref();
+        }
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    // 返回 (执行的指令数, 暂停次数, 结果)
+    fn run_with_counting_hook(code: &str, pause_every: Option<usize>) -> (usize, usize, String) {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let (mut lambda, mut default_args_tuple) = build_main_lambda(code, &mut gc);
+        let count = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut coroutine_pool = xlang_vm_core::executor::vm::VMCoroutinePool::new(true);
+        coroutine_pool.hook = Some(Box::new(CountingHook {
+            count: count.clone(),
+            pause_every,
+        }));
+        lambda.clone_ref();
+        coroutine_pool
+            .new_coroutine(&mut lambda, &mut default_args_tuple, &mut gc)
+            .expect("Failed to create coroutine");
+
+        let mut pauses = 0;
+        loop {
+            coroutine_pool
+                .run_until_finished(&mut gc)
+                .unwrap_or_else(|mut e| panic!("Failed to run code: {}", e.to_string()));
+            if !coroutine_pool.is_paused() {
+                break;
+            }
+            pauses += 1;
+            // 暂停时协程保留在池中，结果尚未产生
+            assert_eq!(coroutine_pool.executors.len(), 1);
+            assert_eq!(
+                try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None).unwrap(),
+                "null"
+            );
+        }
+        let repr = try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None)
+            .expect("Failed to repr result");
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+        (count.get(), pauses, repr)
+    }
+
+    #[test]
+    fn test_executor_hook() {
+        let code = r#"
+        n := 0;
+        while (n < 10) {
+            n = n + 1;
+        };
+        return n;
+        "#;
+        let (count, pauses, repr) = run_with_counting_hook(code, None);
+        assert_eq!(repr, "10");
+        assert_eq!(pauses, 0);
+        // 每轮循环都会执行若干条指令
+        assert!(count > 10 * 3, "only {} instructions executed", count);
+
+        // 暂停后恢复不会重复执行或重复统计指令
+        let (paused_count, pauses, repr) = run_with_counting_hook(code, Some(7));
+        assert_eq!(repr, "10");
+        assert_eq!(paused_count, count);
+        assert_eq!(pauses, count / 7);
+    }
+
+    #[test]
+    fn test_traceback_lists_all_frames() {
+        let code = r#"inner := () -> {
+    raise "boom";
+};
+middle := () -> inner();
+outer := () -> {
+    return middle();
+};
+outer();
+"#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let (mut lambda, mut default_args_tuple) = build_main_lambda(code, &mut gc);
+        let mut coroutine_pool = xlang_vm_core::executor::vm::VMCoroutinePool::new(false);
+        lambda.clone_ref();
+        coroutine_pool
+            .new_coroutine(&mut lambda, &mut default_args_tuple, &mut gc)
+            .expect("Failed to create coroutine");
+        let mut err = coroutine_pool
+            .run_until_finished(&mut gc)
+            .expect_err("raise without boundary should crash");
+
+        let frames = err.traceback().expect("Missing traceback");
+        let frames: Vec<(String, usize, String)> = frames
+            .iter()
+            .map(|frame| {
+                let position = frame.position.as_ref().expect("Frame without position");
+                (
+                    frame.signature.clone(),
+                    position.line,
+                    position.source_line.trim().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].0, "__main__");
+        assert_eq!(
+            frames
+                .iter()
+                .map(|(_, line, source)| (*line, source.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (8, "outer();"),
+                (6, "return middle();"),
+                (4, "middle := () -> inner();"),
+                (2, "raise \"boom\";"),
+            ]
+        );
+        assert_eq!(err.source_position().unwrap().line, 2);
+
+        err.consume_ref();
+        lambda.drop_ref();
+        gc.collect();
+    }
+
+    #[test]
+    fn test_into_variable_error_keeps_kind_through_wrappers() {
+        let code = r#"
+        f := () -> (1, 2)["x"];
+        f();
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let (mut lambda, mut default_args_tuple) = build_main_lambda(code, &mut gc);
+        let mut coroutine_pool = xlang_vm_core::executor::vm::VMCoroutinePool::new(false);
+        lambda.clone_ref();
+        coroutine_pool
+            .new_coroutine(&mut lambda, &mut default_args_tuple, &mut gc)
+            .expect("Failed to create coroutine");
+        let err = coroutine_pool
+            .run_until_finished(&mut gc)
+            .expect_err("indexing a tuple with a string should fail");
+        assert!(err.source_position().is_some());
+        assert!(err.traceback().is_some());
+
+        // native 回调中的错误经 into_variable_error 向上传递时保留原本的类别
+        match err.into_variable_error() {
+            mut inner @ VMVariableError::TypeError(..) => inner.consume_ref(),
+            mut other => panic!("expected TypeError, got {}", other.to_string()),
+        }
+        lambda.drop_ref();
+        gc.collect();
+    }
+
+    #[test]
+    fn test_self_referential_method_is_collected() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut params = gc.new_object(VMTuple::new(&mut vec![]));
+        let mut result = gc.new_object(VMNull::new());
+        let mut method = gc.new_object(VMLambda::new(
+            0,
+            "<test>::method".to_string(),
+            &mut params,
+            None,
+            None,
+            &mut VMLambdaBody::VMNativeFunction(|_, _, args, _| Ok(args.clone_ref())),
+            &mut result,
+            false,
+        ));
+        let mut name = gc.new_object(VMString::new("method"));
+        let mut named = gc.new_object(VMNamed::new(&mut name, &mut method));
+        let mut object = gc.new_object(VMTuple::new(&mut vec![&mut named]));
+        for obj in [&mut params, &mut result, &mut name, &mut named] {
+            obj.drop_ref();
+        }
+
+        VMTuple::set_lambda_self(&mut object);
+        // 方法强引用所属对象，对象与方法之间形成循环
+        assert_eq!(object.get_const_traceable().ref_count, 1);
+        assert!(method.as_const_type::<VMLambda>().self_object.as_ref() == Some(&object));
+
+        // 方法仍被外部持有时，对象随方法一起存活
+        object.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 6);
+
+        // 外部引用全部释放后，循环由完整回收清理
+        method.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_bound_method_call() {
+        let code = r#"
+        obj := bind (x => 41, get => () -> self.x + 1);
+        return obj.get();
+        "#;
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+
+        let repr = try_repr_vmobject(&mut lambda.as_type::<VMLambda>().result, None)
+            .expect("Failed to repr result");
+        assert_eq!(repr, "42");
+
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_gc_object_threshold_bounds_live_objects() {
+        use xlang_vm_core::gc::GCConfig;
+
+        let mut gc = xlang_vm_core::gc::GCSystem::new(Some(GCConfig {
+            object_threshold: Some(10_000),
+            maximum_allocation_size: usize::MAX, // 只依靠对象数量触发
+            ..GCConfig::default()
+        }));
+        gc.check_and_collect(); // 消耗初始的回收标记
+
+        let mut kept = gc.new_object(VMInt::new(-1));
+        let mut peak = 0;
+        for i in 0..2_000_000 {
+            let mut value = gc.new_object(VMInt::new(i));
+            value.drop_ref();
+            // 与协程池一致，在每一步之后的安全点检查是否需要回收
+            gc.check_and_collect();
+            peak = peak.max(gc._count());
+        }
+        assert!(
+            peak <= 10_001,
+            "peak object count {} exceeds threshold",
+            peak
+        );
+        assert!(gc.collection_count() >= 2_000_000 / 10_000);
+        assert_eq!(kept.as_const_type::<VMInt>().value, -1);
+
+        kept.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_gc_default_config_does_not_collect_by_object_count() {
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        gc.check_and_collect();
+        let collections = gc.collection_count();
+        for i in 0..10_000 {
+            let mut value = gc.new_object(VMInt::new(i));
+            value.drop_ref();
+            gc.check_and_collect();
+        }
+        // 默认只按分配字节数触发，10000 个整数远未达到 4MB
+        assert_eq!(gc.collection_count(), collections);
+        assert_eq!(gc._count(), 10_000);
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_bytecode_version_check() {
+        let package = compile_package("return 1 + 2;");
+        let path =
+            std::env::temp_dir().join(format!("xlang_bytecode_version_{}.xbc", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        package
+            .write_to_file(path_str)
+            .expect("Failed to write bytecode");
+        let loaded = VMInstructionPackage::read_from_file(path_str).expect("Failed to load");
+        assert_eq!(loaded.get_code(), package.get_code());
+
+        // 版本号紧跟在 4 字节 magic 之后（小端）
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[4] = bytes[4].wrapping_add(1);
+        std::fs::write(&path, &bytes).unwrap();
+        let err = VMInstructionPackage::read_from_file(path_str)
+            .expect_err("Version mismatch should be rejected");
+        let expected = format!(
+            "bytecode version mismatch: file={} runtime={}",
+            xlang_vm_core::instruction_set::BYTECODE_VERSION + 1,
+            xlang_vm_core::instruction_set::BYTECODE_VERSION
+        );
+        assert_eq!(err.to_string(), expected);
+
+        bytes[0] = b'?';
+        std::fs::write(&path, &bytes).unwrap();
+        let err = VMInstructionPackage::read_from_file(path_str)
+            .expect_err("Bad magic should be rejected");
+        assert!(err.to_string().contains("bad magic number"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_embedded_bytecode() {
+        let package = compile_package("foo := (a?, b?) -> a * b; return foo(6, 7);");
+        let bytes = package.to_bytes().expect("Failed to serialize");
+        let loaded = VMInstructionPackage::from_bytes(&bytes).expect("Failed to load from bytes");
+        assert_eq!(loaded.get_code(), package.get_code());
+        assert_eq!(loaded.get_table(), package.get_table());
+
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let (mut lambda, mut default_args_tuple) = build_package_lambda(&loaded, &mut gc);
+        let mut coroutine_pool = VMCoroutinePool::new(true);
+        lambda.clone_ref();
+        coroutine_pool
+            .new_coroutine(&mut lambda, &mut default_args_tuple, &mut gc)
+            .expect("Failed to create coroutine");
+        if let Err(mut e) = coroutine_pool.run_until_finished(&mut gc) {
+            panic!("Failed to execute code: {}", e.to_string());
+        }
+        let result = &lambda.as_const_type::<VMLambda>().result;
+        assert_eq!(result.as_const_type::<VMInt>().value, 42);
+        lambda.drop_ref();
+
+        assert!(VMInstructionPackage::from_bytes(&bytes[..4]).is_err());
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let package = compile_package("x := 42; return x;");
+        let instructions = package.disassemble();
+        let first = &instructions[0];
+        assert_eq!(first.index, 0);
+        assert_eq!(first.mnemonic, "ResetStack");
+        assert_eq!(first.function_name.as_deref(), Some("__main__"));
+        assert!(instructions
+            .iter()
+            .any(|inst| inst.mnemonic == "LoadInt64" && inst.operands == ["42"]));
+        assert!(instructions
+            .iter()
+            .any(|inst| inst.mnemonic == "StoreVar" && inst.operands == ["\"x\""]));
+    }
+
+    fn mnemonics(package: &VMInstructionPackage) -> Vec<String> {
+        package
+            .disassemble()
+            .into_iter()
+            .map(|inst| inst.mnemonic)
+            .collect()
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        let package = compile_package("return 2 + 3 * 4;");
+        assert!(!mnemonics(&package)
+            .iter()
+            .any(|m| m == "BinaryAdd" || m == "BinaryMul"));
+        assert!(package
+            .disassemble()
+            .iter()
+            .any(|inst| inst.mnemonic == "LoadInt64" && inst.operands == ["14"]));
+
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        for (code, expected) in [
+            ("return 2 + 3 * 4;", "14"),
+            ("return 10 - 3 - 2;", "5"),
+            ("return 1 + 0.5 * 3;", "2.5"),
+        ] {
+            let mut lambda = run_code(code, &mut gc);
+            let result = &mut lambda.as_type::<VMLambda>().result;
+            assert_eq!(try_repr_vmobject(result, None).unwrap(), expected);
+            lambda.drop_ref();
+        }
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+
+        // 溢出的整数运算保留到运行时
+        let package = compile_package("return 9223372036854775807 + 1;");
+        assert!(mnemonics(&package).iter().any(|m| m == "BinaryAdd"));
+    }
+
+    #[test]
+    fn test_dead_code_after_return_is_removed() {
+        let code = r#"
+        f := () -> {
+            return 1;
+            unreachable_value := "never stored";
+            return unreachable_value;
+        };
+        return f();
+        "#;
+        let package = compile_package(code);
+        let instructions = package.disassemble();
+        assert!(!instructions
+            .iter()
+            .any(|inst| inst.mnemonic == "StoreVar" && inst.operands == ["\"unreachable_value\""]));
+        assert!(!instructions
+            .iter()
+            .any(|inst| inst.operands == ["\"never stored\""]));
+
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut lambda = run_code(code, &mut gc);
+        let result = &mut lambda.as_type::<VMLambda>().result;
+        assert_eq!(result.as_const_type::<VMInt>().value, 1);
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    fn translate_ir(instructions: Vec<IR>, entries: &[(&str, usize)]) -> VMInstructionPackage {
+        let ir_package = IRPackage {
+            instructions: instructions
+                .into_iter()
+                .map(|ir| (DebugInfo { code_position: 0 }, ir))
+                .collect(),
+            function_ips: entries
+                .iter()
+                .map(|(name, ip)| (name.to_string(), *ip))
+                .collect(),
+            source: None,
+        };
+        let mut translator = xlang_vm_core::ir_translator::IRTranslator::new(&ir_package);
+        translator
+            .translate()
+            .expect("Failed to translate IR package");
+        translator.get_result()
+    }
+
+    #[test]
+    fn test_peephole_removes_push_pop_and_double_negation() {
+        let package = translate_ir(
+            vec![
+                IR::LoadInt(1),
+                IR::Pop,
+                IR::LoadBool(false),
+                IR::JumpIfFalseOffset(2),
+                IR::LoadInt(100),
+                IR::Return,
+                IR::LoadInt(42),
+                IR::UnaryOp(IROperation::Subtract),
+                IR::UnaryOp(IROperation::Subtract),
+                IR::Return,
+            ],
+            &[("__main__", 0), ("helper", 6)],
+        );
+        let instructions = package.disassemble();
+        let mnemonics: Vec<&str> = instructions
+            .iter()
+            .map(|inst| inst.mnemonic.as_str())
+            .collect();
+        assert_eq!(
+            mnemonics,
+            [
+                "LoadBool",
+                "JumpIfFalse",
+                "LoadInt64",
+                "Return",
+                "LoadInt64",
+                "Return"
+            ]
+        );
+        // 函数入口与跳转目标都指向 LoadInt64 42
+        let target = &instructions[4];
+        assert_eq!(target.operands, ["42"]);
+        assert_eq!(target.function_name.as_deref(), Some("helper"));
+
+        let mut gc = xlang_vm_core::gc::GCSystem::new(None);
+        let mut default_args_tuple = gc.new_object(VMTuple::new(&mut vec![]));
+        let mut default_result = gc.new_object(VMNull::new());
+        let mut lambda_body = gc.new_object(VMInstructions::new(&package));
+        let mut lambda = gc.new_object(VMLambda::new(
+            0,
+            "__main__".to_string(),
+            &mut default_args_tuple,
+            None,
+            None,
+            &mut VMLambdaBody::VMInstruction(lambda_body.clone()),
+            &mut default_result,
+            false,
+        ));
+        lambda_body.drop_ref();
+        default_result.drop_ref();
+        let mut coroutine_pool = xlang_vm_core::executor::vm::VMCoroutinePool::new(true);
+        lambda.clone_ref();
+        coroutine_pool
+            .new_coroutine(&mut lambda, &mut default_args_tuple, &mut gc)
+            .expect("Failed to create coroutine");
+        coroutine_pool
+            .run_until_finished(&mut gc)
+            .unwrap_or_else(|e| panic!("Failed to execute code: {:?}", e));
+        let result = &lambda.as_const_type::<VMLambda>().result;
+        assert_eq!(result.as_const_type::<VMInt>().value, 42);
+        lambda.drop_ref();
+        gc.collect();
+        assert_eq!(gc._count(), 0);
+    }
+
+    #[test]
+    fn test_peephole_keeps_jump_targets() {
+        // Pop 是跳转目标时不能删除前面的字面量
+        let package = translate_ir(
+            vec![
+                IR::LoadInt(1),
+                IR::LoadBool(true),
+                IR::JumpIfFalseOffset(1),
+                IR::LoadInt(2),
+                IR::Pop,
+                IR::Return,
+            ],
+            &[("__main__", 0)],
+        );
+        let mnemonics: Vec<String> = package
+            .disassemble()
+            .into_iter()
+            .map(|inst| inst.mnemonic)
+            .collect();
+        assert_eq!(
+            mnemonics,
+            [
+                "LoadInt64",
+                "LoadBool",
+                "JumpIfFalse",
+                "LoadInt64",
+                "Pop",
+                "Return"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assert_uses_if_truthiness() {
+        let code = r#"
+        @required io;
+        @required types;
+        io.assert(types.bytes("x"), "non-empty bytes are truthy");
+        io.assert(0..3, "non-empty ranges are truthy");
+        io.assert((), "empty tuples are falsy");
+        "#;
+        let report = script_error(code);
+        assert!(report.contains("Assertion failed: empty tuples are falsy"));
+        assert!(!report.contains("TypeError"));
+    }
+
+    #[test]
+    fn test_sleeping_coroutines_interleave() {
+        // 后启动但休眠更短的协程应先醒来，且两次休眠的总耗时接近较长的一次
+        let code = r#"
+        @required io;
+        @required time;
+        start := time.timestamp();
+        slow := () -> { time.sleep(0.3)(); return time.timestamp(); };
+        fast := () -> { time.sleep(0.1)(); return time.timestamp(); };
+        async slow();
+        async fast();
+        await slow;
+        await fast;
+        io.assert(valueof fast < valueof slow, "fast should wake first");
+        io.assert(time.timestamp() - start < 0.39, "sleeps should overlap");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_channel_with_huge_capacity() {
+        let code = r#"
+        @required io;
+        @required asyncio;
+        chan := asyncio.channel(1000000000000000);
+        asyncio.send(chan, 1)();
+        io.assert(asyncio.recv(chan)() == 1, "a huge capacity should not be preallocated");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_channel_producer_consumer() {
+        // 通道容量小于发送的值的数量，生产者必须等待消费者取走数据才能继续
+        let code = r#"
+        @required io;
+        @required asyncio;
+        chan := asyncio.channel(2);
+        producer := () -> {
+            i := 0;
+            while (i < 10) {
+                asyncio.send(chan, copy i)();
+                i = i + 1;
+            };
+            asyncio.send(chan, null)();
+        };
+        consumer := () -> {
+            received := ();
+            while (true) {
+                value := asyncio.recv(chan)();
+                if (value == null) { break };
+                received = received + (value,);
+            };
+            return received;
+        };
+        async producer();
+        async consumer();
+        await producer;
+        await consumer;
+        io.assert(valueof consumer == (0, 1, 2, 3, 4, 5, 6, 7, 8, 9), "values should arrive in order");
+        io.assert(typeof chan == "channel", "channel should report its type");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_type_name_matches_typeof() {
+        let code = r#"
+        @required io;
+        @required types;
+        values := (1, 1.5, "s", true, (1, 2), () -> 1, null, "a": 1, "a" => 1, 0..3, types.bytes("x"), types.dict((("k": 1, "j": 2),)));
+        i := 0;
+        while (i < lengthof values) {
+            value := values[i];
+            io.assert(types.type_name(value) == typeof value, "type_name should agree with typeof");
+            i = i + 1;
+        };
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_functional_map_filter_reduce_with_script_lambdas() {
+        let code = r#"
+        @required io;
+        @required functional;
+        values := (1, 2, 3, 4);
+        io.assert(functional.map(values, (x?) -> x * 2) == (2, 4, 6, 8), "map should double each element");
+        io.assert(functional.filter(values, (x?) -> x % 2 == 0) == (2, 4), "filter should keep even elements");
+        io.assert(functional.reduce(values, (acc?, x?) -> acc + x, 0) == 10, "reduce should sum the tuple");
+        empty := ();
+        io.assert(functional.map(empty, (x?) -> x * 2) == (), "empty tuples map to empty tuples");
+        io.assert(functional.reduce(empty, (acc?, x?) -> acc + x, 5) == 5, "reduce of empty returns init");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_functional_partial() {
+        let code = r#"
+        @required io;
+        @required functional;
+        add := (a?, b?) -> a + b;
+        add3 := functional.partial(add, (3,));
+        io.assert(add3(4) == 7, "remaining positional argument should fill b");
+        io.assert(add(1, 2) == 3, "original lambda should keep its defaults");
+        sub := (a?, b?) -> a - b;
+        minus10 := functional.partial(sub, (b => 10,));
+        io.assert(minus10(15) == 5, "named binding should leave a open");
+        obj := bind (x => 1, get => (y?) -> self.x + y);
+        get2 := functional.partial(obj.get, (2,));
+        io.assert(get2() == 3, "self should be preserved");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_functional_arity() {
+        let code = r#"
+        @required io;
+        @required functional;
+        io.assert(functional.arity((a?, b?) -> a + b) == 2, "two declared parameters");
+        io.assert(functional.arity(() -> 1) == 0, "no declared parameters");
+        io.assert(functional.arity(functional.map) == -1, "native functions are variadic");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_functional_fix_factorial() {
+        let code = r#"
+        @required io;
+        @required functional;
+        fact := functional.fix((self_fn?, n?) -> {
+            if (n <= 1) {
+                return 1;
+            };
+            return n * self_fn(n - 1);
+        });
+        io.assert(fact(5) == 120, "fix should pass the lambda itself as the first argument");
+        io.assert(fact(1) == 1, "base case");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_functional_fix_unbounded_recursion_overflows() {
+        let code = r#"
+        @required functional;
+        forever := functional.fix((self_fn?, n?) -> self_fn(n + 1));
+        forever(0);
+        "#;
+        let report = script_error(code);
+        assert!(report.contains("call depth limit exceeded"), "{}", report);
+    }
+
+    #[test]
+    fn test_recursion_through_native_callbacks_overflows() {
+        // 每次递归都经过 functional.map 进入新的协程池，应报告 StackOverflow 而不是耗尽宿主栈
+        let code = r#"
+        @required io;
+        @required functional;
+        f := (n?, fm => functional.map) -> {
+            if (n == 0) { return 0; };
+            return fm((n - 1,), this)[0] + 1;
+        };
+        io.assert(f(50) == 50, "shallow recursion through map should work");
+        f(100000);
+        "#;
+        let report = script_error(code);
+        assert!(report.contains("call depth limit exceeded"), "{}", report);
+        assert!(!report.contains("Assertion failed"), "{}", report);
+    }
+
+    #[test]
+    fn test_functional_memoize() {
+        let code = r#"
+        @required io;
+        @required functional;
+        calls := 0;
+        square := (n?) -> &calls {
+            $this = $this + 1;
+            return n * n;
+        };
+        cached := functional.memoize(square);
+        io.assert(cached(3) == 9, "first call computes the result");
+        io.assert(cached(3) == 9, "second call returns the cached result");
+        io.assert(calls == 1, "underlying lambda runs once per distinct input");
+        io.assert(cached(4) == 16, "a new input misses the cache");
+        io.assert(cached(3) == 9 and cached(4) == 16, "both results stay cached");
+        io.assert(calls == 2, "each distinct input is computed once");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_bound_method_outlives_its_object() {
+        let code = r#"
+        @required io;
+        @required gc;
+        make := (v?) -> { return bind (x => v, get => () -> self.x) };
+        f := make(1).get;
+        make(2);
+        io.assert(f() == 1, "bound method sees its own object");
+        gc.collect();
+        io.assert(f() == 1, "the object stays alive while the method is reachable");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_control_try_catches_errors() {
+        let code = r#"
+        @required io;
+        @required control;
+        caught := control.try(() -> (1, 2)["x"], (err?) -> err);
+        io.assert(caught.kind == "TypeError", "indexing with a string should be a TypeError");
+        io.assert(caught.value == "x", "the error should carry the offending value");
+        caught = control.try(() -> 1 + "x", (err?) -> err);
+        io.assert(caught.kind == "ValueError", "mismatched operands are reported as ValueError");
+        recovered := control.try(() -> 1 + "x", (err?) -> 0);
+        io.assert(recovered == 0, "the handler result replaces the failed body");
+        io.assert(control.try(() -> 1 + 2, (err?) -> 0) == 3, "body result is returned");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_frozen_tuple_rejects_assignment() {
+        let code = r#"
+        @required io;
+        @required types;
+        @required control;
+        config := (host => "localhost", limits => (retries => 3,));
+        io.assert(types.freeze(config) == config, "freeze returns its argument");
+        types.freeze(config);
+        io.assert(types.is_frozen(config.limits), "freezing is recursive");
+        update_host := () -> &config { $this.host = "example.com" };
+        caught := control.try(update_host, (err?) -> err);
+        io.assert(caught.kind == "AssignError", "assigning to a frozen member fails");
+        update_retries := () -> &config { $this.limits.retries = 5 };
+        caught = control.try(update_retries, (err?) -> err);
+        io.assert(caught.kind == "AssignError", "nested members are frozen too");
+        io.assert(config.host == "localhost", "the frozen value is unchanged");
+        bumps := 0;
+        handlers := (bump => () -> &bumps { $this = $this + 1 },);
+        types.freeze(handlers);
+        handlers.bump();
+        io.assert(bumps == 1, "lambda captures are not frozen");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_frozen_values_reject_in_place_mutation() {
+        let code = r#"
+        @required io;
+        @required types;
+        @required control;
+        @required bytes;
+        data := types.freeze(types.bytes("abc"));
+        splice_data := () -> &data { bytes.splice($this, 0, 1, types.bytes("z")) };
+        caught := control.try(splice_data, (err?) -> err);
+        io.assert(caught.kind == "AssignError", "splicing frozen bytes fails");
+        io.assert(data == types.bytes("abc"), "the frozen bytes are unchanged");
+        methods := (get => () -> self.name,);
+        types.freeze(methods);
+        pair := (name => "owner",) : methods;
+        rebind := () -> &pair { bind $this };
+        caught = control.try(rebind, (err?) -> err);
+        io.assert(caught.kind == "AssignError", "binding self to frozen methods fails");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_freeze_reaches_shared_members() {
+        let code = r#"
+        @required io;
+        @required types;
+        @required asyncio;
+        shared := (1, 2);
+        left := (shared,);
+        right := (shared, 3);
+        types.freeze(left);
+        io.assert(not types.is_frozen(right), "unrelated tuples stay unfrozen");
+        io.assert(types.is_frozen(right[0]), "shared members are frozen through any tuple");
+        other := (4, 5);
+        copied := (deepcopy other,);
+        types.freeze(copied);
+        io.assert(not types.is_frozen(other), "freezing a deep copy leaves the original alone");
+        chan := asyncio.channel(2);
+        holder := (chan => chan,);
+        types.freeze(holder);
+        asyncio.send(holder.chan, 1)();
+        io.assert(asyncio.recv(holder.chan)() == 1, "frozen channels still send and receive");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_control_try_catches_errors_from_native_callbacks() {
+        let code = r#"
+        @required io;
+        @required control;
+        @required functional;
+        caught := control.try(() -> functional.map((1, 2), (x?) -> x + "a"), (err?) -> err);
+        io.assert(caught.kind == "ValueError", "the callback's error kind reaches the handler");
+        caught = control.try(() -> functional.map((1, 2), (x?) -> (1, 2)["x"]), (err?) -> err);
+        io.assert(caught.kind == "TypeError", "the callback's error kind reaches the handler");
+        io.assert(caught.value == "x", "the callback's error keeps its value");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_control_throw_is_caught_by_try() {
+        let code = r#"
+        @required io;
+        @required control;
+        caught := control.try(() -> control.throw("disk full"), (err?) -> err);
+        io.assert(caught.kind == "Error", "throw uses the generic Error kind");
+        io.assert(caught.value == "disk full", "throw carries its message");
+        check := (n?) -> {
+            if (n < 0) {
+                control.raise_with("NegativeInput", (input => n,));
+            };
+            return n;
+        };
+        caught = control.try(() -> check(-3), (err?) -> err);
+        io.assert(caught.kind == "NegativeInput", "raise_with uses the given kind");
+        io.assert(caught.value.input == -3, "raise_with carries the given value");
+        io.assert(control.try(() -> check(2), (err?) -> -1) == 2, "no error, no handler");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_uncaught_throw_reports_message() {
+        let code = r#"
+        @required control;
+        control.throw("custom failure");
+        "#;
+        let message = script_error(code);
+        assert!(message.contains("Error: custom failure"), "{}", message);
+    }
+
+    #[test]
+    fn test_native_error_names_function() {
+        let code = r#"
+        @required fs;
+        fs.read("__xlang_missing_file__.txt");
+        "#;
+        let message = script_error(code);
+        assert!(message.contains("<builtins>::fs.read: "), "{}", message);
+        assert!(
+            message.contains("__xlang_missing_file__.txt"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_asyncio_with_timeout() {
+        let code = r#"
+        @required io;
+        @required asyncio;
+        spin := () -> { n := 0; while (true) { n = n + 1; }; return n; };
+        io.assert(asyncio.with_timeout(spin, 50) == null, "spin should time out");
+        io.assert(asyncio.with_timeout(() -> 42, 1000) == 42, "quick lambda should finish");
+        "#;
+        assert_script_ok(code);
+    }
+
+    #[test]
+    fn test_integer_modulo_by_zero_is_catchable() {
+        let code = r#"
+        @required io;
+        result := boundary { 5 % 0 };
+        io.assert((aliasof result)[1] == "Err", "5 % 0 should raise a catchable error");
+        io.assert(5 % 3 == 2, "modulo of non-zero divisor");
+        io.assert(-7 % 2 == -1, "remainder keeps the sign of the dividend");
+        "#;
+        assert_script_ok(code);
+    }
 }
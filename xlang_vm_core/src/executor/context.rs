@@ -16,6 +16,7 @@ pub enum ContextFrameType {
 pub struct Context {
     pub frames: Vec<(HashMap<String, GCRef>, ContextFrameType, usize, bool)>, // vars, is_function_frame, function_code_position, is_hidden_frame
     pub stack_pointers: Vec<usize>,
+    function_depth: usize, // 当前函数帧的数量，即调用深度
 }
 
 #[derive(Debug)]
@@ -55,9 +56,14 @@ impl Context {
         Context {
             frames: Vec::new(),
             stack_pointers: Vec::new(),
+            function_depth: 0,
         }
     }
 
+    pub fn function_depth(&self) -> usize {
+        self.function_depth
+    }
+
     pub fn drop_all_frames(&mut self) {
         // 处理所有帧的情况
         while !self.frames.is_empty() {
@@ -67,6 +73,7 @@ impl Context {
             }
         }
         self.stack_pointers.clear();
+        self.function_depth = 0;
     }
 
     pub fn new_frame(
@@ -76,6 +83,9 @@ impl Context {
         function_code_position: usize,
         is_hidden_frame: bool,
     ) {
+        if frame_type == ContextFrameType::FunctionFrame {
+            self.function_depth += 1;
+        }
         self.frames.push((
             HashMap::default(),
             frame_type,
@@ -156,7 +166,9 @@ impl Context {
         let stack_pointer = self.stack_pointers.pop().unwrap_or(0);
 
         // 3. 安全地更新帧和栈指针
-        self.frames.pop();
+        if let Some((_, ContextFrameType::FunctionFrame, _, _)) = self.frames.pop() {
+            self.function_depth -= 1;
+        }
 
         // 4. 离线变量（在数据结构已更新后）
 
@@ -228,6 +240,21 @@ impl Context {
         Err(ContextError::NoVariable(name.to_string()))
     }
 
+    // 由内向外遍历当前可见的变量，被内层同名变量遮蔽的外层变量会被跳过
+    pub fn iter_vars(&self) -> impl Iterator<Item = (&String, &GCRef)> {
+        self.frames
+            .iter()
+            .enumerate()
+            .rev()
+            .flat_map(move |(depth, (vars, _, _, _))| {
+                vars.iter().filter(move |(name, _)| {
+                    !self.frames[depth + 1..]
+                        .iter()
+                        .any(|(inner, _, _, _)| inner.contains_key(*name))
+                })
+            })
+    }
+
     pub fn format_context(&mut self, stack: &mut Vec<VMStackObject>) -> String {
         use colored::*;
 
@@ -2,4 +2,5 @@ pub mod context;
 pub mod vm;
 pub mod variable;
 pub mod ffi;
-pub mod vm_instructions;
\ No newline at end of file
+pub mod vm_instructions;
+pub mod native_module;
\ No newline at end of file
@@ -0,0 +1,142 @@
+use crate::executor::context::{Context, ContextError};
+use crate::executor::variable::{
+    VMKeyVal, VMLambda, VMLambdaBody, VMNull, VMString, VMTuple, VMVariableError,
+};
+use crate::gc::{GCRef, GCSystem};
+
+/**
+ * native 函数的签名，与内置模块中的函数相同
+ * self_object: 作为方法调用时绑定的 self
+ * capture: 创建 lambda 时指定的捕获对象
+ * args: 参数元组
+ */
+pub type NativeFunction = fn(
+    Option<&mut GCRef>,
+    Option<&mut GCRef>,
+    &mut GCRef,
+    &mut GCSystem,
+) -> Result<GCRef, VMVariableError>;
+
+/**
+ * 创建一个以 native 函数为函数体的 VMLambda
+ * signature: lambda 的签名，会出现在 repr 与调用栈中
+ * capture: 调用时作为第二个参数传入的捕获对象
+ *
+ * 注意：
+ * + 返回值持有一个新的引用，调用者负责 drop_ref()
+ */
+pub fn create_native_lambda(
+    signature: String,
+    native_fn: NativeFunction,
+    capture: Option<&mut GCRef>,
+    gc_system: &mut GCSystem,
+) -> GCRef {
+    let mut params = gc_system.new_object(VMTuple::new(&mut vec![]));
+    let mut result = gc_system.new_object(VMNull::new());
+    let lambda = gc_system.new_object(VMLambda::new(
+        0,
+        signature,
+        &mut params,
+        capture,
+        None,
+        &mut VMLambdaBody::VMNativeFunction(native_fn),
+        &mut result,
+        false,
+    ));
+    params.drop_ref();
+    result.drop_ref();
+    lambda
+}
+
+/**
+ * 供宿主程序注册自定义内置模块，构建出的模块与标准库模块相同，是 (name : lambda, ...) 元组
+ * 函数的签名为 `<builtins>::模块名.函数名`
+ *
+ * 例如：
+ * ```ignore
+ * let mut builder = NativeModuleBuilder::new("greeter");
+ * builder.function("greet", greet);
+ * builder.inject(context, gc_system)?;
+ * ```
+ * 之后脚本中即可通过 `greeter.greet(...)` 调用
+ */
+pub struct NativeModuleBuilder {
+    name: String,
+    functions: Vec<(String, NativeFunction, Option<GCRef>)>,
+}
+
+impl NativeModuleBuilder {
+    pub fn new(name: &str) -> Self {
+        NativeModuleBuilder {
+            name: name.to_string(),
+            functions: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // 注册一个函数，同名函数以最后一次注册的为准
+    pub fn function(&mut self, name: &str, native_fn: NativeFunction) -> &mut Self {
+        self.add(name, native_fn, None)
+    }
+
+    /**
+     * 注册一个带捕获对象的函数，调用时 capture 作为第二个参数传入
+     *
+     * 注意：
+     * + builder 不持有 capture 的引用，调用者需保证其在 build() 或 inject() 之前有效
+     * + 构建出的 lambda 会持有 capture 的引用
+     */
+    pub fn function_with_capture(
+        &mut self,
+        name: &str,
+        native_fn: NativeFunction,
+        capture: &GCRef,
+    ) -> &mut Self {
+        self.add(name, native_fn, Some(capture.clone()))
+    }
+
+    fn add(&mut self, name: &str, native_fn: NativeFunction, capture: Option<GCRef>) -> &mut Self {
+        self.functions.retain(|(existing, _, _)| existing != name);
+        self.functions.push((name.to_string(), native_fn, capture));
+        self
+    }
+
+    /**
+     * 按注册顺序构建模块元组
+     * 返回值持有一个新的引用，调用者负责 drop_ref()
+     */
+    pub fn build(&self, gc_system: &mut GCSystem) -> GCRef {
+        let mut module = gc_system.new_object(VMTuple::new(&mut vec![]));
+        for (name, native_fn, capture) in &self.functions {
+            let mut capture = capture.clone();
+            let mut func_ref = create_native_lambda(
+                format!("<builtins>::{}.{}", self.name, name),
+                *native_fn,
+                capture.as_mut(),
+                gc_system,
+            );
+            let mut key = gc_system.new_object(VMString::new(name));
+            let mut kv_pair = gc_system.new_object(VMKeyVal::new(&mut key, &mut func_ref));
+            let _ = module.as_type::<VMTuple>().append(&mut kv_pair);
+            func_ref.drop_ref();
+            key.drop_ref();
+            kv_pair.drop_ref();
+        }
+        module
+    }
+
+    // 构建模块并以模块名注入到 context 的当前帧中
+    pub fn inject(
+        &self,
+        context: &mut Context,
+        gc_system: &mut GCSystem,
+    ) -> Result<(), ContextError> {
+        let mut module = self.build(gc_system);
+        let result = context.let_var(&self.name, &mut module, gc_system);
+        module.drop_ref();
+        result
+    }
+}
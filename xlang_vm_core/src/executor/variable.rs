@@ -1,10 +1,12 @@
 use super::{
     super::gc::{GCObject, GCRef, GCSystem, GCTraceable},
     ffi::vm_clambda_loading::{self, CLambda},
+    vm::call_lambda_sync,
 };
 use crate::instruction_set::VMInstructionPackage;
 use base64::{self, Engine};
 use colored::Colorize;
+use rustc_hash::{FxHashMap, FxHasher};
 /**
  * 约定：
  * - vmobject：虚拟机对象
@@ -12,7 +14,7 @@ use colored::Colorize;
  * - 任何new对象的行为都需要使用gc_system，并且会产生一个native_gcref_object_count，虚拟机必须在某处drop_ref直到为0
  *
  */
-use std::{fmt::Debug, sync::Arc};
+use std::{collections::VecDeque, fmt::Debug, hash::Hasher, sync::Arc};
 
 #[derive(Debug, Clone)]
 pub enum VMStackObject {
@@ -33,6 +35,8 @@ pub enum VMVariableError {
     ReferenceError(GCRef, String),
     OverflowError(GCRef, GCRef, String),
     DetailedError(String),
+    InNativeFunction(String, Box<VMVariableError>), // native 函数的签名，函数内抛出的错误
+    Raised(String, GCRef), // 脚本主动抛出的错误：类别，携带的值
 }
 
 impl VMVariableError {
@@ -90,6 +94,57 @@ impl VMVariableError {
                 try_repr_vmobject(other, None).unwrap_or(format!("{:?}", other)),
             ),
             VMVariableError::DetailedError(msg) => format!("DetailedError: {}", msg),
+            VMVariableError::InNativeFunction(signature, err) => {
+                format!("{}: {}", signature, err.to_string())
+            }
+            // 携带字符串时直接作为错误信息显示
+            VMVariableError::Raised(kind, value) if value.isinstance::<VMString>() => {
+                format!("{}: {}", kind, value.as_const_type::<VMString>().value)
+            }
+            VMVariableError::Raised(kind, value) => format!(
+                "{}: {}",
+                kind,
+                try_repr_vmobject(value, None).unwrap_or(format!("{:?}", value))
+            ),
+        }
+    }
+
+    // 错误的类别名，与 to_string 的前缀相同；native 函数包装的错误返回其内部错误的类别
+    pub fn kind(&self) -> &str {
+        match self {
+            VMVariableError::TypeError(..) => "TypeError",
+            VMVariableError::ValueError2Param(..) | VMVariableError::ValueError(..) => {
+                "ValueError"
+            }
+            VMVariableError::KeyNotFound(..) => "KeyNotFound",
+            VMVariableError::UnableToValueOf(..) => "UnableToValueOf",
+            VMVariableError::IndexNotFound(..) => "IndexNotFound",
+            VMVariableError::CopyError(..) => "CopyError",
+            VMVariableError::AssignError(..) => "AssignError",
+            VMVariableError::ReferenceError(..) => "ReferenceError",
+            VMVariableError::OverflowError(..) => "OverflowError",
+            VMVariableError::DetailedError(..) => "DetailedError",
+            VMVariableError::InNativeFunction(_, err) => err.kind(),
+            VMVariableError::Raised(kind, _) => kind,
+        }
+    }
+
+    // 错误所针对的对象（不增加引用计数），DetailedError 没有对应的对象
+    pub fn value(&mut self) -> Option<&mut GCRef> {
+        match self {
+            VMVariableError::TypeError(gc_ref, _)
+            | VMVariableError::ValueError2Param(gc_ref, _, _)
+            | VMVariableError::ValueError(gc_ref, _)
+            | VMVariableError::KeyNotFound(gc_ref, _)
+            | VMVariableError::UnableToValueOf(gc_ref)
+            | VMVariableError::IndexNotFound(gc_ref, _)
+            | VMVariableError::CopyError(gc_ref, _)
+            | VMVariableError::AssignError(gc_ref, _)
+            | VMVariableError::ReferenceError(gc_ref, _)
+            | VMVariableError::OverflowError(gc_ref, _, _)
+            | VMVariableError::Raised(_, gc_ref) => Some(gc_ref),
+            VMVariableError::DetailedError(_) => None,
+            VMVariableError::InNativeFunction(_, err) => err.value(),
         }
     }
 
@@ -129,15 +184,22 @@ impl VMVariableError {
                 gc_ref.drop_ref();
                 other.drop_ref();
             }
+            VMVariableError::InNativeFunction(_, err) => {
+                err.consume_ref();
+            }
+            VMVariableError::Raised(_, value) => {
+                value.drop_ref();
+            }
             _ => {}
         }
     }
 }
 
+// 字符串返回字符数而非字节数，与索引 / 切片使用的字符下标保持一致
 pub fn try_length_of_as_vmobject(value: &mut GCRef) -> Result<usize, VMVariableError> {
     if value.isinstance::<VMString>() {
         let string = value.as_const_type::<VMString>();
-        return Ok(string.value.len());
+        return Ok(string.value.chars().count());
     } else if value.isinstance::<VMTuple>() {
         let tuple = value.as_const_type::<VMTuple>();
         return Ok(tuple.values.len());
@@ -147,6 +209,12 @@ pub fn try_length_of_as_vmobject(value: &mut GCRef) -> Result<usize, VMVariableE
     } else if value.isinstance::<VMBytes>() {
         let bytes = value.as_const_type::<VMBytes>();
         return Ok(bytes.value.len());
+    } else if value.isinstance::<VMDict>() {
+        let dict = value.as_const_type::<VMDict>();
+        return Ok(dict.len());
+    } else if value.isinstance::<VMChannel>() {
+        let channel = value.as_const_type::<VMChannel>();
+        return Ok(channel.len());
     }
     Err(VMVariableError::TypeError(
         value.clone_ref(),
@@ -154,6 +222,72 @@ pub fn try_length_of_as_vmobject(value: &mut GCRef) -> Result<usize, VMVariableE
     ))
 }
 
+// typeof 使用的类型名，无法识别的类型返回 None
+pub fn type_name_of_vmobject(value: &GCRef) -> Option<&'static str> {
+    let name = if value.isinstance::<VMInt>() {
+        "int"
+    } else if value.isinstance::<VMFloat>() {
+        "float"
+    } else if value.isinstance::<VMString>() {
+        "string"
+    } else if value.isinstance::<VMBoolean>() {
+        "bool"
+    } else if value.isinstance::<VMTuple>() {
+        "tuple"
+    } else if value.isinstance::<VMLambda>() {
+        "lambda"
+    } else if value.isinstance::<VMNull>() {
+        "null"
+    } else if value.isinstance::<VMKeyVal>() {
+        "keyval"
+    } else if value.isinstance::<VMNamed>() {
+        "named"
+    } else if value.isinstance::<VMRange>() {
+        "range"
+    } else if value.isinstance::<VMWrapper>() {
+        "wrapper"
+    } else if value.isinstance::<VMInstructions>() {
+        "instructions"
+    } else if value.isinstance::<VMBytes>() {
+        "bytes"
+    } else if value.isinstance::<VMSet>() {
+        "set"
+    } else if value.isinstance::<VMDict>() {
+        "dict"
+    } else if value.isinstance::<VMChannel>() {
+        "channel"
+    } else {
+        return None;
+    };
+    Some(name)
+}
+
+// 统一的真值判断，if / while 等条件也使用同样的规则
+// 数值非零（NaN 为假）、字符串 / 字节串 / 元组 / 区间非空为真，null 为假，其余类型返回 TypeError
+pub fn try_to_bool_as_vmobject(value: &mut GCRef) -> Result<bool, VMVariableError> {
+    if value.isinstance::<VMBoolean>() {
+        return value.as_const_type::<VMBoolean>().to_bool();
+    } else if value.isinstance::<VMInt>() {
+        return value.as_const_type::<VMInt>().to_bool();
+    } else if value.isinstance::<VMFloat>() {
+        return value.as_const_type::<VMFloat>().to_bool();
+    } else if value.isinstance::<VMString>() {
+        return value.as_const_type::<VMString>().to_bool();
+    } else if value.isinstance::<VMBytes>() {
+        return Ok(!value.as_const_type::<VMBytes>().value.is_empty());
+    } else if value.isinstance::<VMTuple>() {
+        return Ok(!value.as_const_type::<VMTuple>().values.is_empty());
+    } else if value.isinstance::<VMRange>() {
+        return Ok(value.as_const_type::<VMRange>().len() > 0);
+    } else if value.isinstance::<VMNull>() {
+        return Ok(false);
+    }
+    Err(VMVariableError::TypeError(
+        value.clone_ref(),
+        "Cannot convert a value of this type to a boolean".to_string(),
+    ))
+}
+
 pub fn try_contains_as_vmobject(
     value: &mut GCRef,
     other: &mut GCRef,
@@ -173,15 +307,39 @@ pub fn try_contains_as_vmobject(
     } else if value.isinstance::<VMBytes>() {
         let bytes = value.as_type::<VMBytes>();
         return bytes.contains(other);
+    } else if value.isinstance::<VMDict>() {
+        let dict = value.as_type::<VMDict>();
+        return dict.contains(other);
     }
     Err(VMVariableError::TypeError(
         value.clone_ref(),
         "Cannot check contains for a non-containable type".to_string(),
     ))
 }
+// 映射类型的字符串表示按键的表示排序，保证输出与插入顺序、哈希值无关
+fn format_sorted_entries(mut entries: Vec<(String, String)>) -> String {
+    entries.sort();
+    let entries: Vec<String> = entries
+        .into_iter()
+        .map(|(key, value)| format!("{}: {}", key, value))
+        .collect();
+    format!("{{{}}}", entries.join(", "))
+}
+
+// repr / to_string 默认的最大嵌套深度，超过后输出 "<...>"；调试构建下每层栈帧较大，不宜设得过高
+pub const DEFAULT_MAX_REPR_DEPTH: usize = 128;
+
 pub fn try_repr_vmobject(
     value: &mut GCRef,
     ref_path: Option<Vec<GCRef>>,
+) -> Result<String, VMVariableError> {
+    try_repr_vmobject_with_depth(value, ref_path, DEFAULT_MAX_REPR_DEPTH)
+}
+
+pub fn try_repr_vmobject_with_depth(
+    value: &mut GCRef,
+    ref_path: Option<Vec<GCRef>>,
+    max_depth: usize,
 ) -> Result<String, VMVariableError> {
     // 检查循环引用
     if let Some(ref path) = ref_path {
@@ -193,6 +351,14 @@ pub fn try_repr_vmobject(
         }
     }
 
+    // 嵌套过深时截断，避免极深（非循环）结构导致栈溢出
+    if ref_path
+        .as_ref()
+        .is_some_and(|path| path.len() >= max_depth)
+    {
+        return Ok("<...>".to_string());
+    }
+
     // 创建新的引用路径，将当前对象添加到路径中
     let new_ref_path = if let Some(mut path) = ref_path {
         path.push(value.clone());
@@ -241,7 +407,7 @@ pub fn try_repr_vmobject(
         escaped_string
     } else if value.isinstance::<VMFloat>() {
         let float = value.as_const_type::<VMFloat>();
-        float.value.to_string()
+        VMFloat::format_value(float.value)
     } else if value.isinstance::<VMBoolean>() {
         let boolean = value.as_const_type::<VMBoolean>();
         boolean.value.to_string()
@@ -250,13 +416,13 @@ pub fn try_repr_vmobject(
     } else if value.isinstance::<VMKeyVal>() {
         let kv = value.as_type::<VMKeyVal>();
         // 递归调用时不传递别名，因为别名是顶层对象的
-        let key = try_repr_vmobject(kv.get_key(), new_ref_path.clone())?;
-        let val = try_repr_vmobject(kv.get_value(), new_ref_path)?;
+        let key = try_repr_vmobject_with_depth(kv.get_key(), new_ref_path.clone(), max_depth)?;
+        let val = try_repr_vmobject_with_depth(kv.get_value(), new_ref_path, max_depth)?;
         format!("{}: {}", key, val)
     } else if value.isinstance::<VMNamed>() {
         let named = value.as_type::<VMNamed>();
-        let key = try_repr_vmobject(named.get_key(), new_ref_path.clone())?;
-        let val = try_repr_vmobject(named.get_value(), new_ref_path)?;
+        let key = try_repr_vmobject_with_depth(named.get_key(), new_ref_path.clone(), max_depth)?;
+        let val = try_repr_vmobject_with_depth(named.get_value(), new_ref_path, max_depth)?;
         format!("{} => {}", key, val)
     } else if value.isinstance::<VMTuple>() {
         let tuple = value.as_type::<VMTuple>();
@@ -265,7 +431,11 @@ pub fn try_repr_vmobject(
         } else {
             let mut items_repr = Vec::new();
             for val in &mut tuple.values {
-                items_repr.push(try_repr_vmobject(val, new_ref_path.clone())?);
+                items_repr.push(try_repr_vmobject_with_depth(
+                    val,
+                    new_ref_path.clone(),
+                    max_depth,
+                )?);
             }
             if tuple.values.len() == 1 {
                 format!("({},)", items_repr[0]) // 单元素元组特殊表示
@@ -275,14 +445,20 @@ pub fn try_repr_vmobject(
         }
     } else if value.isinstance::<VMLambda>() {
         let lambda = value.as_type::<VMLambda>();
-        let default_args = try_repr_vmobject(&mut lambda.default_args_tuple, new_ref_path.clone())?;
-        let result_repr = try_repr_vmobject(&mut lambda.result, new_ref_path)?;
+        let default_args = try_repr_vmobject_with_depth(
+            &mut lambda.default_args_tuple,
+            new_ref_path.clone(),
+            max_depth,
+        )?;
+        let result_repr =
+            try_repr_vmobject_with_depth(&mut lambda.result, new_ref_path, max_depth)?;
         format!("{}::{} -> {}", lambda.signature, default_args, result_repr)
     } else if value.isinstance::<VMInstructions>() {
         "VMInstructions".to_string() // 指令集通常不显示内部细节
     } else if value.isinstance::<VMWrapper>() {
         let wrapper = value.as_type::<VMWrapper>();
-        let inner_repr = try_repr_vmobject(&mut wrapper.value_ref, new_ref_path)?;
+        let inner_repr =
+            try_repr_vmobject_with_depth(&mut wrapper.value_ref, new_ref_path, max_depth)?;
         format!("wrap({})", inner_repr)
     } else if value.isinstance::<VMRange>() {
         let range = value.as_const_type::<VMRange>();
@@ -295,9 +471,32 @@ pub fn try_repr_vmobject(
         )
     } else if value.isinstance::<VMSet>() {
         let set = value.as_type::<VMSet>();
-        let collection_repr = try_repr_vmobject(&mut set.collection, new_ref_path.clone())?;
-        let filter_repr = try_repr_vmobject(&mut set.filter, new_ref_path)?;
+        let collection_repr =
+            try_repr_vmobject_with_depth(&mut set.collection, new_ref_path.clone(), max_depth)?;
+        let filter_repr = if set.filter.isinstance::<VMLambda>() {
+            let filter = set.filter.as_type::<VMLambda>();
+            let default_args = try_repr_vmobject_with_depth(
+                &mut filter.default_args_tuple,
+                new_ref_path,
+                max_depth,
+            )?;
+            format!("{}::{}", filter.signature, default_args)
+        } else {
+            try_repr_vmobject_with_depth(&mut set.filter, new_ref_path, max_depth)?
+        };
         format!("{{{} | {}}}", collection_repr, filter_repr)
+    } else if value.isinstance::<VMDict>() {
+        let dict = value.as_type::<VMDict>();
+        let mut entries_repr = Vec::with_capacity(dict.entries.len());
+        for (key, value) in dict.entries.iter_mut() {
+            let key_repr = try_repr_vmobject_with_depth(key, new_ref_path.clone(), max_depth)?;
+            let value_repr = try_repr_vmobject_with_depth(value, new_ref_path.clone(), max_depth)?;
+            entries_repr.push((key_repr, value_repr));
+        }
+        format_sorted_entries(entries_repr)
+    } else if value.isinstance::<VMChannel>() {
+        let channel = value.as_const_type::<VMChannel>();
+        format!("channel({}/{})", channel.len(), channel.capacity())
     } else if value.isinstance::<VMCLambdaInstruction>() {
         let clambda = value.as_const_type::<VMCLambdaInstruction>();
         // CLambda 的 repr 可能需要更详细的信息，这里暂时简化
@@ -317,6 +516,14 @@ pub fn try_repr_vmobject(
 pub fn try_to_string_vmobject(
     value: &mut GCRef,
     ref_path: Option<Vec<GCRef>>,
+) -> Result<String, VMVariableError> {
+    try_to_string_vmobject_with_depth(value, ref_path, DEFAULT_MAX_REPR_DEPTH)
+}
+
+pub fn try_to_string_vmobject_with_depth(
+    value: &mut GCRef,
+    ref_path: Option<Vec<GCRef>>,
+    max_depth: usize,
 ) -> Result<String, VMVariableError> {
     // 检查循环引用
     if let Some(ref path) = ref_path {
@@ -327,6 +534,14 @@ pub fn try_to_string_vmobject(
         }
     }
 
+    // 嵌套过深时截断，避免极深（非循环）结构导致栈溢出
+    if ref_path
+        .as_ref()
+        .is_some_and(|path| path.len() >= max_depth)
+    {
+        return Ok("<...>".to_string());
+    }
+
     // 创建新的引用路径，将当前对象添加到路径中
     let new_ref_path = if let Some(mut path) = ref_path {
         path.push(value.clone());
@@ -344,7 +559,7 @@ pub fn try_to_string_vmobject(
         string.value.clone() // 直接返回值，不转义不加引号
     } else if value.isinstance::<VMFloat>() {
         let float = value.as_const_type::<VMFloat>();
-        float.value.to_string()
+        VMFloat::format_value(float.value)
     } else if value.isinstance::<VMBoolean>() {
         let boolean = value.as_const_type::<VMBoolean>();
         boolean.value.to_string()
@@ -352,13 +567,14 @@ pub fn try_to_string_vmobject(
         "null".to_string()
     } else if value.isinstance::<VMKeyVal>() {
         let kv = value.as_type::<VMKeyVal>();
-        let key = try_to_string_vmobject(kv.get_key(), new_ref_path.clone())?;
-        let val = try_to_string_vmobject(kv.get_value(), new_ref_path)?;
+        let key = try_to_string_vmobject_with_depth(kv.get_key(), new_ref_path.clone(), max_depth)?;
+        let val = try_to_string_vmobject_with_depth(kv.get_value(), new_ref_path, max_depth)?;
         format!("{}: {}", key, val)
     } else if value.isinstance::<VMNamed>() {
         let named = value.as_type::<VMNamed>();
-        let key = try_to_string_vmobject(named.get_key(), new_ref_path.clone())?;
-        let val = try_to_string_vmobject(named.get_value(), new_ref_path)?;
+        let key =
+            try_to_string_vmobject_with_depth(named.get_key(), new_ref_path.clone(), max_depth)?;
+        let val = try_to_string_vmobject_with_depth(named.get_value(), new_ref_path, max_depth)?;
         format!("{} => {}", key, val)
     } else if value.isinstance::<VMTuple>() {
         let tuple = value.as_type::<VMTuple>();
@@ -367,7 +583,11 @@ pub fn try_to_string_vmobject(
         } else {
             let mut items_repr = Vec::new();
             for val in &mut tuple.values {
-                items_repr.push(try_to_string_vmobject(val, new_ref_path.clone())?);
+                items_repr.push(try_to_string_vmobject_with_depth(
+                    val,
+                    new_ref_path.clone(),
+                    max_depth,
+                )?);
             }
             if tuple.values.len() == 1 {
                 format!("({},)", items_repr[0]) // 单元素元组特殊表示
@@ -377,15 +597,20 @@ pub fn try_to_string_vmobject(
         }
     } else if value.isinstance::<VMLambda>() {
         let lambda = value.as_type::<VMLambda>();
-        let default_args =
-            try_to_string_vmobject(&mut lambda.default_args_tuple, new_ref_path.clone())?;
-        let result_repr = try_to_string_vmobject(&mut lambda.result, new_ref_path)?;
+        let default_args = try_to_string_vmobject_with_depth(
+            &mut lambda.default_args_tuple,
+            new_ref_path.clone(),
+            max_depth,
+        )?;
+        let result_repr =
+            try_to_string_vmobject_with_depth(&mut lambda.result, new_ref_path, max_depth)?;
         format!("{}::{} -> {}", lambda.signature, default_args, result_repr)
     } else if value.isinstance::<VMInstructions>() {
         "VMInstructions".to_string()
     } else if value.isinstance::<VMWrapper>() {
         let wrapper = value.as_type::<VMWrapper>();
-        let inner_repr = try_to_string_vmobject(&mut wrapper.value_ref, new_ref_path)?;
+        let inner_repr =
+            try_to_string_vmobject_with_depth(&mut wrapper.value_ref, new_ref_path, max_depth)?;
         format!("wrap({})", inner_repr)
     } else if value.isinstance::<VMRange>() {
         let range = value.as_const_type::<VMRange>();
@@ -399,9 +624,27 @@ pub fn try_to_string_vmobject(
         )
     } else if value.isinstance::<VMSet>() {
         let set = value.as_type::<VMSet>();
-        let collection_repr = try_to_string_vmobject(&mut set.collection, new_ref_path.clone())?;
-        let filter_repr = try_to_string_vmobject(&mut set.filter, new_ref_path)?;
+        let collection_repr = try_to_string_vmobject_with_depth(
+            &mut set.collection,
+            new_ref_path.clone(),
+            max_depth,
+        )?;
+        let filter_repr =
+            try_to_string_vmobject_with_depth(&mut set.filter, new_ref_path, max_depth)?;
         format!("{{{} | {}}}", collection_repr, filter_repr)
+    } else if value.isinstance::<VMDict>() {
+        let dict = value.as_type::<VMDict>();
+        let mut entries_repr = Vec::with_capacity(dict.entries.len());
+        for (key, value) in dict.entries.iter_mut() {
+            let key_repr = try_to_string_vmobject_with_depth(key, new_ref_path.clone(), max_depth)?;
+            let value_repr =
+                try_to_string_vmobject_with_depth(value, new_ref_path.clone(), max_depth)?;
+            entries_repr.push((key_repr, value_repr));
+        }
+        format_sorted_entries(entries_repr)
+    } else if value.isinstance::<VMChannel>() {
+        let channel = value.as_const_type::<VMChannel>();
+        format!("channel({}/{})", channel.len(), channel.capacity())
     } else if value.isinstance::<VMCLambdaInstruction>() {
         let clambda = value.as_const_type::<VMCLambdaInstruction>();
         format!("CLambda({:?})", clambda.clambda)
@@ -727,6 +970,10 @@ pub fn try_get_attr_as_vmobject<'t>(
     if value.isinstance::<VMKeyVal>() {
         return VMKeyVal::get_member(value, attr, gc_system)
     }
+    if value.isinstance::<VMDict>() {
+        let dict = value.as_type::<VMDict>();
+        return dict.get_member(attr).map(|v| v.clone_ref());
+    }
     Err(VMVariableError::KeyNotFound(
         attr.clone_ref(),
         value.clone_ref(),
@@ -750,12 +997,40 @@ pub fn try_index_of_as_vmobject(
         let range = value.as_type::<VMBytes>();
         return range.index_of(index, gc_system);
     }
+    if value.isinstance::<VMRange>() {
+        let range = value.as_type::<VMRange>();
+        return range.index_of(index, gc_system);
+    }
+    if value.isinstance::<VMDict>() {
+        let dict = value.as_type::<VMDict>();
+        return dict.get_member(index).map(|v| v.clone_ref());
+    }
     Err(VMVariableError::IndexNotFound(
         index.clone_ref(),
         value.clone_ref(),
     ))
 }
 
+// 将可能为负的索引换算为非负位置，负数表示从末尾开始计数（-1 为最后一个元素）
+// 换算后仍越界时返回 None
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let resolved = if index < 0 { index + len as i64 } else { index };
+    if resolved < 0 || resolved >= len as i64 {
+        return None;
+    }
+    Some(resolved as usize)
+}
+
+// 与 resolve_index 相同的规则换算切片的左闭右开区间，要求 start <= end
+fn resolve_range(start: i64, end: i64, len: usize) -> Option<(usize, usize)> {
+    let start = if start < 0 { start + len as i64 } else { start };
+    let end = if end < 0 { end + len as i64 } else { end };
+    if start < 0 || end > len as i64 || start > end {
+        return None;
+    }
+    Some((start as usize, end as usize))
+}
+
 pub fn try_key_of_as_vmobject(value: &mut GCRef) -> Result<&mut GCRef, VMVariableError> {
     if value.isinstance::<VMKeyVal>() {
         let kv = value.as_type::<VMKeyVal>();
@@ -813,7 +1088,77 @@ pub fn try_deepcopy_as_vmobject(
     value: &mut GCRef,
     gc_system: &mut GCSystem,
 ) -> Result<GCRef, VMVariableError> {
-    try_deepcopy_as_type!(value, gc_system; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMCLambdaInstruction);
+    try_deepcopy_with_memo(value, &mut DeepcopyMemo::default(), gc_system)
+}
+
+// deepcopy 过程中已访问的对象：原对象地址 -> 副本（不持有引用计数）
+// 副本尚未创建时为 None，说明该对象仍在复制其成员
+pub type DeepcopyMemo = FxHashMap<*const (), Option<GCRef>>;
+
+fn object_address(value: &GCRef) -> *const () {
+    value.get_const_reference() as *const ()
+}
+
+// 类似 try_repr_vmobject 的 ref_path，在递归中传递已访问表
+// 再次遇到已复制的对象时复用其副本，从而保留共享结构并终止循环引用
+// 元组、字典与 lambda 会在复制成员前登记副本，其余容器只能出现在经过它们的环中
+pub fn try_deepcopy_with_memo(
+    value: &mut GCRef,
+    memo: &mut DeepcopyMemo,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let address = object_address(value);
+    match memo.get_mut(&address) {
+        Some(Some(copied)) => return Ok(copied.clone_ref()),
+        Some(None) => return Err(VMVariableError::CopyError(
+            value.clone_ref(),
+            "Cannot deepcopy a cyclic reference that does not pass through a tuple, dict or lambda"
+                .to_string(),
+        )),
+        None => {}
+    }
+    memo.insert(address, None);
+    let copied = if value.isinstance::<VMTuple>() {
+        value
+            .as_type::<VMTuple>()
+            .deepcopy_with_memo(address, memo, gc_system)
+    } else if value.isinstance::<VMDict>() {
+        value
+            .as_type::<VMDict>()
+            .deepcopy_with_memo(address, memo, gc_system)
+    } else if value.isinstance::<VMLambda>() {
+        value
+            .as_type::<VMLambda>()
+            .deepcopy_with_memo(address, memo, gc_system)
+    } else if value.isinstance::<VMKeyVal>() {
+        value
+            .as_type::<VMKeyVal>()
+            .deepcopy_with_memo(memo, gc_system)
+    } else if value.isinstance::<VMNamed>() {
+        value
+            .as_type::<VMNamed>()
+            .deepcopy_with_memo(memo, gc_system)
+    } else if value.isinstance::<VMSet>() {
+        value.as_type::<VMSet>().deepcopy_with_memo(memo, gc_system)
+    } else if value.isinstance::<VMWrapper>() {
+        try_deepcopy_with_memo(&mut value.as_type::<VMWrapper>().value_ref, memo, gc_system)
+    } else if value.isinstance::<VMChannel>() {
+        value
+            .as_type::<VMChannel>()
+            .deepcopy_with_memo(memo, gc_system)
+    } else {
+        try_deepcopy_leaf_as_vmobject(value, gc_system)
+    }?;
+    memo.insert(address, Some(copied.clone()));
+    Ok(copied)
+}
+
+// 不包含其他对象的类型直接复制
+fn try_deepcopy_leaf_as_vmobject(
+    value: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    try_deepcopy_as_type!(value, gc_system; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMInstructions, VMRange, VMBytes, VMCLambdaInstruction);
     Err(VMVariableError::CopyError(
         value.clone_ref(),
         "Cannot deepcopy a value of non-copyable type".to_string(),
@@ -835,7 +1180,7 @@ pub fn try_copy_as_vmobject(
     value: &mut GCRef,
     gc_system: &mut GCSystem,
 ) -> Result<GCRef, VMVariableError> {
-    try_copy_as_type!(value, gc_system; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMCLambdaInstruction);
+    try_copy_as_type!(value, gc_system; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMDict, VMChannel, VMCLambdaInstruction);
     Err(VMVariableError::CopyError(
         value.clone_ref(),
         "Cannot copy a value of non-copyable type".to_string(),
@@ -857,7 +1202,13 @@ pub fn try_assign_as_vmobject<'t>(
     value: &mut GCRef,
     other: &'t mut GCRef,
 ) -> Result<&'t mut GCRef, VMVariableError> {
-    try_assign_as_type!(value, other; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMCLambdaInstruction);
+    if value.is_frozen() {
+        return Err(VMVariableError::AssignError(
+            value.clone_ref(),
+            "Cannot assign to a frozen object".to_string(),
+        ));
+    }
+    try_assign_as_type!(value, other; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMDict, VMChannel, VMCLambdaInstruction);
     Err(VMVariableError::AssignError(
         value.clone_ref(),
         "Cannot assign a value of non-assignable type".to_string(),
@@ -876,7 +1227,7 @@ macro_rules! try_const_alias_as_type {
 }
 
 pub fn try_const_alias_as_vmobject(value: &mut GCRef) -> Result<&Vec<String>, VMVariableError> {
-    try_const_alias_as_type!(value; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMCLambdaInstruction);
+    try_const_alias_as_type!(value; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMDict, VMChannel, VMCLambdaInstruction);
     Err(VMVariableError::ReferenceError(
         value.clone_ref(),
         "Cannot get reference of a non-referenceable type".to_string(),
@@ -895,7 +1246,7 @@ macro_rules! try_alias_as_type {
 }
 
 pub fn try_alias_as_vmobject(value: &mut GCRef) -> Result<&mut Vec<String>, VMVariableError> {
-    try_alias_as_type!(value; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMCLambdaInstruction);
+    try_alias_as_type!(value; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMLambda, VMInstructions, VMWrapper, VMRange, VMBytes, VMSet, VMDict, VMChannel, VMCLambdaInstruction);
     Err(VMVariableError::ReferenceError(
         value.clone_ref(),
         "Cannot get reference of a non-referenceable type".to_string(),
@@ -914,10 +1265,68 @@ macro_rules! try_binary_op_as_type {
 }
 
 pub fn try_eq_as_vmobject(value: &GCRef, other: &GCRef) -> bool {
-    try_binary_op_as_type!(value, eq, other; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMRange, VMBytes, VMSet);
+    try_binary_op_as_type!(value, eq, other; VMInt, VMString, VMFloat, VMBoolean, VMNull, VMKeyVal, VMTuple, VMNamed, VMRange, VMBytes, VMSet, VMDict, VMLambda);
     false
 }
 
+// 结构化哈希：try_eq_as_vmobject 判定相等的值总是得到相同的哈希值
+// 使用固定种子的 FxHasher，结果在不同运行之间保持稳定
+// 仅支持不可变的值类型，lambda 等其他对象返回 TypeError
+pub fn try_hash_as_vmobject(value: &mut GCRef) -> Result<u64, VMVariableError> {
+    let mut hasher = FxHasher::default();
+    hash_vmobject_into(value, &mut hasher)?;
+    Ok(hasher.finish())
+}
+
+fn hash_vmobject_into(value: &mut GCRef, hasher: &mut FxHasher) -> Result<(), VMVariableError> {
+    // 整数与浮点数可以相等（1 == 1.0），统一按 f64 哈希
+    let number = if value.isinstance::<VMInt>() {
+        Some(value.as_const_type::<VMInt>().value as f64)
+    } else if value.isinstance::<VMFloat>() {
+        Some(value.as_const_type::<VMFloat>().value)
+    } else {
+        None
+    };
+    if let Some(number) = number {
+        hasher.write_u8(0);
+        let bits = if number == 0.0 {
+            0.0f64.to_bits() // -0.0 == 0.0
+        } else if number.is_nan() {
+            f64::NAN.to_bits()
+        } else {
+            number.to_bits()
+        };
+        hasher.write_u64(bits);
+    } else if value.isinstance::<VMString>() {
+        hasher.write_u8(1);
+        hasher.write(value.as_const_type::<VMString>().value.as_bytes());
+        hasher.write_u8(0xff);
+    } else if value.isinstance::<VMBoolean>() {
+        hasher.write_u8(2);
+        hasher.write_u8(value.as_const_type::<VMBoolean>().value as u8);
+    } else if value.isinstance::<VMNull>() {
+        hasher.write_u8(3);
+    } else if value.isinstance::<VMTuple>() {
+        hasher.write_u8(4);
+        let tuple = value.as_type::<VMTuple>();
+        hasher.write_usize(tuple.values.len());
+        for element in tuple.values.iter_mut() {
+            hash_vmobject_into(element, hasher)?;
+        }
+    } else if value.isinstance::<VMKeyVal>() {
+        hasher.write_u8(5);
+        let kv = value.as_type::<VMKeyVal>();
+        hash_vmobject_into(&mut kv.key, hasher)?;
+        hash_vmobject_into(&mut kv.value, hasher)?;
+    } else {
+        return Err(VMVariableError::TypeError(
+            value.clone_ref(),
+            "Unhashable type".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 pub trait VMObject {
     fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError>;
     fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError>;
@@ -931,6 +1340,122 @@ pub trait VMIterable {
     fn next(&mut self, gc_system: &mut GCSystem) -> Option<GCRef>;
 }
 
+// 脚本迭代器协议：带有 lambda 成员 next 与 reset 的元组
+// 迭代开始时调用 reset()，之后反复调用 next()，返回 null 表示迭代结束
+// 对元组而言该协议优先于按元素迭代
+fn get_script_iter_method(value: &mut GCRef, name: &str) -> Option<GCRef> {
+    if !value.isinstance::<VMTuple>() {
+        return None;
+    }
+    value
+        .as_type::<VMTuple>()
+        .values
+        .iter_mut()
+        .find_map(|member| {
+            if !member.isinstance::<VMNamed>() {
+                return None;
+            }
+            let named = member.as_type::<VMNamed>();
+            let key = named.get_const_key();
+            if key.isinstance::<VMString>()
+                && key.as_const_type::<VMString>().value == name
+                && named.value.isinstance::<VMLambda>()
+            {
+                Some(named.value.clone())
+            } else {
+                None
+            }
+        })
+}
+
+fn is_script_iterator(value: &mut GCRef) -> bool {
+    get_script_iter_method(value, "next").is_some()
+        && get_script_iter_method(value, "reset").is_some()
+}
+
+// 无参数调用脚本迭代器的方法，返回值持有一个新的引用
+fn call_script_iter_method(
+    value: &mut GCRef,
+    name: &str,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMVariableError> {
+    let mut method = get_script_iter_method(value, name).ok_or_else(|| {
+        VMVariableError::TypeError(
+            value.clone_ref(),
+            format!("Iterator has no '{}' method", name),
+        )
+    })?;
+    let mut args = gc_system.new_object(VMTuple::new(&mut vec![]));
+    let result = call_lambda_sync(&mut method, &mut args, gc_system);
+    args.drop_ref();
+    result.map_err(|err| err.into_variable_error())
+}
+
+// 重置迭代状态：先匹配脚本迭代器协议，再匹配 Rust 实现的 VMIterable
+pub fn try_iter_reset_as_vmobject(
+    value: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<(), VMVariableError> {
+    if is_script_iterator(value) {
+        let mut result = call_script_iter_method(value, "reset", gc_system)?;
+        result.drop_ref();
+        return Ok(());
+    }
+    if value.isinstance::<VMTuple>() {
+        value.as_type::<VMTuple>().reset();
+    } else if value.isinstance::<VMString>() {
+        value.as_type::<VMString>().reset();
+    } else if value.isinstance::<VMBytes>() {
+        value.as_type::<VMBytes>().reset();
+    } else if value.isinstance::<VMRange>() {
+        value.as_type::<VMRange>().reset();
+    } else if value.isinstance::<VMSet>() {
+        value.as_type::<VMSet>().reset();
+    } else if value.isinstance::<VMDict>() {
+        value.as_type::<VMDict>().reset();
+    } else {
+        return Err(VMVariableError::TypeError(
+            value.clone_ref(),
+            "Cannot iterate over a non-iterable type".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// 取出下一个元素，迭代结束时返回 None
+// 脚本迭代器的 next() 返回 null 即视为结束
+pub fn try_iter_next_as_vmobject(
+    value: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<Option<GCRef>, VMVariableError> {
+    if is_script_iterator(value) {
+        let mut result = call_script_iter_method(value, "next", gc_system)?;
+        if result.isinstance::<VMNull>() {
+            result.drop_ref();
+            return Ok(None);
+        }
+        return Ok(Some(result));
+    }
+    if value.isinstance::<VMTuple>() {
+        Ok(value.as_type::<VMTuple>().next(gc_system))
+    } else if value.isinstance::<VMString>() {
+        Ok(value.as_type::<VMString>().next(gc_system))
+    } else if value.isinstance::<VMBytes>() {
+        Ok(value.as_type::<VMBytes>().next(gc_system))
+    } else if value.isinstance::<VMRange>() {
+        Ok(value.as_type::<VMRange>().next(gc_system))
+    } else if value.isinstance::<VMSet>() {
+        Ok(value.as_type::<VMSet>().next(gc_system))
+    } else if value.isinstance::<VMDict>() {
+        Ok(value.as_type::<VMDict>().next(gc_system))
+    } else {
+        Err(VMVariableError::TypeError(
+            value.clone_ref(),
+            "Cannot iterate over a non-iterable type".to_string(),
+        ))
+    }
+}
+
 // 值包装器
 // 将任意值包装为一个可操作对象
 // 用于实现值的引用和解包
@@ -973,8 +1498,9 @@ impl GCObject for VMWrapper {
 
 impl VMObject for VMWrapper {
     fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
-        try_deepcopy_as_vmobject(&mut self.value_ref, gc_system)
+        try_deepcopy_as_vmobject(&mut GCRef::wrap(self), gc_system)
     }
+
     fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
         try_copy_as_vmobject(&mut self.value_ref, gc_system)
     }
@@ -1115,6 +1641,42 @@ impl VMInt {
         ))
     }
 
+    // 向下取整的整数除法，商向负无穷取整（-7 floordiv 2 == -4）
+    pub fn floor_div(
+        &mut self,
+        other: &mut GCRef,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        if !other.isinstance::<VMInt>() {
+            return Err(VMVariableError::ValueError2Param(
+                GCRef::wrap(self).clone_ref(),
+                other.clone_ref(),
+                "Cannot floor-divide by a value of non-integer type".to_string(),
+            ));
+        }
+        let divisor = other.as_const_type::<VMInt>().value;
+        if divisor == 0 {
+            return Err(VMVariableError::ValueError(
+                other.clone_ref(),
+                "division by zero".to_string(),
+            ));
+        }
+        // 只有 i64::MIN floordiv -1 会溢出
+        let Some(quotient) = self.value.checked_div(divisor) else {
+            return Err(VMVariableError::OverflowError(
+                GCRef::wrap(self).clone_ref(),
+                other.clone_ref(),
+                "Integer floor division overflow".to_string(),
+            ));
+        };
+        let quotient = if self.value % divisor != 0 && (self.value < 0) != (divisor < 0) {
+            quotient - 1
+        } else {
+            quotient
+        };
+        Ok(gc_system.new_object(VMInt::new(quotient)))
+    }
+
     pub fn mod_op(
         &mut self,
         other: &mut GCRef,
@@ -1122,7 +1684,14 @@ impl VMInt {
     ) -> Result<GCRef, VMVariableError> {
         if other.isinstance::<VMInt>() {
             let other_int = other.as_const_type::<VMInt>();
-            return Ok(gc_system.new_object(VMInt::new(self.value % other_int.value)));
+            if other_int.value == 0 {
+                return Err(VMVariableError::ValueError(
+                    other.clone_ref(),
+                    "division by zero".to_string(),
+                ));
+            }
+            // i64::MIN % -1 的结果为 0，但直接计算会溢出
+            return Ok(gc_system.new_object(VMInt::new(self.value.wrapping_rem(other_int.value))));
         } else if other.isinstance::<VMFloat>() {
             let other_float = other.as_const_type::<VMFloat>();
             return Ok(gc_system.new_object(VMFloat::new(self.value as f64 % other_float.value)));
@@ -1578,29 +2147,29 @@ impl VMString {
         index: &mut GCRef,
         gc_system: &mut GCSystem,
     ) -> Result<GCRef, VMVariableError> {
+        // 按字符而非字节计数，保证负索引对非 ASCII 字符串同样有效
+        let char_count = self.value.chars().count();
         if index.isinstance::<VMInt>() {
             let index_int = index.as_const_type::<VMInt>();
-            if index_int.value < 0 || index_int.value >= self.value.len() as i64 {
+            let Some(position) = resolve_index(index_int.value, char_count) else {
                 return Err(VMVariableError::IndexNotFound(
                     index.clone_ref(),
                     GCRef::wrap(self).clone_ref(),
                 ));
-            }
-            let char = self.value.chars().nth(index_int.value as usize).unwrap();
+            };
+            let char = self.value.chars().nth(position).unwrap();
 
             return Ok(gc_system.new_object(VMString::new(&char.to_string())));
         } else if index.isinstance::<VMRange>() {
             let range = index.as_const_type::<VMRange>();
-            let start = range.start;
-            let end = range.end;
-            if start < 0 || end > self.value.len() as i64 {
+            let Some((start, end)) = resolve_range(range.start, range.end, char_count) else {
                 return Err(VMVariableError::IndexNotFound(
                     index.clone_ref(),
                     GCRef::wrap(self).clone_ref(),
                 ));
-            }
-            let substring = &self.value[start as usize..end as usize];
-            return Ok(gc_system.new_object(VMString::new(substring)));
+            };
+            let substring: String = self.value.chars().skip(start).take(end - start).collect();
+            return Ok(gc_system.new_object(VMString::new(&substring)));
         }
 
         Err(VMVariableError::ValueError2Param(
@@ -1711,6 +2280,21 @@ impl VMFloat {
         }
     }
 
+    // 统一浮点数的文本形式，NaN 和无穷大不依赖 f64::to_string 的格式
+    pub fn format_value(value: f64) -> String {
+        if value.is_nan() {
+            "NaN".to_string()
+        } else if value.is_infinite() {
+            if value > 0.0 {
+                "inf".to_string()
+            } else {
+                "-inf".to_string()
+            }
+        } else {
+            value.to_string()
+        }
+    }
+
     pub fn eq(&self, other: &GCRef) -> bool {
         if other.isinstance::<VMFloat>() {
             self.value == other.as_const_type::<VMFloat>().value
@@ -1869,10 +2453,10 @@ impl VMFloat {
         Ok(self.value as i64)
     }
     pub fn to_string(&self) -> Result<String, VMVariableError> {
-        Ok(self.value.to_string())
+        Ok(VMFloat::format_value(self.value))
     }
     pub fn to_bool(&self) -> Result<bool, VMVariableError> {
-        Ok(self.value != 0.0)
+        Ok(self.value != 0.0 && !self.value.is_nan())
     }
     pub fn to_float(&self) -> Result<f64, VMVariableError> {
         Ok(self.value)
@@ -2156,6 +2740,29 @@ pub struct VMKeyVal {
 }
 
 impl VMKeyVal {
+    fn deepcopy_with_memo(
+        &mut self,
+        memo: &mut DeepcopyMemo,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut new_key = try_deepcopy_with_memo(&mut self.key, memo, gc_system)?;
+        let mut new_value = match try_deepcopy_with_memo(&mut self.value, memo, gc_system) {
+            Ok(new_value) => new_value,
+            Err(err) => {
+                new_key.drop_ref();
+                return Err(err);
+            }
+        };
+        let new_keyval = gc_system.new_object(VMKeyVal::new_with_alias(
+            &mut new_key,
+            &mut new_value,
+            &self.alias,
+        ));
+        new_key.drop_ref();
+        new_value.drop_ref();
+        Ok(new_keyval)
+    }
+
     pub fn new(key: &mut GCRef, value: &mut GCRef) -> Self {
         VMKeyVal {
             key: key.clone(),
@@ -2306,16 +2913,7 @@ impl GCObject for VMKeyVal {
 
 impl VMObject for VMKeyVal {
     fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
-        let mut new_key = try_deepcopy_as_vmobject(&mut self.key, gc_system)?;
-        let mut new_value = try_deepcopy_as_vmobject(&mut self.value, gc_system)?;
-        let new_keyval = gc_system.new_object(VMKeyVal::new_with_alias(
-            &mut new_key,
-            &mut new_value,
-            &self.alias,
-        ));
-        new_key.drop_ref();
-        new_value.drop_ref();
-        Ok(new_keyval)
+        try_deepcopy_as_vmobject(&mut GCRef::wrap(self), gc_system)
     }
 
     fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
@@ -2356,6 +2954,29 @@ pub struct VMNamed {
 }
 
 impl VMNamed {
+    fn deepcopy_with_memo(
+        &mut self,
+        memo: &mut DeepcopyMemo,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut new_key = try_deepcopy_with_memo(&mut self.key, memo, gc_system)?;
+        let mut new_value = match try_deepcopy_with_memo(&mut self.value, memo, gc_system) {
+            Ok(new_value) => new_value,
+            Err(err) => {
+                new_key.drop_ref();
+                return Err(err);
+            }
+        };
+        let new_named = gc_system.new_object(VMNamed::new_with_alias(
+            &mut new_key,
+            &mut new_value,
+            &self.alias,
+        ));
+        new_key.drop_ref();
+        new_value.drop_ref();
+        Ok(new_named)
+    }
+
     pub fn new(key: &mut GCRef, value: &mut GCRef) -> Self {
         VMNamed {
             key: key.clone(),
@@ -2423,16 +3044,7 @@ impl GCObject for VMNamed {
 
 impl VMObject for VMNamed {
     fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
-        let mut new_key = try_deepcopy_as_vmobject(&mut self.key, gc_system)?;
-        let mut new_value = try_deepcopy_as_vmobject(&mut self.value, gc_system)?;
-        let new_named = gc_system.new_object(VMNamed::new_with_alias(
-            &mut new_key,
-            &mut new_value,
-            &self.alias,
-        ));
-        new_key.drop_ref();
-        new_value.drop_ref();
-        Ok(new_named)
+        try_deepcopy_as_vmobject(&mut GCRef::wrap(self), gc_system)
     }
 
     fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
@@ -2474,14 +3086,43 @@ pub struct VMTuple {
 }
 
 impl VMTuple {
-    pub fn new(values: &mut Vec<&mut GCRef>) -> Self {
-        let gc_traceable = GCTraceable::new::<VMTuple>(Some(values));
-        let mut cloned_refs = Vec::new();
-        for value in values {
-            cloned_refs.push(value.clone());
-        }
-        // 创建对象并设置引用跟踪
-        VMTuple {
+    // 先登记空的副本再逐个复制元素，元素引用回本元组时复用该副本
+    fn deepcopy_with_memo(
+        &mut self,
+        address: *const (),
+        memo: &mut DeepcopyMemo,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut new_tuple = gc_system.new_object(VMTuple::new_with_alias(&mut vec![], &self.alias));
+        memo.insert(address, Some(new_tuple.clone()));
+
+        let values = self.values.clone();
+        for mut value in values {
+            let mut copied_value = match try_deepcopy_with_memo(&mut value, memo, gc_system) {
+                Ok(copied_value) => copied_value,
+                Err(err) => {
+                    new_tuple.drop_ref();
+                    return Err(err);
+                }
+            };
+            new_tuple.as_type::<VMTuple>().append(&mut copied_value)?;
+            copied_value.drop_ref();
+        }
+
+        if self.auto_bind {
+            VMTuple::set_lambda_self(&mut new_tuple);
+        }
+        Ok(new_tuple)
+    }
+
+    pub fn new(values: &mut Vec<&mut GCRef>) -> Self {
+        let gc_traceable = GCTraceable::new::<VMTuple>(Some(values));
+        let mut cloned_refs = Vec::new();
+        for value in values {
+            cloned_refs.push(value.clone());
+        }
+        // 创建对象并设置引用跟踪
+        VMTuple {
             values: cloned_refs,
             traceable: gc_traceable,
             alias: Vec::new(),
@@ -2592,18 +3233,17 @@ impl VMTuple {
     ) -> Result<GCRef, VMVariableError> {
         if index.isinstance::<VMRange>() {
             let range = index.as_const_type::<VMRange>();
-            let start = range.start;
-            let end = range.end;
-            if start < 0 || end > self.values.len() as i64 {
+            let Some((start, end)) = resolve_range(range.start, range.end, self.values.len())
+            else {
                 return Err(VMVariableError::ValueError2Param(
                     GCRef::wrap(self).clone_ref(),
                     index.clone_ref(),
                     "Index out of bounds".to_string(),
                 ));
-            }
+            };
 
             // Collect references first to avoid multiple mutable borrows
-            let mut slice_refs: Vec<GCRef> = self.values[start as usize..end as usize]
+            let mut slice_refs: Vec<GCRef> = self.values[start..end]
                 .iter_mut()
                 .map(|r| r.clone())
                 .collect();
@@ -2616,20 +3256,12 @@ impl VMTuple {
             return Ok(result);
         } else if index.isinstance::<VMInt>() {
             let idx = index.as_const_type::<VMInt>().value;
-            if idx < 0 {
-                return Err(VMVariableError::ValueError(
+            let Some(idx) = resolve_index(idx, self.values.len()) else {
+                return Err(VMVariableError::IndexNotFound(
                     index.clone_ref(),
-                    "Index must be a non-negative integer".to_string(),
-                ));
-            }
-            let idx = idx as usize;
-            if idx >= self.values.len() {
-                return Err(VMVariableError::ValueError2Param(
                     GCRef::wrap(self).clone_ref(),
-                    index.clone_ref(),
-                    "Index out of bounds".to_string(),
                 ));
-            }
+            };
             return Ok(self.values[idx].clone_ref());
         }
         Err(VMVariableError::TypeError(
@@ -2944,38 +3576,7 @@ impl GCObject for VMTuple {
 
 impl VMObject for VMTuple {
     fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
-        // 第一阶段：收集所有需要克隆的值
-        let values = self.values.clone();
-        let auto_bind = self.auto_bind;
-        let alias = self.alias.clone();
-
-        // 第二阶段：脱离 self 的引用关系，单独处理元素的深拷贝
-        let mut new_values = Vec::with_capacity(values.len());
-
-        // 对每个元素进行深拷贝
-        for mut value in values {
-            let copied_value = try_deepcopy_as_vmobject(&mut value, gc_system)?;
-            new_values.push(copied_value);
-        }
-
-        // 将 Vec<GCRef> 转换为 Vec<&mut GCRef>
-        let mut refs_as_mut: Vec<&mut GCRef> = new_values.iter_mut().collect();
-
-        // 创建新的元组对象
-        let mut new_tuple = gc_system.new_object(VMTuple::new_with_alias(&mut refs_as_mut, &alias));
-
-        // 如果原对象有 auto_bind 标记，则设置新对象的 auto_bind
-        if auto_bind {
-            let tuple = new_tuple.as_type::<VMTuple>();
-            tuple.auto_bind = true;
-            VMTuple::set_lambda_self(&mut new_tuple);
-        }
-
-        for val in refs_as_mut {
-            val.drop_ref();
-        }
-
-        Ok(new_tuple)
+        try_deepcopy_as_vmobject(&mut GCRef::wrap(self), gc_system)
     }
 
     fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
@@ -3114,6 +3715,7 @@ pub enum VMCoroutineStatus {
     Pending,
     Finished,
     Crashed,
+    Cancelled,
 }
 
 pub trait VMNativeGeneratorFunction: Debug {
@@ -3122,6 +3724,10 @@ pub trait VMNativeGeneratorFunction: Debug {
     fn get_result(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError>; // 获取结果
     fn is_done(&self) -> bool; // 检查是否完成
     fn clone_generator(&self) -> Arc<Box<dyn VMNativeGeneratorFunction>>; // 克隆生成器
+    // 返回 Some(t) 表示在 t 之前无需再调用 step，协程池会在此期间调度其他协程
+    fn deadline(&self) -> Option<std::time::Instant> {
+        None
+    }
 }
 
 impl VMCoroutineStatus {
@@ -3131,6 +3737,7 @@ impl VMCoroutineStatus {
             VMCoroutineStatus::Finished => "Finished".bright_yellow().bold().to_string(),
             VMCoroutineStatus::Pending => "Pending".bright_blue().bold().to_string(),
             VMCoroutineStatus::Crashed => "Crashed".bright_red().bold().to_string(),
+            VMCoroutineStatus::Cancelled => "Cancelled".bright_magenta().bold().to_string(),
         }
     }
 }
@@ -3196,6 +3803,8 @@ pub struct VMLambda {
     pub signature: String,
     pub default_args_tuple: GCRef,
     pub capture: Option<GCRef>, // 附加的捕获对象
+    // 绑定的 self 对象，保持强引用：取出的方法在对象的其他引用释放后仍需可用
+    // 对象与其方法之间的循环由 GCSystem::collect 回收
     pub self_object: Option<GCRef>,
     pub lambda_body: VMLambdaBody,
     pub result: GCRef,
@@ -3223,6 +3832,62 @@ impl std::fmt::Debug for VMLambda {
 }
 
 impl VMLambda {
+    // 返回值常常引用 lambda 自身（例如返回 this 或 self），因此在复制返回值前先登记副本
+    fn deepcopy_with_memo(
+        &mut self,
+        address: *const (),
+        memo: &mut DeepcopyMemo,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut new_default_args_tuple =
+            try_deepcopy_with_memo(&mut self.default_args_tuple, memo, gc_system)?;
+        let mut new_lambda_body = match self.lambda_body {
+            VMLambdaBody::VMInstruction(ref mut instructions) => {
+                match try_deepcopy_with_memo(instructions, memo, gc_system) {
+                    Ok(instructions) => VMLambdaBody::VMInstruction(instructions),
+                    Err(err) => {
+                        new_default_args_tuple.drop_ref();
+                        return Err(err);
+                    }
+                }
+            }
+            VMLambdaBody::VMNativeFunction(_) => self.lambda_body.clone(),
+            VMLambdaBody::VMNativeGeneratorFunction(ref generator) => {
+                VMLambdaBody::VMNativeGeneratorFunction(generator.clone_generator())
+            }
+        };
+
+        let mut placeholder_result = gc_system.new_object(VMNull::new());
+        let mut new_lambda = gc_system.new_object(VMLambda::new_with_alias(
+            self.code_position,
+            self.signature.clone(),
+            &mut new_default_args_tuple,
+            self.capture.as_mut(), // 捕获对象不会被复制
+            None,
+            &mut new_lambda_body,
+            &mut placeholder_result,
+            &self.alias,
+            self.dynamic_params,
+        ));
+        new_default_args_tuple.drop_ref();
+        if let VMLambdaBody::VMInstruction(ref mut instructions) = new_lambda_body {
+            instructions.drop_ref();
+        }
+        placeholder_result.drop_ref();
+        memo.insert(address, Some(new_lambda.clone()));
+
+        let mut new_result = match try_deepcopy_with_memo(&mut self.result, memo, gc_system) {
+            Ok(new_result) => new_result,
+            Err(err) => {
+                new_lambda.drop_ref();
+                return Err(err);
+            }
+        };
+        new_lambda.as_type::<VMLambda>().set_result(&mut new_result);
+        new_result.drop_ref();
+        Ok(new_lambda)
+    }
+
     pub fn new(
         code_position: usize,
         signature: String,
@@ -3366,14 +4031,49 @@ impl VMLambda {
         self.traceable.remove_reference(&mut result);
     }
 
+    // 结构相等：签名、代码位置、默认参数以及函数体相同
+    // native 函数仅在指向同一个函数指针时相等，生成器仅在共享同一实例时相等
+    pub fn eq(&self, other: &GCRef) -> bool {
+        if !other.isinstance::<VMLambda>() {
+            return false;
+        }
+        let other_lambda = other.as_const_type::<VMLambda>();
+        if self.signature != other_lambda.signature
+            || self.code_position != other_lambda.code_position
+            || self.dynamic_params != other_lambda.dynamic_params
+        {
+            return false;
+        }
+        let same_body = match (&self.lambda_body, &other_lambda.lambda_body) {
+            (VMLambdaBody::VMInstruction(a), VMLambdaBody::VMInstruction(b)) => {
+                a == b
+                    || (a.isinstance::<VMInstructions>()
+                        && b.isinstance::<VMInstructions>()
+                        && a.as_const_type::<VMInstructions>()
+                            .vm_instructions_package
+                            .get_code()
+                            == b.as_const_type::<VMInstructions>()
+                                .vm_instructions_package
+                                .get_code())
+            }
+            (VMLambdaBody::VMNativeFunction(a), VMLambdaBody::VMNativeFunction(b)) => {
+                std::ptr::fn_addr_eq(*a, *b)
+            }
+            (
+                VMLambdaBody::VMNativeGeneratorFunction(a),
+                VMLambdaBody::VMNativeGeneratorFunction(b),
+            ) => Arc::ptr_eq(a, b),
+            _ => false,
+        };
+        same_body && try_eq_as_vmobject(&self.default_args_tuple, &other_lambda.default_args_tuple)
+    }
+
     pub fn set_self_object(&mut self, self_object: &mut GCRef) {
-        if self.self_object.is_some() {
-            self.traceable
-                .remove_reference(self.self_object.as_mut().unwrap());
+        if let Some(old_self) = self.self_object.as_mut() {
+            self.traceable.remove_reference(old_self);
         }
+        self.traceable.add_reference(self_object);
         self.self_object = Some(self_object.clone());
-        self.traceable
-            .add_reference(self.self_object.as_mut().unwrap());
     }
 
     pub fn get_capture(&mut self) -> Option<&mut GCRef> {
@@ -3401,9 +4101,8 @@ impl GCObject for VMLambda {
             VMLambdaBody::VMNativeGeneratorFunction(_) => {}
         }
         self.traceable.remove_reference(&mut self.result);
-        if self.self_object.is_some() {
-            self.traceable
-                .remove_reference(self.self_object.as_mut().unwrap());
+        if let Some(self_object) = self.self_object.as_mut() {
+            self.traceable.remove_reference(self_object);
         }
         if self.capture.is_some() {
             self.traceable
@@ -3422,49 +4121,7 @@ impl GCObject for VMLambda {
 
 impl VMObject for VMLambda {
     fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
-        let mut new_default_args_tuple = self
-            .default_args_tuple
-            .as_type::<VMTuple>()
-            .deepcopy(gc_system)?;
-
-        let mut new_result: GCRef = try_deepcopy_as_vmobject(&mut self.result, gc_system)?;
-        let mut new_lambda_body = match self.lambda_body {
-            VMLambdaBody::VMInstruction(ref mut instructions) => {
-                VMLambdaBody::VMInstruction(try_deepcopy_as_vmobject(instructions, gc_system)?)
-            }
-            VMLambdaBody::VMNativeFunction(_) => self.lambda_body.clone(),
-            VMLambdaBody::VMNativeGeneratorFunction(_) => {
-                let VMLambdaBody::VMNativeGeneratorFunction(ref gen) = self.lambda_body else {
-                    return Err(VMVariableError::ValueError(
-                        GCRef::wrap(self).clone_ref(),
-                        "Invalid lambda body type".to_string(),
-                    ));
-                };
-                VMLambdaBody::VMNativeGeneratorFunction(gen.clone_generator())
-            }
-        };
-
-        let new_lambda = gc_system.new_object(VMLambda::new_with_alias(
-            self.code_position,
-            self.signature.clone(),
-            &mut new_default_args_tuple,
-            self.capture.as_mut(), // 捕获对象不会被复制
-            None,
-            &mut new_lambda_body,
-            &mut new_result,
-            &self.alias,
-            self.dynamic_params,
-        ));
-        new_default_args_tuple.drop_ref();
-        match new_lambda_body {
-            VMLambdaBody::VMInstruction(ref mut instructions) => {
-                instructions.drop_ref();
-            }
-            VMLambdaBody::VMNativeFunction(_) => {}
-            VMLambdaBody::VMNativeGeneratorFunction(_) => {}
-        }
-        new_result.drop_ref();
-        Ok(new_lambda)
+        try_deepcopy_as_vmobject(&mut GCRef::wrap(self), gc_system)
     }
 
     fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
@@ -3716,6 +4373,42 @@ impl VMRange {
         ))
     }
 
+    // 与元组相同的下标规则：整数下标返回 start + index，区间下标返回子区间
+    pub fn index_of(
+        &mut self,
+        index: &mut GCRef,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let len = self.len().max(0) as usize;
+        if index.isinstance::<VMInt>() {
+            let index_int = index.as_const_type::<VMInt>();
+            let Some(position) = resolve_index(index_int.value, len) else {
+                return Err(VMVariableError::IndexNotFound(
+                    index.clone_ref(),
+                    GCRef::wrap(self).clone_ref(),
+                ));
+            };
+            return Ok(gc_system.new_object(VMInt::new(self.start + position as i64)));
+        } else if index.isinstance::<VMRange>() {
+            let range = index.as_const_type::<VMRange>();
+            let Some((start, end)) = resolve_range(range.start, range.end, len) else {
+                return Err(VMVariableError::IndexNotFound(
+                    index.clone_ref(),
+                    GCRef::wrap(self).clone_ref(),
+                ));
+            };
+            return Ok(gc_system.new_object(VMRange::new(
+                self.start + start as i64,
+                self.start + end as i64,
+            )));
+        }
+        Err(VMVariableError::ValueError2Param(
+            GCRef::wrap(self).clone_ref(),
+            index.clone_ref(),
+            "Cannot index range with a non-integer type".to_string(),
+        ))
+    }
+
     pub fn eq(&self, other: &GCRef) -> bool {
         if other.isinstance::<VMRange>() {
             let other_range = other.as_const_type::<VMRange>();
@@ -3857,25 +4550,23 @@ impl VMBytes {
     ) -> Result<GCRef, VMVariableError> {
         if index.isinstance::<VMInt>() {
             let index_int = index.as_const_type::<VMInt>();
-            if index_int.value < 0 || index_int.value >= self.value.len() as i64 {
+            let Some(position) = resolve_index(index_int.value, self.value.len()) else {
                 return Err(VMVariableError::IndexNotFound(
                     index.clone_ref(),
                     GCRef::wrap(self).clone_ref(),
                 ));
-            }
-            let byte = self.value[index_int.value as usize];
+            };
+            let byte = self.value[position];
             return Ok(gc_system.new_object(VMInt::new(byte as i64)));
         } else if index.isinstance::<VMRange>() {
             let range = index.as_const_type::<VMRange>();
-            let start = range.start;
-            let end = range.end;
-            if start < 0 || end > self.value.len() as i64 {
+            let Some((start, end)) = resolve_range(range.start, range.end, self.value.len()) else {
                 return Err(VMVariableError::IndexNotFound(
                     index.clone_ref(),
                     GCRef::wrap(self).clone_ref(),
                 ));
-            }
-            let slice = &self.value[start as usize..end as usize];
+            };
+            let slice = &self.value[start..end];
             return Ok(gc_system.new_object(VMBytes::new(&slice.to_vec())));
         }
 
@@ -4134,6 +4825,9 @@ impl VMObject for VMBytes {
     }
 }
 
+// 集合类型 {collection | filter}
+// repr 只由 collection 与 filter 的定义决定：filter 为 lambda 时只显示签名与默认参数，
+// 不显示其上一次调用的结果，因此同一个集合在过滤前后的 repr 保持一致
 #[derive(Debug)]
 pub struct VMSet {
     pub collection: GCRef,
@@ -4143,6 +4837,29 @@ pub struct VMSet {
 }
 
 impl VMSet {
+    fn deepcopy_with_memo(
+        &mut self,
+        memo: &mut DeepcopyMemo,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut new_collection = try_deepcopy_with_memo(&mut self.collection, memo, gc_system)?;
+        let mut new_filter = match try_deepcopy_with_memo(&mut self.filter, memo, gc_system) {
+            Ok(new_filter) => new_filter,
+            Err(err) => {
+                new_collection.drop_ref();
+                return Err(err);
+            }
+        };
+        let new_set = gc_system.new_object(VMSet::new_with_alias(
+            &mut new_collection,
+            &mut new_filter,
+            &self.alias,
+        ));
+        new_collection.drop_ref();
+        new_filter.drop_ref();
+        Ok(new_set)
+    }
+
     pub fn new(collection: &mut GCRef, filter: &mut GCRef) -> Self {
         VMSet {
             collection: collection.clone(),
@@ -4178,17 +4895,9 @@ impl GCObject for VMSet {
 }
 impl VMObject for VMSet {
     fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
-        let mut new_collection = try_deepcopy_as_vmobject(&mut self.collection, gc_system)?;
-        let mut new_filter = try_deepcopy_as_vmobject(&mut self.filter, gc_system)?;
-        let new_set = gc_system.new_object(VMSet::new_with_alias(
-            &mut new_collection,
-            &mut new_filter,
-            &self.alias,
-        ));
-        new_collection.drop_ref();
-        new_filter.drop_ref();
-        Ok(new_set)
+        try_deepcopy_as_vmobject(&mut GCRef::wrap(self), gc_system)
     }
+
     fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
         let mut new_collection = try_copy_as_vmobject(&mut self.collection, gc_system)?;
         let mut new_filter = try_copy_as_vmobject(&mut self.filter, gc_system)?;
@@ -4244,6 +4953,64 @@ impl VMSet {
         try_contains_as_vmobject(&mut self.collection, other)
     }
 }
+
+impl VMSet {
+    /**
+     * 对 collection 中的每个元素调用 filter，保留结果为 true 的元素并返回一个新的 VMTuple
+     * filter 为 VMNull 时视为恒等过滤器，保留所有元素
+     */
+    pub fn materialize(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        if !self.collection.isinstance::<VMTuple>()
+            && !self.collection.isinstance::<VMString>()
+            && !self.collection.isinstance::<VMBytes>()
+            && !self.collection.isinstance::<VMRange>()
+        {
+            return Err(VMVariableError::TypeError(
+                self.collection.clone_ref(),
+                "Set collection is not iterable".to_string(),
+            ));
+        }
+        let identity_filter = self.filter.isinstance::<VMNull>();
+        if !identity_filter && !self.filter.isinstance::<VMLambda>() {
+            return Err(VMVariableError::TypeError(
+                self.filter.clone_ref(),
+                "Set filter must be a lambda or null".to_string(),
+            ));
+        }
+
+        let mut result = gc_system.new_object(VMTuple::new(&mut vec![]));
+        self.reset();
+        while let Some(mut item) = self.next(gc_system) {
+            let keep = if identity_filter {
+                true
+            } else {
+                let mut args = gc_system.new_object(VMTuple::new(&mut vec![&mut item]));
+                let outcome = call_lambda_sync(&mut self.filter, &mut args, gc_system);
+                args.drop_ref();
+                match outcome {
+                    Ok(mut value) => {
+                        let keep = value.isinstance::<VMBoolean>()
+                            && value.as_const_type::<VMBoolean>().value;
+                        value.drop_ref();
+                        keep
+                    }
+                    Err(err) => {
+                        item.drop_ref();
+                        result.drop_ref();
+                        self.reset();
+                        return Err(err.into_variable_error());
+                    }
+                }
+            };
+            if keep {
+                result.as_type::<VMTuple>().append(&mut item)?;
+            }
+            item.drop_ref();
+        }
+        self.reset();
+        Ok(result)
+    }
+}
 impl VMIterable for VMSet {
     fn next(&mut self, gc_system: &mut GCSystem) -> Option<GCRef> {
         if self.collection.isinstance::<VMTuple>() {
@@ -4271,3 +5038,451 @@ impl VMIterable for VMSet {
         }
     }
 }
+
+// 键的哈希函数，默认使用 try_hash_as_vmobject
+pub type VMDictHasher = fn(&mut GCRef) -> Result<u64, VMVariableError>;
+
+// 字典类型
+// 基于结构化哈希的散列表，查找与插入的平均复杂度为 O(1)；删除需要移动其后的键值对并更新下标，为 O(n)
+// 键值对按插入顺序保存，迭代时依次产生 VMKeyVal，顺序与哈希值无关
+#[derive(Debug)]
+pub struct VMDict {
+    entries: Vec<(GCRef, GCRef)>,
+    // 哈希值 -> entries 中的下标，哈希冲突的键共享同一个桶，再用 try_eq_as_vmobject 区分
+    buckets: FxHashMap<u64, Vec<usize>>,
+    hasher: VMDictHasher,
+    traceable: GCTraceable,
+    alias: Vec<String>,
+    iter_index: usize,
+}
+
+impl Default for VMDict {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VMDict {
+    // 与元组相同，先登记空的副本，值引用回本字典时复用该副本
+    fn deepcopy_with_memo(
+        &mut self,
+        address: *const (),
+        memo: &mut DeepcopyMemo,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut new_dict = VMDict::with_hasher(self.hasher);
+        new_dict.alias = self.alias.clone();
+        let mut new_dict = gc_system.new_object(new_dict);
+        memo.insert(address, Some(new_dict.clone()));
+
+        let entries = self.entries.clone();
+        for (mut key, mut value) in entries {
+            let copied =
+                try_deepcopy_with_memo(&mut key, memo, gc_system).and_then(|mut new_key| {
+                    match try_deepcopy_with_memo(&mut value, memo, gc_system) {
+                        Ok(new_value) => Ok((new_key, new_value)),
+                        Err(err) => {
+                            new_key.drop_ref();
+                            Err(err)
+                        }
+                    }
+                });
+            let (mut new_key, mut new_value) = match copied {
+                Ok(entry) => entry,
+                Err(err) => {
+                    new_dict.drop_ref();
+                    return Err(err);
+                }
+            };
+            let result = new_dict
+                .as_type::<VMDict>()
+                .insert(&mut new_key, &mut new_value);
+            new_key.drop_ref();
+            new_value.drop_ref();
+            if let Err(err) = result {
+                new_dict.drop_ref();
+                return Err(err);
+            }
+        }
+        Ok(new_dict)
+    }
+
+    pub fn new() -> Self {
+        VMDict::with_hasher(try_hash_as_vmobject)
+    }
+
+    pub fn with_hasher(hasher: VMDictHasher) -> Self {
+        VMDict {
+            entries: Vec::new(),
+            buckets: FxHashMap::default(),
+            hasher,
+            traceable: GCTraceable::new::<VMDict>(None),
+            alias: Vec::new(),
+            iter_index: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 返回键的哈希值以及它在 entries 中的位置（不存在时为 None）
+    fn find(&self, key: &mut GCRef) -> Result<(u64, Option<usize>), VMVariableError> {
+        let hash = (self.hasher)(key)?;
+        let position = self.buckets.get(&hash).and_then(|bucket| {
+            bucket
+                .iter()
+                .copied()
+                .find(|&index| try_eq_as_vmobject(&self.entries[index].0, key))
+        });
+        Ok((hash, position))
+    }
+
+    // 插入键值对，键已存在时只替换值并保留原有的插入位置
+    pub fn insert(&mut self, key: &mut GCRef, value: &mut GCRef) -> Result<(), VMVariableError> {
+        let (hash, position) = self.find(key)?;
+        self.traceable.add_reference(value);
+        match position {
+            Some(index) => {
+                let mut old_value = std::mem::replace(&mut self.entries[index].1, value.clone());
+                self.traceable.remove_reference(&mut old_value);
+            }
+            None => {
+                self.traceable.add_reference(key);
+                self.entries.push((key.clone(), value.clone()));
+                self.buckets
+                    .entry(hash)
+                    .or_default()
+                    .push(self.entries.len() - 1);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&mut self, key: &mut GCRef) -> Result<Option<&mut GCRef>, VMVariableError> {
+        let (_, position) = self.find(key)?;
+        Ok(position.map(|index| &mut self.entries[index].1))
+    }
+
+    pub fn contains(&mut self, key: &mut GCRef) -> Result<bool, VMVariableError> {
+        Ok(self.find(key)?.1.is_some())
+    }
+
+    // 删除键值对，返回键是否存在；其后的键值对保持原有顺序，因此需要 O(n) 地移动元素并更新桶中的下标
+    pub fn remove(&mut self, key: &mut GCRef) -> Result<bool, VMVariableError> {
+        let (hash, Some(position)) = self.find(key)? else {
+            return Ok(false);
+        };
+        let (mut old_key, mut old_value) = self.entries.remove(position);
+        self.traceable.remove_reference(&mut old_key);
+        self.traceable.remove_reference(&mut old_value);
+
+        if let Some(bucket) = self.buckets.get_mut(&hash) {
+            bucket.retain(|&index| index != position);
+            if bucket.is_empty() {
+                self.buckets.remove(&hash);
+            }
+        }
+        for bucket in self.buckets.values_mut() {
+            for index in bucket.iter_mut() {
+                if *index > position {
+                    *index -= 1;
+                }
+            }
+        }
+        if self.iter_index > position {
+            self.iter_index -= 1;
+        }
+        Ok(true)
+    }
+
+    pub fn get_member(&mut self, key: &mut GCRef) -> Result<&mut GCRef, VMVariableError> {
+        let (_, position) = self.find(key)?;
+        match position {
+            Some(index) => Ok(&mut self.entries[index].1),
+            None => Err(VMVariableError::KeyNotFound(
+                key.clone_ref(),
+                GCRef::wrap(self).clone_ref(),
+            )),
+        }
+    }
+
+    fn eq(&self, other: &GCRef) -> bool {
+        if !other.isinstance::<VMDict>() {
+            return false;
+        }
+        let other_dict = other.as_const_type::<VMDict>();
+        if self.len() != other_dict.len() {
+            return false;
+        }
+        // 与插入顺序无关：每个键都能在对方中找到且值相等
+        for (key, value) in &self.entries {
+            match other_dict.find(&mut key.clone()) {
+                Ok((_, Some(index))) => {
+                    if !try_eq_as_vmobject(value, &other_dict.entries[index].1) {
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    // 依次插入一组键值对，调用方仍持有传入的引用
+    fn extend_from(&mut self, entries: &mut [(GCRef, GCRef)]) -> Result<(), VMVariableError> {
+        for (key, value) in entries.iter_mut() {
+            self.insert(key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl VMIterable for VMDict {
+    fn next(&mut self, gc_system: &mut GCSystem) -> Option<GCRef> {
+        if self.iter_index < self.entries.len() {
+            let (key, value) = &mut self.entries[self.iter_index];
+            self.iter_index += 1;
+            Some(gc_system.new_object(VMKeyVal::new(key, value)))
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        self.iter_index = 0;
+    }
+}
+
+impl GCObject for VMDict {
+    fn free(&mut self) {
+        // 移除对所有键和值的引用
+        for (key, value) in &mut self.entries {
+            self.traceable.remove_reference(key);
+            self.traceable.remove_reference(value);
+        }
+    }
+
+    fn get_traceable(&mut self) -> &mut GCTraceable {
+        &mut self.traceable
+    }
+
+    fn get_const_traceable(&self) -> &GCTraceable {
+        &self.traceable
+    }
+}
+
+impl VMObject for VMDict {
+    fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        try_deepcopy_as_vmobject(&mut GCRef::wrap(self), gc_system)
+    }
+
+    fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        let mut new_dict = VMDict::with_hasher(self.hasher);
+        new_dict.alias = self.alias.clone();
+        let mut new_dict = gc_system.new_object(new_dict);
+        if let Err(err) = new_dict.as_type::<VMDict>().extend_from(&mut self.entries) {
+            new_dict.drop_ref();
+            return Err(err);
+        }
+        Ok(new_dict)
+    }
+
+    fn assign<'t>(&mut self, value: &'t mut GCRef) -> Result<&'t mut GCRef, VMVariableError> {
+        if value.isinstance::<VMDict>() {
+            let other_dict = value.as_type::<VMDict>();
+            // 先添加对新键值对的引用，再移除旧的，避免两者共享的对象被提前释放
+            for (key, value) in other_dict.entries.iter_mut() {
+                self.traceable.add_reference(key);
+                self.traceable.add_reference(value);
+            }
+            for (key, value) in self.entries.iter_mut() {
+                self.traceable.remove_reference(key);
+                self.traceable.remove_reference(value);
+            }
+            self.entries = other_dict.entries.clone();
+            self.buckets = other_dict.buckets.clone();
+            self.hasher = other_dict.hasher;
+            self.iter_index = 0;
+            Ok(value)
+        } else {
+            Err(VMVariableError::ValueError2Param(
+                GCRef::wrap(self).clone_ref(),
+                value.clone_ref(),
+                "Cannot assign a value of non-dict type".to_string(),
+            ))
+        }
+    }
+
+    fn alias_const(&self) -> &Vec<String> {
+        &self.alias
+    }
+
+    fn alias(&mut self) -> &mut Vec<String> {
+        &mut self.alias
+    }
+}
+
+// 通道类型
+// 有界的先进先出队列，用于在协程之间传递值
+// 队列满时 send 失败、队列空时 recv 失败，由调用者（asyncio.send / asyncio.recv）负责让出协程并重试
+#[derive(Debug)]
+pub struct VMChannel {
+    buffer: VecDeque<GCRef>,
+    capacity: usize,
+    traceable: GCTraceable,
+    alias: Vec<String>,
+}
+
+impl VMChannel {
+    // 缓冲区按需增长，容量只在 try_send 时检查，避免按脚本给出的容量预先分配内存
+    pub fn new(capacity: usize) -> Self {
+        VMChannel {
+            buffer: VecDeque::new(),
+            capacity,
+            traceable: GCTraceable::new::<VMChannel>(None),
+            alias: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.buffer.len() >= self.capacity
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // 尝试把值放入队尾，队列已满时返回 false 且不持有该值
+    pub fn try_send(&mut self, value: &mut GCRef) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.traceable.add_reference(value);
+        self.buffer.push_back(value.clone());
+        true
+    }
+
+    // 尝试从队首取出一个值，返回值持有一个新的引用，调用者负责 drop_ref()
+    pub fn try_recv(&mut self) -> Option<GCRef> {
+        let mut value = self.buffer.pop_front()?;
+        let result = value.clone_ref();
+        self.traceable.remove_reference(&mut value);
+        Some(result)
+    }
+
+    // 用给定的值填充一个新通道，values 的引用计数不会被消耗
+    fn deepcopy_with_memo(
+        &mut self,
+        memo: &mut DeepcopyMemo,
+        gc_system: &mut GCSystem,
+    ) -> Result<GCRef, VMVariableError> {
+        let mut new_values = Vec::with_capacity(self.buffer.len());
+        for value in self.buffer.iter_mut() {
+            match try_deepcopy_with_memo(value, memo, gc_system) {
+                Ok(new_value) => new_values.push(new_value),
+                Err(err) => {
+                    for new_value in new_values.iter_mut() {
+                        new_value.drop_ref();
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        let channel = VMChannel::new_filled(self.capacity, &mut new_values, &self.alias, gc_system);
+        for new_value in new_values.iter_mut() {
+            new_value.drop_ref();
+        }
+        Ok(channel)
+    }
+
+    fn new_filled(
+        capacity: usize,
+        values: &mut [GCRef],
+        alias: &[String],
+        gc_system: &mut GCSystem,
+    ) -> GCRef {
+        let mut channel = VMChannel::new(capacity);
+        channel.alias = alias.to_vec();
+        let mut channel = gc_system.new_object(channel);
+        let channel_obj = channel.as_type::<VMChannel>();
+        for value in values.iter_mut() {
+            channel_obj.traceable.add_reference(value);
+            channel_obj.buffer.push_back(value.clone());
+        }
+        channel
+    }
+}
+
+impl GCObject for VMChannel {
+    fn free(&mut self) {
+        for value in &mut self.buffer {
+            self.traceable.remove_reference(value);
+        }
+    }
+
+    fn get_traceable(&mut self) -> &mut GCTraceable {
+        &mut self.traceable
+    }
+
+    fn get_const_traceable(&self) -> &GCTraceable {
+        &self.traceable
+    }
+}
+
+impl VMObject for VMChannel {
+    fn deepcopy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        try_deepcopy_as_vmobject(&mut GCRef::wrap(self), gc_system)
+    }
+
+    fn copy(&mut self, gc_system: &mut GCSystem) -> Result<GCRef, VMVariableError> {
+        Ok(VMChannel::new_filled(
+            self.capacity,
+            self.buffer.make_contiguous(),
+            &self.alias,
+            gc_system,
+        ))
+    }
+
+    fn assign<'t>(&mut self, value: &'t mut GCRef) -> Result<&'t mut GCRef, VMVariableError> {
+        if value.isinstance::<VMChannel>() {
+            let other_channel = value.as_type::<VMChannel>();
+            // 先添加对新元素的引用，再移除旧的，避免两者共享的对象被提前释放
+            for item in other_channel.buffer.iter_mut() {
+                self.traceable.add_reference(item);
+            }
+            for item in self.buffer.iter_mut() {
+                self.traceable.remove_reference(item);
+            }
+            self.buffer = other_channel.buffer.clone();
+            self.capacity = other_channel.capacity;
+            Ok(value)
+        } else {
+            Err(VMVariableError::ValueError2Param(
+                GCRef::wrap(self).clone_ref(),
+                value.clone_ref(),
+                "Cannot assign a value of non-channel type".to_string(),
+            ))
+        }
+    }
+
+    fn alias_const(&self) -> &Vec<String> {
+        &self.alias
+    }
+
+    fn alias(&mut self) -> &mut Vec<String> {
+        &mut self.alias
+    }
+}
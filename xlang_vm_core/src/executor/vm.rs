@@ -1,7 +1,11 @@
 use crate::instruction_set::VMInstruction;
+use crate::instruction_set::VMInstructionPackage;
 use crate::opcode::Instruction32;
 use crate::opcode::ProcessedOpcode;
 use colored::Colorize;
+use rustc_hash::FxHashSet;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
 
 use super::super::gc::*;
 use super::context::*;
@@ -22,6 +26,25 @@ pub enum VMError {
     InvalidArgument(GCRef, String),
     FileError(String),
     DetailedError(String),
+    StackOverflow(Vec<String>), // 超出最大调用深度，附带由外向内的函数签名链
+    AtSourcePosition(Box<VMError>, SourcePosition),
+    WithTraceback(Box<VMError>, Vec<TraceFrame>),
+}
+
+// 运行时错误对应的源码位置，行列号均从 1 开始
+#[derive(Debug, Clone)]
+pub struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+    pub source_line: String,
+}
+
+// 调用栈中的一帧，code_position 与 position 为该帧当前执行到的位置
+#[derive(Debug, Clone)]
+pub struct TraceFrame {
+    pub signature: String,
+    pub code_position: Option<usize>,
+    pub position: Option<SourcePosition>,
 }
 
 impl VMError {
@@ -74,6 +97,70 @@ impl VMError {
             ),
             VMError::FileError(msg) => format!("{}: {}", "FileError".bright_red().bold(), msg),
             VMError::DetailedError(msg) => msg.to_string(),
+            VMError::StackOverflow(signatures) => {
+                // 调用链可能很长，只展示最内层的若干帧
+                const SHOWN_FRAMES: usize = 8;
+                let skipped = signatures.len().saturating_sub(SHOWN_FRAMES);
+                let mut chain: Vec<String> = signatures[skipped..].to_vec();
+                if skipped > 0 {
+                    chain.insert(0, format!("... ({} more)", skipped));
+                }
+                format!(
+                    "{}: call depth limit exceeded after {} frames\n  {}",
+                    "StackOverflow".bright_red().bold(),
+                    signatures.len(),
+                    chain.join("\n  -> ")
+                )
+            }
+            VMError::AtSourcePosition(err, position) => format!(
+                "{}\n{} {}\n{} {}",
+                err.to_string(),
+                "-->".bright_blue().bold(),
+                format!("{}:{}", position.line, position.column).bright_white(),
+                format!("{:4} |", position.line).bright_black(),
+                position.source_line
+            ),
+            VMError::WithTraceback(err, _) => err.to_string(),
+        }
+    }
+
+    // 去掉源码位置与调用栈等外层信息，取出脚本可以捕获的变量错误
+    // 其余执行器错误（如 StackOverflow、AssertFailed）原样返回
+    pub fn into_catchable(self) -> Result<VMVariableError, VMError> {
+        match self {
+            VMError::VMVariableError(err) => Ok(err),
+            VMError::AtSourcePosition(err, _) | VMError::WithTraceback(err, _) => {
+                err.into_catchable()
+            }
+            err => Err(err),
+        }
+    }
+
+    // 供 native 函数向上传递执行器错误，保留变量错误的类别，其余错误会被转换为 DetailedError
+    pub fn into_variable_error(self) -> VMVariableError {
+        match self.into_catchable() {
+            Ok(err) => err,
+            Err(mut err) => {
+                let message = err.to_string();
+                err.consume_ref();
+                VMVariableError::DetailedError(message)
+            }
+        }
+    }
+
+    pub fn source_position(&self) -> Option<&SourcePosition> {
+        match self {
+            VMError::AtSourcePosition(_, position) => Some(position),
+            _ => None,
+        }
+    }
+
+    // 出错时的调用栈，由外向内排列
+    pub fn traceback(&self) -> Option<&Vec<TraceFrame>> {
+        match self {
+            VMError::AtSourcePosition(err, _) => err.traceback(),
+            VMError::WithTraceback(_, frames) => Some(frames),
+            _ => None,
         }
     }
 
@@ -85,28 +172,122 @@ impl VMError {
             VMError::InvalidArgument(obj, _) => obj.drop_ref(),
             VMError::ContextError(err) => err.consume_ref(),
             VMError::VMVariableError(err) => err.consume_ref(),
+            VMError::AtSourcePosition(err, _) => err.consume_ref(),
+            VMError::WithTraceback(err, _) => err.consume_ref(),
             _ => (),
         }
     }
 }
 
-#[derive(Debug)]
+/// 调试钩子的返回值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookAction {
+    Continue, // 继续执行该指令
+    Pause,    // 在该指令执行前暂停，将控制权交还宿主
+}
+
+/**
+ * 在每条字节码指令执行前被调用的钩子，可用于实现调试器
+ * ip: 即将执行的指令位置
+ * coroutine_id: 所属协程的 ID
+ * opcode: 解码后的指令
+ * executor: 所属执行器，可用于查看栈与上下文
+ *
+ * 注意：
+ * + 返回 Pause 时该指令不会被执行，恢复运行后会直接执行该指令而不再调用钩子
+ * + native 生成器函数的步进不会触发钩子
+ */
+pub trait ExecutorHook {
+    fn on_instruction(
+        &mut self,
+        ip: usize,
+        coroutine_id: isize,
+        opcode: &ProcessedOpcode,
+        executor: &VMExecutor,
+    ) -> HookAction;
+}
+
+// 默认的最大调用深度
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10000;
+
+// native 函数回调脚本时嵌套协程池的最大层数
+// 每层嵌套都会占用宿主线程的栈，max_call_depth 只限制单个协程池内的调用深度
+pub const MAX_NATIVE_CALL_NESTING: usize = 100;
+
+thread_local! {
+    static NATIVE_CALL_NESTING: Cell<usize> = const { Cell::new(0) };
+}
+
+// 记录一层 native 到脚本的嵌套调用，离开作用域时恢复计数
+struct NativeCallNesting;
+
+impl NativeCallNesting {
+    fn enter(signature: &str) -> Result<Self, VMError> {
+        NATIVE_CALL_NESTING.with(|nesting| {
+            if nesting.get() >= MAX_NATIVE_CALL_NESTING {
+                return Err(VMError::StackOverflow(vec![signature.to_string()]));
+            }
+            nesting.set(nesting.get() + 1);
+            Ok(NativeCallNesting)
+        })
+    }
+}
+
+impl Drop for NativeCallNesting {
+    fn drop(&mut self) {
+        NATIVE_CALL_NESTING.with(|nesting| nesting.set(nesting.get() - 1));
+    }
+}
+
 // 协程池
 pub struct VMCoroutinePool {
     pub executors: Vec<(VMExecutor, isize)>, // executor, id
     pub gen_id: isize,
     pub enable_dump: bool,
+    pub steps_per_tick: usize, // run_one_step 每次调用最多调度的轮数
+    pub hook: Option<Box<dyn ExecutorHook>>, // 每条指令执行前调用的调试钩子
+    pub max_call_depth: usize, // 新建协程的最大调用深度，超出时返回 StackOverflow
+    paused: bool,
+    resume_coroutines: Vec<isize>, // 恢复运行时跳过一次钩子的协程
+}
+
+impl std::fmt::Debug for VMCoroutinePool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VMCoroutinePool")
+            .field("executors", &self.executors)
+            .field("gen_id", &self.gen_id)
+            .field("enable_dump", &self.enable_dump)
+            .field("steps_per_tick", &self.steps_per_tick)
+            .field("hook", &self.hook.is_some())
+            .field("max_call_depth", &self.max_call_depth)
+            .field("paused", &self.paused)
+            .finish()
+    }
 }
 
 impl VMCoroutinePool {
     pub fn new(enable_dump: bool) -> Self {
+        VMCoroutinePool::with_max_call_depth(enable_dump, DEFAULT_MAX_CALL_DEPTH)
+    }
+
+    pub fn with_max_call_depth(enable_dump: bool, max_call_depth: usize) -> Self {
         VMCoroutinePool {
             executors: Vec::new(),
             gen_id: 0,
             enable_dump,
+            steps_per_tick: 1024,
+            hook: None,
+            max_call_depth,
+            paused: false,
+            resume_coroutines: Vec::new(),
         }
     }
 
+    // 上一次运行是否因钩子返回 Pause 而中断，再次调用 run_* 即可从中断处继续
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     /**
      * 创建一个新的协程
      * lambda_object: 协程对象
@@ -132,6 +313,7 @@ impl VMCoroutinePool {
             ));
         }
         let mut executor = VMExecutor::new(&lambda_object.clone_ref());
+        executor.max_call_depth = self.max_call_depth;
 
         // 检查是否已有执行器使用该 lambda
         for (executor, _) in &self.executors {
@@ -174,16 +356,66 @@ impl VMCoroutinePool {
         None
     }
 
+    /**
+     * 取消一个协程
+     * id: 协程的 ID
+     *
+     * 注意：
+     * + 协程会被标记为 Cancelled，之后不再被调度
+     * + 被取消的协程会立即从协程池中移除并释放其持有的引用
+     * + 已经结束的协程无法被取消
+     */
+    pub fn cancel_coroutine(&mut self, id: isize) -> Result<(), VMError> {
+        let Some(executor) = self.get_executor_mut(id) else {
+            return Err(VMError::DetailedError(format!(
+                "Coroutine {} does not exist",
+                id
+            )));
+        };
+        let lambda = executor.entry_lambda.as_type::<VMLambda>();
+        if lambda.coroutine_status == VMCoroutineStatus::Finished {
+            return Err(VMError::DetailedError(format!(
+                "Coroutine {} has already finished",
+                id
+            )));
+        }
+        lambda.coroutine_status = VMCoroutineStatus::Cancelled;
+        self.sweep_finished();
+        Ok(())
+    }
+
     pub fn step_all(
         &mut self,
         gc_system: &mut GCSystem,
     ) -> Result<Option<Vec<SpawnedCoroutine>>, (isize, VMError)> {
         let mut spawned_coroutines = Vec::<SpawnedCoroutine>::new();
+        let now = Instant::now();
         for (e, id) in &mut self.executors {
+            // 休眠中的协程在截止时间前不参与调度
+            if e.sleep_deadline().is_some_and(|deadline| deadline > now) {
+                continue;
+            }
+            if let Some(hook) = self.hook.as_mut() {
+                if let Some(pos) = self.resume_coroutines.iter().position(|i| i == id) {
+                    self.resume_coroutines.swap_remove(pos);
+                } else if let Some(opcode) = e.peek_instruction() {
+                    let ip = e.ip as usize;
+                    if hook.on_instruction(ip, *id, &opcode, e) == HookAction::Pause {
+                        self.paused = true;
+                        self.resume_coroutines.push(*id);
+                        break;
+                    }
+                }
+            }
             let new_coroutines = e.step(gc_system);
             if new_coroutines.is_err() {
                 e.entry_lambda.as_type::<VMLambda>().coroutine_status = VMCoroutineStatus::Crashed;
-                return Err((*id, new_coroutines.err().unwrap()));
+                let err = new_coroutines.err().unwrap();
+                let err = match e.current_source_position() {
+                    Some(position) => VMError::AtSourcePosition(Box::new(err), position),
+                    None => err,
+                };
+                return Err((*id, err));
             }
             let new_coroutines = new_coroutines.unwrap();
             if let Some(new_coroutines) = new_coroutines {
@@ -196,16 +428,39 @@ impl VMCoroutinePool {
         Ok(Some(spawned_coroutines))
     }
 
+    // 若所有协程都在休眠，返回最早的唤醒时间；只要有协程可以运行就返回 None
+    pub fn next_wake_time(&self) -> Option<Instant> {
+        let now = Instant::now();
+        let mut earliest: Option<Instant> = None;
+        for (e, _) in &self.executors {
+            match e.sleep_deadline() {
+                Some(deadline) if deadline > now => {
+                    earliest = Some(earliest.map_or(deadline, |t| t.min(deadline)));
+                }
+                _ => return None,
+            }
+        }
+        earliest
+    }
+
+    // 所有协程都在休眠时阻塞当前线程直到最早的唤醒时间，避免空转
+    fn wait_for_wake(&self) {
+        if let Some(wake_time) = self.next_wake_time() {
+            std::thread::sleep(wake_time.saturating_duration_since(Instant::now()));
+        }
+    }
+
     pub fn sweep_finished(&mut self) {
-        // 第一阶段：收集已完成的协程索引
+        // 第一阶段：收集已完成或已取消的协程索引
         let mut finished_indices = Vec::new();
         for (i, (executor, _id)) in self.executors.iter().enumerate() {
-            if executor
-                .entry_lambda
-                .as_const_type::<VMLambda>()
-                .coroutine_status
-                == VMCoroutineStatus::Finished
-            {
+            if matches!(
+                executor
+                    .entry_lambda
+                    .as_const_type::<VMLambda>()
+                    .coroutine_status,
+                VMCoroutineStatus::Finished | VMCoroutineStatus::Cancelled
+            ) {
                 finished_indices.push(i);
             }
         }
@@ -220,73 +475,183 @@ impl VMCoroutinePool {
         }
     }
 
-    pub fn run_until_finished(&mut self, gc_system: &mut GCSystem) -> Result<(), VMError> {
-        loop {
-            let spawned_coroutines = self.step_all(gc_system).map_err(|mut vm_error| {
-                let err = if self.enable_dump {
-                    let all_coroutines_contexts_repr = self
-                        .executors
-                        .iter_mut()
-                        .map(|(e, _)| {
-                            let lambda = e.entry_lambda.as_const_type::<VMLambda>();
+    // 调度一轮：每个协程执行一步，处理崩溃、回收已结束的协程并启动新 spawn 的协程
+    fn schedule_once(&mut self, gc_system: &mut GCSystem) -> Result<(), VMError> {
+        let spawned_coroutines = self.step_all(gc_system).map_err(|vm_error| {
+            let err = if self.enable_dump {
+                let all_coroutines_contexts_repr = self
+                    .executors
+                    .iter_mut()
+                    .map(|(e, _)| {
+                        let lambda = e.entry_lambda.as_const_type::<VMLambda>();
+                        format!(
+                            "{}\n{}\n\n{}\n\n{}",
                             format!(
-                                "{}\n{}\n\n{}\n\n{}",
-                                format!(
-                                    "-> {}: {}",
-                                    lambda.signature,
-                                    lambda.coroutine_status.to_string()
-                                )
-                                .bright_yellow()
-                                .bold(),
-                                e.context.format_context(&mut e.stack),
-                                "=== Code ===".bright_blue().bold(),
-                                e.repr_current_code(Some(2))
+                                "-> {}: {}",
+                                lambda.signature,
+                                lambda.coroutine_status.to_string()
                             )
-                        })
-                        .collect::<Vec<String>>()
-                        .join("\n\n");
-
-                    let err = VMError::DetailedError(format!(
-                        "{}\n\n{}\n{}\n\n{}",
-                        "** CoroutinePool Step Error! **".bright_red().bold(),
-                        "# Main Error".bright_red().bold().underline(),
-                        vm_error.1.to_string().red(),
-                        format!("All Coroutine Contexts:\n{}", all_coroutines_contexts_repr)
-                    ));
-                    vm_error.1.consume_ref();
-                    err
-                } else {
-                    vm_error.1
+                            .bright_yellow()
+                            .bold(),
+                            e.context.format_context(&mut e.stack),
+                            "=== Code ===".bright_blue().bold(),
+                            e.repr_current_code(Some(2))
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n\n");
+
+                // 源码位置保留在最外层，便于调用者直接读取
+                let (main_error, position) = match vm_error.1 {
+                    VMError::AtSourcePosition(err, position) => (*err, Some(position)),
+                    err => (err, None),
                 };
-                err
-            });
-
-            if spawned_coroutines.is_err() {
-                let err = spawned_coroutines.err().unwrap();
-                for coroutine in self.executors.iter_mut() {
-                    coroutine.0.clean();
+                let (mut main_error, traceback) = match main_error {
+                    VMError::WithTraceback(err, frames) => (*err, Some(frames)),
+                    err => (err, None),
+                };
+                let err = VMError::DetailedError(format!(
+                    "{}\n\n{}\n{}\n\n{}",
+                    "** CoroutinePool Step Error! **".bright_red().bold(),
+                    "# Main Error".bright_red().bold().underline(),
+                    main_error.to_string().red(),
+                    format!("All Coroutine Contexts:\n{}", all_coroutines_contexts_repr)
+                ));
+                main_error.consume_ref();
+                let err = match traceback {
+                    Some(frames) => VMError::WithTraceback(Box::new(err), frames),
+                    None => err,
+                };
+                match position {
+                    Some(position) => VMError::AtSourcePosition(Box::new(err), position),
+                    None => err,
                 }
-                return Err(err);
+            } else {
+                vm_error.1
+            };
+            err
+        });
+
+        if spawned_coroutines.is_err() {
+            let err = spawned_coroutines.err().unwrap();
+            for coroutine in self.executors.iter_mut() {
+                coroutine.0.clean();
             }
+            return Err(err);
+        }
 
-            let spawned_coroutines = spawned_coroutines.unwrap();
+        let spawned_coroutines = spawned_coroutines.unwrap();
 
-            self.sweep_finished();
+        self.sweep_finished();
 
-            if let Some(mut coroutines) = spawned_coroutines {
-                for coroutine in coroutines.iter_mut() {
-                    self.new_coroutine(&mut coroutine.lambda_ref, &mut coroutine.args, gc_system)?;
-                }
+        if let Some(mut coroutines) = spawned_coroutines {
+            for coroutine in coroutines.iter_mut() {
+                self.new_coroutine(&mut coroutine.lambda_ref, &mut coroutine.args, gc_system)?;
             }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * 在当前协程池中调用一个 lambda，运行到所有协程结束后返回其结果，供宿主程序调用脚本中的函数
+     * lambda_object: 被调用的 VMLambda
+     * args: 参数元组，会按照 lambda 的默认参数进行赋值
+     * gc_system: 垃圾回收系统
+     *
+     * 注意：
+     * + 不会消耗 lambda_object 和 args 的引用计数，返回值持有一个新的引用，调用者负责 drop_ref()
+     * + 协程使用本协程池的 hook 与 max_call_depth，池中已有的协程会一并运行到结束
+     * + native 函数、native 生成器与 C lambda 无法在协程中运行，与 call_lambda_sync 相同直接同步调用
+     * + 钩子返回 Pause 时返回 Ok(None)，协程保留在池中，
+     *   之后通过 run_until_finished 继续运行，结束后结果位于该 lambda 的 result 中
+     */
+    pub fn call_lambda(
+        &mut self,
+        lambda_object: &mut GCRef,
+        args: &mut GCRef,
+        gc_system: &mut GCSystem,
+    ) -> Result<Option<GCRef>, VMError> {
+        if !lambda_object.isinstance::<VMLambda>() {
+            return Err(VMError::TryEnterNotLambda(lambda_object.clone_ref()));
+        }
+        if !args.isinstance::<VMTuple>() {
+            return Err(VMError::ArgumentIsNotTuple(args.clone_ref()));
+        }
+        let mut lambda_ref = lambda_object.clone();
+        let lambda = lambda_ref.as_type::<VMLambda>();
+        let runs_as_coroutine = matches!(
+            &lambda.lambda_body,
+            VMLambdaBody::VMInstruction(body) if !body.isinstance::<VMCLambdaInstruction>()
+        );
+        if !runs_as_coroutine {
+            return call_lambda_sync(lambda_object, args, gc_system).map(Some);
+        }
+
+        let mut arg_tuple = assign_call_args(lambda, args, gc_system)?;
+        // 已结束的 lambda 不会被执行器再次运行，这里临时重置状态并在结束后恢复
+        let previous_status =
+            std::mem::replace(&mut lambda.coroutine_status, VMCoroutineStatus::Running);
+        let outcome = self
+            .new_coroutine(
+                &mut lambda_object.clone_ref(),
+                &mut arg_tuple.clone_ref(),
+                gc_system,
+            )
+            .and_then(|_| self.run_until_finished(gc_system));
+        arg_tuple.drop_ref();
+        if outcome.is_ok() && self.is_paused() {
+            // 协程仍在池中运行，保持其状态不变
+            return Ok(None);
+        }
+        let lambda = lambda_ref.as_type::<VMLambda>();
+        lambda.coroutine_status = previous_status;
+        outcome.map(|_| Some(lambda.result.clone_ref()))
+    }
+
+    /**
+     * 运行协程池直到所有协程结束
+     *
+     * 注意：
+     * + 若钩子返回 Pause，会提前返回 Ok(())，此时 is_paused() 为 true 且协程保留在池中
+     * + 再次调用即可从暂停处继续
+     */
+    pub fn run_until_finished(&mut self, gc_system: &mut GCSystem) -> Result<(), VMError> {
+        self.paused = false;
+        loop {
+            self.schedule_once(gc_system)?;
 
-            if self.executors.is_empty() {
+            if self.executors.is_empty() || self.paused {
                 break;
             }
+            self.wait_for_wake();
         }
 
         Ok(())
     }
 
+    /**
+     * 以有限的步数推进协程池，便于宿主程序（例如游戏循环）交替执行自己的逻辑
+     * gc_system: 垃圾回收系统
+     * 返回是否仍有协程未结束
+     *
+     * 注意：
+     * + 每次调用最多调度 steps_per_tick 轮，每轮中每个协程执行一条指令
+     * + 未执行完的协程保留在协程池中，下一次调用时从中断处继续
+     * + 出错时与 run_until_finished 一致，会清理所有协程并返回错误
+     * + 所有协程都在休眠时提前返回而不阻塞，可通过 next_wake_time() 获取唤醒时间
+     */
+    pub fn run_one_step(&mut self, gc_system: &mut GCSystem) -> Result<bool, VMError> {
+        self.paused = false;
+        for _ in 0..self.steps_per_tick {
+            if self.executors.is_empty() || self.paused || self.next_wake_time().is_some() {
+                break;
+            }
+            self.schedule_once(gc_system)?;
+        }
+        Ok(!self.executors.is_empty())
+    }
+
     pub fn run_while<F>(
         &mut self,
         gc_system: &mut GCSystem,
@@ -295,6 +660,7 @@ impl VMCoroutinePool {
     where
         F: FnMut(&VMCoroutinePool) -> Result<(), VMError>,
     {
+        self.paused = false;
         loop {
             let condition_result = condition(self);
             if condition_result.is_err() {
@@ -304,71 +670,226 @@ impl VMCoroutinePool {
                 }
                 return Err(err);
             }
-            let spawned_coroutines = self.step_all(gc_system).map_err(|mut vm_error| {
-                let err = if self.enable_dump {
-                    let all_coroutines_contexts_repr = self
-                        .executors
-                        .iter_mut()
-                        .map(|(e, _)| {
-                            let lambda = e.entry_lambda.as_const_type::<VMLambda>();
-                            format!(
-                                "{}\n{}\n\n{}\n\n{}",
-                                format!(
-                                    "-> {}: {}",
-                                    lambda.signature,
-                                    lambda.coroutine_status.to_string()
-                                )
-                                .bright_yellow()
-                                .bold(),
-                                e.context.format_context(&mut e.stack),
-                                "=== Code ===".bright_blue().bold(),
-                                e.repr_current_code(Some(2))
-                            )
-                        })
-                        .collect::<Vec<String>>()
-                        .join("\n\n");
-
-                    let err = VMError::DetailedError(format!(
-                        "{}\n\n{}\n{}\n\n{}",
-                        "** CoroutinePool Step Error! **".bright_red().bold(),
-                        "# Main Error".bright_red().bold().underline(),
-                        vm_error.1.to_string().red(),
-                        format!("All Coroutine Contexts:\n{}", all_coroutines_contexts_repr)
-                    ));
-                    vm_error.1.consume_ref();
-                    err
-                } else {
-                    vm_error.1
-                };
-                err
-            });
+            self.schedule_once(gc_system)?;
 
-            if spawned_coroutines.is_err() {
-                let err = spawned_coroutines.err().unwrap();
-                for coroutine in self.executors.iter_mut() {
-                    coroutine.0.clean();
-                }
-                return Err(err);
+            if self.executors.is_empty() || self.paused {
+                break;
             }
+            self.wait_for_wake();
+        }
 
-            let spawned_coroutines = spawned_coroutines.unwrap();
-
-            self.sweep_finished();
+        Ok(())
+    }
+}
 
-            if let Some(mut coroutines) = spawned_coroutines {
-                for coroutine in coroutines.iter_mut() {
-                    self.new_coroutine(&mut coroutine.lambda_ref, &mut coroutine.args, gc_system)?;
-                }
-            }
+// 按照 lambda 的默认参数对 args 赋值，返回实际传入的参数元组（持有一个新的引用）
+fn assign_call_args(
+    lambda: &mut VMLambda,
+    args: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMError> {
+    if lambda.dynamic_params {
+        lambda
+            .default_args_tuple
+            .as_type::<VMTuple>()
+            .assign_members(args)
+            .map_err(VMError::VMVariableError)?;
+        Ok(lambda.default_args_tuple.clone_ref())
+    } else {
+        lambda
+            .default_args_tuple
+            .as_type::<VMTuple>()
+            .clone_and_assign_members(args, gc_system)
+            .map_err(VMError::VMVariableError)
+    }
+}
 
-            if self.executors.is_empty() {
-                break;
+/**
+ * 在 native 代码中同步调用一个 lambda 并返回其结果
+ * lambda_object: 被调用的 VMLambda
+ * args: 参数元组，会按照 lambda 的默认参数进行赋值
+ * gc_system: 垃圾回收系统
+ *
+ * 注意：
+ * + 不会消耗 lambda_object 和 args 的引用计数
+ * + 返回值持有一个新的引用，调用者负责 drop_ref()
+ * + 脚本定义的 lambda 会在一个独立的协程池中运行直到结束，期间 spawn 的协程也会在该池中完成
+ * + 嵌套的协程池超过 MAX_NATIVE_CALL_NESTING 层时返回 StackOverflow
+ */
+pub fn call_lambda_sync(
+    lambda_object: &mut GCRef,
+    args: &mut GCRef,
+    gc_system: &mut GCSystem,
+) -> Result<GCRef, VMError> {
+    if !lambda_object.isinstance::<VMLambda>() {
+        return Err(VMError::TryEnterNotLambda(lambda_object.clone_ref()));
+    }
+    if !args.isinstance::<VMTuple>() {
+        return Err(VMError::ArgumentIsNotTuple(args.clone_ref()));
+    }
+    let mut lambda_ref = lambda_object.clone();
+    let lambda = lambda_ref.as_type::<VMLambda>();
+
+    let mut arg_tuple = assign_call_args(lambda, args, gc_system)?;
+    let clambda_signature = lambda
+        .alias_const()
+        .first()
+        .unwrap_or(&lambda.signature)
+        .clone();
+
+    let result = match lambda.lambda_body {
+        VMLambdaBody::VMNativeFunction(native_function) => native_function(
+            lambda.self_object.as_mut(),
+            lambda.capture.as_mut(),
+            &mut arg_tuple,
+            gc_system,
+        )
+        .map_err(|err| {
+            VMError::VMVariableError(VMVariableError::InNativeFunction(
+                lambda.signature.clone(),
+                Box::new(err),
+            ))
+        }),
+        VMLambdaBody::VMNativeGeneratorFunction(ref mut generator) => {
+            match std::sync::Arc::get_mut(generator) {
+                Some(generator) => (|| -> Result<GCRef, VMVariableError> {
+                    generator.init(&mut arg_tuple, gc_system)?;
+                    while !generator.is_done() {
+                        if let Some(deadline) = generator.deadline() {
+                            std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                        }
+                        let mut yielded = generator.step(gc_system)?;
+                        yielded.drop_ref();
+                    }
+                    generator.get_result(gc_system)
+                })()
+                .map_err(VMError::VMVariableError),
+                None => Err(VMError::DetailedError(
+                    "Internal Error: Attempted to run a shared generator.".to_string(),
+                )),
             }
         }
+        VMLambdaBody::VMInstruction(ref mut body) if body.isinstance::<VMCLambdaInstruction>() => {
+            body.as_type::<VMCLambdaInstruction>()
+                .call(&clambda_signature, &mut arg_tuple, gc_system)
+                .map_err(VMError::VMVariableError)
+        }
+        VMLambdaBody::VMInstruction(_) => match NativeCallNesting::enter(&lambda.signature) {
+            Ok(_nesting) => {
+                // 已结束的 lambda 不会被执行器再次运行，这里临时重置状态并在结束后恢复
+                let previous_status =
+                    std::mem::replace(&mut lambda.coroutine_status, VMCoroutineStatus::Running);
+                let mut pool = VMCoroutinePool::new(false);
+                let outcome = pool
+                    .new_coroutine(
+                        &mut lambda_object.clone_ref(),
+                        &mut arg_tuple.clone_ref(),
+                        gc_system,
+                    )
+                    .and_then(|_| pool.run_until_finished(gc_system));
+                let lambda = lambda_ref.as_type::<VMLambda>();
+                lambda.coroutine_status = previous_status;
+                outcome.map(|_| lambda.result.clone_ref())
+            }
+            Err(err) => Err(err),
+        },
+    };
+    arg_tuple.drop_ref();
+    result
+}
 
-        Ok(())
+/**
+ * 在独立的协程池中运行一个 lambda，超过 timeout 仍未结束时取消该协程
+ * lambda_object: 被调用的 VMLambda
+ * args: 参数元组，会按照 lambda 的默认参数进行赋值
+ * timeout: 最长运行时间
+ * gc_system: 垃圾回收系统
+ *
+ * 注意：
+ * + 返回 Some(result) 表示按时结束，result 持有一个新的引用；超时返回 None
+ * + 超时在协程池的每轮调度之间检查，单条指令或生成器的单步不会被打断
+ * + native 函数与 C lambda 无法在协程中运行，直接同步调用
+ */
+pub fn call_lambda_with_timeout(
+    lambda_object: &mut GCRef,
+    args: &mut GCRef,
+    timeout: Duration,
+    gc_system: &mut GCSystem,
+) -> Result<Option<GCRef>, VMError> {
+    if !lambda_object.isinstance::<VMLambda>() {
+        return Err(VMError::TryEnterNotLambda(lambda_object.clone_ref()));
+    }
+    if !args.isinstance::<VMTuple>() {
+        return Err(VMError::ArgumentIsNotTuple(args.clone_ref()));
     }
+    let mut lambda_ref = lambda_object.clone();
+    let lambda = lambda_ref.as_type::<VMLambda>();
+    match lambda.lambda_body {
+        VMLambdaBody::VMNativeFunction(_) => {
+            return call_lambda_sync(lambda_object, args, gc_system).map(Some);
+        }
+        VMLambdaBody::VMInstruction(ref body) if body.isinstance::<VMCLambdaInstruction>() => {
+            return call_lambda_sync(lambda_object, args, gc_system).map(Some);
+        }
+        _ => {}
+    }
+    let _nesting = NativeCallNesting::enter(&lambda.signature)?;
+
+    let mut arg_tuple = assign_call_args(lambda, args, gc_system)?;
+
+    // 与 async 调用一致，生成器在进入协程池之前完成初始化
+    if let VMLambdaBody::VMNativeGeneratorFunction(ref mut generator) = lambda.lambda_body {
+        let initialized = match std::sync::Arc::get_mut(generator) {
+            Some(generator) => generator
+                .init(&mut arg_tuple, gc_system)
+                .map_err(VMError::VMVariableError),
+            None => Err(VMError::DetailedError(
+                "Internal Error: Attempted to run a shared generator.".to_string(),
+            )),
+        };
+        if let Err(err) = initialized {
+            arg_tuple.drop_ref();
+            return Err(err);
+        }
+    }
+
+    // 已结束的 lambda 不会被执行器再次运行，这里临时重置状态并在结束后恢复
+    let previous_status =
+        std::mem::replace(&mut lambda.coroutine_status, VMCoroutineStatus::Running);
+    let deadline = Instant::now() + timeout;
+    let mut pool = VMCoroutinePool::new(false);
+    let outcome = pool
+        .new_coroutine(
+            &mut lambda_object.clone_ref(),
+            &mut arg_tuple.clone_ref(),
+            gc_system,
+        )
+        .and_then(|id| loop {
+            if !pool.run_one_step(gc_system)? {
+                return Ok(true);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                pool.cancel_coroutine(id)?;
+                // 超时的 lambda 中 spawn 的其他协程也一并取消
+                for (executor, _) in pool.executors.iter_mut() {
+                    executor.entry_lambda.as_type::<VMLambda>().coroutine_status =
+                        VMCoroutineStatus::Cancelled;
+                }
+                pool.sweep_finished();
+                return Ok(false);
+            }
+            // 所有协程都在休眠时等待到唤醒时间或超时时间
+            if let Some(wake_time) = pool.next_wake_time() {
+                std::thread::sleep(wake_time.min(deadline).saturating_duration_since(now));
+            }
+        });
+    arg_tuple.drop_ref();
+    let lambda = lambda_ref.as_type::<VMLambda>();
+    lambda.coroutine_status = previous_status;
+    outcome.map(|finished| finished.then(|| lambda.result.clone_ref()))
 }
+
 #[derive(Debug)]
 pub struct SpawnedCoroutine {
     pub lambda_ref: GCRef,
@@ -389,6 +910,7 @@ pub struct VMExecutor {
     pub ip: isize,
     pub entry_lambda: GCRef,
     pub instruction_table: Vec<InstructionHandler>,
+    pub max_call_depth: usize,
 }
 
 impl VMExecutor {
@@ -507,6 +1029,7 @@ impl VMExecutor {
             lambda_instructions: Vec::new(),
             entry_lambda: entry_lambda.clone(),
             instruction_table,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
         }
     }
 
@@ -601,6 +1124,11 @@ impl VMExecutor {
                 "Only lambda defined by VMInstruction can be entered".to_string(),
             ));
         };
+        if self.context.function_depth() >= self.max_call_depth {
+            let mut signatures = self.call_signatures();
+            signatures.push(lambda_object.as_const_type::<VMLambda>().signature.clone());
+            return Err(VMError::StackOverflow(signatures));
+        }
         let use_new_instructions = self.lambda_instructions.is_empty()
             || *lambda_body != *self.lambda_instructions.last().unwrap();
 
@@ -666,8 +1194,7 @@ impl VMExecutor {
             }
         }
 
-        if lambda.self_object.is_some() {
-            let self_obj_ref = lambda.self_object.as_mut().unwrap();
+        if let Some(self_obj_ref) = lambda.self_object.as_mut() {
             let result = self.context.let_var("self", self_obj_ref, gc_system);
             if result.is_err() {
                 return Err(VMError::ContextError(result.unwrap_err()));
@@ -711,6 +1238,46 @@ impl VMExecutor {
         Ok(())
     }
 
+    // 当前正在执行的 native 生成器声明的唤醒时间，未休眠时返回 None
+    pub fn sleep_deadline(&self) -> Option<Instant> {
+        let lambda = self.entry_lambda.as_const_type::<VMLambda>();
+        if lambda.coroutine_status != VMCoroutineStatus::Running {
+            return None;
+        }
+        let current = self.lambda_instructions.last()?;
+        if !current.isinstance::<VMLambda>() {
+            return None;
+        }
+        match &current.as_const_type::<VMLambda>().lambda_body {
+            VMLambdaBody::VMNativeGeneratorFunction(generator) if !generator.is_done() => {
+                generator.deadline()
+            }
+            _ => None,
+        }
+    }
+
+    // 解码下一条将要执行的字节码指令，不推进 ip
+    // 协程未运行、指令已执行完或当前为 native 生成器时返回 None
+    pub fn peek_instruction(&self) -> Option<ProcessedOpcode> {
+        let lambda = self.entry_lambda.as_const_type::<VMLambda>();
+        if lambda.coroutine_status != VMCoroutineStatus::Running {
+            return None;
+        }
+        let instructions = self.lambda_instructions.last()?;
+        if !instructions.isinstance::<VMInstructions>() {
+            return None;
+        }
+        let code = instructions
+            .as_const_type::<VMInstructions>()
+            .vm_instructions_package
+            .get_code();
+        if self.ip < 0 || self.ip as usize >= code.len() {
+            return None;
+        }
+        let mut ip = self.ip as usize;
+        Instruction32::new(code, &mut ip).get_processed_opcode()
+    }
+
     pub fn step(
         &mut self,
         gc_system: &mut GCSystem,
@@ -721,7 +1288,10 @@ impl VMExecutor {
             .entry_lambda
             .as_const_type::<VMLambda>()
             .coroutine_status;
-        if *coroutine_status == VMCoroutineStatus::Pending {
+        if matches!(
+            coroutine_status,
+            VMCoroutineStatus::Pending | VMCoroutineStatus::Cancelled
+        ) {
             return Ok(None);
         }
         let mut spawned_coroutines = None;
@@ -972,7 +1542,9 @@ impl VMExecutor {
                                 vm_error.consume_ref();
                                 Ok(result)
                             }
-                            Err(_) => Err(vm_error),
+                            Err(_) => {
+                                Err(VMError::WithTraceback(Box::new(vm_error), self.traceback()))
+                            }
                         };
                     }
                 }
@@ -997,7 +1569,185 @@ impl VMExecutor {
     }
 }
 
+// 将源码字节偏移解析为行列号（从 1 开始）及所在行
+fn resolve_source_position(
+    package: &VMInstructionPackage,
+    code_position: usize,
+) -> Option<SourcePosition> {
+    let source_code = package.get_source().as_ref()?;
+    let (line, column) = find_source_position(source_code, code_position);
+    let source_line = source_code.lines().nth(line).unwrap_or("").to_string();
+    Some(SourcePosition {
+        line: line + 1,
+        column: column + 1,
+        source_line,
+    })
+}
+
+impl TraceFrame {
+    fn set_code_position(
+        &mut self,
+        code_position: Option<usize>,
+        package: Option<&VMInstructionPackage>,
+    ) {
+        self.code_position = code_position;
+        self.position = code_position
+            .zip(package)
+            .and_then(|(code_position, package)| resolve_source_position(package, code_position));
+    }
+}
+
+// 根据字节偏移计算源码中的行号和列号（均从 0 开始，列号按字素簇计数）
+fn find_source_position(source_code: &str, byte_pos: usize) -> (usize, usize) {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let lines: Vec<&str> = source_code.lines().collect();
+    let mut current_byte = 0;
+    for (line_num, line) in lines.iter().enumerate() {
+        // 计算行长度（包括换行符）
+        // Windows通常使用CRLF (\r\n)，而Unix使用LF (\n)
+        // 我们需要检测使用的是哪种换行符
+        let eol_len = if source_code.contains("\r\n") { 2 } else { 1 };
+        let line_bytes = line.len() + eol_len; // 加上实际的换行符长度
+
+        if current_byte + line_bytes > byte_pos {
+            // 计算行内的字节偏移
+            let line_offset = byte_pos - current_byte;
+
+            // 边界检查
+            if line_offset > line.len() {
+                return (line_num, line.graphemes(true).count()); // 位置在行尾
+            }
+
+            // 找到有效的字符边界
+            let valid_offset = line
+                .char_indices()
+                .map(|(i, _)| i)
+                .take_while(|&i| i <= line_offset)
+                .last()
+                .unwrap_or(0);
+
+            // 使用有效的字节偏移获取文本
+            let column_text = &line[..valid_offset];
+            let column = column_text.graphemes(true).count();
+            return (line_num, column);
+        }
+        current_byte += line_bytes;
+    }
+    (lines.len().saturating_sub(1), 0) // Default to last line
+}
+
 impl VMExecutor {
+    /**
+     * 解析当前指令对应的源码位置
+     * 当指令来自 native 生成器或缺少调试信息/源码时返回 None
+     */
+    pub fn current_source_position(&self) -> Option<SourcePosition> {
+        let instructions = self.lambda_instructions.last()?;
+        if !instructions.isinstance::<VMInstructions>() || self.ip < 0 {
+            return None;
+        }
+        let package = &instructions
+            .as_const_type::<VMInstructions>()
+            .vm_instructions_package;
+        let debug_info = package.get_debug_info().get(&(self.ip as usize))?;
+        resolve_source_position(package, debug_info.code_position)
+    }
+
+    // 当前调用栈中各函数的签名，由外向内排列
+    fn call_signatures(&self) -> Vec<String> {
+        let function_stack_pointers: FxHashSet<usize> = self
+            .context
+            .frames
+            .iter()
+            .zip(&self.context.stack_pointers)
+            .filter(|(frame, _)| frame.1 == ContextFrameType::FunctionFrame)
+            .map(|(_, stack_pointer)| *stack_pointer)
+            .collect();
+        self.stack
+            .iter()
+            .enumerate()
+            .filter_map(|(i, object)| match object {
+                VMStackObject::LastIP(lambda, _, _)
+                    if function_stack_pointers.contains(&(i + 1)) =>
+                {
+                    Some(lambda.as_const_type::<VMLambda>().signature.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /**
+     * 收集当前的函数调用栈（即栈上属于函数帧的 LastIP），由外向内排列
+     * 最后一帧为正在执行的函数，其位置为当前 ip，其余帧的位置为各自发起调用的指令
+     */
+    pub fn traceback(&self) -> Vec<TraceFrame> {
+        // 函数帧的栈指针恰好位于其 LastIP 之后，边界帧的 LastIP 不计入调用栈
+        let function_stack_pointers: Vec<usize> = self
+            .context
+            .frames
+            .iter()
+            .zip(&self.context.stack_pointers)
+            .filter(|(frame, _)| frame.1 == ContextFrameType::FunctionFrame)
+            .map(|(_, stack_pointer)| *stack_pointer)
+            .collect();
+
+        let mut frames: Vec<TraceFrame> = Vec::new();
+        let mut instructions_index: Option<usize> = None;
+        for (i, object) in self.stack.iter().enumerate() {
+            let VMStackObject::LastIP(lambda, return_ip, use_new_instructions) = object else {
+                continue;
+            };
+            if function_stack_pointers.contains(&(i + 1)) {
+                if let (Some(caller), Some(index)) = (frames.last_mut(), instructions_index) {
+                    // 返回地址指向调用指令的下一条，取其之前最近的调试信息
+                    let code_position = self.instructions_package(index).and_then(|package| {
+                        package
+                            .get_debug_info()
+                            .iter()
+                            .filter(|(ip, _)| **ip < *return_ip)
+                            .max_by_key(|(ip, _)| **ip)
+                            .map(|(_, info)| info.code_position)
+                    });
+                    caller.set_code_position(code_position, self.instructions_package(index));
+                }
+                frames.push(TraceFrame {
+                    signature: lambda.as_const_type::<VMLambda>().signature.clone(),
+                    code_position: None,
+                    position: None,
+                });
+            }
+            if *use_new_instructions {
+                instructions_index = Some(instructions_index.map_or(0, |index| index + 1));
+            }
+        }
+
+        if let (Some(current), Some(index)) = (frames.last_mut(), instructions_index) {
+            let package = self.instructions_package(index);
+            let code_position = package.and_then(|package| {
+                package
+                    .get_debug_info()
+                    .get(&(self.ip as usize))
+                    .map(|info| info.code_position)
+            });
+            current.set_code_position(code_position, package);
+        }
+        frames
+    }
+
+    fn instructions_package(&self, index: usize) -> Option<&VMInstructionPackage> {
+        let instructions = self.lambda_instructions.get(index)?;
+        if !instructions.isinstance::<VMInstructions>() {
+            return None;
+        }
+        Some(
+            &instructions
+                .as_const_type::<VMInstructions>()
+                .vm_instructions_package,
+        )
+    }
+
     pub fn repr_current_code(&self, context_lines: Option<usize>) -> String {
         use colored::*;
         use unicode_segmentation::UnicodeSegmentation;
@@ -1069,45 +1819,8 @@ impl VMExecutor {
         // Split source code into lines
         let lines: Vec<&str> = source_code.lines().collect();
 
-        // Helper function to find line and column from byte position
-        let find_position = |byte_pos: usize| -> (usize, usize) {
-            let mut current_byte = 0;
-            for (line_num, line) in lines.iter().enumerate() {
-                // 计算行长度（包括换行符）
-                // Windows通常使用CRLF (\r\n)，而Unix使用LF (\n)
-                // 我们需要检测使用的是哪种换行符
-                let eol_len = if source_code.contains("\r\n") { 2 } else { 1 };
-                let line_bytes = line.len() + eol_len; // 加上实际的换行符长度
-
-                if current_byte + line_bytes > byte_pos {
-                    // 计算行内的字节偏移
-                    let line_offset = byte_pos - current_byte;
-
-                    // 边界检查
-                    if line_offset > line.len() {
-                        return (line_num, line.graphemes(true).count()); // 位置在行尾
-                    }
-
-                    // 找到有效的字符边界
-                    let valid_offset = line
-                        .char_indices()
-                        .map(|(i, _)| i)
-                        .take_while(|&i| i <= line_offset)
-                        .last()
-                        .unwrap_or(0);
-
-                    // 使用有效的字节偏移获取文本
-                    let column_text = &line[..valid_offset];
-                    let column = column_text.graphemes(true).count();
-                    return (line_num, column);
-                }
-                current_byte += line_bytes;
-            }
-            (lines.len().saturating_sub(1), 0) // Default to last line
-        };
-
         // Get line and column number for current position
-        let (line_num, col_num) = find_position(current_pos);
+        let (line_num, col_num) = find_source_position(source_code, current_pos);
 
         // Calculate range of lines to display
         let start_line = line_num.saturating_sub(context_lines);
@@ -3,6 +3,7 @@ use crate::executor::variable::*;
 use crate::executor::vm::VMError;
 use crate::executor::vm::VMExecutor;
 use crate::gc::GCSystem;
+use crate::instruction_set::VMInstructionPackage;
 use crate::opcode::{OpcodeArgument, ProcessedOpcode};
 use std::fs::File;
 use std::io::Read;
@@ -231,6 +232,26 @@ pub fn bind_self(
                 "Bind's value requires a VMLambda or VMTuple".to_string(),
             )));
         }
+        // 绑定会原地修改 lambda 的 self，因此已冻结的 lambda 或元组中已冻结的方法不能重新绑定
+        let frozen = keyval.value.is_frozen()
+            || (keyval.value.isinstance::<VMTuple>()
+                && keyval
+                    .value
+                    .as_const_type::<VMTuple>()
+                    .values
+                    .iter()
+                    .any(|value| {
+                        value.isinstance::<VMNamed>() && {
+                            let method = &value.as_const_type::<VMNamed>().value;
+                            method.isinstance::<VMLambda>() && method.is_frozen()
+                        }
+                    }));
+        if frozen {
+            return Err(VMError::VMVariableError(VMVariableError::AssignError(
+                keyval.value.clone_ref(),
+                "Cannot bind self to a frozen object".to_string(),
+            )));
+        }
         if keyval.value.isinstance::<VMTuple>() {
             keyval.value.as_type::<VMTuple>().bind_lambda_self(&mut keyval.key);
         } else {
@@ -900,7 +921,11 @@ pub fn is_finished(
         ));
     }
     let lambda = obj.as_const_type::<VMLambda>();
-    let is_finished = lambda.coroutine_status == VMCoroutineStatus::Finished;
+    // 被取消的协程不会再产生结果，视为已结束以免 await 永远等待
+    let is_finished = matches!(
+        lambda.coroutine_status,
+        VMCoroutineStatus::Finished | VMCoroutineStatus::Cancelled
+    );
 
     // Pop object from stack after successful operation
     vm.pop_object()?;
@@ -1009,13 +1034,7 @@ pub fn jump_if_false(
         return Err(VMError::InvalidInstruction(opcode.clone()));
     };
     let mut obj = vm.get_object_and_check(0)?;
-    if !obj.isinstance::<VMBoolean>() {
-        return Err(VMError::VMVariableError(VMVariableError::TypeError(
-            obj.clone_ref(),
-            "JumpIfFalseOffset: Not a boolean".to_string(),
-        )));
-    }
-    let jump = !obj.as_const_type::<VMBoolean>().value;
+    let jump = !try_to_bool_as_vmobject(&mut obj).map_err(VMError::VMVariableError)?;
 
     // Pop object from stack after successful check
     vm.pop_object()?;
@@ -1285,6 +1304,7 @@ pub fn call_lambda(
             // Drop references after potential enter_lambda
             arg_tuple.drop_ref();
             lambda.drop_ref(); // lambda is now managed by enter_lambda or dropped if error
+            original_arg_tuple.drop_ref(); // already popped, so release it even if entering failed
 
             enter_result?; // Propagate error from enter_lambda
 
@@ -1297,14 +1317,17 @@ pub fn call_lambda(
                 .get_table();
             let ip = *func_ips.get(&signature).unwrap() as isize;
             vm.ip = ip;
-            original_arg_tuple.drop_ref();
             Ok(None)
         }
         VMLambdaBody::VMNativeFunction(native_function) => {
             let result = native_function(lambda_obj.self_object.as_mut(), lambda_obj.capture.as_mut(), &mut arg_tuple, gc_system); // Clone arg_tuple for native call
             if result.is_err() {
                 arg_tuple.drop_ref();
-                return Err(VMError::VMVariableError(result.unwrap_err()));
+                // 标注出错的 native 函数，错误对象通常只指向参数元组
+                return Err(VMError::VMVariableError(VMVariableError::InNativeFunction(
+                    lambda_obj.signature.clone(),
+                    Box::new(result.unwrap_err()),
+                )));
             }
             let mut result = result.unwrap();
             lambda_obj.set_result(&mut result);
@@ -1531,38 +1554,7 @@ pub fn type_of(
 ) -> Result<Option<Vec<SpawnedCoroutine>>, VMError> {
     let mut ref_obj = vm.get_object_and_check(0)?;
 
-    let type_str = if ref_obj.isinstance::<VMInt>() {
-        "int"
-    } else if ref_obj.isinstance::<VMFloat>() {
-        "float"
-    } else if ref_obj.isinstance::<VMString>() {
-        "string"
-    } else if ref_obj.isinstance::<VMBoolean>() {
-        "bool"
-    } else if ref_obj.isinstance::<VMTuple>() {
-        "tuple"
-    } else if ref_obj.isinstance::<VMLambda>() {
-        "lambda"
-    } else if ref_obj.isinstance::<VMNull>() {
-        "null"
-    } else if ref_obj.isinstance::<VMKeyVal>() {
-        "keyval"
-    } else if ref_obj.isinstance::<VMNamed>() {
-        "named"
-    } else if ref_obj.isinstance::<VMRange>() {
-        "range"
-    } else if ref_obj.isinstance::<VMWrapper>() {
-        "wrapper"
-    } else if ref_obj.isinstance::<VMInstructions>() {
-        "instructions"
-    } else if ref_obj.isinstance::<VMBytes>() {
-        "bytes"
-    } else if ref_obj.isinstance::<VMSet>() {
-        // Added VMSet
-        "set"
-    } else {
-        ""
-    };
+    let type_str = type_name_of_vmobject(&ref_obj).unwrap_or("");
     let result = gc_system.new_object(VMString::new(type_str));
 
     // Pop object from stack after successful operation
@@ -1616,10 +1608,10 @@ pub fn import(
             result.unwrap_err()
         )));
     }
-    let vm_instruction_package = bincode::deserialize(&contents);
+    let vm_instruction_package = VMInstructionPackage::from_bytes(&contents);
     if vm_instruction_package.is_err() {
         return Err(VMError::FileError(format!(
-            "Cannot deserialize file: {} : {:?}",
+            "Cannot deserialize file: {} : {}",
             path_str,
             vm_instruction_package.unwrap_err()
         )));
@@ -1827,30 +1819,10 @@ pub fn push_value_into_tuple(
 pub fn reset_iter(
     vm: &mut VMExecutor,
     _opcode: &ProcessedOpcode,
-    _gc_system: &mut GCSystem,
+    gc_system: &mut GCSystem,
 ) -> Result<Option<Vec<SpawnedCoroutine>>, VMError> {
     let mut obj = vm.get_object_and_check(0)?;
-    if obj.isinstance::<VMTuple>() {
-        let tuple = obj.as_type::<VMTuple>();
-        tuple.reset();
-    } else if obj.isinstance::<VMString>() {
-        let string = obj.as_type::<VMString>();
-        string.reset();
-    } else if obj.isinstance::<VMBytes>() {
-        let bytes = obj.as_type::<VMBytes>();
-        bytes.reset();
-    } else if obj.isinstance::<VMRange>() {
-        let range = obj.as_type::<VMRange>();
-        range.reset();
-    } else if obj.isinstance::<VMSet>() {
-        let set = obj.as_type::<VMSet>();
-        set.reset();
-    } else {
-        return Err(VMError::InvalidArgument(
-            obj.clone_ref(),
-            "ResetIter: Not a iterable".to_string(),
-        ));
-    }
+    try_iter_reset_as_vmobject(&mut obj, gc_system).map_err(VMError::VMVariableError)?;
     Ok(None)
 }
 pub fn next_or_jump(
@@ -1862,53 +1834,10 @@ pub fn next_or_jump(
         return Err(VMError::InvalidInstruction(opcode.clone()));
     };
     let mut obj = vm.get_object_and_check(0)?;
-    if obj.isinstance::<VMTuple>() {
-        let tuple = obj.as_type::<VMTuple>();
-        let result = tuple.next(gc_system);
-        if result.is_none() {
-            vm.ip += offset as isize;
-        } else {
-            vm.push_vmobject(result.unwrap())?;
-        }
-    } else if obj.isinstance::<VMString>() {
-        let tuple = obj.as_type::<VMString>();
-        let result = tuple.next(gc_system);
-        if result.is_none() {
-            vm.ip += offset as isize;
-        } else {
-            vm.push_vmobject(result.unwrap())?;
-        }
-    } else if obj.isinstance::<VMBytes>() {
-        let tuple = obj.as_type::<VMBytes>();
-        let result = tuple.next(gc_system);
-        if result.is_none() {
-            vm.ip += offset as isize;
-        } else {
-            vm.push_vmobject(result.unwrap())?;
-        }
-    } else if obj.isinstance::<VMRange>() {
-        let tuple = obj.as_type::<VMRange>();
-        let result = tuple.next(gc_system);
-        if result.is_none() {
-            vm.ip += offset as isize;
-        } else {
-            vm.push_vmobject(result.unwrap())?;
-        }
-    } else if obj.isinstance::<VMSet>() {
-        let tuple = obj.as_type::<VMSet>();
-        let result = tuple.next(gc_system);
-        if result.is_none() {
-            vm.ip += offset as isize;
-        } else {
-            vm.push_vmobject(result.unwrap())?;
-        }
-    } else {
-        return Err(VMError::InvalidArgument(
-            obj.clone_ref(),
-            "NextOrJump: Not a iterable".to_string(),
-        ));
+    match try_iter_next_as_vmobject(&mut obj, gc_system).map_err(VMError::VMVariableError)? {
+        Some(result) => vm.push_vmobject(result)?,
+        None => vm.ip += offset as isize,
     }
-
     Ok(None)
 }
 pub fn get_lambda_capture(
@@ -156,6 +156,20 @@ impl GCRef {
         }
     }
 
+    pub fn freeze(&self) {
+        unsafe {
+            let obj = self.reference;
+            (*obj).get_traceable().frozen = true;
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        unsafe {
+            let obj = self.reference as *const dyn GCObject;
+            (*obj).get_const_traceable().frozen
+        }
+    }
+
     pub fn clone_ref(&mut self) -> Self {
         if !self.is_online() && self.get_const_traceable().ref_count == 0 {
             panic!("Cannot clone an offline object!");
@@ -187,6 +201,7 @@ pub struct GCTraceable {
     pub ref_count: usize,
     pub should_free: bool,
     pub lock: bool, // 是否锁定对象禁止回收
+    pub frozen: bool, // 是否冻结对象禁止赋值
     pub references: HashMap<GCRef, usize>,
     pub type_id: TypeId,              // type id of the object
 }
@@ -207,6 +222,7 @@ impl GCTraceable {
             ref_count: 0,
             should_free: false,
             lock: false,
+            frozen: false,
             references: refs_map,
             type_id: TypeId::of::<T>(),
         }
@@ -282,6 +298,27 @@ impl Drop for GCTraceable {
     }
 }
 
+// GC 触发配置
+// maximum_new_objects_count / maximum_allocation_size 对应原先 GCSystem::new 的 (usize, usize) 参数
+// object_threshold 为 Some(n) 时，托管对象数量达到阈值就在下一个安全点（check_and_collect）回收，
+// 回收后阈值调整为 max(n, 存活对象数 * 2)，避免存活对象较多时每次分配都触发回收
+#[derive(Debug, Clone, Copy)]
+pub struct GCConfig {
+    pub maximum_new_objects_count: usize,
+    pub maximum_allocation_size: usize,
+    pub object_threshold: Option<usize>,
+}
+
+impl Default for GCConfig {
+    fn default() -> Self {
+        GCConfig {
+            maximum_new_objects_count: 100,
+            maximum_allocation_size: 4096 * 1024,
+            object_threshold: None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GCSystem {
     objects: Vec<GCRef>,
@@ -289,21 +326,25 @@ pub struct GCSystem {
     new_objects_sum_size: usize,
     _maximum_new_objects_count: usize, // GC触发对象数量限制
     maximum_allocation_size: usize,    // GC触发内存限制
+    object_threshold: Option<usize>,
+    next_object_threshold: usize, // 下一次按对象数量触发回收的位置
     should_collect: bool,
+    collections: usize, // 已执行的完整回收次数
 }
 
 impl GCSystem {
-    pub fn new(trigger: Option<(usize, usize)>) -> GCSystem {
-        let trigger = trigger.unwrap_or((100, 4096 * 1024));
-        let maximum_new_objects_count = trigger.0;
-        let maximum_allocation_size = trigger.1;
+    pub fn new(config: Option<GCConfig>) -> GCSystem {
+        let config = config.unwrap_or_default();
         GCSystem {
             objects: Vec::new(),
             new_objects_count: 0,
             new_objects_sum_size: 0,
-            maximum_allocation_size,
-            _maximum_new_objects_count: maximum_new_objects_count,
+            maximum_allocation_size: config.maximum_allocation_size,
+            _maximum_new_objects_count: config.maximum_new_objects_count,
+            object_threshold: config.object_threshold,
+            next_object_threshold: config.object_threshold.unwrap_or(usize::MAX),
             should_collect: true,
+            collections: 0,
         }
     }
 
@@ -311,6 +352,10 @@ impl GCSystem {
         self.new_objects_sum_size += std::mem::size_of::<T>();
         self.new_objects_count += 1;
 
+        if self.objects.len() >= self.next_object_threshold {
+            self.should_collect = true;
+        }
+
         let trigger_threshold = self.objects.len() / 2; // 20%的增长率触发GC
 
         if self.new_objects_sum_size > self.maximum_allocation_size
@@ -526,6 +571,10 @@ impl GCSystem {
         self.immediate_collect();
         self.mark();
         self.sweep();
+        self.collections += 1;
+        if let Some(threshold) = self.object_threshold {
+            self.next_object_threshold = threshold.max(self.objects.len() * 2);
+        }
     }
 
     pub fn check_and_collect(&mut self) {
@@ -610,6 +659,18 @@ impl GCSystem {
         self.objects.len()
     }
 
+    // 所有对象的原生引用计数之和，即 Rust 侧持有的 GCRef 数量
+    pub fn native_ref_count(&self) -> usize {
+        self.objects
+            .iter()
+            .map(|obj| obj.get_const_traceable().native_gcref_object_count)
+            .sum()
+    }
+
+    pub fn collection_count(&self) -> usize {
+        self.collections
+    }
+
     pub fn _get_all_objects(&self) -> &Vec<GCRef> {
         &self.objects
     }
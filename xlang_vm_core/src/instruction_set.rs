@@ -209,8 +209,28 @@ impl VMInstruction {
     }
 }
 
+use base64::Engine;
 use rustc_hash::FxHashMap as HashMap;
 use std::fs;
+
+use super::opcode::{Instruction32, OpcodeArgument};
+
+/// 字节码文件的 magic number
+pub const BYTECODE_MAGIC: [u8; 4] = *b"XLBC";
+/// 字节码格式版本，指令集或 VMInstructionPackage 的结构发生变化时必须递增
+pub const BYTECODE_VERSION: u32 = 1;
+const BYTECODE_HEADER_SIZE: usize = BYTECODE_MAGIC.len() + 4;
+
+/// 反汇编得到的一条指令
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisassembledInstruction {
+    pub index: usize,                  // 指令在 code 中的起始位置，即 ip
+    pub function_name: Option<String>, // 所属函数（不大于 index 的最近入口点）
+    pub debug_position: Option<usize>, // 对应的源码位置
+    pub mnemonic: String,
+    pub operands: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VMInstructionPackage {
     function_ips: HashMap<String, usize>, // 签名定位表
@@ -258,17 +278,123 @@ impl VMInstructionPackage {
         &self.debug_infos
     }
 
-    pub fn write_to_file(&self, path: &str) -> Result<(), std::io::Error> {
+    /// 将字节码解码为结构化的指令列表，供调试工具自行渲染
+    /// 遇到无法完整解码的尾部数据时停止
+    pub fn disassemble(&self) -> Vec<DisassembledInstruction> {
+        let mut entries: Vec<(usize, &String)> = self
+            .function_ips
+            .iter()
+            .map(|(name, ip)| (*ip, name))
+            .collect();
+        entries.sort();
+
+        let mut result = Vec::new();
+        let mut ip = 0;
+        while ip < self.code.len() {
+            let index = ip;
+            let Some(opcode) = Instruction32::new(&self.code, &mut ip).get_processed_opcode()
+            else {
+                break;
+            };
+            let function_name = entries
+                .iter()
+                .take_while(|(entry_ip, _)| *entry_ip <= index)
+                .last()
+                .map(|(_, name)| (*name).clone());
+            let instruction = VMInstruction::from_opcode(opcode.instruction);
+            let mnemonic = match instruction {
+                Some(instruction) => format!("{:?}", instruction),
+                None => format!("Unknown({})", opcode.instruction),
+            };
+            // 变量名以普通整数形式存放字符串池下标
+            let operand1 = match (instruction, &opcode.operand1) {
+                (
+                    Some(VMInstruction::LoadVar | VMInstruction::StoreVar | VMInstruction::Alias),
+                    OpcodeArgument::Int64(index),
+                ) => OpcodeArgument::String(*index as u64),
+                (_, operand) => operand.clone(),
+            };
+            let operands = [&operand1, &opcode.operand2, &opcode.operand3]
+                .into_iter()
+                .filter_map(|operand| self.format_operand(operand))
+                .collect();
+            result.push(DisassembledInstruction {
+                index,
+                function_name,
+                debug_position: self.debug_infos.get(&index).map(|info| info.code_position),
+                mnemonic,
+                operands,
+            });
+        }
+        result
+    }
+
+    fn format_operand(&self, operand: &OpcodeArgument) -> Option<String> {
+        let text = match operand {
+            OpcodeArgument::None => return None,
+            OpcodeArgument::Int32(value) => value.to_string(),
+            OpcodeArgument::Int64(value) => value.to_string(),
+            OpcodeArgument::Float32(value) => format!("{:?}", value),
+            OpcodeArgument::Float64(value) => format!("{:?}", value),
+            OpcodeArgument::String(index) => match self.string_pool.get(*index as usize) {
+                Some(value) => format!("{:?}", value),
+                None => format!("<string #{}>", index),
+            },
+            OpcodeArgument::ByteArray(index) => match self.bytes_pool.get(*index as usize) {
+                Some(value) => format!(
+                    "$\"{}\"",
+                    base64::engine::general_purpose::STANDARD.encode(value)
+                ),
+                None => format!("<bytes #{}>", index),
+            },
+        };
+        Some(text)
+    }
+
+    /// 序列化为带文件头的字节码：magic (4 字节) + 格式版本 (u32, 小端) + bincode 数据
+    pub fn to_bytes(&self) -> Result<Vec<u8>, std::io::Error> {
         let serialized = bincode::serialize(self)
             .map_err(|e| std::io::Error::other(format!("Serialization error: {}", e)))?;
+        let mut bytes = Vec::with_capacity(BYTECODE_HEADER_SIZE + serialized.len());
+        bytes.extend_from_slice(&BYTECODE_MAGIC);
+        bytes.extend_from_slice(&BYTECODE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&serialized);
+        Ok(bytes)
+    }
 
-        fs::write(path, serialized)
+    /// 校验文件头后反序列化，版本不一致时拒绝加载
+    ///
+    /// 不访问文件系统，可直接加载嵌入宿主程序的字节码，例如：
+    /// `VMInstructionPackage::from_bytes(include_bytes!("main.xbc"))`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        if bytes.len() < BYTECODE_HEADER_SIZE || bytes[..BYTECODE_MAGIC.len()] != BYTECODE_MAGIC {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "not an XLang bytecode file (bad magic number)",
+            ));
+        }
+        let mut version = [0u8; 4];
+        version.copy_from_slice(&bytes[BYTECODE_MAGIC.len()..BYTECODE_HEADER_SIZE]);
+        let version = u32::from_le_bytes(version);
+        if version != BYTECODE_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "bytecode version mismatch: file={} runtime={}",
+                    version, BYTECODE_VERSION
+                ),
+            ));
+        }
+        bincode::deserialize(&bytes[BYTECODE_HEADER_SIZE..])
+            .map_err(|e| std::io::Error::other(format!("Deserialization error: {}", e)))
+    }
+
+    pub fn write_to_file(&self, path: &str) -> Result<(), std::io::Error> {
+        fs::write(path, self.to_bytes()?)
     }
 
     pub fn read_from_file(path: &str) -> Result<Self, std::io::Error> {
         let bytes = fs::read(path)?;
-        bincode::deserialize(&bytes)
-            .map_err(|e| std::io::Error::other(format!("Deserialization error: {}", e)))
+        Self::from_bytes(&bytes)
     }
-
 }
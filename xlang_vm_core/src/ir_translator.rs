@@ -49,8 +49,263 @@ impl IRTranslator {
     }
 }
 
+// 跳转类 IR 的偏移，目标为 idx + offset + 1
+fn jump_offset(ir: &IR) -> Option<isize> {
+    match ir {
+        IR::JumpOffset(offset)
+        | IR::JumpIfFalseOffset(offset)
+        | IR::NewBoundaryFrame(offset)
+        | IR::NextOrJump(offset) => Some(*offset),
+        _ => None,
+    }
+}
+
+fn set_jump_offset(ir: &mut IR, new_offset: isize) {
+    match ir {
+        IR::JumpOffset(offset)
+        | IR::JumpIfFalseOffset(offset)
+        | IR::NewBoundaryFrame(offset)
+        | IR::NextOrJump(offset) => *offset = new_offset,
+        _ => {}
+    }
+}
+
+// 标记所有可能被跳转到的位置（包括函数入口），长度为指令数 + 1
+fn jump_targets(
+    instructions: &[(DebugInfo, IR)],
+    function_ips: &std::collections::HashMap<String, usize>,
+) -> Vec<bool> {
+    let mut targets = vec![false; instructions.len() + 1];
+    for (idx, (_, ir)) in instructions.iter().enumerate() {
+        if let Some(offset) = jump_offset(ir) {
+            let target = idx as isize + offset + 1;
+            if target >= 0 && (target as usize) < targets.len() {
+                targets[target as usize] = true;
+            }
+        }
+    }
+    for ip in function_ips.values() {
+        if *ip < targets.len() {
+            targets[*ip] = true;
+        }
+    }
+    targets
+}
+
+// 删除被标记的指令并修正跳转偏移与函数入口
+// 指向被删除指令的跳转会落到其后第一条保留的指令上
+fn remove_instructions(
+    instructions: &mut Vec<(DebugInfo, IR)>,
+    function_ips: &mut std::collections::HashMap<String, usize>,
+    removed: &[bool],
+) {
+    let mut new_index = vec![0; instructions.len() + 1];
+    let mut kept = 0;
+    for idx in 0..instructions.len() {
+        new_index[idx] = kept;
+        if !removed[idx] {
+            kept += 1;
+        }
+    }
+    new_index[instructions.len()] = kept;
+
+    let old_instructions = std::mem::take(instructions);
+    for (idx, (debug_info, mut ir)) in old_instructions.into_iter().enumerate() {
+        if removed[idx] {
+            continue;
+        }
+        if let Some(offset) = jump_offset(&ir) {
+            let target = (idx as isize + offset + 1).clamp(0, new_index.len() as isize - 1);
+            let new_offset = new_index[target as usize] as isize - new_index[idx] as isize - 1;
+            set_jump_offset(&mut ir, new_offset);
+        }
+        instructions.push((debug_info, ir));
+    }
+    for ip in function_ips.values_mut() {
+        *ip = new_index[(*ip).min(new_index.len() - 1)];
+    }
+}
+
+// 计算两个字面量的二元运算，语义与 VMInt / VMFloat 的运算一致
+// 整数运算溢出或结果不确定时返回 None，保留到运行时处理
+fn fold_binary_op(lhs: &IR, rhs: &IR, op: &IROperation) -> Option<IR> {
+    match (lhs, rhs) {
+        (IR::LoadInt(a), IR::LoadInt(b)) => match op {
+            IROperation::Add => a.checked_add(*b).map(IR::LoadInt),
+            IROperation::Subtract => a.checked_sub(*b).map(IR::LoadInt),
+            IROperation::Multiply => a.checked_mul(*b).map(IR::LoadInt),
+            _ => None,
+        },
+        (IR::LoadInt(_), IR::LoadFloat(_))
+        | (IR::LoadFloat(_), IR::LoadInt(_))
+        | (IR::LoadFloat(_), IR::LoadFloat(_)) => {
+            let as_float = |ir: &IR| match ir {
+                IR::LoadInt(value) => *value as f64,
+                IR::LoadFloat(value) => *value,
+                _ => unreachable!(),
+            };
+            let (a, b) = (as_float(lhs), as_float(rhs));
+            match op {
+                IROperation::Add => Some(IR::LoadFloat(a + b)),
+                IROperation::Subtract => Some(IR::LoadFloat(a - b)),
+                IROperation::Multiply => Some(IR::LoadFloat(a * b)),
+                IROperation::Divide => Some(IR::LoadFloat(a / b)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 impl IRTranslator {
+    /**
+     * 常量折叠：把两个数字字面量后紧跟的二元运算替换为一次字面量加载
+     * 例如 2 + 3 * 4 会被折叠为 LoadInt(14)
+     *
+     * 注意：
+     * + 运算的两个操作数与运算本身之间不能有跳转目标，否则不折叠
+     * + 嵌套的表达式通过多轮折叠完成
+     */
+    fn fold_constants(&mut self) {
+        let package = &mut self.ir_package;
+        loop {
+            let targets = jump_targets(&package.instructions, &package.function_ips);
+            let mut removed = vec![false; package.instructions.len()];
+            let mut folded = false;
+            let mut idx = 2;
+            while idx < package.instructions.len() {
+                if targets[idx - 1] || targets[idx] || removed[idx - 2] {
+                    idx += 1;
+                    continue;
+                }
+                let result = match &package.instructions[idx].1 {
+                    IR::BinaryOp(op) => fold_binary_op(
+                        &package.instructions[idx - 2].1,
+                        &package.instructions[idx - 1].1,
+                        op,
+                    ),
+                    _ => None,
+                };
+                if let Some(result) = result {
+                    package.instructions[idx].1 = result;
+                    removed[idx - 2] = true;
+                    removed[idx - 1] = true;
+                    folded = true;
+                    // 跳过刚折叠出的字面量，避免同一轮中与后续指令重叠
+                    idx += 3;
+                } else {
+                    idx += 1;
+                }
+            }
+            if !folded {
+                break;
+            }
+            remove_instructions(
+                &mut package.instructions,
+                &mut package.function_ips,
+                &removed,
+            );
+        }
+    }
+
+    /**
+     * 删除不可达的指令：无条件跳转或 Return 之后、下一个跳转目标之前的指令永远不会执行
+     * 例如 return 之后的语句以及函数末尾自动生成的返回序列
+     *
+     * 注意：
+     * + 只有没有任何跳转目标（包括函数入口）落在其中的区间才会被删除
+     */
+    fn eliminate_dead_code(&mut self) {
+        let package = &mut self.ir_package;
+        let targets = jump_targets(&package.instructions, &package.function_ips);
+        let mut removed = vec![false; package.instructions.len()];
+        let mut unreachable = false;
+        for (idx, (_, ir)) in package.instructions.iter().enumerate() {
+            if targets[idx] {
+                unreachable = false;
+            }
+            removed[idx] = unreachable;
+            if matches!(ir, IR::Return | IR::JumpOffset(_)) {
+                unreachable = true;
+            }
+        }
+        if removed.contains(&true) {
+            remove_instructions(
+                &mut package.instructions,
+                &mut package.function_ips,
+                &removed,
+            );
+        }
+    }
+
+    /**
+     * 窥孔优化：
+     * + 字面量加载后紧跟 Pop 时两条指令一起删除
+     * + 数字字面量之后连续两次取负（或两次按位取反）时删除这两次运算
+     *
+     * 注意：
+     * + 被删除的 Pop 或运算指令不能是跳转目标，否则跳转过来时栈上的值不同
+     * + 只处理字面量，避免改变其他类型在运算时的报错行为
+     */
+    fn peephole_optimize(&mut self) {
+        let package = &mut self.ir_package;
+        let targets = jump_targets(&package.instructions, &package.function_ips);
+        let instructions = &package.instructions;
+        let mut removed = vec![false; instructions.len()];
+        let mut idx = 0;
+        while idx + 1 < instructions.len() {
+            let is_literal = matches!(
+                instructions[idx].1,
+                IR::LoadNull
+                    | IR::LoadInt(_)
+                    | IR::LoadFloat(_)
+                    | IR::LoadString(_)
+                    | IR::LoadBytes(_)
+                    | IR::LoadBool(_)
+            );
+            if is_literal && matches!(instructions[idx + 1].1, IR::Pop) && !targets[idx + 1] {
+                removed[idx] = true;
+                removed[idx + 1] = true;
+                idx += 2;
+                continue;
+            }
+            let is_number = matches!(instructions[idx].1, IR::LoadInt(_) | IR::LoadFloat(_));
+            if is_number
+                && idx + 2 < instructions.len()
+                && !targets[idx + 1]
+                && !targets[idx + 2]
+            {
+                let double_negation = match (&instructions[idx + 1].1, &instructions[idx + 2].1) {
+                    (IR::UnaryOp(IROperation::Subtract), IR::UnaryOp(IROperation::Subtract)) => {
+                        true
+                    }
+                    (IR::UnaryOp(IROperation::Not), IR::UnaryOp(IROperation::Not)) => {
+                        matches!(instructions[idx].1, IR::LoadInt(_))
+                    }
+                    _ => false,
+                };
+                if double_negation {
+                    removed[idx + 1] = true;
+                    removed[idx + 2] = true;
+                    idx += 3;
+                    continue;
+                }
+            }
+            idx += 1;
+        }
+        if removed.contains(&true) {
+            remove_instructions(
+                &mut package.instructions,
+                &mut package.function_ips,
+                &removed,
+            );
+        }
+    }
+
     pub fn translate(&mut self) -> Result<(), IRTranslatorError> {
+        self.fold_constants();
+        self.peephole_optimize();
+        self.eliminate_dead_code();
         let mut redirect_table = Vec::<(usize/*偏移计算位置*/, usize/*填充位置*/, usize/*跳转的ir*/, bool)>::new(); // bool 表示是 i64 填充
         let cloned = self.ir_package.instructions.clone();
         for idx in 0..cloned.len() {